@@ -6,6 +6,7 @@ pub fn derive_enum(input: TokenStream) -> TokenStream {
     let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
     let struct_name = &ast.ident;
+    let struct_vis = &ast.vis;
     let variants = match ast.data {
         syn::Data::Enum(syn::DataEnum { variants, .. }) => variants,
         _ => {
@@ -17,13 +18,66 @@ pub fn derive_enum(input: TokenStream) -> TokenStream {
 
     // The `Enum` trait is super simple: variant names are mapped to their index in the declaration
     // order, and the names are either just the variant name or a `#[name = "..."]` attribute in
-    // case the name should contain a space.
-    let mut variant_names = Vec::new();
-    // IDs are optional, but they must either be set for all variants or for none of them
-    let mut variant_ids = Vec::new();
-    let mut to_index_tokens = Vec::new();
-    let mut from_index_tokens = Vec::new();
-    for (variant_idx, variant) in variants.iter().enumerate() {
+    // case the name should contain a space. The index can be pinned with a `#[value = N]`
+    // attribute so reordering the variants doesn't change the values stored in presets. With the
+    // `#[use_discriminants]` attribute on the enum, explicit discriminants are used as the indices
+    // instead. Variants marked with `#[skip]` are hidden from the parameter entirely.
+    // Display names can also be computed at runtime with a `#[name_fn = path::to::function]`
+    // attribute on either a variant or the enum itself.
+    //
+    // These are `(index, variant, name, id, name_fn)` tuples, sorted by index after parsing all
+    // variants
+    let mut indexed_variants: Vec<(
+        usize,
+        &syn::Variant,
+        String,
+        Option<String>,
+        Option<syn::Path>,
+    )> = Vec::new();
+    // Skipped variants are converted to the index of the closest preceding variant that isn't
+    // skipped. That's the position of that variant in `indexed_variants`, or `None` if there is no
+    // such variant.
+    let mut skipped_variants: Vec<(&syn::Variant, Option<usize>)> = Vec::new();
+    // IDs are optional, but they must either be set for all (non-skipped) variants or for none of
+    // them
+    let mut has_ids = false;
+    // This function is called with the variant for every variant without its own `name_fn`
+    let mut enum_name_fn_attr: Option<syn::Path> = None;
+    // Discriminants are ignored unless this is set, since using them would otherwise change the
+    // indices of existing enums with explicit discriminants
+    let mut use_discriminants_attr = false;
+    for attr in &ast.attrs {
+        if attr.path.is_ident("use_discriminants") {
+            match attr.parse_meta() {
+                Ok(syn::Meta::Path(_)) if !use_discriminants_attr => use_discriminants_attr = true,
+                Ok(syn::Meta::Path(_)) => {
+                    return syn::Error::new(attr.span(), "Duplicate use_discriminants attribute")
+                        .to_compile_error()
+                        .into()
+                }
+                _ => {
+                    return syn::Error::new(
+                        attr.span(),
+                        "The use_discriminants attribute does not take any arguments: \
+                         #[use_discriminants]",
+                    )
+                    .to_compile_error()
+                    .into()
+                }
+            };
+        } else if attr.path.is_ident("name_fn") {
+            match (&enum_name_fn_attr, parse_name_fn_attr(attr)) {
+                (None, Ok(path)) => enum_name_fn_attr = Some(path),
+                (Some(_), _) => {
+                    return syn::Error::new(attr.span(), "Duplicate name_fn attribute")
+                        .to_compile_error()
+                        .into()
+                }
+                (None, Err(err)) => return err.to_compile_error().into(),
+            }
+        }
+    }
+    for variant in variants.iter() {
         if !variant.fields.is_empty() {
             return syn::Error::new(variant.span(), "Variants cannot have any fields")
                 .to_compile_error()
@@ -31,7 +85,10 @@ pub fn derive_enum(input: TokenStream) -> TokenStream {
         }
 
         let mut name_attr: Option<String> = None;
+        let mut name_fn_attr: Option<syn::Path> = None;
         let mut id_attr: Option<String> = None;
+        let mut value_attr: Option<usize> = None;
+        let mut skip_attr = false;
         for attr in &variant.attrs {
             if attr.path.is_ident("name") {
                 match attr.parse_meta() {
@@ -57,6 +114,16 @@ pub fn derive_enum(input: TokenStream) -> TokenStream {
                         .into()
                     }
                 };
+            } else if attr.path.is_ident("name_fn") {
+                match (&name_fn_attr, parse_name_fn_attr(attr)) {
+                    (None, Ok(path)) => name_fn_attr = Some(path),
+                    (Some(_), _) => {
+                        return syn::Error::new(attr.span(), "Duplicate name_fn attribute")
+                            .to_compile_error()
+                            .into()
+                    }
+                    (None, Err(err)) => return err.to_compile_error().into(),
+                }
             } else if attr.path.is_ident("id") {
                 match attr.parse_meta() {
                     Ok(syn::Meta::NameValue(syn::MetaNameValue {
@@ -81,15 +148,68 @@ pub fn derive_enum(input: TokenStream) -> TokenStream {
                         .into()
                     }
                 };
+            } else if attr.path.is_ident("value") {
+                match attr.parse_meta() {
+                    Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                        lit: syn::Lit::Int(i),
+                        ..
+                    })) => match (value_attr, i.base10_parse::<usize>()) {
+                        (None, Ok(value)) => value_attr = Some(value),
+                        (Some(_), _) => {
+                            return syn::Error::new(attr.span(), "Duplicate value attribute")
+                                .to_compile_error()
+                                .into()
+                        }
+                        (None, Err(err)) => return err.to_compile_error().into(),
+                    },
+                    _ => {
+                        return syn::Error::new(
+                            attr.span(),
+                            "The value attribute should be a key-value pair with an integer \
+                             argument: #[value = 3]",
+                        )
+                        .to_compile_error()
+                        .into()
+                    }
+                };
+            } else if attr.path.is_ident("skip") {
+                match attr.parse_meta() {
+                    Ok(syn::Meta::Path(_)) if !skip_attr => skip_attr = true,
+                    Ok(syn::Meta::Path(_)) => {
+                        return syn::Error::new(attr.span(), "Duplicate skip attribute")
+                            .to_compile_error()
+                            .into()
+                    }
+                    _ => {
+                        return syn::Error::new(
+                            attr.span(),
+                            "The skip attribute does not take any arguments: #[skip]",
+                        )
+                        .to_compile_error()
+                        .into()
+                    }
+                };
             }
         }
 
-        // IDs must either be set for all variants or for none of them
-        match (id_attr, variant_idx == 0, variant_ids.is_empty()) {
-            (Some(id), true, true) | (Some(id), false, false) => {
-                variant_ids.push(id);
+        if skip_attr {
+            if value_attr.is_some() {
+                return syn::Error::new(
+                    variant.span(),
+                    "Skipped variants cannot have a value attribute",
+                )
+                .to_compile_error()
+                .into();
             }
-            (None, _, true) => (),
+
+            skipped_variants.push((variant, indexed_variants.len().checked_sub(1)));
+            continue;
+        }
+
+        // IDs must either be set for all variants or for none of them
+        match (&id_attr, indexed_variants.is_empty(), has_ids) {
+            (Some(_), true, false) => has_ids = true,
+            (Some(_), false, true) | (None, _, false) => (),
             _ => {
                 return syn::Error::new(
                     variant.span(),
@@ -100,28 +220,169 @@ pub fn derive_enum(input: TokenStream) -> TokenStream {
             }
         }
 
-        match name_attr {
-            Some(name) => variant_names.push(name),
-            None => variant_names.push(variant.ident.to_string()),
+        // With `#[use_discriminants]`, integer literal discriminants like `Foo = 3` are used as the
+        // index just like the value attribute. Other discriminant expressions are ignored.
+        let discriminant_value = match &variant.discriminant {
+            Some(_) if !use_discriminants_attr => None,
+            Some((
+                _,
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(i),
+                    ..
+                }),
+            )) => match i.base10_parse::<usize>() {
+                Ok(value) => Some(value),
+                Err(err) => return err.to_compile_error().into(),
+            },
+            _ => None,
+        };
+        if value_attr.is_some() && discriminant_value.is_some() {
+            return syn::Error::new(
+                variant.span(),
+                "Variants with an explicit discriminant cannot also have a value attribute",
+            )
+            .to_compile_error()
+            .into();
+        }
+
+        // Variants without a value attribute or discriminant use their declaration order index,
+        // not counting the skipped variants
+        let index = value_attr
+            .or(discriminant_value)
+            .unwrap_or(indexed_variants.len());
+        if let Some((_, other_variant, _, _, _)) = indexed_variants
+            .iter()
+            .find(|(other_index, _, _, _, _)| *other_index == index)
+        {
+            return syn::Error::new(
+                variant.span(),
+                format!(
+                    "Variant {} has the same value as {} ({index})",
+                    variant.ident, other_variant.ident
+                ),
+            )
+            .to_compile_error()
+            .into();
         }
 
+        let name = name_attr.unwrap_or_else(|| variant.ident.to_string());
+        indexed_variants.push((index, variant, name, id_attr, name_fn_attr));
+    }
+
+    if indexed_variants.is_empty() {
+        return syn::Error::new(
+            struct_name.span(),
+            "At least one variant must not be skipped",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    // The indices are also the parameter's plain values, so they need to start at zero without any
+    // gaps. Since they're unique, this is the case when none of them are out of range.
+    let num_indices = indexed_variants.len();
+    if let Some((index, variant, _, _, _)) = indexed_variants
+        .iter()
+        .find(|(index, _, _, _, _)| *index >= num_indices)
+    {
+        return syn::Error::new(
+            variant.span(),
+            format!(
+                "Variant {} has value {index}, but the values need to range from 0 to {}",
+                variant.ident,
+                num_indices - 1
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    // If a skipped variant comes before all other variants, then the first variant after it is
+    // used instead
+    let mut to_index_tokens = Vec::new();
+    for (variant, preceding_variant_pos) in skipped_variants {
+        let (index, _, _, _, _) = &indexed_variants[preceding_variant_pos.unwrap_or(0)];
         let variant_ident = &variant.ident;
-        to_index_tokens.push(quote! { #struct_name::#variant_ident => #variant_idx, });
-        from_index_tokens.push(quote! { #variant_idx => #struct_name::#variant_ident, });
+        to_index_tokens.push(quote! { #struct_name::#variant_ident => #index, });
     }
+    indexed_variants.sort_by_key(|(index, _, _, _, _)| *index);
 
-    let ids_tokens = if variant_ids.is_empty() {
-        quote! { None }
-    } else {
+    let mut variant_names = Vec::new();
+    let mut variant_ids = Vec::new();
+    let mut variant_idents = Vec::new();
+    let mut from_index_tokens = Vec::new();
+    let mut variant_name_tokens = Vec::new();
+    for (index, variant, name, id, name_fn) in &indexed_variants {
+        variant_names.push(name);
+        variant_idents.push(&variant.ident);
+        if let Some(id) = id {
+            variant_ids.push(id);
+        }
+
+        let variant_ident = &variant.ident;
+        to_index_tokens.push(quote! { #struct_name::#variant_ident => #index, });
+        from_index_tokens.push(quote! { #index => #struct_name::#variant_ident, });
+        variant_name_tokens.push(match (name_fn, &enum_name_fn_attr) {
+            (Some(name_fn), _) => quote! { #index => #name_fn(), },
+            (None, Some(enum_name_fn)) => {
+                quote! { #index => #enum_name_fn(#struct_name::#variant_ident), }
+            }
+            (None, None) => quote! { #index => String::from(#name), },
+        });
+    }
+
+    let ids_tokens = if has_ids {
         quote! { Some(&[#(#variant_ids),*]) }
+    } else {
+        quote! { None }
     };
 
-    let from_index_default_tokens = variants.first().map(|v| {
+    let from_index_default_tokens = indexed_variants.first().map(|(_, v, _, _, _)| {
         let variant_ident = &v.ident;
         quote! { _ => #struct_name::#variant_ident, }
     });
 
+    // The trait's default implementation already returns the static names, so this only needs to
+    // be overridden when the names are computed at runtime
+    let uses_name_fn = enum_name_fn_attr.is_some()
+        || indexed_variants
+            .iter()
+            .any(|(_, _, _, _, name_fn)| name_fn.is_some());
+    let variant_name_fn_tokens = if uses_name_fn {
+        quote! {
+            fn variant_name(index: usize) -> String {
+                match index {
+                    #(#variant_name_tokens)*
+                    _ => Self::variant_name(0),
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // These are also useful outside of parameters, so they're defined directly on the enum
+    let num_variants = indexed_variants.len();
+    let variants_len_doc = format!(
+        "The number of variants returned by `{struct_name}::all()`. Variants marked with \
+         `#[skip]` are not included. This can be used to size arrays."
+    );
+    let all_doc = format!(
+        "All of `{struct_name}`'s variants in the same order as `Enum::variants()`. Variants \
+         marked with `#[skip]` are not included."
+    );
+
     quote! {
+        impl #struct_name {
+            #[doc = #variants_len_doc]
+            #struct_vis const VARIANTS_LEN: usize = #num_variants;
+
+            #[doc = #all_doc]
+            #struct_vis const fn all() -> &'static [Self] {
+                &[#(#struct_name::#variant_idents),*]
+            }
+        }
+
         impl Enum for #struct_name {
             fn variants() -> &'static [&'static str] {
                 &[#(#variant_names),*]
@@ -143,7 +404,31 @@ pub fn derive_enum(input: TokenStream) -> TokenStream {
                     #from_index_default_tokens
                 }
             }
+
+            #variant_name_fn_tokens
         }
     }
     .into()
 }
+
+/// Parse a `#[name_fn = path::to::function]` attribute. `Attribute::parse_meta()` only accepts
+/// literals in key-value pairs, so this needs to be parsed manually.
+fn parse_name_fn_attr(attr: &syn::Attribute) -> syn::Result<syn::Path> {
+    let parser = |input: syn::parse::ParseStream| {
+        input.parse::<syn::Token![=]>()?;
+        let path = input.parse::<syn::Path>()?;
+        if input.is_empty() {
+            Ok(path)
+        } else {
+            Err(input.error("Unexpected tokens after the function path"))
+        }
+    };
+
+    syn::parse::Parser::parse2(parser, attr.tokens.clone()).map_err(|_| {
+        syn::Error::new(
+            attr.span(),
+            "The name_fn attribute should be a key-value pair with a function path: \
+             #[name_fn = path::to::function]",
+        )
+    })
+}
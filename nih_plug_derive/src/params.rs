@@ -6,7 +6,56 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
     let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
     let struct_name = &ast.ident;
+    let struct_vis = &ast.vis;
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    // The optional `#[snapshot(FooParamsSnapshot)]` attribute on the struct itself generates a
+    // snapshot struct with that name containing the plain values of all of this struct's
+    // parameters
+    let mut snapshot_name: Option<syn::Ident> = None;
+    for attr in &ast.attrs {
+        if !attr.path.is_ident("snapshot") {
+            continue;
+        }
+
+        match attr.parse_meta() {
+            Ok(syn::Meta::List(syn::MetaList { nested, .. })) if nested.len() == 1 => {
+                match nested.first() {
+                    Some(syn::NestedMeta::Meta(syn::Meta::Path(path)))
+                        if snapshot_name.is_none() && path.get_ident().is_some() =>
+                    {
+                        snapshot_name = path.get_ident().cloned();
+                    }
+                    _ => {
+                        return syn::Error::new(
+                            attr.span(),
+                            "The snapshot attribute should contain the snapshot struct's name \
+                             and it can only be used once: #[snapshot(FooParamsSnapshot)]",
+                        )
+                        .to_compile_error()
+                        .into()
+                    }
+                }
+            }
+            _ => {
+                return syn::Error::new(
+                    attr.span(),
+                    "The snapshot attribute should contain the snapshot struct's name: \
+                     #[snapshot(FooParamsSnapshot)]",
+                )
+                .to_compile_error()
+                .into()
+            }
+        }
+    }
+    if snapshot_name.is_some() && !ast.generics.params.is_empty() {
+        return syn::Error::new(
+            ast.generics.span(),
+            "The snapshot attribute cannot be used on generic structs",
+        )
+        .to_compile_error()
+        .into();
+    }
     let fields = match ast.data {
         syn::Data::Struct(syn::DataStruct {
             fields: syn::Fields::Named(named_fields),
@@ -40,10 +89,51 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
         };
 
         // All attributes are mutually exclusive. If we encounter multiple or duplicate attributes,
-        // then we'll error out.
+        // then we'll error out. The only exception is the `group` attribute, which can be added to
+        // `id` fields to place that parameter in a group.
         let mut processed_attribute = false;
+        let mut single_param_idx: Option<usize> = None;
+        let mut single_param_group: Option<syn::LitStr> = None;
         for attr in &field.attrs {
-            if attr.path.is_ident("id") {
+            if attr.path.is_ident("group") {
+                match attr.parse_meta() {
+                    Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                        lit: syn::Lit::Str(s),
+                        ..
+                    })) => {
+                        if single_param_group.is_some() {
+                            return syn::Error::new(attr.span(), "Duplicate group attribute found")
+                                .to_compile_error()
+                                .into();
+                        }
+
+                        let group_name = s.value();
+                        if group_name.is_empty() {
+                            return syn::Error::new(attr.span(), "Group names cannot be empty")
+                                .to_compile_error()
+                                .into();
+                        } else if group_name.contains('/') {
+                            return syn::Error::new(
+                                attr.span(),
+                                "Group names may not contain slashes",
+                            )
+                            .to_compile_error()
+                            .into();
+                        }
+
+                        single_param_group = Some(s);
+                    }
+                    _ => {
+                        return syn::Error::new(
+                            attr.span(),
+                            "The group attribute should be a key-value pair with a string \
+                             argument: #[group = \"Group Name\"]",
+                        )
+                        .to_compile_error()
+                        .into()
+                    }
+                };
+            } else if attr.path.is_ident("id") {
                 match attr.parse_meta() {
                     Ok(syn::Meta::NameValue(syn::MetaNameValue {
                         lit: syn::Lit::Str(s),
@@ -73,9 +163,13 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
                             .into();
                         }
 
+                        single_param_idx = Some(params.len());
                         params.push(Param::Single {
                             id: s,
                             field: field_name.clone(),
+                            ty: field.ty.clone(),
+                            vis: field.vis.clone(),
+                            group: None,
                         });
 
                         processed_attribute = true;
@@ -246,6 +340,24 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
                 processed_attribute = true;
             }
         }
+
+        // The group can be specified before or after the ID, so it's assigned once all of the
+        // field's attributes have been processed
+        if let Some(group_name) = single_param_group {
+            match single_param_idx.map(|idx| &mut params[idx]) {
+                Some(Param::Single { group, .. }) => *group = Some(group_name),
+                _ => {
+                    return syn::Error::new(
+                        group_name.span(),
+                        "The group attribute can only be used on parameter fields with an id \
+                         attribute. Use #[nested(group = \"Group Name\")] to group nested \
+                         parameters.",
+                    )
+                    .to_compile_error()
+                    .into()
+                }
+            }
+        }
     }
 
     // The next step is build the gathered information into tokens that can be spliced into a
@@ -412,7 +524,51 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
         (serialize_fields_tokens, deserialize_fields_tokens)
     };
 
+    let snapshot_tokens = match snapshot_name {
+        Some(snapshot_name) => {
+            let (snapshot_field_tokens, snapshot_init_tokens): (Vec<_>, Vec<_>) = params
+                .iter()
+                .filter_map(|p| match p {
+                    Param::Single { field, ty, vis, .. } => Some((
+                        quote! { #vis #field: <#ty as ::nih_plug::params::Param>::Plain },
+                        quote! { #field: self.#field.smoothed_plain_value() },
+                    )),
+                    Param::Nested(_) => None,
+                })
+                .unzip();
+            let snapshot_doc = format!(
+                "The plain values of [`{struct_name}`]'s parameters at the time \
+                 [`{struct_name}::snapshot()`] was called."
+            );
+
+            quote! {
+                #[doc = #snapshot_doc]
+                #[allow(dead_code)]
+                #struct_vis struct #snapshot_name {
+                    #(#snapshot_field_tokens),*
+                }
+
+                impl #struct_name {
+                    /// Read the current smoothed value of all parameters on this struct. Does not
+                    /// advance the parameters' smoothers.
+                    #[allow(dead_code)]
+                    #struct_vis fn snapshot(&self) -> #snapshot_name {
+                        // This may not be in scope otherwise
+                        use ::nih_plug::params::Param;
+
+                        #snapshot_name {
+                            #(#snapshot_init_tokens),*
+                        }
+                    }
+                }
+            }
+        }
+        None => quote! {},
+    };
+
     quote! {
+        #snapshot_tokens
+
         unsafe impl #impl_generics Params for #struct_name #ty_generics #where_clause {
             fn param_map(&self) -> Vec<(String, nih_plug::prelude::ParamPtr, String)> {
                 #param_map_tokens
@@ -440,6 +596,13 @@ enum Param {
         field: syn::Ident,
         /// The parameter's unique ID.
         id: syn::LitStr,
+        /// The field's type. Used for the snapshot struct.
+        ty: syn::Type,
+        /// The field's visibility. The snapshot struct's fields get the same visibility.
+        vis: syn::Visibility,
+        /// The group set using the `#[group = "..."]` attribute, if any. Parameters without a group
+        /// are placed at the top level.
+        group: Option<syn::LitStr>,
     },
     /// Another struct also implementing `Params`. This object's parameters are inlined in the
     /// parameter list.
@@ -451,7 +614,20 @@ impl Param {
     /// parameter map.
     fn param_map_tokens(&self) -> proc_macro2::TokenStream {
         match self {
-            Param::Single { field, id } => {
+            Param::Single {
+                field,
+                id,
+                group: Some(group),
+                ..
+            } => {
+                quote! { [(String::from(#id), self.#field.as_ptr(), String::from(#group))] }
+            }
+            Param::Single {
+                field,
+                id,
+                group: None,
+                ..
+            } => {
                 quote! { [(String::from(#id), self.#field.as_ptr(), String::new())] }
             }
             Param::Nested(params) => params.param_map_tokens(),
@@ -0,0 +1,268 @@
+use nih_plug::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+enum DeclarationOrder {
+    Foo,
+    #[name = "Bar Baz"]
+    Bar,
+    Qux,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+enum PinnedValues {
+    #[value = 2]
+    Foo,
+    #[name = "Bar Baz"]
+    #[value = 0]
+    Bar,
+    // This uses its declaration order index
+    Qux,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+enum PinnedValuesWithIds {
+    #[id = "foo"]
+    #[value = 1]
+    Foo,
+    #[id = "bar"]
+    #[value = 0]
+    Bar,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+enum SkippedVariants {
+    #[skip]
+    Old,
+    #[value = 1]
+    Foo,
+    #[skip]
+    OldFoo,
+    #[value = 0]
+    Bar,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[use_discriminants]
+#[repr(u8)]
+enum Discriminants {
+    Foo = 2,
+    Bar = 0,
+    Baz = 1,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[repr(u8)]
+enum IgnoredDiscriminants {
+    Foo = 1,
+    Bar = 2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+enum VariantNameFns {
+    #[name_fn = foo_name]
+    Foo,
+    #[name = "Bar Baz"]
+    Bar,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[name_fn = enum_name]
+enum EnumNameFn {
+    #[value = 1]
+    Foo,
+    #[name = "Bar Baz"]
+    #[value = 0]
+    Bar,
+    #[name_fn = foo_name]
+    #[value = 2]
+    Baz,
+}
+
+fn foo_name() -> String {
+    String::from("Translated Foo")
+}
+
+fn enum_name(value: EnumNameFn) -> String {
+    format!("Translated {value:?}")
+}
+
+mod declaration_order {
+    use super::*;
+
+    #[test]
+    fn indices() {
+        assert_eq!(DeclarationOrder::variants(), ["Foo", "Bar Baz", "Qux"]);
+        assert_eq!(DeclarationOrder::ids(), None);
+        assert_eq!(DeclarationOrder::Foo.to_index(), 0);
+        assert_eq!(DeclarationOrder::Qux.to_index(), 2);
+        assert_eq!(DeclarationOrder::from_index(1), DeclarationOrder::Bar);
+    }
+}
+
+mod pinned_values {
+    use super::*;
+
+    #[test]
+    fn indices() {
+        assert_eq!(PinnedValues::variants(), ["Bar Baz", "Qux", "Foo"]);
+        assert_eq!(PinnedValues::Foo.to_index(), 2);
+        assert_eq!(PinnedValues::Bar.to_index(), 0);
+        assert_eq!(PinnedValues::Qux.to_index(), 1);
+        for index in 0..PinnedValues::variants().len() {
+            assert_eq!(PinnedValues::from_index(index).to_index(), index);
+        }
+    }
+
+    #[test]
+    fn out_of_range_index() {
+        assert_eq!(PinnedValues::from_index(3), PinnedValues::Bar);
+    }
+
+    #[test]
+    fn ids() {
+        assert_eq!(PinnedValuesWithIds::variants(), ["Bar", "Foo"]);
+        assert_eq!(PinnedValuesWithIds::ids(), Some(&["bar", "foo"][..]));
+    }
+
+    #[test]
+    fn param_value() {
+        let param = EnumParam::new("Pinned", PinnedValues::Foo);
+        assert_eq!(param.value(), PinnedValues::Foo);
+        assert_eq!(param.unmodulated_plain_value(), PinnedValues::Foo);
+        assert_eq!(param.to_string(), "Foo");
+    }
+}
+
+mod discriminants {
+    use super::*;
+
+    #[test]
+    fn indices() {
+        assert_eq!(Discriminants::variants(), ["Bar", "Baz", "Foo"]);
+        for variant in [Discriminants::Foo, Discriminants::Bar, Discriminants::Baz] {
+            assert_eq!(variant.to_index(), variant as usize);
+            assert_eq!(Discriminants::from_index(variant as usize), variant);
+        }
+    }
+
+    #[test]
+    fn ignored_without_attribute() {
+        assert_eq!(IgnoredDiscriminants::variants(), ["Foo", "Bar"]);
+        assert_eq!(IgnoredDiscriminants::Foo.to_index(), 0);
+        assert_eq!(IgnoredDiscriminants::Bar.to_index(), 1);
+        assert_eq!(
+            IgnoredDiscriminants::from_index(1),
+            IgnoredDiscriminants::Bar
+        );
+    }
+}
+
+mod skipped_variants {
+    use super::*;
+
+    #[test]
+    fn indices() {
+        assert_eq!(SkippedVariants::variants(), ["Bar", "Foo"]);
+        assert_eq!(SkippedVariants::Bar.to_index(), 0);
+        assert_eq!(SkippedVariants::Foo.to_index(), 1);
+        assert_eq!(SkippedVariants::from_index(0), SkippedVariants::Bar);
+        assert_eq!(SkippedVariants::from_index(1), SkippedVariants::Foo);
+        assert_eq!(SkippedVariants::from_index(2), SkippedVariants::Bar);
+    }
+
+    #[test]
+    fn skipped_to_index() {
+        // This uses the preceding variant's index
+        assert_eq!(SkippedVariants::OldFoo.to_index(), 1);
+        // There is no preceding variant, so the following variant is used instead
+        assert_eq!(SkippedVariants::Old.to_index(), 1);
+    }
+
+    #[test]
+    fn param_value() {
+        let param = EnumParam::new("Skipped", SkippedVariants::OldFoo);
+        assert_eq!(param.value(), SkippedVariants::Foo);
+    }
+}
+
+mod all_variants {
+    use super::*;
+
+    #[test]
+    fn all() {
+        assert_eq!(
+            DeclarationOrder::all(),
+            [
+                DeclarationOrder::Foo,
+                DeclarationOrder::Bar,
+                DeclarationOrder::Qux
+            ]
+        );
+        assert_eq!(
+            PinnedValues::all(),
+            [PinnedValues::Bar, PinnedValues::Qux, PinnedValues::Foo]
+        );
+        assert_eq!(
+            SkippedVariants::all(),
+            [SkippedVariants::Bar, SkippedVariants::Foo]
+        );
+    }
+
+    #[test]
+    fn variants_len() {
+        // This can be used to size arrays
+        let values = [0.0f32; SkippedVariants::VARIANTS_LEN];
+        assert_eq!(values.len(), 2);
+        assert_eq!(
+            DeclarationOrder::VARIANTS_LEN,
+            DeclarationOrder::variants().len()
+        );
+    }
+
+    #[test]
+    fn indices_match() {
+        for (index, variant) in PinnedValues::all().iter().enumerate() {
+            assert_eq!(variant.to_index(), index);
+        }
+    }
+}
+
+mod name_fns {
+    use super::*;
+
+    #[test]
+    fn variant_names() {
+        // The static names are unchanged
+        assert_eq!(VariantNameFns::variants(), ["Foo", "Bar Baz"]);
+        assert_eq!(VariantNameFns::variant_name(0), "Translated Foo");
+        assert_eq!(VariantNameFns::variant_name(1), "Bar Baz");
+        assert_eq!(VariantNameFns::variant_name(2), "Translated Foo");
+        assert_eq!(DeclarationOrder::variant_name(1), "Bar Baz");
+    }
+
+    #[test]
+    fn enum_name_fn() {
+        assert_eq!(EnumNameFn::variants(), ["Bar Baz", "Foo", "Baz"]);
+        assert_eq!(EnumNameFn::variant_name(0), "Translated Bar");
+        assert_eq!(EnumNameFn::variant_name(1), "Translated Foo");
+        // The variant's own function takes precedence
+        assert_eq!(EnumNameFn::variant_name(2), "Translated Foo");
+    }
+
+    #[test]
+    fn param_strings() {
+        let param = EnumParam::new("Names", VariantNameFns::Foo);
+        assert_eq!(param.to_string(), "Translated Foo");
+        assert_eq!(
+            param.normalized_value_to_string(0.0, true),
+            "Translated Foo"
+        );
+        assert_eq!(
+            param.string_to_normalized_value("Translated Foo"),
+            Some(0.0)
+        );
+        // The static names can still be parsed
+        assert_eq!(param.string_to_normalized_value("Foo"), Some(0.0));
+        assert_eq!(param.string_to_normalized_value("Bar Baz"), Some(1.0));
+    }
+}
@@ -109,6 +109,66 @@ impl Default for NestedArrayParams {
     }
 }
 
+#[derive(Params)]
+struct SingleGroupedParams {
+    #[id = "one"]
+    #[group = "Global"]
+    pub one: BoolParam,
+
+    #[group = "Some Group"]
+    #[id = "two"]
+    pub two: FloatParam,
+
+    #[id = "three"]
+    pub three: IntParam,
+
+    #[nested(group = "Some Group", id_prefix = "nested")]
+    pub nested: FlatParams,
+
+    #[id = "four"]
+    #[group = "Some Group"]
+    pub four: IntParam,
+}
+
+impl Default for SingleGroupedParams {
+    fn default() -> Self {
+        SingleGroupedParams {
+            one: BoolParam::new("one", true),
+            two: FloatParam::new("two", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            three: IntParam::new("three", 0, IntRange::Linear { min: 0, max: 100 }),
+            nested: FlatParams::default(),
+            four: IntParam::new("four", 0, IntRange::Linear { min: 0, max: 100 }),
+        }
+    }
+}
+
+#[derive(Params)]
+#[snapshot(SnapshotParamsSnapshot)]
+struct SnapshotParams {
+    #[id = "one"]
+    pub one: BoolParam,
+
+    #[id = "two"]
+    pub two: FloatParam,
+
+    #[nested(id_prefix = "nested")]
+    pub nested: FlatParams,
+
+    #[id = "three"]
+    pub three: IntParam,
+}
+
+impl Default for SnapshotParams {
+    fn default() -> Self {
+        SnapshotParams {
+            one: BoolParam::new("one", true),
+            two: FloatParam::new("two", 0.25, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            nested: FlatParams::default(),
+            three: IntParam::new("three", 42, IntRange::Linear { min: 0, max: 100 }),
+        }
+    }
+}
+
 mod param_order {
     use super::*;
 
@@ -315,4 +375,42 @@ mod param_groups {
             ]
         );
     }
+
+    #[test]
+    fn single_grouped() {
+        let p = SingleGroupedParams::default();
+
+        // The order should not change, and ungrouped parameters stay at the top level
+        let param_ids_groups: Vec<(String, String)> = p
+            .param_map()
+            .into_iter()
+            .map(|(id, _, group)| (id, group))
+            .collect();
+        assert_eq!(
+            param_ids_groups,
+            [
+                (String::from("one"), String::from("Global")),
+                (String::from("two"), String::from("Some Group")),
+                (String::from("three"), String::new()),
+                (String::from("nested_one"), String::from("Some Group")),
+                (String::from("nested_two"), String::from("Some Group")),
+                (String::from("nested_three"), String::from("Some Group")),
+                (String::from("four"), String::from("Some Group")),
+            ]
+        );
+    }
+}
+
+mod snapshot {
+    use super::*;
+
+    #[test]
+    fn default_values() {
+        let p = SnapshotParams::default();
+        let snapshot = p.snapshot();
+
+        assert!(snapshot.one);
+        assert_eq!(snapshot.two, 0.25);
+        assert_eq!(snapshot.three, 42);
+    }
 }
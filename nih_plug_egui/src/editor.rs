@@ -70,10 +70,14 @@ where
 
         let (unscaled_width, unscaled_height) = self.egui_state.size();
         let scaling_factor = self.scaling_factor.load();
+        let title = self
+            .egui_state
+            .window_title()
+            .unwrap_or_else(|| context.plugin_name().to_owned());
         let window = EguiWindow::open_parented(
             &ParentWindowHandleAdapter(parent),
             WindowOpenOptions {
-                title: String::from("egui window"),
+                title,
                 // Baseview should be doing the DPI scaling for us
                 size: Size::new(unscaled_width as f64, unscaled_height as f64),
                 // NOTE: For some reason passing 1.0 here causes the UI to be scaled on macOS but
@@ -66,6 +66,10 @@ pub struct EguiState {
     /// The window's size in logical pixels before applying `scale_factor`.
     #[serde(with = "nih_plug::params::persist::serialize_atomic_cell")]
     size: AtomicCell<(u32, u32)>,
+    /// The title for the editor's window, if one has been set with
+    /// [`set_window_title()`][Self::set_window_title()]. This is not persisted.
+    #[serde(skip)]
+    window_title: RwLock<Option<String>>,
     /// Whether the editor's window is currently open.
     #[serde(skip)]
     open: AtomicBool,
@@ -90,6 +94,7 @@ impl EguiState {
     pub fn from_size(width: u32, height: u32) -> Arc<EguiState> {
         Arc::new(EguiState {
             size: AtomicCell::new((width, height)),
+            window_title: RwLock::new(None),
             open: AtomicBool::new(false),
         })
     }
@@ -99,6 +104,17 @@ impl EguiState {
         self.size.load()
     }
 
+    /// Set the title for the editor's window instead of using the plugin's name. The title is read
+    /// when the editor opens, so a window that's already open keeps its old title.
+    pub fn set_window_title(&self, title: impl Into<String>) {
+        *self.window_title.write() = Some(title.into());
+    }
+
+    /// The title set with [`set_window_title()`][Self::set_window_title()], if any.
+    pub fn window_title(&self) -> Option<String> {
+        self.window_title.read().clone()
+    }
+
     /// Whether the GUI is currently visible.
     // Called `is_open()` instead of `open()` to avoid the ambiguity.
     pub fn is_open(&self) -> bool {
@@ -1,28 +1,29 @@
 //! And [`Editor`] implementation for iced.
 
 use baseview::{WindowOpenOptions, WindowScalePolicy};
-use crossbeam::atomic::AtomicCell;
 use crossbeam::channel;
 pub use iced_baseview::*;
+use nih_plug::debug::*;
 use nih_plug::prelude::{Editor, GuiContext, ParentWindowHandle};
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::atomic::Ordering;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, RwLock};
 
 use crate::{wrapper, IcedEditor, IcedState, ParameterUpdate};
 
 /// An [`Editor`] implementation that renders an iced [`Application`].
 pub(crate) struct IcedEditorWrapper<E: IcedEditor> {
     pub(crate) iced_state: Arc<IcedState>,
-    pub(crate) initialization_flags: E::InitializationFlags,
+    /// Creates the initialization flags passed to the `IcedEditor` every time the editor is
+    /// opened.
+    pub(crate) initialization_flags_factory:
+        Arc<dyn Fn() -> E::InitializationFlags + 'static + Send + Sync>,
 
-    /// The scaling factor reported by the host, if any. On macOS this will never be set and we
-    /// should use the system scaling factor instead.
-    pub(crate) scaling_factor: AtomicCell<Option<f32>>,
-
-    /// A subscription for sending messages about parameter updates to the `IcedEditor`.
-    pub(crate) parameter_updates_sender: channel::Sender<ParameterUpdate>,
-    pub(crate) parameter_updates_receiver: Arc<channel::Receiver<ParameterUpdate>>,
+    /// Used to send messages about parameter updates to the `IcedEditor`s. Every open editor
+    /// window has its own channel, so every window gets redrawn when a parameter changes.
+    pub(crate) parameter_updates_senders: Arc<RwLock<Vec<channel::Sender<ParameterUpdate>>>>,
 }
 
 /// This version of `baseview` uses a different version of `raw_window_handle than NIH-plug, so we
@@ -57,66 +58,37 @@ impl<E: IcedEditor> Editor for IcedEditorWrapper<E> {
         parent: ParentWindowHandle,
         context: Arc<dyn GuiContext>,
     ) -> Box<dyn std::any::Any + Send> {
-        let (unscaled_width, unscaled_height) = self.iced_state.size();
-        let scaling_factor = self.scaling_factor.load();
-
-        // TODO: iced_baseview does not have gracefuly error handling for context creation failures.
-        //       This will panic if the context could not be created.
-        let window = IcedWindow::<wrapper::IcedEditorWrapperApplication<E>>::open_parented(
-            &ParentWindowHandleAdapter(parent),
-            Settings {
-                window: WindowOpenOptions {
-                    title: String::from("iced window"),
-                    // Baseview should be doing the DPI scaling for us
-                    size: baseview::Size::new(unscaled_width as f64, unscaled_height as f64),
-                    // NOTE: For some reason passing 1.0 here causes the UI to be scaled on macOS but
-                    //       not the mouse events.
-                    scale: scaling_factor
-                        .map(|factor| WindowScalePolicy::ScaleFactor(factor as f64))
-                        .unwrap_or(WindowScalePolicy::SystemScaleFactor),
-
-                    #[cfg(feature = "opengl")]
-                    gl_config: Some(baseview::gl::GlConfig {
-                        // FIXME: glow_glyph forgot to add an `#extension`, so this won't work under
-                        //        OpenGL 3.2 at the moment. With that change applied this should work on
-                        //        OpenGL 3.2/macOS.
-                        version: (3, 3),
-                        red_bits: 8,
-                        blue_bits: 8,
-                        green_bits: 8,
-                        alpha_bits: 8,
-                        depth_bits: 24,
-                        stencil_bits: 8,
-                        samples: None,
-                        srgb: true,
-                        double_buffer: true,
-                        vsync: true,
-                        ..Default::default()
-                    }),
-                    // FIXME: Rust analyzer always thinks baseview/opengl is enabled even if we
-                    //        don't explicitly enable it, so you'd get a compile error if this line
-                    //        is missing
-                    #[cfg(not(feature = "opengl"))]
-                    gl_config: None,
-                },
-                iced_baseview: IcedBaseviewSettings {
-                    ignore_non_modifier_keys: false,
-                    always_redraw: true,
-                },
-                // We use this wrapper to be able to pass the GUI context to the editor
-                flags: (
-                    context,
-                    self.parameter_updates_receiver.clone(),
-                    self.initialization_flags.clone(),
-                ),
-            },
-        );
-
-        self.iced_state.open.store(true, Ordering::Release);
-        Box::new(IcedEditorHandle {
+        // We need some way to communicate parameter changes to the `IcedEditor` since parameter
+        // updates come from outside of the editor's reactive model. This contains only capacity to
+        // store only one parameter update, since we're only storing _that_ a parameter update has
+        // happened and not which parameter so we'd need to redraw the entire GUI either way.
+        let (parameter_updates_sender, parameter_updates_receiver) = channel::bounded(1);
+
+        let editor_window = Arc::new(EditorWindow {
+            parent: ParentWindowHandleAdapter(parent),
+            context,
             iced_state: self.iced_state.clone(),
-            window,
-        })
+            initialization_flags_factory: self.initialization_flags_factory.clone(),
+            parameter_updates_receiver: Arc::new(parameter_updates_receiver),
+            window: Mutex::new(None),
+        });
+
+        match editor_window.open() {
+            Some(window) => {
+                *editor_window.window.lock().unwrap() = Some(window);
+
+                Box::new(IcedEditorHandle {
+                    _open_guard: IcedStateOpenGuard::new(self.iced_state.clone()),
+                    _parameter_updates_registration: ParameterUpdatesRegistration::new(
+                        self.parameter_updates_senders.clone(),
+                        parameter_updates_sender,
+                    ),
+                    editor_window,
+                })
+            }
+            // The editor is never marked as open, and there's no window to close
+            None => Box::new(()),
+        }
     }
 
     fn size(&self) -> (u32, u32) {
@@ -130,40 +102,416 @@ impl<E: IcedEditor> Editor for IcedEditorWrapper<E> {
             return false;
         }
 
-        self.scaling_factor.store(Some(factor));
+        self.iced_state.scale_factor.store(Some(factor));
         true
     }
 
     fn param_value_changed(&self, _id: &str, _normalized_value: f32) {
-        // If there's already a paramter change notification in the channel then we don't need
-        // to do anything else. This avoids queueing up redundant GUI redraws.
         // NOTE: We could add an event containing the parameter's ID and the normalized value, but
         //       these events aren't really necessary for Vizia.
-        let _ = self.parameter_updates_sender.try_send(ParameterUpdate);
+        self.send_parameter_update();
     }
 
     fn param_modulation_changed(&self, _id: &str, _modulation_offset: f32) {
-        let _ = self.parameter_updates_sender.try_send(ParameterUpdate);
+        self.send_parameter_update();
     }
 
     fn param_values_changed(&self) {
-        let _ = self.parameter_updates_sender.try_send(ParameterUpdate);
+        self.send_parameter_update();
+    }
+}
+
+impl<E: IcedEditor> IcedEditorWrapper<E> {
+    /// Notify every open editor window that a parameter has changed.
+    fn send_parameter_update(&self) {
+        // If there's already a paramter change notification in a window's channel then we don't
+        // need to do anything else for that window. This avoids queueing up redundant GUI redraws.
+        for sender in self.parameter_updates_senders.read().unwrap().iter() {
+            let _ = sender.try_send(ParameterUpdate);
+        }
     }
 }
 
+/// Get the message from a panic's payload, if it has one. Panics created with `panic!()` either
+/// contain a `&'static str` or a `String`.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&'static str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown error")
+}
+
 /// The window handle used for [`IcedEditorWrapper`].
-struct IcedEditorHandle<Message: 'static + Send> {
+struct IcedEditorHandle<E: IcedEditor> {
+    /// Marks the editor as closed again after the window has been closed.
+    _open_guard: IcedStateOpenGuard,
+    /// Stops sending parameter updates to the window after it has been closed.
+    _parameter_updates_registration: ParameterUpdatesRegistration,
+    editor_window: Arc<EditorWindow<E>>,
+}
+
+/// Opens an editor window, and reopens it when the editor gets resized. This version of
+/// `iced_baseview` cannot resize an open window, so the window is replaced by a new window with the
+/// new size instead. The new window gets a new [`IcedEditor`] instance.
+pub(crate) struct EditorWindow<E: IcedEditor> {
+    parent: ParentWindowHandleAdapter,
+    pub(crate) context: Arc<dyn GuiContext>,
+    pub(crate) iced_state: Arc<IcedState>,
+    initialization_flags_factory: Arc<dyn Fn() -> E::InitializationFlags + 'static + Send + Sync>,
+    /// Every window opened for the same [`IcedEditorHandle`] reuses the same channel, since the
+    /// channel's sender is registered with [`IcedEditorWrapper`] for as long as the handle exists.
+    pub(crate) parameter_updates_receiver: Arc<channel::Receiver<ParameterUpdate>>,
+
+    /// The currently open window. This is `None` once the editor has been closed.
+    window: Mutex<Option<iced_baseview::WindowHandle<wrapper::Message<E>>>>,
+}
+
+/// The parent window handle and the window handle enum stored within 'WindowHandle' contain raw
+/// pointers. Is there a way around having this requirement?
+unsafe impl<E: IcedEditor> Send for EditorWindow<E> {}
+unsafe impl<E: IcedEditor> Sync for EditorWindow<E> {}
+
+impl<E: IcedEditor> EditorWindow<E> {
+    /// Open a new window with the size currently stored in the [`IcedState`]. This does not
+    /// replace the current window. Returns `None` if the window could not be opened.
+    fn open(self: &Arc<Self>) -> Option<iced_baseview::WindowHandle<wrapper::Message<E>>> {
+        let (unscaled_width, unscaled_height) = self.iced_state.size();
+        let scaling_factor = self.iced_state.scale_factor();
+        let title = self
+            .iced_state
+            .window_title()
+            .unwrap_or_else(|| self.context.plugin_name().to_owned());
+
+        // iced_baseview panics when it cannot create the graphics context, for instance because
+        // OpenGL 3.3 is not available on older versions of macOS. Unwinding into the host would
+        // likely crash it, so the panic is caught here and the editor window stays empty instead.
+        let window = panic::catch_unwind(AssertUnwindSafe(|| {
+            IcedWindow::<wrapper::IcedEditorWrapperApplication<E>>::open_parented(
+                &self.parent,
+                Settings {
+                    window: WindowOpenOptions {
+                        title,
+                        // Baseview should be doing the DPI scaling for us
+                        size: baseview::Size::new(unscaled_width as f64, unscaled_height as f64),
+                        // NOTE: For some reason passing 1.0 here causes the UI to be scaled on
+                        //       macOS but not the mouse events.
+                        scale: scaling_factor
+                            .map(|factor| WindowScalePolicy::ScaleFactor(factor as f64))
+                            .unwrap_or(WindowScalePolicy::SystemScaleFactor),
+
+                        #[cfg(feature = "opengl")]
+                        gl_config: Some(baseview::gl::GlConfig {
+                            // FIXME: glow_glyph forgot to add an `#extension`, so this won't work
+                            //        under OpenGL 3.2 at the moment. With that change applied this
+                            //        should work on OpenGL 3.2/macOS.
+                            version: (3, 3),
+                            red_bits: 8,
+                            blue_bits: 8,
+                            green_bits: 8,
+                            alpha_bits: 8,
+                            depth_bits: 24,
+                            stencil_bits: 8,
+                            samples: None,
+                            srgb: true,
+                            double_buffer: true,
+                            vsync: true,
+                            ..Default::default()
+                        }),
+                        // FIXME: Rust analyzer always thinks baseview/opengl is enabled even if
+                        //        we don't explicitly enable it, so you'd get a compile error if
+                        //        this line is missing
+                        #[cfg(not(feature = "opengl"))]
+                        gl_config: None,
+                    },
+                    iced_baseview: IcedBaseviewSettings {
+                        ignore_non_modifier_keys: false,
+                        always_redraw: true,
+                    },
+                    // We use this wrapper to be able to pass the GUI context to the editor, and
+                    // so the window can reopen itself when it gets resized
+                    flags: (self.clone(), (self.initialization_flags_factory)()),
+                },
+            )
+        }));
+
+        match window {
+            Ok(window) => Some(window),
+            Err(payload) => {
+                nih_error!(
+                    "Could not open the editor window: {}",
+                    panic_message(payload.as_ref())
+                );
+
+                None
+            }
+        }
+    }
+
+    /// Replace the current window with a new window using the size currently stored in the
+    /// [`IcedState`]. Called by the window itself after the host agreed to a new size. If the new
+    /// window cannot be opened, then the current window is kept. Nothing happens if the editor has
+    /// already been closed.
+    pub(crate) fn reopen(self: &Arc<Self>) {
+        let mut window = self.window.lock().unwrap();
+        if window.is_none() {
+            return;
+        }
+
+        if let Some(new_window) = self.open() {
+            if let Some(mut old_window) = window.replace(new_window) {
+                old_window.close_window();
+            }
+        }
+    }
+
+    /// Close the current window, if it's still open.
+    fn close(&self) {
+        if let Some(mut window) = self.window.lock().unwrap().take() {
+            window.close_window();
+        }
+    }
+}
+
+/// Registers an editor window's parameter update channel with [`IcedEditorWrapper`] for as long as
+/// this object is alive.
+pub(crate) struct ParameterUpdatesRegistration {
+    senders: Arc<RwLock<Vec<channel::Sender<ParameterUpdate>>>>,
+    sender: channel::Sender<ParameterUpdate>,
+}
+
+impl ParameterUpdatesRegistration {
+    pub(crate) fn new(
+        senders: Arc<RwLock<Vec<channel::Sender<ParameterUpdate>>>>,
+        sender: channel::Sender<ParameterUpdate>,
+    ) -> Self {
+        senders.write().unwrap().push(sender.clone());
+
+        Self { senders, sender }
+    }
+}
+
+impl Drop for ParameterUpdatesRegistration {
+    fn drop(&mut self) {
+        self.senders
+            .write()
+            .unwrap()
+            .retain(|sender| !sender.same_channel(&self.sender));
+    }
+}
+
+/// Marks an [`IcedState`] as open for as long as this object is alive. There is one of these per
+/// editor window, and [`IcedState::is_open()`] returns false again once all of them have been
+/// dropped.
+pub(crate) struct IcedStateOpenGuard {
     iced_state: Arc<IcedState>,
-    window: iced_baseview::WindowHandle<Message>,
 }
 
-/// The window handle enum stored within 'WindowHandle' contains raw pointers. Is there a way around
-/// having this requirement?
-unsafe impl<Message: Send> Send for IcedEditorHandle<Message> {}
+impl IcedStateOpenGuard {
+    pub(crate) fn new(iced_state: Arc<IcedState>) -> Self {
+        iced_state.open_count.fetch_add(1, Ordering::AcqRel);
+
+        Self { iced_state }
+    }
+}
+
+impl Drop for IcedStateOpenGuard {
+    fn drop(&mut self) {
+        let previous_open_count = self.iced_state.open_count.fetch_sub(1, Ordering::AcqRel);
+        nih_debug_assert_ne!(previous_open_count, 0);
+    }
+}
 
-impl<Message: Send> Drop for IcedEditorHandle<Message> {
+impl<E: IcedEditor> Drop for IcedEditorHandle<E> {
     fn drop(&mut self) {
-        self.iced_state.open.store(false, Ordering::Release);
-        self.window.close_window();
+        // The open guard is dropped after this, so the state only reports the editor as closed
+        // once the window is actually gone
+        self.editor_window.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_iced_editor;
+    use nih_plug::params::persist::PersistentField;
+
+    /// A do-nothing editor used to test the wrapper without opening a window.
+    struct TestEditor {
+        context: Arc<dyn GuiContext>,
+    }
+
+    impl IcedEditor for TestEditor {
+        type Executor = executor::Default;
+        type Message = ();
+        type InitializationFlags = ();
+
+        fn new(
+            _initialization_flags: Self::InitializationFlags,
+            context: Arc<dyn GuiContext>,
+        ) -> (Self, Command<Self::Message>) {
+            (TestEditor { context }, Command::none())
+        }
+
+        fn context(&self) -> &dyn GuiContext {
+            self.context.as_ref()
+        }
+
+        fn update(
+            &mut self,
+            _window: &mut WindowQueue,
+            _message: Self::Message,
+        ) -> Command<Self::Message> {
+            Command::none()
+        }
+
+        fn view(&mut self) -> Element<'_, Self::Message> {
+            Column::new().into()
+        }
+    }
+
+    #[test]
+    fn panic_messages() {
+        let payload = panic::catch_unwind(|| panic!("static message")).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "static message");
+
+        let payload = panic::catch_unwind(|| panic!("formatted {}", "message")).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "formatted message");
+
+        let payload = panic::catch_unwind(|| panic::panic_any(42)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "unknown error");
+    }
+
+    #[test]
+    fn open_guard_lifecycle() {
+        let iced_state = IcedState::from_size(200, 150);
+        assert!(!iced_state.is_open());
+
+        let guard = IcedStateOpenGuard::new(iced_state.clone());
+        assert!(iced_state.is_open());
+
+        drop(guard);
+        assert!(!iced_state.is_open());
+    }
+
+    #[test]
+    fn multiple_open_windows() {
+        let iced_state = IcedState::from_size(200, 150);
+        let first_guard = IcedStateOpenGuard::new(iced_state.clone());
+        let second_guard = IcedStateOpenGuard::new(iced_state.clone());
+        assert!(iced_state.is_open());
+
+        // Closing the first window should not mark the editor as closed while the second window is
+        // still open
+        drop(first_guard);
+        assert!(iced_state.is_open());
+
+        drop(second_guard);
+        assert!(!iced_state.is_open());
+    }
+
+    #[test]
+    fn parameter_updates_reach_every_window() {
+        let senders = Arc::new(RwLock::new(Vec::new()));
+        let editor = IcedEditorWrapper::<TestEditor> {
+            iced_state: IcedState::from_size(200, 150),
+            initialization_flags_factory: Arc::new(|| ()),
+            parameter_updates_senders: senders.clone(),
+        };
+
+        let (first_sender, first_receiver) = channel::bounded(1);
+        let (second_sender, second_receiver) = channel::bounded(1);
+        let first_registration = ParameterUpdatesRegistration::new(senders.clone(), first_sender);
+        let _second_registration =
+            ParameterUpdatesRegistration::new(senders.clone(), second_sender);
+
+        editor.param_values_changed();
+        assert!(first_receiver.try_recv().is_ok());
+        assert!(second_receiver.try_recv().is_ok());
+
+        // Closed windows don't receive any updates anymore
+        drop(first_registration);
+        editor.param_value_changed("foo", 0.5);
+        assert!(first_receiver.try_recv().is_err());
+        assert!(second_receiver.try_recv().is_ok());
+    }
+
+    #[test]
+    fn reports_configured_size() {
+        let iced_state = IcedState::from_size(200, 150);
+        let editor = create_iced_editor::<TestEditor>(iced_state.clone(), ()).unwrap();
+        assert_eq!(editor.size(), (200, 150));
+
+        // The size should stay the same while the editor is open
+        let guard = IcedStateOpenGuard::new(iced_state.clone());
+        assert_eq!(editor.size(), (200, 150));
+        drop(guard);
+        assert_eq!(editor.size(), (200, 150));
+    }
+
+    #[test]
+    fn reports_updated_size() {
+        let iced_state = IcedState::from_size(200, 150);
+        let editor = create_iced_editor::<TestEditor>(iced_state.clone(), ()).unwrap();
+        assert_eq!(iced_state.resize_requests(), 0);
+
+        // Resizing a closed editor only changes the stored size
+        iced_state.set_size(120, 80);
+        assert_eq!(editor.size(), (120, 80));
+        assert_eq!(iced_state.resize_requests(), 0);
+
+        // Every open window compares this to its own count, so checking it doesn't consume it
+        let guard = IcedStateOpenGuard::new(iced_state.clone());
+        iced_state.set_size(240, 160);
+        assert_eq!(editor.size(), (240, 160));
+        assert_eq!(iced_state.resize_requests(), 1);
+        assert_eq!(iced_state.resize_requests(), 1);
+        drop(guard);
+    }
+
+    #[test]
+    fn persists_size_and_collapsed_groups() {
+        let iced_state = IcedState::from_size(200, 150);
+        let guard = IcedStateOpenGuard::new(iced_state.clone());
+        iced_state.set_size(640, 480);
+        iced_state.set_group_expanded("band", false);
+        iced_state.set_window_title("Title");
+
+        let serialized = serde_json::to_string(iced_state.as_ref()).unwrap();
+        drop(guard);
+
+        // Only these two fields should be persisted, everything else is runtime state
+        let serialized_value: serde_json::Value = serde_json::from_str(&serialized).unwrap();
+        let mut persisted_fields: Vec<_> = serialized_value.as_object().unwrap().keys().collect();
+        persisted_fields.sort();
+        assert_eq!(persisted_fields, ["collapsed_groups", "size"]);
+
+        let restored: IcedState = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(restored.size(), (640, 480));
+        assert!(!restored.is_group_expanded("band"));
+        assert!(!restored.is_open());
+        assert_eq!(restored.resize_requests(), 0);
+        assert_eq!(restored.window_title(), None);
+
+        // This is what happens when the state is restored into a `#[persist]` field
+        let persisted_state = IcedState::from_size(200, 150);
+        persisted_state.set(restored);
+        assert_eq!(persisted_state.size(), (640, 480));
+        assert!(!persisted_state.is_group_expanded("band"));
+        assert!(!persisted_state.is_open());
+    }
+
+    #[test]
+    fn scale_factor_is_only_changed_while_closed() {
+        let iced_state = IcedState::from_size(200, 150);
+        let editor = create_iced_editor::<TestEditor>(iced_state.clone(), ()).unwrap();
+
+        let guard = IcedStateOpenGuard::new(iced_state.clone());
+        assert!(!editor.set_scale_factor(2.0));
+        assert_ne!(iced_state.scale_factor(), Some(2.0));
+
+        drop(guard);
+        assert!(editor.set_scale_factor(2.0));
+        assert_eq!(iced_state.scale_factor(), Some(2.0));
     }
 }
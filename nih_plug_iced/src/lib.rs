@@ -91,15 +91,15 @@
 
 use baseview::WindowScalePolicy;
 use crossbeam::atomic::AtomicCell;
-use crossbeam::channel;
 use nih_plug::params::persist::PersistentField;
-use nih_plug::prelude::{Editor, GuiContext};
+use nih_plug::prelude::{Editor, GuiContext, ParamSetter};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 // This doesn't need to be re-export but otherwise the compiler complains about
 // `hidden_glob_reexports`
 pub use std::fmt::Debug;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
 
 use crate::widgets::ParamMessage;
 
@@ -122,31 +122,38 @@ mod wrapper;
 /// the GUI is not open. If you want this size to be persisted when restoring a plugin instance,
 /// then you can store it in a `#[persist = "key"]` field on your parameters struct.
 ///
-/// See the [module's documentation][self] for an example on how to use this.
+/// Every editor window gets its own [`IcedEditor`] instance, so if the host opens multiple windows
+/// for the same plugin instance then these will be fully independent. They share the same
+/// [`IcedState`], and [`IcedState::is_open()`] returns true as long as any of them is open.
+///
+/// See the [module's documentation][self] for an example on how to use this. The initialization
+/// flags are cloned every time the editor is opened. Use
+/// [`create_iced_editor_with_flags_factory()`] if the flags are expensive to clone or if they
+/// cannot be cloned at all.
 pub fn create_iced_editor<E: IcedEditor>(
     iced_state: Arc<IcedState>,
     initialization_flags: E::InitializationFlags,
-) -> Option<Box<dyn Editor>> {
-    // We need some way to communicate parameter changes to the `IcedEditor` since parameter updates
-    // come from outside of the editor's reactive model. This contains only capacity to store only
-    // one parameter update, since we're only storing _that_ a parameter update has happened and not
-    // which parameter so we'd need to redraw the entire GUI either way.
-    let (parameter_updates_sender, parameter_updates_receiver) = channel::bounded(1);
+) -> Option<Box<dyn Editor>>
+where
+    E::InitializationFlags: Clone + Sync,
+{
+    create_iced_editor_with_flags_factory::<E>(iced_state, move || initialization_flags.clone())
+}
 
+/// The same as [`create_iced_editor()`], but the editor's initialization flags are created by
+/// calling `initialization_flags_factory` every time the editor is opened. This makes it possible
+/// to use flags that are not `Clone`, or that should be constructed anew for every editor
+/// instance. The factory is called from the thread that opens the editor, before the editor's
+/// window is created.
+pub fn create_iced_editor_with_flags_factory<E: IcedEditor>(
+    iced_state: Arc<IcedState>,
+    initialization_flags_factory: impl Fn() -> E::InitializationFlags + 'static + Send + Sync,
+) -> Option<Box<dyn Editor>> {
     Some(Box::new(editor::IcedEditorWrapper::<E> {
         iced_state,
-        initialization_flags,
-
-        // TODO: We can't get the size of the window when baseview does its own scaling, so if the
-        //       host does not set a scale factor on Windows or Linux we should just use a factor of
-        //       1. That may make the GUI tiny but it also prevents it from getting cut off.
-        #[cfg(target_os = "macos")]
-        scaling_factor: AtomicCell::new(None),
-        #[cfg(not(target_os = "macos"))]
-        scaling_factor: AtomicCell::new(Some(1.0)),
-
-        parameter_updates_sender,
-        parameter_updates_receiver: Arc::new(parameter_updates_receiver),
+        initialization_flags_factory: Arc::new(initialization_flags_factory),
+
+        parameter_updates_senders: Arc::new(RwLock::new(Vec::new())),
     }))
 }
 
@@ -160,8 +167,11 @@ pub trait IcedEditor: 'static + Send + Sync + Sized {
     type Executor: Executor;
     /// See [`Application::Message`]. You should have one variant containing a [`ParamMessage`].
     type Message: 'static + Clone + Debug + Send;
-    /// See [`Application::Flags`].
-    type InitializationFlags: 'static + Clone + Send + Sync;
+    /// See [`Application::Flags`]. These flags are passed to the editor every time it is opened.
+    /// When using [`create_iced_editor()`] they are cloned for every editor instance, and with
+    /// [`create_iced_editor_with_flags_factory()`] they are created by the factory function
+    /// instead.
+    type InitializationFlags: 'static + Send;
 
     /// See [`Application::new`]. This also receivs the GUI context in addition to the flags.
     fn new(
@@ -174,6 +184,31 @@ pub trait IcedEditor: 'static + Send + Sync + Sized {
     /// parameters.
     fn context(&self) -> &dyn GuiContext;
 
+    /// Returns a [`ParamSetter`] for the GUI context. This can be used to change parameter values
+    /// from the editor when not using the parameter widgets from [`widgets`].
+    ///
+    /// # Automation gestures
+    ///
+    /// Every change must be part of an automation gesture so the host can record it. Call
+    /// [`ParamSetter::begin_set_parameter()`] once when the user starts interacting with a
+    /// control, [`ParamSetter::set_parameter()`] any number of times while the value changes, and
+    /// [`ParamSetter::end_set_parameter()`] once the interaction has finished. Gestures for the
+    /// same parameter must not overlap, and every begin must be followed by an end, even if the
+    /// value didn't change. A one-off change, like resetting a parameter on a double click, still
+    /// needs all three calls:
+    ///
+    /// ```ignore
+    /// let setter = self.param_setter();
+    /// setter.begin_set_parameter(&self.params.gain);
+    /// setter.set_parameter(&self.params.gain, self.params.gain.default_plain_value());
+    /// setter.end_set_parameter(&self.params.gain);
+    /// ```
+    ///
+    /// The [`ParamMessage`]s emitted by the widgets follow the same rules.
+    fn param_setter(&self) -> ParamSetter<'_> {
+        ParamSetter::new(self.context())
+    }
+
     /// See [`Application::update`]. When receiving the variant that contains a
     /// [`widgets::ParamMessage`] you can call
     /// [`handle_param_message()`][Self::handle_param_message()] to handle the parameter update.
@@ -240,14 +275,35 @@ pub struct IcedState {
     /// The window's size in logical pixels before applying `scale_factor`.
     #[serde(with = "nih_plug::params::persist::serialize_atomic_cell")]
     size: AtomicCell<(u32, u32)>,
-    /// Whether the editor's window is currently open.
+    /// The group paths of the parameter groups that have been collapsed in the editor. Groups are
+    /// expanded by default, so only collapsed groups need to be stored.
+    #[serde(default)]
+    collapsed_groups: RwLock<HashSet<String>>,
+    /// The title for the editor's window, if one has been set with
+    /// [`set_window_title()`][Self::set_window_title()]. This is not persisted.
+    #[serde(skip)]
+    window_title: RwLock<Option<String>>,
+    /// The number of editor windows that are currently open. Some hosts can open multiple editor
+    /// windows for the same plugin instance, so this is reference counted.
     #[serde(skip)]
-    open: AtomicBool,
+    open_count: AtomicUsize,
+    /// Incremented by [`set_size()`][Self::set_size()] while the editor is open. Every open editor
+    /// window compares this to the last value it has seen to know it should ask the host to resize
+    /// the window. This is a counter instead of a flag so one window handling the request doesn't
+    /// hide it from the other windows. This is not persisted.
+    #[serde(skip)]
+    resize_requests: AtomicUsize,
+    /// The DPI scaling factor for the editor window. `None` means that baseview uses the system's
+    /// scaling factor. See [`scale_factor()`][Self::scale_factor()]. This is not persisted.
+    #[serde(skip)]
+    scale_factor: AtomicCell<Option<f32>>,
 }
 
 impl<'a> PersistentField<'a, IcedState> for Arc<IcedState> {
     fn set(&self, new_value: IcedState) {
         self.size.store(new_value.size.load());
+        *self.collapsed_groups.write().unwrap() =
+            new_value.collapsed_groups.into_inner().unwrap_or_default();
     }
 
     fn map<F, R>(&self, f: F) -> R
@@ -264,7 +320,19 @@ impl IcedState {
     pub fn from_size(width: u32, height: u32) -> Arc<IcedState> {
         Arc::new(IcedState {
             size: AtomicCell::new((width, height)),
-            open: AtomicBool::new(false),
+            collapsed_groups: RwLock::new(HashSet::new()),
+            window_title: RwLock::new(None),
+            open_count: AtomicUsize::new(0),
+            resize_requests: AtomicUsize::new(0),
+
+            // TODO: We can't get the size of the window when baseview does its own scaling, so if
+            //       the host does not set a scale factor on Windows or Linux we should just use a
+            //       factor of 1. That may make the GUI tiny but it also prevents it from getting
+            //       cut off.
+            #[cfg(target_os = "macos")]
+            scale_factor: AtomicCell::new(None),
+            #[cfg(not(target_os = "macos"))]
+            scale_factor: AtomicCell::new(Some(1.0)),
         })
     }
 
@@ -273,10 +341,74 @@ impl IcedState {
         self.size.load()
     }
 
-    /// Whether the GUI is currently visible.
+    /// Change the GUI's size in logical pixels, for instance from [`IcedEditor::update()`] in
+    /// response to the user toggling a compact mode. [`Editor::size()`] immediately reports the new
+    /// size. If the editor is open, then the host is asked to resize the window after the editor
+    /// has handled the current message. When the host agrees, the window is reopened with the new
+    /// size, which also creates a new [`IcedEditor`] instance. When the host rejects the new size
+    /// the old size is restored. The size is stored before applying any DPI scaling, so it stays
+    /// valid if the scaling factor is different the next time the editor is opened. The host
+    /// cannot change the scaling factor while the editor is open. If this state is stored in a
+    /// `#[persist]` field, then the new size is also restored along with the plugin's state.
+    pub fn set_size(&self, width: u32, height: u32) {
+        self.size.store((width, height));
+
+        // A closed editor simply opens with the new size the next time
+        if self.is_open() {
+            self.resize_requests.fetch_add(1, Ordering::AcqRel);
+        }
+    }
+
+    /// The number of times [`set_size()`][Self::set_size()] has been called while the editor was
+    /// open. An editor window should ask the host to resize the window when this changes.
+    pub(crate) fn resize_requests(&self) -> usize {
+        self.resize_requests.load(Ordering::Acquire)
+    }
+
+    /// The DPI scaling factor used for the editor window. Widgets that draw raw pixels or load
+    /// bitmaps can use this to render at the window's physical resolution. On Windows and Linux
+    /// this is the factor set by the host, or 1.0 if the host never set one. This returns `None` on
+    /// macOS, where the host doesn't set a scaling factor and the system's scaling factor is used
+    /// instead. The host can only change the scaling factor while the editor is closed, so this
+    /// value stays the same for as long as an editor window is open.
+    pub fn scale_factor(&self) -> Option<f32> {
+        self.scale_factor.load()
+    }
+
+    /// Whether the parameter group with the given group path should be shown expanded. Groups that
+    /// have not been collapsed before are expanded.
+    pub fn is_group_expanded(&self, group_path: &str) -> bool {
+        !self.collapsed_groups.read().unwrap().contains(group_path)
+    }
+
+    /// Change whether the parameter group with the given group path should be shown expanded. This
+    /// is stored as part of the editor's state, so it will be restored along with the plugin's
+    /// state if this `IcedState` is persisted.
+    pub fn set_group_expanded(&self, group_path: &str, expanded: bool) {
+        let mut collapsed_groups = self.collapsed_groups.write().unwrap();
+        if expanded {
+            collapsed_groups.remove(group_path);
+        } else {
+            collapsed_groups.insert(group_path.to_owned());
+        }
+    }
+
+    /// Set the title for the editor's windows instead of using the plugin's name. Open windows pick
+    /// up the new title when they're reopened after a resize.
+    pub fn set_window_title(&self, title: impl Into<String>) {
+        *self.window_title.write().unwrap() = Some(title.into());
+    }
+
+    /// The title set with [`set_window_title()`][Self::set_window_title()], if any.
+    pub fn window_title(&self) -> Option<String> {
+        self.window_title.read().unwrap().clone()
+    }
+
+    /// Whether the GUI is currently visible. If the host opens multiple editor windows for the
+    /// same plugin instance, then this stays true until the last of those windows has been closed.
     // Called `is_open()` instead of `open()` to avoid the ambiguity.
     pub fn is_open(&self) -> bool {
-        self.open.load(Ordering::Acquire)
+        self.open_count.load(Ordering::Acquire) > 0
     }
 }
 
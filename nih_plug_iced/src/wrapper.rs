@@ -5,16 +5,29 @@ use crossbeam::channel;
 use nih_plug::prelude::GuiContext;
 use std::sync::Arc;
 
+use crate::editor::EditorWindow;
 use crate::futures::FutureExt;
 use crate::{
-    futures, subscription, Application, Color, Command, Element, IcedEditor, ParameterUpdate,
-    Subscription, WindowQueue, WindowScalePolicy, WindowSubs,
+    futures, subscription, Application, Color, Command, Element, IcedEditor, IcedState,
+    ParameterUpdate, Subscription, WindowQueue, WindowScalePolicy, WindowSubs,
 };
 
 /// Wraps an `iced_baseview` [`Application`] around [`IcedEditor`]. Needed to allow editors to
 /// always receive a copy of the GUI context.
 pub(crate) struct IcedEditorWrapperApplication<E: IcedEditor> {
     editor: E,
+    /// Used to ask the host to resize the window after the editor called
+    /// [`IcedState::set_size()`].
+    context: Arc<dyn GuiContext>,
+    iced_state: Arc<IcedState>,
+    /// Used to reopen the window with the new size after the host agreed to a resize.
+    editor_window: Arc<EditorWindow<E>>,
+    /// The last window size in logical pixels the host agreed to. This is restored when the host
+    /// rejects a resize.
+    window_size: (u32, u32),
+    /// The value of [`IcedState::resize_requests()`] the last time this window checked for resize
+    /// requests. This is tracked per window so every open window handles every resize request.
+    handled_resize_requests: usize,
 
     /// We will receive notifications about parameters being changed on here. Whenever a parameter
     /// update gets sent, we will trigger a [`Message::parameterUpdate`] which causes the UI to be
@@ -51,21 +64,26 @@ impl<E: IcedEditor> Clone for Message<E> {
 impl<E: IcedEditor> Application for IcedEditorWrapperApplication<E> {
     type Executor = E::Executor;
     type Message = Message<E>;
-    type Flags = (
-        Arc<dyn GuiContext>,
-        Arc<channel::Receiver<ParameterUpdate>>,
-        E::InitializationFlags,
-    );
+    type Flags = (Arc<EditorWindow<E>>, E::InitializationFlags);
 
-    fn new(
-        (context, parameter_updates_receiver, flags): Self::Flags,
-    ) -> (Self, Command<Self::Message>) {
-        let (editor, command) = E::new(flags, context);
+    fn new((editor_window, flags): Self::Flags) -> (Self, Command<Self::Message>) {
+        let context = editor_window.context.clone();
+        let (editor, command) = E::new(flags, context.clone());
+
+        // Resizes requested before the window was opened are already reflected in the window's size
+        let iced_state = editor_window.iced_state.clone();
+        let handled_resize_requests = iced_state.resize_requests();
+        let window_size = iced_state.size();
 
         (
             Self {
                 editor,
-                parameter_updates_receiver,
+                context,
+                iced_state,
+                parameter_updates_receiver: editor_window.parameter_updates_receiver.clone(),
+                editor_window,
+                window_size,
+                handled_resize_requests,
             },
             command.map(Message::EditorMessage),
         )
@@ -77,14 +95,18 @@ impl<E: IcedEditor> Application for IcedEditorWrapperApplication<E> {
         window: &mut WindowQueue,
         message: Self::Message,
     ) -> Command<Self::Message> {
-        match message {
+        let command = match message {
             Message::EditorMessage(message) => self
                 .editor
                 .update(window, message)
                 .map(Message::EditorMessage),
             // This message only exists to force a redraw
             Message::ParameterUpdate => Command::none(),
-        }
+        };
+
+        self.handle_resize_request();
+
+        command
     }
 
     #[inline]
@@ -156,3 +178,30 @@ impl<E: IcedEditor> Application for IcedEditorWrapperApplication<E> {
         E::renderer_settings()
     }
 }
+
+impl<E: IcedEditor> IcedEditorWrapperApplication<E> {
+    /// Ask the host to resize the window if the editor changed the size stored in the
+    /// [`IcedState`]. If the host agrees, then the window is reopened with the new size since this
+    /// version of `iced_baseview` cannot resize an open window. If the host rejects the new size,
+    /// then the previous size is restored so
+    /// [`Editor::size()`][nih_plug::prelude::Editor::size()] keeps matching the host's window.
+    fn handle_resize_request(&mut self) {
+        let resize_requests = self.iced_state.resize_requests();
+        if resize_requests == self.handled_resize_requests {
+            return;
+        }
+        self.handled_resize_requests = resize_requests;
+
+        let new_size = self.iced_state.size();
+        if new_size == self.window_size {
+            return;
+        }
+
+        if self.context.request_resize() {
+            self.window_size = new_size;
+            self.editor_window.reopen();
+        } else {
+            self.iced_state.size.store(self.window_size);
+        }
+    }
+}
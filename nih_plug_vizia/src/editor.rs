@@ -45,6 +45,9 @@ impl Editor for ViziaEditor {
         let (unscaled_width, unscaled_height) = vizia_state.inner_logical_size();
         let system_scaling_factor = self.scaling_factor.load();
         let user_scale_factor = vizia_state.user_scale_factor();
+        let title = vizia_state
+            .window_title()
+            .unwrap_or_else(|| context.plugin_name().to_owned());
 
         let mut application = Application::new(move |cx| {
             // Set some default styles to match the iced integration
@@ -86,6 +89,7 @@ impl Editor for ViziaEditor {
                 .map(|factor| WindowScalePolicy::ScaleFactor(factor as f64))
                 .unwrap_or(WindowScalePolicy::SystemScaleFactor),
         )
+        .title(&title)
         .inner_size((unscaled_width, unscaled_height))
         .user_scale_factor(user_scale_factor)
         .with_text_config(TextConfig {
@@ -7,9 +7,10 @@ use crossbeam::atomic::AtomicCell;
 use nih_plug::params::persist::PersistentField;
 use nih_plug::prelude::{Editor, GuiContext};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use vizia::prelude::*;
 
 // Re-export for convenience
@@ -95,6 +96,15 @@ pub struct ViziaState {
     /// This can be used to allow GUIs to be scaled uniformly.
     #[serde(with = "nih_plug::params::persist::serialize_atomic_cell")]
     scale_factor: AtomicCell<f64>,
+    /// The group paths of the parameter groups that have been collapsed in the editor. Groups are
+    /// expanded by default, so only collapsed groups need to be stored. See
+    /// [`CollapsibleGroup`][widgets::CollapsibleGroup].
+    #[serde(default)]
+    collapsed_groups: RwLock<HashSet<String>>,
+    /// The title for the editor's window, if one has been set with
+    /// [`set_window_title()`][Self::set_window_title()]. This is not persisted.
+    #[serde(skip)]
+    window_title: RwLock<Option<String>>,
     /// Whether the editor's window is currently open.
     #[serde(skip)]
     open: AtomicBool,
@@ -112,6 +122,8 @@ impl Debug for ViziaState {
         f.debug_struct("ViziaState")
             .field("size_fn", &format!("<fn> ({}, {})", width, height))
             .field("scale_factor", &self.scale_factor)
+            .field("collapsed_groups", &self.collapsed_groups)
+            .field("window_title", &self.window_title)
             .field("open", &self.open)
             .finish()
     }
@@ -120,6 +132,8 @@ impl Debug for ViziaState {
 impl<'a> PersistentField<'a, ViziaState> for Arc<ViziaState> {
     fn set(&self, new_value: ViziaState) {
         self.scale_factor.store(new_value.scale_factor.load());
+        *self.collapsed_groups.write().unwrap() =
+            new_value.collapsed_groups.into_inner().unwrap_or_default();
     }
 
     fn map<F, R>(&self, f: F) -> R
@@ -139,6 +153,8 @@ impl ViziaState {
         Arc::new(ViziaState {
             size_fn: Box::new(size_fn),
             scale_factor: AtomicCell::new(1.0),
+            collapsed_groups: RwLock::new(HashSet::new()),
+            window_title: RwLock::new(None),
             open: AtomicBool::new(false),
         })
     }
@@ -153,6 +169,8 @@ impl ViziaState {
         Arc::new(ViziaState {
             size_fn: Box::new(size_fn),
             scale_factor: AtomicCell::new(default_scale_factor),
+            collapsed_groups: RwLock::new(HashSet::new()),
+            window_title: RwLock::new(None),
             open: AtomicBool::new(false),
         })
     }
@@ -181,6 +199,36 @@ impl ViziaState {
         self.scale_factor.load()
     }
 
+    /// Whether the parameter group with the given group path should be shown expanded. Groups that
+    /// have not been collapsed before are expanded.
+    pub fn is_group_expanded(&self, group_path: &str) -> bool {
+        !self.collapsed_groups.read().unwrap().contains(group_path)
+    }
+
+    /// Change whether the parameter group with the given group path should be shown expanded. This
+    /// is stored as part of the editor's state, so it will be restored along with the plugin's
+    /// state if this `ViziaState` is persisted.
+    pub fn set_group_expanded(&self, group_path: &str, expanded: bool) {
+        let mut collapsed_groups = self.collapsed_groups.write().unwrap();
+        if expanded {
+            collapsed_groups.remove(group_path);
+        } else {
+            collapsed_groups.insert(group_path.to_owned());
+        }
+    }
+
+    /// Override the window title, which otherwise is the plugin's name. Unlike the scale factor and
+    /// the collapsed groups, this is not persisted. It takes effect the next time the editor is
+    /// opened.
+    pub fn set_window_title(&self, title: impl Into<String>) {
+        *self.window_title.write().unwrap() = Some(title.into());
+    }
+
+    /// The title set with [`set_window_title()`][Self::set_window_title()], if any.
+    pub fn window_title(&self) -> Option<String> {
+        self.window_title.read().unwrap().clone()
+    }
+
     /// Whether the GUI is currently visible.
     // Called `is_open()` instead of `open()` to avoid the ambiguity.
     pub fn is_open(&self) -> bool {
@@ -13,6 +13,7 @@ use vizia::prelude::*;
 
 use super::ViziaState;
 
+mod collapsible_group;
 mod generic_ui;
 pub mod param_base;
 mod param_button;
@@ -20,12 +21,15 @@ mod param_slider;
 mod peak_meter;
 mod resize_handle;
 pub mod util;
+mod xy_pad;
 
+pub use collapsible_group::CollapsibleGroup;
 pub use generic_ui::GenericUi;
 pub use param_button::{ParamButton, ParamButtonExt};
 pub use param_slider::{ParamSlider, ParamSliderExt, ParamSliderStyle};
-pub use peak_meter::PeakMeter;
+pub use peak_meter::{MultiChannelPeakMeter, PeakMeter, PeakMeterOrientation};
 pub use resize_handle::ResizeHandle;
+pub use xy_pad::XyPad;
 
 /// Register the default theme for the widgets exported by this module. This is automatically called
 /// for you when using [`create_vizia_editor()`][super::create_vizia_editor()].
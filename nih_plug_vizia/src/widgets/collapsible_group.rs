@@ -0,0 +1,102 @@
+//! A container for a parameter group that can be collapsed and expanded by clicking on its title.
+
+use std::sync::Arc;
+use vizia::prelude::*;
+
+use crate::ViziaState;
+
+/// A container for a group of parameters with a title that can be clicked on to collapse or expand
+/// the group. The expanded state is stored in the editor's [`ViziaState`] using the group's path as
+/// reported by [`Params::param_map()`][nih_plug::prelude::Params::param_map()], so it is
+/// remembered when the editor is reopened and it is restored together with the plugin's state if
+/// the `ViziaState` is persisted. Groups that have never been collapsed start out expanded.
+///
+/// The `:checked` pseudoclass indicates whether or not the group is currently expanded.
+#[derive(Lens)]
+pub struct CollapsibleGroup {
+    vizia_state: Arc<ViziaState>,
+    group_path: String,
+
+    /// Whether the group's contents are currently shown.
+    expanded: bool,
+}
+
+/// Sent by the title to its [`CollapsibleGroup`] when it has been clicked on.
+enum CollapsibleGroupEvent {
+    Toggle,
+}
+
+/// The clickable title of a [`CollapsibleGroup`].
+struct CollapsibleGroupTitle;
+
+impl CollapsibleGroup {
+    /// Creates a new [`CollapsibleGroup`] for the parameter group with path `group_path`. The title
+    /// is displayed at the top of the group, and `content` is used to build the group's contents
+    /// whenever the group is expanded.
+    pub fn new<F>(
+        cx: &mut Context,
+        vizia_state: Arc<ViziaState>,
+        group_path: impl Into<String>,
+        title: impl Into<String>,
+        content: F,
+    ) -> Handle<Self>
+    where
+        F: Fn(&mut Context) + 'static,
+    {
+        let group_path = group_path.into();
+        let title = title.into();
+        let expanded = vizia_state.is_group_expanded(&group_path);
+
+        Self {
+            vizia_state,
+            group_path,
+
+            expanded,
+        }
+        .build(cx, move |cx| {
+            CollapsibleGroupTitle.build(cx, |cx| {
+                Label::new(cx, &title).hoverable(false);
+            });
+
+            Binding::new(cx, Self::expanded, move |cx, expanded| {
+                if expanded.get(cx) {
+                    VStack::new(cx, &content).class("content");
+                }
+            });
+        })
+        .checked(Self::expanded)
+    }
+}
+
+impl View for CollapsibleGroup {
+    fn element(&self) -> Option<&'static str> {
+        Some("collapsible-group")
+    }
+
+    fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
+        event.map(|group_event, meta| match group_event {
+            CollapsibleGroupEvent::Toggle => {
+                self.expanded = !self.expanded;
+                self.vizia_state
+                    .set_group_expanded(&self.group_path, self.expanded);
+
+                meta.consume();
+            }
+        });
+    }
+}
+
+impl View for CollapsibleGroupTitle {
+    fn element(&self) -> Option<&'static str> {
+        Some("collapsible-group-title")
+    }
+
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|window_event, meta| {
+            if let WindowEvent::MouseDown(MouseButton::Left) = window_event {
+                cx.emit(CollapsibleGroupEvent::Toggle);
+                meta.consume();
+            }
+        });
+    }
+}
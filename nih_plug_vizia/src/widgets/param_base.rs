@@ -227,6 +227,14 @@ impl ParamWidgetBase {
         cx.emit(RawParamEvent::EndSetParameter(self.param_ptr));
     }
 
+    /// Reset the parameter to its default value. This is a complete automation gesture, so this
+    /// should not be surrounded by calls to `begin_set_parameter()` and `end_set_parameter()`.
+    pub fn reset_parameter(&self, cx: &mut EventContext) {
+        self.begin_set_parameter(cx);
+        self.set_normalized_value(cx, self.default_normalized_value());
+        self.end_set_parameter(cx);
+    }
+
     param_ptr_forward!(pub fn name(&self) -> &str);
     param_ptr_forward!(pub fn unit(&self) -> &'static str);
     param_ptr_forward!(pub fn poly_modulation_id(&self) -> Option<u32>);
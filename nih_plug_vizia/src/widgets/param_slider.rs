@@ -445,10 +445,7 @@ impl View for ParamSlider {
                 } else if cx.modifiers().command() {
                     // Ctrl+Click, double click, and right clicks should reset the parameter instead
                     // of initiating a drag operation
-                    self.param_base.begin_set_parameter(cx);
-                    self.param_base
-                        .set_normalized_value(cx, self.param_base.default_normalized_value());
-                    self.param_base.end_set_parameter(cx);
+                    self.param_base.reset_parameter(cx);
                 } else if !self.text_input_active {
                     // The `!self.text_input_active` check shouldn't be needed, but the textbox does
                     // not consume the mouse down event. So clicking on the textbox to move the
@@ -484,10 +481,7 @@ impl View for ParamSlider {
             | WindowEvent::MouseTripleClick(MouseButton::Right) => {
                 // Ctrl+Click, double click, and right clicks should reset the parameter instead of
                 // initiating a drag operation
-                self.param_base.begin_set_parameter(cx);
-                self.param_base
-                    .set_normalized_value(cx, self.param_base.default_normalized_value());
-                self.param_base.end_set_parameter(cx);
+                self.param_base.reset_parameter(cx);
 
                 meta.consume();
             }
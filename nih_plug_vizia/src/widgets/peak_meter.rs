@@ -1,7 +1,9 @@
-//! A super simple peak meter widget.
+//! Super simple peak meter widgets.
 
 use nih_plug::prelude::util;
+use nih_plug::util::PeakMeterLevels;
 use std::cell::Cell;
+use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
 use vizia::prelude::*;
@@ -26,7 +28,24 @@ const TEXT_TICKS: [i32; 6] = [-80, -60, -40, -20, 0, 12];
 /// TODO: Vertical peak meter, this is just a proof of concept to fit the gain GUI example.
 pub struct PeakMeter;
 
-/// The bar bit for the peak meter, manually drawn using vertical lines.
+/// A peak meter with one bar per channel, for use with [`PeakMeterLevels`]. Every bar has a label
+/// next to it, and a clip indicator at the end of the bar that lights up when that channel has
+/// clipped. See [`PeakMeterLevels::set_clip_threshold()`] for more information. The clip indicators
+/// stay lit until they are clicked on.
+pub struct MultiChannelPeakMeter;
+
+/// The direction a [`MultiChannelPeakMeter`]'s bars are drawn in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeakMeterOrientation {
+    /// The bars fill from left to right and are stacked on top of each other, with the labels on
+    /// the left.
+    Horizontal,
+    /// The bars fill from bottom to top and are placed next to each other, with the labels below
+    /// the bars.
+    Vertical,
+}
+
+/// The bar bit for the peak meter, manually drawn using lines perpendicular to the bar's direction.
 struct PeakMeterBar<L, P>
 where
     L: Lens<Target = f32>,
@@ -34,6 +53,14 @@ where
 {
     level_dbfs: L,
     peak_dbfs: P,
+    orientation: PeakMeterOrientation,
+}
+
+/// A [`MultiChannelPeakMeter`] channel's clip indicator. This is filled with the font color while
+/// the channel's clip indicator in [`PeakMeterLevels`] is set. Clicking on it clears the indicator.
+struct ClipIndicator {
+    levels: Arc<PeakMeterLevels>,
+    channel_idx: usize,
 }
 
 impl PeakMeter {
@@ -44,36 +71,12 @@ impl PeakMeter {
         L: Lens<Target = f32>,
     {
         Self.build(cx, |cx| {
-            // Now for something that may be illegal under some jurisdictions. If a hold time is
-            // given, then we'll build a new lens that always gives the held peak level for the
-            // current moment in time by mutating some values captured into the mapping closure.
-            let held_peak_value_db = Cell::new(f32::MIN);
-            let last_held_peak_value: Cell<Option<Instant>> = Cell::new(None);
-            let peak_dbfs = level_dbfs.map(move |level| -> f32 {
-                match hold_time {
-                    Some(hold_time) => {
-                        let mut peak_level = held_peak_value_db.get();
-                        let peak_time = last_held_peak_value.get();
-
-                        let now = Instant::now();
-                        if *level >= peak_level
-                            || peak_time.is_none()
-                            || now > peak_time.unwrap() + hold_time
-                        {
-                            peak_level = *level;
-                            held_peak_value_db.set(peak_level);
-                            last_held_peak_value.set(Some(now));
-                        }
-
-                        peak_level
-                    }
-                    None => util::MINUS_INFINITY_DB,
-                }
-            });
+            let peak_dbfs = held_peak_lens(level_dbfs, hold_time);
 
             PeakMeterBar {
                 level_dbfs,
                 peak_dbfs,
+                orientation: PeakMeterOrientation::Horizontal,
             }
             .build(cx, |_| {})
             .class("bar");
@@ -133,12 +136,184 @@ impl PeakMeter {
     }
 }
 
+impl MultiChannelPeakMeter {
+    /// Creates a new [`MultiChannelPeakMeter`] showing a bar for every channel in `levels`,
+    /// optionally holding the peak values for a certain amount of time. The bars are labeled using
+    /// `labels`. Channels without a label are labeled with their one-based channel number.
+    pub fn new<L>(
+        cx: &mut Context,
+        levels: L,
+        labels: &[&str],
+        hold_time: Option<Duration>,
+        orientation: PeakMeterOrientation,
+    ) -> Handle<Self>
+    where
+        L: Lens<Target = Arc<PeakMeterLevels>> + Clone,
+    {
+        let levels_arc = levels.get(cx);
+        let num_channels = levels_arc.num_channels();
+        let labels: Vec<String> = (0..num_channels)
+            .map(|channel_idx| match labels.get(channel_idx) {
+                Some(label) => label.to_string(),
+                None => (channel_idx + 1).to_string(),
+            })
+            .collect();
+
+        Self.build(cx, move |cx| {
+            for (channel_idx, label) in labels.into_iter().enumerate() {
+                let level_dbfs = levels
+                    .clone()
+                    .map(move |levels| util::gain_to_db(levels.level(channel_idx)));
+                let peak_dbfs = held_peak_lens(
+                    levels
+                        .clone()
+                        .map(move |levels| util::gain_to_db(levels.level(channel_idx))),
+                    hold_time,
+                );
+                let bar = PeakMeterBar {
+                    level_dbfs,
+                    peak_dbfs,
+                    orientation,
+                };
+                let clip_indicator = ClipIndicator {
+                    levels: levels_arc.clone(),
+                    channel_idx,
+                };
+
+                match orientation {
+                    PeakMeterOrientation::Horizontal => HStack::new(cx, move |cx| {
+                        Label::new(cx, &label).class("channel__label");
+                        bar.build(cx, |_| {}).class("bar");
+                        clip_indicator.build(cx, |_| {});
+                    }),
+                    PeakMeterOrientation::Vertical => VStack::new(cx, move |cx| {
+                        clip_indicator.build(cx, |_| {});
+                        bar.build(cx, |_| {}).class("bar");
+                        Label::new(cx, &label).class("channel__label");
+                    }),
+                }
+                .class("channel");
+            }
+        })
+        .layout_type(match orientation {
+            PeakMeterOrientation::Horizontal => LayoutType::Column,
+            PeakMeterOrientation::Vertical => LayoutType::Row,
+        })
+        .class(match orientation {
+            PeakMeterOrientation::Horizontal => "horizontal",
+            PeakMeterOrientation::Vertical => "vertical",
+        })
+    }
+}
+
+/// Build a lens that gives the held peak level for the current moment in time if `hold_time` is
+/// set, or minus infinity otherwise.
+fn held_peak_lens<L>(level_dbfs: L, hold_time: Option<Duration>) -> impl Lens<Target = f32>
+where
+    L: Lens<Target = f32>,
+{
+    // Now for something that may be illegal under some jurisdictions. If a hold time is given,
+    // then we'll build a new lens that always gives the held peak level for the current moment in
+    // time by mutating some values captured into the mapping closure.
+    let held_peak_value_db = Cell::new(f32::MIN);
+    let last_held_peak_value: Cell<Option<Instant>> = Cell::new(None);
+    level_dbfs.map(move |level| -> f32 {
+        match hold_time {
+            Some(hold_time) => {
+                let mut peak_level = held_peak_value_db.get();
+                let peak_time = last_held_peak_value.get();
+
+                let now = Instant::now();
+                if *level >= peak_level
+                    || peak_time.is_none()
+                    || now > peak_time.unwrap() + hold_time
+                {
+                    peak_level = *level;
+                    held_peak_value_db.set(peak_level);
+                    last_held_peak_value.set(Some(now));
+                }
+
+                peak_level
+            }
+            None => util::MINUS_INFINITY_DB,
+        }
+    })
+}
+
 impl View for PeakMeter {
     fn element(&self) -> Option<&'static str> {
         Some("peak-meter")
     }
 }
 
+impl View for MultiChannelPeakMeter {
+    fn element(&self) -> Option<&'static str> {
+        Some("multi-channel-peak-meter")
+    }
+}
+
+impl View for ClipIndicator {
+    fn element(&self) -> Option<&'static str> {
+        Some("clip-indicator")
+    }
+
+    fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
+        event.map(|window_event, meta| {
+            // This only clears this channel's indicator, so the other channels' indicators stay lit
+            if let WindowEvent::MouseDown(MouseButton::Left)
+            | WindowEvent::MouseDoubleClick(MouseButton::Left) = window_event
+            {
+                self.levels.reset_clip_indicator(self.channel_idx);
+                meta.consume();
+            }
+        });
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        // These basics are taken directly from the default implementation of this function
+        let bounds = cx.bounds();
+        if bounds.w == 0.0 || bounds.h == 0.0 {
+            return;
+        }
+
+        let opacity = cx.opacity();
+        let mut background_color: vg::Color = cx.background_color().into();
+        background_color.set_alphaf(background_color.a * opacity);
+        let mut border_color: vg::Color = cx.border_color().into();
+        border_color.set_alphaf(border_color.a * opacity);
+        let border_width = cx.border_width();
+
+        let mut path = vg::Path::new();
+        {
+            let x = bounds.x + border_width / 2.0;
+            let y = bounds.y + border_width / 2.0;
+            let w = bounds.w - border_width;
+            let h = bounds.h - border_width;
+            path.move_to(x, y);
+            path.line_to(x, y + h);
+            path.line_to(x + w, y + h);
+            path.line_to(x + w, y);
+            path.line_to(x, y);
+            path.close();
+        }
+
+        // The indicator is read directly from the levels object since the audio thread sets it
+        // without notifying the editor
+        let fill_color = if self.levels.clipped(self.channel_idx) {
+            let mut clip_color: vg::Color = cx.font_color().into();
+            clip_color.set_alphaf(clip_color.a * opacity);
+            clip_color
+        } else {
+            background_color
+        };
+        canvas.fill_path(&path, &vg::Paint::color(fill_color));
+
+        let mut paint = vg::Paint::color(border_color);
+        paint.set_line_width(border_width);
+        canvas.stroke_path(&path, &paint);
+    }
+}
+
 impl<L, P> View for PeakMeterBar<L, P>
 where
     L: Lens<Target = f32>,
@@ -184,29 +359,55 @@ where
         canvas.fill_path(&path, &paint);
 
         // And now for the fun stuff. We'll try to not overlap the border, but we'll draw that last
-        // just in case.
+        // just in case. The bar is filled from left to right or from bottom to top depending on
+        // the orientation, and all positions below are offsets along that direction.
         let bar_bounds = bounds.shrink(border_width / 2.0);
-        let bar_ticks_start_x = bar_bounds.left().floor() as i32;
-        let bar_ticks_end_x = bar_bounds.right().ceil() as i32;
+        let (bar_ticks_start, bar_ticks_end) = match self.orientation {
+            PeakMeterOrientation::Horizontal => (
+                bar_bounds.left().floor() as i32,
+                bar_bounds.right().ceil() as i32,
+            ),
+            PeakMeterOrientation::Vertical => (
+                bar_bounds.top().floor() as i32,
+                bar_bounds.bottom().ceil() as i32,
+            ),
+        };
+        let bar_length = bar_ticks_end - bar_ticks_start;
 
         // NOTE: We'll scale this with the nearest integer DPI ratio. That way it will still look
         //       good at 2x scaling, and it won't look blurry at 1.x times scaling.
         let dpi_scale = cx.logical_to_physical(1.0).floor().max(1.0);
-        let bar_tick_coordinates = (bar_ticks_start_x..bar_ticks_end_x)
-            .step_by(((TICK_WIDTH + TICK_GAP) * dpi_scale).round() as usize);
-        for tick_x in bar_tick_coordinates {
-            let tick_fraction =
-                (tick_x - bar_ticks_start_x) as f32 / (bar_ticks_end_x - bar_ticks_start_x) as f32;
+
+        // femtovg draws paths centered on these coordinates, so in order to be pixel perfect we
+        // need to account for that. Otherwise the ticks will be 2px wide instead of 1px.
+        let tick_path = |offset: f32| {
+            let mut path = vg::Path::new();
+            match self.orientation {
+                PeakMeterOrientation::Horizontal => {
+                    let x = bar_ticks_start as f32 + offset + (dpi_scale / 2.0);
+                    path.move_to(x, bar_bounds.top());
+                    path.line_to(x, bar_bounds.bottom());
+                }
+                PeakMeterOrientation::Vertical => {
+                    let y = bar_ticks_end as f32 - offset - (dpi_scale / 2.0);
+                    path.move_to(bar_bounds.left(), y);
+                    path.line_to(bar_bounds.right(), y);
+                }
+            }
+
+            path
+        };
+
+        let bar_tick_offsets =
+            (0..bar_length).step_by(((TICK_WIDTH + TICK_GAP) * dpi_scale).round() as usize);
+        for tick_offset in bar_tick_offsets {
+            let tick_fraction = tick_offset as f32 / bar_length as f32;
             let tick_db = (tick_fraction * (MAX_TICK - MIN_TICK)) + MIN_TICK;
             if tick_db > level_dbfs {
                 break;
             }
 
-            // femtovg draws paths centered on these coordinates, so in order to be pixel perfect we
-            // need to account for that. Otherwise the ticks will be 2px wide instead of 1px.
-            let mut path = vg::Path::new();
-            path.move_to(tick_x as f32 + (dpi_scale / 2.0), bar_bounds.top());
-            path.line_to(tick_x as f32 + (dpi_scale / 2.0), bar_bounds.bottom());
+            let path = tick_path(tick_offset as f32);
 
             let grayscale_color = 0.3 + ((1.0 - tick_fraction) * 0.5);
             let mut paint = vg::Paint::color(vg::Color::rgbaf(
@@ -220,18 +421,12 @@ where
         }
 
         // Draw the hold peak value if the hold time option has been set
-        let db_to_x_coord = |db: f32| {
+        let db_to_offset = |db: f32| {
             let tick_fraction = (db - MIN_TICK) / (MAX_TICK - MIN_TICK);
-            bar_ticks_start_x as f32
-                + ((bar_ticks_end_x - bar_ticks_start_x) as f32 * tick_fraction).round()
+            (bar_length as f32 * tick_fraction).round()
         };
         if (MIN_TICK..MAX_TICK).contains(&peak_dbfs) {
-            // femtovg draws paths centered on these coordinates, so in order to be pixel perfect we
-            // need to account for that. Otherwise the ticks will be 2px wide instead of 1px.
-            let peak_x = db_to_x_coord(peak_dbfs);
-            let mut path = vg::Path::new();
-            path.move_to(peak_x + (dpi_scale / 2.0), bar_bounds.top());
-            path.line_to(peak_x + (dpi_scale / 2.0), bar_bounds.bottom());
+            let path = tick_path(db_to_offset(peak_dbfs));
 
             let mut paint = vg::Paint::color(vg::Color::rgbaf(0.3, 0.3, 0.3, opacity));
             paint.set_line_width(TICK_WIDTH * dpi_scale);
@@ -1,7 +1,86 @@
 //! Utilities for writing VIZIA widgets.
 
+use atomic_float::AtomicF32;
+use nih_plug::params::smoothing::Smoothable;
+use nih_plug::util::ParamMirror;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering};
+use std::sync::Arc;
 use vizia::prelude::*;
 
+/// A value that's shared with the audio thread and that can be read from the editor without
+/// locking. See [`atomic_value_lens()`].
+pub trait AtomicValue {
+    /// The type of the value stored in the atomic.
+    type Value;
+
+    /// Get the current value using a relaxed load.
+    fn load_value(&self) -> Self::Value;
+}
+
+impl AtomicValue for AtomicF32 {
+    type Value = f32;
+
+    fn load_value(&self) -> f32 {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+impl AtomicValue for AtomicI32 {
+    type Value = i32;
+
+    fn load_value(&self) -> i32 {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+impl AtomicValue for AtomicU32 {
+    type Value = u32;
+
+    fn load_value(&self) -> u32 {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+impl AtomicValue for AtomicBool {
+    type Value = bool;
+
+    fn load_value(&self) -> bool {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+impl<T: Smoothable> AtomicValue for ParamMirror<T> {
+    type Value = T;
+
+    fn load_value(&self) -> T {
+        self.load()
+    }
+}
+
+/// Bind to a value the plugin publishes from the audio thread through an `Arc<AtomicF32>` or any
+/// other [`AtomicValue`]. `lens` should point to that `Arc` in your model, and the `map` function
+/// converts the loaded value to whatever the widget needs. This is usually a decibel value for a
+/// meter, or a formatted string for a label. VIZIA checks the bound value every frame, so the
+/// widget only updates when the mapped value actually changes.
+///
+/// ```ignore
+/// Label::new(
+///     cx,
+///     atomic_value_lens(Data::gain_reduction, |gain| {
+///         format!("{:.1} dB", util::gain_to_db(gain))
+///     }),
+/// );
+/// ```
+pub fn atomic_value_lens<L, A, F, R>(lens: L, map: F) -> impl Lens<Target = R>
+where
+    L: Lens<Target = Arc<A>>,
+    A: AtomicValue + 'static,
+    F: Fn(A::Value) -> R + Clone + 'static,
+    R: Clone + 'static,
+{
+    lens.map(move |value| map(value.load_value()))
+}
+
 /// An extension trait for [`Modifiers`] that adds platform-independent getters.
 pub trait ModifiersExt {
     /// Returns true if the Command (on macOS) or Ctrl (on any other platform) key is pressed.
@@ -0,0 +1,366 @@
+//! An X-Y pad that controls two of NIH-plug's [`Param`] types at the same time.
+
+use nih_plug::prelude::Param;
+use vizia::prelude::*;
+
+use super::param_base::ParamWidgetBase;
+use super::util::{self, ModifiersExt};
+
+/// When shift+dragging the X-Y pad, one pixel dragged corresponds to this much change in the
+/// normalized parameter.
+const GRANULAR_DRAG_MULTIPLIER: f32 = 0.1;
+
+/// An X-Y pad that controls two parameters at the same time by binding them to one of the two
+/// axes. The handle's position follows the parameters' normalized values, so skewed ranges are
+/// displayed the same way a [`ParamSlider`][super::ParamSlider] would display them. Both
+/// parameters are changed as part of a single gesture, and double clicking, right clicking, or
+/// Ctrl+clicking the pad resets both parameters to their default values.
+///
+/// Holding Shift while dragging allows for more granular changes, and the scroll wheel changes the
+/// parameters in steps. Vertical scrolling changes the Y-parameter and horizontal scrolling changes
+/// the X-parameter.
+#[derive(Lens)]
+pub struct XyPad {
+    x_param_base: ParamWidgetBase,
+    y_param_base: ParamWidgetBase,
+
+    /// Will be set to `true` if we're dragging the parameter. Resetting the parameter should not
+    /// initiate a drag.
+    drag_active: bool,
+    /// This keeps track of whether the user has pressed shift and a granular drag is active. This
+    /// works exactly the same as in `ParamSlider`.
+    granular_drag_status: Option<GranularDragStatus>,
+    /// The number of (fractional) scrolled lines that have not yet been turned into parameter
+    /// change events, as an `(x, y)` pair. This is needed to support trackpads with smooth
+    /// scrolling.
+    scrolled_lines: (f32, f32),
+}
+
+/// The [`XyPad`]'s handle. This is a separate element to allow easier positioning.
+struct XyPadHandle;
+
+// TODO: Vizia's derive macro requires this to be pub
+#[derive(Debug, Clone, Copy)]
+pub struct GranularDragStatus {
+    /// The mouse's X-coordinate when the granular drag was started.
+    pub starting_x_coordinate: f32,
+    /// The normalized value when the granular drag was started for the X-parameter.
+    pub x_starting_value: f32,
+    /// The mouse's Y-coordinate when the granular drag was started.
+    pub starting_y_coordinate: f32,
+    /// The normalized value when the granular drag was started for the Y-parameter.
+    pub y_starting_value: f32,
+}
+
+impl XyPad {
+    /// Creates a new [`XyPad`] for the given parameters. See
+    /// [`ParamSlider`][super::ParamSlider] for more information on this function's arguments. The
+    /// first parameter is bound to the X-axis, and the second parameter is bound to the Y-axis.
+    pub fn new<L, Params, P1, P2, FMap1, FMap2>(
+        cx: &mut Context,
+        params: L,
+        params_to_x_param: FMap1,
+        params_to_y_param: FMap2,
+    ) -> Handle<Self>
+    where
+        L: Lens<Target = Params> + Clone,
+        Params: 'static,
+        P1: Param + 'static,
+        P2: Param + 'static,
+        FMap1: Fn(&Params) -> &P1 + Copy + 'static,
+        FMap2: Fn(&Params) -> &P2 + Copy + 'static,
+    {
+        Self {
+            x_param_base: ParamWidgetBase::new(cx, params, params_to_x_param),
+            y_param_base: ParamWidgetBase::new(cx, params, params_to_y_param),
+
+            drag_active: false,
+            granular_drag_status: None,
+            scrolled_lines: (0.0, 0.0),
+        }
+        .build(
+            cx,
+            // We need to create lenses for both the x-parameter's values and the y-parameter's
+            // values
+            ParamWidgetBase::build_view(params, params_to_x_param, move |cx, x_param_data| {
+                ParamWidgetBase::view(cx, params, params_to_y_param, move |cx, y_param_data| {
+                    let x_position_lens = x_param_data.make_lens(|param| {
+                        Percentage(param.unmodulated_normalized_value() * 100.0)
+                    });
+                    let y_position_lens = y_param_data.make_lens(|param| {
+                        // NOTE: The y-axis increments downards, and we want high values at
+                        //       the top and low values at the bottom
+                        Percentage((1.0 - param.unmodulated_normalized_value()) * 100.0)
+                    });
+
+                    // Another handle is drawn below the regular handle to show the
+                    // modualted value
+                    let modulated_x_position_lens = x_param_data
+                        .make_lens(|param| Percentage(param.modulated_normalized_value() * 100.0));
+                    let modulated_y_position_lens = y_param_data.make_lens(|param| {
+                        Percentage((1.0 - param.modulated_normalized_value()) * 100.0)
+                    });
+
+                    XyPadHandle::new(cx)
+                        .class("xy-pad__handle--modulated")
+                        .position_type(PositionType::SelfDirected)
+                        .top(modulated_y_position_lens)
+                        .left(modulated_x_position_lens)
+                        .hoverable(false);
+                    XyPadHandle::new(cx)
+                        .position_type(PositionType::SelfDirected)
+                        .top(y_position_lens)
+                        .left(x_position_lens)
+                        .hoverable(false);
+                });
+            }),
+        )
+    }
+
+    /// Should be called at the start of a drag operation.
+    fn begin_set_parameters(&self, cx: &mut EventContext) {
+        // NOTE: We'll always modify the X-parameter last so the host will keep that parameter
+        //       highlighted
+        self.y_param_base.begin_set_parameter(cx);
+        self.x_param_base.begin_set_parameter(cx);
+    }
+
+    /// Resets both parameters to their default values. `begin_set_parameters()` needs to be called
+    /// first.
+    fn reset_parameters(&self, cx: &mut EventContext) {
+        self.y_param_base
+            .set_normalized_value(cx, self.y_param_base.default_normalized_value());
+        self.x_param_base
+            .set_normalized_value(cx, self.x_param_base.default_normalized_value());
+    }
+
+    /// Set a normalized value for both parameters based on mouse coordinates.
+    /// `begin_set_parameters()` needs to be called first.
+    fn set_normalized_values_for_mouse_pos(
+        &self,
+        cx: &mut EventContext,
+        (x_pos, y_pos): (f32, f32),
+    ) {
+        let x_value = util::remap_current_entity_x_coordinate(cx, x_pos);
+        // We want the top of the widget to be 1.0 and the bottom to be 0.0, this is the opposite of
+        // how the y-coordinate works
+        let y_value = 1.0 - util::remap_current_entity_y_coordinate(cx, y_pos);
+
+        self.y_param_base.set_normalized_value(cx, y_value);
+        self.x_param_base.set_normalized_value(cx, x_value);
+    }
+
+    /// Should be called at the end of a drag operation.
+    fn end_set_parameters(&self, cx: &mut EventContext) {
+        self.x_param_base.end_set_parameter(cx);
+        self.y_param_base.end_set_parameter(cx);
+    }
+
+    /// Change a parameter by the number of whole lines in `scrolled_lines`, leaving only the
+    /// fractional part.
+    fn scroll_parameter(
+        cx: &mut EventContext,
+        param_base: &ParamWidgetBase,
+        scrolled_lines: &mut f32,
+        drag_active: bool,
+    ) {
+        if scrolled_lines.abs() < 1.0 {
+            return;
+        }
+
+        let use_finer_steps = cx.modifiers().shift();
+
+        // Scrolling while dragging needs to be taken into account here
+        if !drag_active {
+            param_base.begin_set_parameter(cx);
+        }
+
+        let mut current_value = param_base.unmodulated_normalized_value();
+
+        while *scrolled_lines >= 1.0 {
+            current_value = param_base.next_normalized_step(current_value, use_finer_steps);
+            param_base.set_normalized_value(cx, current_value);
+            *scrolled_lines -= 1.0;
+        }
+
+        while *scrolled_lines <= -1.0 {
+            current_value = param_base.previous_normalized_step(current_value, use_finer_steps);
+            param_base.set_normalized_value(cx, current_value);
+            *scrolled_lines += 1.0;
+        }
+
+        if !drag_active {
+            param_base.end_set_parameter(cx);
+        }
+    }
+}
+
+impl XyPadHandle {
+    fn new(cx: &mut Context) -> Handle<Self> {
+        // This doesn't have or need any special behavior, it's just a marker element used for
+        // positioning the handle
+        Self.build(cx, |_| ())
+    }
+}
+
+impl View for XyPad {
+    fn element(&self) -> Option<&'static str> {
+        Some("xy-pad")
+    }
+
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|window_event, meta| match window_event {
+            // Vizia always captures the third mouse click as a triple click. See `ParamSlider` for
+            // more information.
+            WindowEvent::MouseDown(MouseButton::Left)
+            | WindowEvent::MouseTripleClick(MouseButton::Left) => {
+                if cx.modifiers().command() {
+                    // Ctrl+Click, double click, and right clicks should reset the parameters
+                    // instead of initiating a drag operation
+                    self.begin_set_parameters(cx);
+                    self.reset_parameters(cx);
+                    self.end_set_parameters(cx);
+                } else {
+                    self.drag_active = true;
+                    cx.capture();
+                    // NOTE: Otherwise we don't get key up events
+                    cx.focus();
+                    cx.set_active(true);
+
+                    // When holding down shift while clicking on the X-Y pad we want to granuarly
+                    // edit the parameters without jumping to a new value
+                    self.begin_set_parameters(cx);
+                    if cx.modifiers().shift() {
+                        self.granular_drag_status = Some(GranularDragStatus {
+                            starting_x_coordinate: cx.mouse().cursorx,
+                            x_starting_value: self.x_param_base.unmodulated_normalized_value(),
+                            starting_y_coordinate: cx.mouse().cursory,
+                            y_starting_value: self.y_param_base.unmodulated_normalized_value(),
+                        });
+                    } else {
+                        self.granular_drag_status = None;
+                        self.set_normalized_values_for_mouse_pos(
+                            cx,
+                            (cx.mouse().cursorx, cx.mouse().cursory),
+                        );
+                    }
+                }
+
+                meta.consume();
+            }
+            WindowEvent::MouseDoubleClick(MouseButton::Left)
+            | WindowEvent::MouseDown(MouseButton::Right)
+            | WindowEvent::MouseDoubleClick(MouseButton::Right)
+            | WindowEvent::MouseTripleClick(MouseButton::Right) => {
+                // Ctrl+Click, double click, and right clicks should reset the parameters instead of
+                // initiating a drag operation
+                self.begin_set_parameters(cx);
+                self.reset_parameters(cx);
+                self.end_set_parameters(cx);
+
+                meta.consume();
+            }
+            WindowEvent::MouseUp(MouseButton::Left) => {
+                if self.drag_active {
+                    self.drag_active = false;
+                    cx.release();
+                    cx.set_active(false);
+
+                    self.end_set_parameters(cx);
+
+                    meta.consume();
+                }
+            }
+            WindowEvent::MouseMove(x, y) => {
+                if self.drag_active {
+                    if cx.modifiers().shift() {
+                        // If shift is being held then the drag should be more granular instead of
+                        // absolute
+                        let granular_drag_status =
+                            *self
+                                .granular_drag_status
+                                .get_or_insert_with(|| GranularDragStatus {
+                                    starting_x_coordinate: *x,
+                                    x_starting_value: self
+                                        .x_param_base
+                                        .unmodulated_normalized_value(),
+                                    starting_y_coordinate: *y,
+                                    y_starting_value: self
+                                        .y_param_base
+                                        .unmodulated_normalized_value(),
+                                });
+
+                        // These positions should be compensated for the DPI scale so it remains
+                        // consistent
+                        let dpi_scale = cx.scale_factor();
+                        let start_x = util::remap_current_entity_x_t(
+                            cx,
+                            granular_drag_status.x_starting_value,
+                        );
+                        let delta_x = (*x - granular_drag_status.starting_x_coordinate)
+                            * GRANULAR_DRAG_MULTIPLIER
+                            * dpi_scale;
+
+                        let start_y = util::remap_current_entity_y_t(
+                            cx,
+                            // NOTE: Just like above, the corodinates go from top to bottom
+                            //       while we want the X-Y pad to go from bottom to top
+                            1.0 - granular_drag_status.y_starting_value,
+                        );
+                        let delta_y = (*y - granular_drag_status.starting_y_coordinate)
+                            * GRANULAR_DRAG_MULTIPLIER
+                            * dpi_scale;
+
+                        self.set_normalized_values_for_mouse_pos(
+                            cx,
+                            (start_x + delta_x, start_y + delta_y),
+                        );
+                    } else {
+                        self.granular_drag_status = None;
+                        self.set_normalized_values_for_mouse_pos(cx, (*x, *y));
+                    }
+                }
+            }
+            WindowEvent::KeyUp(_, Some(Key::Shift)) => {
+                // If this happens while dragging, snap back to reality uh I mean the current screen
+                // position
+                if self.drag_active && self.granular_drag_status.is_some() {
+                    self.granular_drag_status = None;
+                    self.set_normalized_values_for_mouse_pos(
+                        cx,
+                        (cx.mouse().cursorx, cx.mouse().cursory),
+                    );
+                }
+            }
+            WindowEvent::MouseScroll(scroll_x, scroll_y) => {
+                // With a regular scroll wheel `scroll_*` will only ever be -1 or 1, but with smooth
+                // scrolling trackpads being a thing `scroll_*` can be anything.
+                let (remaining_scroll_x, remaining_scroll_y) = &mut self.scrolled_lines;
+                *remaining_scroll_x += scroll_x;
+                *remaining_scroll_y += scroll_y;
+
+                Self::scroll_parameter(
+                    cx,
+                    &self.x_param_base,
+                    remaining_scroll_x,
+                    self.drag_active,
+                );
+                Self::scroll_parameter(
+                    cx,
+                    &self.y_param_base,
+                    remaining_scroll_y,
+                    self.drag_active,
+                );
+
+                meta.consume();
+            }
+            _ => {}
+        });
+    }
+}
+
+impl View for XyPadHandle {
+    fn element(&self) -> Option<&'static str> {
+        Some("xy-pad__handle")
+    }
+}
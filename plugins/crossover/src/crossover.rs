@@ -16,3 +16,5 @@
 
 pub mod fir;
 pub mod iir;
+#[cfg(test)]
+pub mod test_util;
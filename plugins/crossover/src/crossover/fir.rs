@@ -15,21 +15,35 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use nih_plug::debug::*;
+use nih_plug::util::SharedCache;
 use realfft::num_complex::Complex32;
 use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
 use std::f32;
 use std::sync::Arc;
 
-use self::filter::{FftFirFilter, FirCoefficients, FFT_INPUT_SIZE, FFT_SIZE};
+use self::filter::{FftFirFilter, FirCoefficients, FirWindow, FFT_INPUT_SIZE, FFT_SIZE};
 use crate::crossover::fir::filter::FILTER_SIZE;
-use crate::crossover::iir::biquad::{BiquadCoefficients, NEUTRAL_Q};
+use crate::crossover::iir::biquad::{BiquadCoefficients, BUTTERWORTH_4_QS, NEUTRAL_Q};
 use crate::{NUM_BANDS, NUM_CHANNELS};
 
 pub mod filter;
 
+/// The FFT plans only depend on the FFT size, so all instances in the same process share them.
+static FFT_PLANS: SharedCache<usize, FftPlans> = SharedCache::new();
+
+/// The forward and inverse real FFT plans for a specific FFT size.
+struct FftPlans {
+    /// The algorithm for the FFT operation.
+    r2c: Arc<dyn RealToComplex<f32>>,
+    /// The algorithm for the IFFT operation.
+    c2r: Arc<dyn ComplexToReal<f32>>,
+}
+
 pub struct FirCrossover {
     /// The kind of crossover to use. `.update_filters()` must be called after changing this.
     mode: FirCrossoverType,
+    /// The filter length and window used for the filters. Set through `.update()`.
+    sharpness: FirSharpness,
 
     /// Filters for each of the bands. Depending on the number of bands argument passed to
     /// `.process()`, two to five of these may be used. The first one always contains a low-pass
@@ -57,11 +71,13 @@ pub struct FirCrossover {
     /// This is stored as an array since each channel is processed individually. While this should
     /// of course stay in sync, this makes it much simpler to process both channels in sequence.
     io_buffers_next_indices: [usize; NUM_CHANNELS as usize],
+    /// The padded input DFT from the previous period for each channel. When a band filter
+    /// crossfades to new coefficients, it needs this to compute the new filter's overlap from the
+    /// previous period.
+    previous_input_ffts: Box<[[Complex32; FFT_SIZE / 2 + 1]; NUM_CHANNELS as usize]>,
 
-    /// The algorithm for the FFT operation.
-    r2c_plan: Arc<dyn RealToComplex<f32>>,
-    /// The algorithm for the IFFT operation.
-    c2r_plan: Arc<dyn ComplexToReal<f32>>,
+    /// The FFT and IFFT algorithms, shared with the other instances through [`FFT_PLANS`].
+    fft_plans: Arc<FftPlans>,
 
     /// A real buffer that may be written to in place during the FFT and IFFT operations.
     real_scratch_buffer: Box<[f32; FFT_SIZE]>,
@@ -70,13 +86,83 @@ pub struct FirCrossover {
     complex_scratch_buffer: Box<[Complex32; FFT_SIZE / 2 + 1]>,
 }
 
-/// The type of FIR crossover to use.
-#[derive(Debug, Clone, Copy)]
+/// The type of FIR crossover to use. These emulate the filter slopes of the Linkwitz-Riley
+/// [`super::iir::IirCrossoverType`]s, but with linear-phase FIR filters instead of minimum-phase
+/// IIR filters. The FIR filters are designed from the same filters, see
+/// [`FirCoefficients::design_linear_phase_low_pass_from_biquads()`]. That only works for filters
+/// whose magnitude response is the square of another filter's response, so there is no
+/// linear-phase version of the Butterworth crossover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FirCrossoverType {
-    /// Emulates the filter slope of [`super::iir::IirCrossoverType`], but with linear-phase FIR
-    /// filters instead of minimum-phase IIR filters. The exact same filters are used to design the
-    /// FIR filters.
+    /// Emulates the LR12 crossover's 12 dB/octave slopes.
+    LinkwitzRiley12LinearPhase,
+    /// Emulates the LR24 crossover's 24 dB/octave slopes.
     LinkwitzRiley24LinearPhase,
+    /// Emulates the LR48 crossover's 48 dB/octave slopes.
+    LinkwitzRiley48LinearPhase,
+}
+
+/// The tradeoff between the steepness of the crossover's transition bands and the amount of
+/// pre-ringing. Linear-phase filters have symmetrical impulse responses, so any ringing caused by
+/// the filter also happens before a transient. This pre-ringing can be audible as a soft swell
+/// before sharp transients, especially for low crossover frequencies. Shorter filters can only ring
+/// for half their length before the transient, and windows that taper off more gradually reduce the
+/// ringing itself. But shorter filters truncate the impulse response of the filter they emulate, so
+/// the slopes become shallower than the IIR version's at low crossover frequencies. The latency is
+/// half of the filter's length plus the FFT block size, so shorter filters also reduce the latency.
+///
+/// The bands always sum back to the (delayed) input regardless of this setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirSharpness {
+    /// The full length filter with a Blackman window. This matches the IIR version's slopes most
+    /// closely.
+    Sharp,
+    /// A filter with half the length and a Blackman window.
+    Balanced,
+    /// A filter with a quarter of the length and a Blackman-Harris window. Pre-ringing is much
+    /// shorter, but low crossover frequencies will have noticeably shallower slopes.
+    LowPreRinging,
+}
+
+impl FirCrossoverType {
+    /// The biquad low-pass filters that result in this crossover's low-pass response at
+    /// `frequency` when they're applied to a signal twice. The LR12 and LR24 crossovers only need a
+    /// single filter, so the second filter is an identity filter for those.
+    fn low_pass_biquads(self, sample_rate: f32, frequency: f32) -> [BiquadCoefficients<f32>; 2] {
+        match self {
+            FirCrossoverType::LinkwitzRiley12LinearPhase => [
+                BiquadCoefficients::first_order_lowpass(sample_rate, frequency),
+                BiquadCoefficients::identity(),
+            ],
+            FirCrossoverType::LinkwitzRiley24LinearPhase => [
+                BiquadCoefficients::lowpass(sample_rate, frequency, NEUTRAL_Q),
+                BiquadCoefficients::identity(),
+            ],
+            FirCrossoverType::LinkwitzRiley48LinearPhase => {
+                BUTTERWORTH_4_QS.map(|q| BiquadCoefficients::lowpass(sample_rate, frequency, q))
+            }
+        }
+    }
+}
+
+impl FirSharpness {
+    /// The number of taps for the FIR filters. This is always odd.
+    pub fn num_taps(self) -> usize {
+        match self {
+            FirSharpness::Sharp => FILTER_SIZE,
+            FirSharpness::Balanced => FILTER_SIZE / 2 + 1,
+            FirSharpness::LowPreRinging => FILTER_SIZE / 4 + 1,
+        }
+    }
+
+    /// The window function that best fits this setting. A different window can be passed to
+    /// [`FirCrossover::update()`] instead.
+    pub fn default_window(self) -> FirWindow {
+        match self {
+            FirSharpness::Sharp | FirSharpness::Balanced => FirWindow::Blackman,
+            FirSharpness::LowPreRinging => FirWindow::BlackmanHarris,
+        }
+    }
 }
 
 impl FirCrossover {
@@ -87,10 +173,18 @@ impl FirCrossover {
     /// Make sure to add the latency reported by [`latency()`][Self::latency()] to the plugin's
     /// reported latency.
     pub fn new(mode: FirCrossoverType) -> Self {
-        let mut fft_planner = RealFftPlanner::new();
+        let fft_plans = FFT_PLANS.get_or_insert_with(FFT_SIZE, |&fft_size| {
+            let mut fft_planner = RealFftPlanner::new();
+
+            FftPlans {
+                r2c: fft_planner.plan_fft_forward(fft_size),
+                c2r: fft_planner.plan_fft_inverse(fft_size),
+            }
+        });
 
         Self {
             mode,
+            sharpness: FirSharpness::Sharp,
             band_filters: Default::default(),
 
             input_buffers: Box::new([[0.0; FFT_INPUT_SIZE]; NUM_CHANNELS as usize]),
@@ -98,22 +192,31 @@ impl FirCrossover {
                 [[[0.0; FFT_INPUT_SIZE]; NUM_CHANNELS as usize]; NUM_BANDS],
             ),
             io_buffers_next_indices: [0; NUM_CHANNELS as usize],
-            r2c_plan: fft_planner.plan_fft_forward(FFT_SIZE),
-            c2r_plan: fft_planner.plan_fft_inverse(FFT_SIZE),
+            previous_input_ffts: Box::new(
+                [[Complex32::default(); FFT_SIZE / 2 + 1]; NUM_CHANNELS as usize],
+            ),
+            fft_plans,
             real_scratch_buffer: Box::new([0.0; FFT_SIZE]),
             complex_scratch_buffer: Box::new([Complex32::default(); FFT_SIZE / 2 + 1]),
         }
     }
 
-    /// Get the current latency in samples. This depends on the selected mode.
+    /// Change the kind of crossover to use. `.update()` needs to be called afterwards to compute
+    /// the new filters, which are then crossfaded to like when the crossover frequencies change.
+    pub fn set_mode(&mut self, mode: FirCrossoverType) {
+        self.mode = mode;
+    }
+
+    /// Get the current latency in samples. This depends on the sharpness, but not on the selected
+    /// mode.
     pub fn latency(&self) -> u32 {
-        // Actually, that's a lie, since we currently only do linear-phase filters with a constant
-        // size
-        match self.mode {
-            FirCrossoverType::LinkwitzRiley24LinearPhase => {
-                (FFT_INPUT_SIZE + (FILTER_SIZE / 2)) as u32
-            }
-        }
+        (FFT_INPUT_SIZE + (self.sharpness.num_taps() / 2)) as u32
+    }
+
+    /// Get the number of samples the crossover keeps producing output after the input becomes
+    /// silent. This is the latency plus the second half of the filter kernel.
+    pub fn tail_length(&self) -> u32 {
+        self.latency() + (self.sharpness.num_taps() / 2) as u32
     }
 
     /// Split the signal into bands using the crossovers previously configured through `.update()`.
@@ -173,7 +276,8 @@ impl FirCrossover {
                     .copy_from_slice(&self.input_buffers[channel_idx]);
                 self.real_scratch_buffer[FFT_INPUT_SIZE..].fill(0.0);
 
-                self.r2c_plan
+                self.fft_plans
+                    .r2c
                     .process_with_scratch(
                         &mut *self.real_scratch_buffer,
                         &mut *self.complex_scratch_buffer,
@@ -193,123 +297,149 @@ impl FirCrossover {
                 {
                     band_filter.process(
                         &input_fft,
+                        &self.previous_input_ffts[channel_idx],
                         &mut band_output_buffers[channel_idx],
                         channel_idx,
-                        &*self.c2r_plan,
+                        &*self.fft_plans.c2r,
                         &mut self.real_scratch_buffer,
                         &mut self.complex_scratch_buffer,
                     )
                 }
 
+                self.previous_input_ffts[channel_idx] = input_fft;
                 self.io_buffers_next_indices[channel_idx] = 0;
             }
         }
     }
 
     /// Update the crossover frequencies for all filters. `num_bands` is assumed to be in `[2,
-    /// NUM_BANDS]`.
+    /// NUM_BANDS]`. The filters will crossfade to the new frequencies over the next period, see
+    /// [`FftFirFilter`] for more information. Call [`reset()`][Self::reset()] afterwards to skip
+    /// this crossfade. Changing the sharpness changes the latency, so in that case the crossover is
+    /// reset instead. `window` is applied to the filters' impulse responses, see [`FirWindow`].
     pub fn update(
         &mut self,
         sample_rate: f32,
         num_bands: usize,
         frequencies: [f32; NUM_BANDS - 1],
+        sharpness: FirSharpness,
+        window: FirWindow,
     ) {
-        match self.mode {
-            FirCrossoverType::LinkwitzRiley24LinearPhase => {
-                // The goal here is to design 2-5 filters with the same frequency response
-                // magnitudes as the split bands in the IIR LR24 crossover version with the same
-                // center frequencies would have. The algorithm works in two stages. First, the IIR
-                // low-pass filters for the 1-4 crossovers used in the equivalent IIR LR24 version
-                // are computed and converted to equivalent linear-phase FIR filters using the
-                // algorithm described below in `FirCoefficients`. Then these are used to build the
-                // coefficients for the 2-5 bands:
+        let sharpness_changed = sharpness != self.sharpness;
+        self.sharpness = sharpness;
+        let num_taps = sharpness.num_taps();
+
+        // The goal here is to design 2-5 filters with the same frequency response magnitudes as the
+        // split bands in the equivalent IIR Linkwitz-Riley crossover with the same center
+        // frequencies would have. The algorithm works in two stages. First, the IIR low-pass
+        // filters for the 1-4 crossovers used in the equivalent IIR version are computed and
+        // converted to equivalent linear-phase FIR filters using the algorithm described below in
+        // `FirCoefficients`. Then these are used to build the coefficients for the 2-5 bands:
+        //
+        // - The first band is always simply the first band's
+        //   low-pass filter.
+        // - The middle bands are band-pass filters. These are created by taking the next
+        //   crossover's low-pass filter and subtracting the accumulated band impulse response up
+        //   to that point. The accumulated band impulse response is initialized with the first
+        //   band's low-pass filter, and the band-pass filter for every band after that gets added
+        //   to it.
+        // - The final band is a high-pass filter that's computed through spectral inversion from
+        //   the accumulated band impulse response.
+
+        // As explained above, we'll start with the low-pass band
+        nih_debug_assert!(num_bands >= 2);
+        let iir_coefs = self.mode.low_pass_biquads(sample_rate, frequencies[0]);
+        let lp_fir_coefs = FirCoefficients::design_linear_phase_low_pass_from_biquads(
+            &iir_coefs, num_taps, window,
+        );
+        self.band_filters[0].recompute_coefficients(
+            lp_fir_coefs.clone(),
+            &*self.fft_plans.r2c,
+            &mut self.real_scratch_buffer,
+            &mut self.complex_scratch_buffer,
+        );
+
+        // For the band-pass filters and the final high-pass filter, we need to keep track of the
+        // accumulated impulse response
+        let mut accumulated_ir = lp_fir_coefs;
+        for (split_frequency, band_filter) in frequencies
+            .iter()
+            .zip(self.band_filters.iter_mut())
+            // There are `num_bands` bands, so there are `num_bands - 1` crossovers. The last band
+            // is formed from the accumulated impulse response.
+            .take(num_bands - 1)
+            // And the first band is already taken care of
+            .skip(1)
+        {
+            let iir_coefs = self.mode.low_pass_biquads(sample_rate, *split_frequency);
+            let lp_fir_coefs = FirCoefficients::design_linear_phase_low_pass_from_biquads(
+                &iir_coefs, num_taps, window,
+            );
+
+            // We want the band between the accumulated frequency response and the next crossover's
+            // low-pass filter
+            let mut fir_bp_coefs = lp_fir_coefs;
+            for (bp_coef, accumulated_coef) in
+                fir_bp_coefs.0.iter_mut().zip(accumulated_ir.0.iter_mut())
+            {
+                // At this poing `bp_coef` is the low-pass filter
+                *bp_coef -= *accumulated_coef;
+
+                // And the accumulated coefficients for the next band/for the high-pass filter
+                // should contain this band-pass filter. This becomes a bit weirder to read when
+                // it's a single loop, but essentially this is what's going on here:
                 //
-                // - The first band is always simply the first band's
-                //   low-pass filter.
-                // - The middle bands are band-pass filters. These are created by taking the next
-                //   crossover's low-pass filter and subtracting the accumulated band impulse
-                //   response up to that point. The accumulated band impulse response is initialized
-                //   with the first band's low-pass filter, and the band-pass filter for every band
-                //   after that gets added to it.
-                // - The final band is a high-pass filter that's computed through spectral inversion
-                //   from the accumulated band impulse response.
-
-                // As explained above, we'll start with the low-pass band
-                nih_debug_assert!(num_bands >= 2);
-                let iir_coefs = BiquadCoefficients::lowpass(sample_rate, frequencies[0], NEUTRAL_Q);
-                let lp_fir_coefs =
-                    FirCoefficients::design_fourth_order_linear_phase_low_pass_from_biquad(
-                        iir_coefs,
-                    );
-                self.band_filters[0].recompute_coefficients(
-                    lp_fir_coefs.clone(),
-                    &*self.r2c_plan,
-                    &mut self.real_scratch_buffer,
-                    &mut self.complex_scratch_buffer,
-                );
+                //     fir_bp_coefs = fir_lp_coefs - accumulated_ir
+                //     accumulated_ir += fir_bp_coefs
 
-                // For the band-pass filters and the final high-pass filter, we need to keep track
-                // of the accumulated impulse response
-                let mut accumulated_ir = lp_fir_coefs;
-                for (split_frequency, band_filter) in frequencies
-                    .iter()
-                    .zip(self.band_filters.iter_mut())
-                    // There are `num_bands` bands, so there are `num_bands - 1` crossovers. The
-                    // last band is formed from the accumulated impulse response.
-                    .take(num_bands - 1)
-                    // And the first band is already taken care of
-                    .skip(1)
-                {
-                    let iir_coefs =
-                        BiquadCoefficients::lowpass(sample_rate, *split_frequency, NEUTRAL_Q);
-                    let lp_fir_coefs =
-                        FirCoefficients::design_fourth_order_linear_phase_low_pass_from_biquad(
-                            iir_coefs,
-                        );
+                *accumulated_coef += *bp_coef;
+            }
 
-                    // We want the band between the accumulated frequency response and the next
-                    // crossover's low-pass filter
-                    let mut fir_bp_coefs = lp_fir_coefs;
-                    for (bp_coef, accumulated_coef) in
-                        fir_bp_coefs.0.iter_mut().zip(accumulated_ir.0.iter_mut())
-                    {
-                        // At this poing `bp_coef` is the low-pass filter
-                        *bp_coef -= *accumulated_coef;
-
-                        // And the accumulated coefficients for the next band/for the high-pass
-                        // filter should contain this band-pass filter. This becomes a bit weirder
-                        // to read when it's a single loop, but essentially this is what's going on
-                        // here:
-                        //
-                        //     fir_bp_coefs = fir_lp_coefs - accumulated_ir
-                        //     accumulated_ir += fir_bp_coefs
-
-                        *accumulated_coef += *bp_coef;
-                    }
-
-                    band_filter.recompute_coefficients(
-                        fir_bp_coefs,
-                        &*self.r2c_plan,
-                        &mut self.real_scratch_buffer,
-                        &mut self.complex_scratch_buffer,
-                    );
-                }
+            band_filter.recompute_coefficients(
+                fir_bp_coefs,
+                &*self.fft_plans.r2c,
+                &mut self.real_scratch_buffer,
+                &mut self.complex_scratch_buffer,
+            );
+        }
 
-                // And finally we can do a spectral inversion of the accumulated IR to the the last
-                // band's high-pass filter
-                let mut fir_hp_coefs = accumulated_ir;
-                for coef in fir_hp_coefs.0.iter_mut() {
-                    *coef = -*coef;
-                }
-                fir_hp_coefs.0[FILTER_SIZE / 2] += 1.0;
+        // And finally we can do a spectral inversion of the accumulated IR to the the last band's
+        // high-pass filter
+        let mut fir_hp_coefs = accumulated_ir;
+        for coef in fir_hp_coefs.0.iter_mut() {
+            *coef = -*coef;
+        }
+        fir_hp_coefs.0[num_taps / 2] += 1.0;
 
-                self.band_filters[num_bands - 1].recompute_coefficients(
-                    fir_hp_coefs,
-                    &*self.r2c_plan,
-                    &mut self.real_scratch_buffer,
-                    &mut self.complex_scratch_buffer,
-                );
-            }
+        self.band_filters[num_bands - 1].recompute_coefficients(
+            fir_hp_coefs,
+            &*self.fft_plans.r2c,
+            &mut self.real_scratch_buffer,
+            &mut self.complex_scratch_buffer,
+        );
+
+        if sharpness_changed {
+            self.reset();
+        }
+    }
+
+    /// Use the same filters as `other`, for instance when both crossovers use the same crossover
+    /// frequencies. This skips designing the filters again. Like with `.update()`, the filters
+    /// crossfade to the new coefficients over the next period, and the crossover is reset if the
+    /// sharpness changed.
+    pub fn update_from(&mut self, other: &FirCrossover) {
+        let sharpness_changed = other.sharpness != self.sharpness;
+        self.mode = other.mode;
+        self.sharpness = other.sharpness;
+
+        for (band_filter, other_band_filter) in
+            self.band_filters.iter_mut().zip(other.band_filters.iter())
+        {
+            band_filter.copy_coefficients_from(other_band_filter);
+        }
+
+        if sharpness_changed {
+            self.reset();
         }
     }
 
@@ -325,9 +455,106 @@ impl FirCrossover {
                 buffer.fill(0.0);
             }
         }
+        for input_fft in self.previous_input_ffts.iter_mut() {
+            input_fft.fill(Complex32::default());
+        }
 
         // This being 0 means that the very first period will simply output the silence form above
         // and gather input for the next FFT
         self.io_buffers_next_indices.fill(0);
     }
+
+    /// Reset the filter state for band `band_idx` only. The band's output is silent until the next
+    /// period, and any pending crossfade to new coefficients is skipped for this band. The other
+    /// bands are not affected, so this can be used to clear stale state from bands that become
+    /// active when the number of bands increases without causing a discontinuity in the bands
+    /// that were already active. This should be called after `.update()` so the new band starts
+    /// with the new coefficients.
+    pub fn reset_band(&mut self, band_idx: usize) {
+        self.band_filters[band_idx].reset();
+        for buffer in self.band_output_buffers[band_idx].iter_mut() {
+            buffer.fill(0.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crossover::test_util::{process_fir, test_signal};
+
+    #[test]
+    fn bands_sum_to_delayed_input() {
+        for mode in [
+            FirCrossoverType::LinkwitzRiley12LinearPhase,
+            FirCrossoverType::LinkwitzRiley24LinearPhase,
+            FirCrossoverType::LinkwitzRiley48LinearPhase,
+        ] {
+            for sharpness in [
+                FirSharpness::Sharp,
+                FirSharpness::Balanced,
+                FirSharpness::LowPreRinging,
+            ] {
+                let mut crossover = FirCrossover::new(mode);
+                crossover.update(
+                    44_100.0,
+                    NUM_BANDS,
+                    [100.0, 1_000.0, 5_000.0, 15_000.0],
+                    sharpness,
+                    sharpness.default_window(),
+                );
+                crossover.reset();
+
+                let latency = crossover.latency() as usize;
+                let num_samples = latency + FFT_INPUT_SIZE;
+                let mut input = vec![0.0f32; num_samples];
+                input[0] = 1.0;
+                let band_outputs = process_fir(&mut crossover, NUM_BANDS, &input);
+
+                for sample_idx in 0..num_samples {
+                    let sum: f32 = band_outputs.iter().map(|band| band[sample_idx]).sum();
+                    let expected = if sample_idx == latency { 1.0 } else { 0.0 };
+                    assert!(
+                        (sum - expected).abs() < 1e-4,
+                        "{mode:?}, {sharpness:?}: sample {sample_idx} is {sum}, expected {expected}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reset_band_keeps_active_bands() {
+        let input = test_signal(FFT_INPUT_SIZE * 3 + 100);
+
+        let mut crossover = FirCrossover::new(FirCrossoverType::LinkwitzRiley24LinearPhase);
+        let mut reference = FirCrossover::new(FirCrossoverType::LinkwitzRiley24LinearPhase);
+        for crossover in [&mut crossover, &mut reference] {
+            crossover.update(
+                44_100.0,
+                NUM_BANDS,
+                [100.0, 1_000.0, 5_000.0, 15_000.0],
+                FirSharpness::Sharp,
+                FirSharpness::Sharp.default_window(),
+            );
+            crossover.reset();
+            process_fir(crossover, NUM_BANDS, &input);
+        }
+
+        // Resetting the last band must not change the other bands' outputs, but the last band
+        // should be silent until the next period
+        crossover.reset_band(NUM_BANDS - 1);
+        let outputs = process_fir(&mut crossover, NUM_BANDS, &input);
+        let expected_outputs = process_fir(&mut reference, NUM_BANDS, &input);
+        assert_eq!(outputs[..NUM_BANDS - 1], expected_outputs[..NUM_BANDS - 1]);
+
+        // The previous call ended 100 samples into a period
+        let samples_until_next_period = FFT_INPUT_SIZE - 100;
+        assert!(outputs[NUM_BANDS - 1][..samples_until_next_period]
+            .iter()
+            .all(|sample| *sample == 0.0));
+        assert!(expected_outputs[NUM_BANDS - 1][..samples_until_next_period]
+            .iter()
+            .any(|sample| *sample != 0.0));
+    }
 }
@@ -14,9 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use nih_plug::debug::*;
+use nih_plug::util;
 use realfft::num_complex::Complex32;
 use realfft::{ComplexToReal, RealToComplex};
-use std::f32;
 
 use crate::crossover::iir::biquad::{Biquad, BiquadCoefficients};
 use crate::NUM_CHANNELS;
@@ -45,11 +46,33 @@ pub const FILTER_SIZE: usize = FFT_SIZE - FFT_INPUT_SIZE + 1;
 /// input and the padded impulse response FFTs will result one `N_INPUT` period of output that can
 /// be taken as is, followed by one `N_INPUT` period of samples that need to be added to the next
 /// period's outputs as part of the overlap-add process.
+///
+/// Changing the filter's coefficients does not replace the impulse response immediately, since that
+/// would cause audible steps when the crossover frequencies are automated. Instead, the next period
+/// is filtered with both the old and the new impulse response, and the output is linearly
+/// crossfaded from the old filter's output to the new filter's output over the course of that
+/// period. Because the filters are linear-phase with the same latency, this is equivalent to
+/// filtering with a kernel that is interpolated sample by sample between the old and the new
+/// kernel. The crossfade's length is fixed at one `FFT_INPUT_SIZE` period, so the coefficients can
+/// only change once per period. And since the interpolation is linear, the intermediate frequency
+/// responses are blends of the old and new responses rather than exact designs at the intermediate
+/// crossover frequencies. For small steps like those produced by smoothed parameters this
+/// difference is negligible, but a single large jump will briefly sound like a mix of both filters.
+/// Since all bands are crossfaded in lockstep, the bands still sum back to the input during the
+/// crossfade.
 #[derive(Debug, Clone)]
 pub struct FftFirFilter {
     /// An `N_INPUT + 1` sized IIR. Padded, ran through the DFT, and then normalized by dividing by
     /// `FFT_SIZE`.
     padded_ir_fft: [Complex32; FFT_SIZE / 2 + 1],
+    /// The impulse response set during the last `recompute_coefficients()` call, in the same format
+    /// as `padded_ir_fft`. Once every channel has crossfaded to this impulse response, it is copied
+    /// to `padded_ir_fft`.
+    target_padded_ir_fft: [Complex32; FFT_SIZE / 2 + 1],
+    /// Whether the next period for a channel should be crossfaded from `padded_ir_fft` to
+    /// `target_padded_ir_fft`. These are tracked per channel since each channel is processed
+    /// individually.
+    crossfade_pending: [bool; NUM_CHANNELS as usize],
 
     /// The padding from the previous IDFT operation that needs to be added to the next output
     /// buffer. After the IDFT process there will be an `FFT_SIZE` real scratch buffer containing
@@ -61,6 +84,25 @@ pub struct FftFirFilter {
     unapplied_padding_buffers: [[f32; FFT_INPUT_SIZE]; NUM_CHANNELS as usize],
 }
 
+/// The window function applied to a FIR filter's impulse response while designing it. See
+/// [`FirCoefficients::design_linear_phase_low_pass_from_biquads()`]. Windows with a
+/// wider main lobe result in wider transition bands, while windows with lower side lobes result in
+/// less ripple in the stop band.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FirWindow {
+    /// The Blackman window.
+    Blackman,
+    /// The four-term Blackman-Harris window. This has a wider main lobe than the Blackman window,
+    /// which results in smoother transition bands with less ringing at the cost of steepness.
+    BlackmanHarris,
+    /// The Hann window. This has a narrower main lobe than the Blackman window for steeper
+    /// transition bands, but it also has higher side lobes.
+    Hann,
+    /// The Kaiser window with a configurable `beta`. Higher `beta` values trade steepness for lower
+    /// side lobes. A `beta` of around 8.6 is comparable to the Blackman window.
+    Kaiser { beta: f32 },
+}
+
 /// Coefficients for a (linear-phase) FIR filter. This struct includes ways to design the filter.
 /// `T` is the sample type and `N` is the number of taps/coefficients and should be odd for linear-phase filters.
 #[repr(transparent)]
@@ -69,11 +111,15 @@ pub struct FirCoefficients<const N: usize>(pub [f32; N]);
 
 impl Default for FftFirFilter {
     fn default() -> Self {
+        // Would be nicer to initialize this to an impulse response that actually had the correct
+        // position wrt the usual linear-phase latency, but this is fine since it should never be
+        // used anyways
+        let padded_ir_fft = [Complex32::new(1.0 / FFT_SIZE as f32, 0.0); FFT_SIZE / 2 + 1];
+
         Self {
-            // Would be nicer to initialize this to an impulse response that actually had the
-            // correct position wrt the usual linear-phase latency, but this is fine since it should
-            // never be used anyways
-            padded_ir_fft: [Complex32::new(1.0 / FFT_SIZE as f32, 0.0); FFT_SIZE / 2 + 1],
+            padded_ir_fft,
+            target_padded_ir_fft: padded_ir_fft,
+            crossfade_pending: [false; NUM_CHANNELS as usize],
             unapplied_padding_buffers: [[0.0; FFT_INPUT_SIZE]; NUM_CHANNELS as usize],
         }
     }
@@ -96,30 +142,28 @@ impl FftFirFilter {
     /// delay. This is a bit weird and probably difficult to follow because as an optimization the
     /// DFT is taken only once, and then the IDFT is taken once for every filtered band. This
     /// function is thus called inside of the overlap-add loop to avoid duplicate work.
+    ///
+    /// `previous_input_fft` should contain the `input_fft` from the previous period for this
+    /// channel. This is needed to compute the new filter's overlap when crossfading to new
+    /// coefficients.
+    #[allow(clippy::too_many_arguments)]
     pub fn process(
         &mut self,
         input_fft: &[Complex32; FFT_SIZE / 2 + 1],
+        previous_input_fft: &[Complex32; FFT_SIZE / 2 + 1],
         output_samples: &mut [f32; FFT_INPUT_SIZE],
         output_channel_idx: usize,
         c2r_plan: &dyn ComplexToReal<f32>,
         real_scratch_buffer: &mut [f32; FFT_SIZE],
         complex_scratch_buffer: &mut [Complex32; FFT_SIZE / 2 + 1],
     ) {
-        // The padded input FFT has already been taken, so we only need to copy it to the scratch
-        // buffer (the input cannot change as the next band might need it as well).
-        complex_scratch_buffer.copy_from_slice(input_fft);
-
-        // The FFT of the impulse response has already been normalized, so we just need to
-        // multiply the two buffers
-        for (output_bin, ir_bin) in complex_scratch_buffer
-            .iter_mut()
-            .zip(self.padded_ir_fft.iter())
-        {
-            *output_bin *= ir_bin;
-        }
-        c2r_plan
-            .process_with_scratch(complex_scratch_buffer, real_scratch_buffer, &mut [])
-            .unwrap();
+        Self::convolve(
+            input_fft,
+            &self.padded_ir_fft,
+            c2r_plan,
+            real_scratch_buffer,
+            complex_scratch_buffer,
+        );
 
         // At this point the first `FFT_INPUT_SIZE` elements in `real_scratch_buffer`
         // contain the output for the next period, while the last `FFT_INPUT_SIZE` elements
@@ -135,10 +179,103 @@ impl FftFirFilter {
         }
         self.unapplied_padding_buffers[output_channel_idx]
             .copy_from_slice(&real_scratch_buffer[FFT_INPUT_SIZE..]);
+
+        if self.crossfade_pending[output_channel_idx] {
+            self.crossfade_to_target(
+                input_fft,
+                previous_input_fft,
+                output_samples,
+                output_channel_idx,
+                c2r_plan,
+                real_scratch_buffer,
+                complex_scratch_buffer,
+            );
+        }
+    }
+
+    /// Crossfade `output_samples`, which has been filtered using `padded_ir_fft`, to the same
+    /// period filtered using `target_padded_ir_fft`. The new filter's output needs to include the
+    /// overlap from the previous period as if that had been filtered using the new filter as well,
+    /// so the previous period's input is convolved with the new impulse response again. Afterwards
+    /// `unapplied_padding_buffers` contains the new filter's overlap.
+    #[allow(clippy::too_many_arguments)]
+    fn crossfade_to_target(
+        &mut self,
+        input_fft: &[Complex32; FFT_SIZE / 2 + 1],
+        previous_input_fft: &[Complex32; FFT_SIZE / 2 + 1],
+        output_samples: &mut [f32; FFT_INPUT_SIZE],
+        output_channel_idx: usize,
+        c2r_plan: &dyn ComplexToReal<f32>,
+        real_scratch_buffer: &mut [f32; FFT_SIZE],
+        complex_scratch_buffer: &mut [Complex32; FFT_SIZE / 2 + 1],
+    ) {
+        let padding_buffer = &mut self.unapplied_padding_buffers[output_channel_idx];
+
+        // The old filter's overlap has already been added to the output, so the padding buffer can
+        // be reused for the new filter's overlap from the previous period
+        Self::convolve(
+            previous_input_fft,
+            &self.target_padded_ir_fft,
+            c2r_plan,
+            real_scratch_buffer,
+            complex_scratch_buffer,
+        );
+        padding_buffer.copy_from_slice(&real_scratch_buffer[FFT_INPUT_SIZE..]);
+
+        Self::convolve(
+            input_fft,
+            &self.target_padded_ir_fft,
+            c2r_plan,
+            real_scratch_buffer,
+            complex_scratch_buffer,
+        );
+
+        // The last sample in the period is fully filtered by the new filter
+        let crossfade_step = (FFT_INPUT_SIZE as f32).recip();
+        for (sample_idx, ((output_sample, new_sample), padding_sample)) in output_samples
+            .iter_mut()
+            .zip(real_scratch_buffer[..FFT_INPUT_SIZE].iter())
+            .zip(padding_buffer.iter())
+            .enumerate()
+        {
+            let t = (sample_idx + 1) as f32 * crossfade_step;
+            *output_sample += ((*new_sample + *padding_sample) - *output_sample) * t;
+        }
+        padding_buffer.copy_from_slice(&real_scratch_buffer[FFT_INPUT_SIZE..]);
+
+        // The old impulse response can only be replaced once the other channels are done with it
+        self.crossfade_pending[output_channel_idx] = false;
+        if !self.crossfade_pending.contains(&true) {
+            self.padded_ir_fft = self.target_padded_ir_fft;
+        }
+    }
+
+    /// Multiply a padded input's DFT with a padded impulse response's DFT and take the IDFT. The
+    /// result is written to `real_scratch_buffer`.
+    fn convolve(
+        input_fft: &[Complex32; FFT_SIZE / 2 + 1],
+        padded_ir_fft: &[Complex32; FFT_SIZE / 2 + 1],
+        c2r_plan: &dyn ComplexToReal<f32>,
+        real_scratch_buffer: &mut [f32; FFT_SIZE],
+        complex_scratch_buffer: &mut [Complex32; FFT_SIZE / 2 + 1],
+    ) {
+        // The padded input FFT has already been taken, so we only need to copy it to the scratch
+        // buffer (the input cannot change as the next band might need it as well).
+        complex_scratch_buffer.copy_from_slice(input_fft);
+
+        // The FFT of the impulse response has already been normalized, so we just need to
+        // multiply the two buffers
+        for (output_bin, ir_bin) in complex_scratch_buffer.iter_mut().zip(padded_ir_fft.iter()) {
+            *output_bin *= ir_bin;
+        }
+        c2r_plan
+            .process_with_scratch(complex_scratch_buffer, real_scratch_buffer, &mut [])
+            .unwrap();
     }
 
     /// Set the filter's coefficients based on raw FIR filter coefficients. These will be padded,
-    /// ran through the DFT, and normalized.
+    /// ran through the DFT, and normalized. The next period is then crossfaded to the new filter.
+    /// Call [`reset()`][Self::reset()] afterwards to use the new coefficients immediately instead.
     pub fn recompute_coefficients(
         &mut self,
         coefficients: FirCoefficients<FILTER_SIZE>,
@@ -154,36 +291,62 @@ impl FftFirFilter {
             .process_with_scratch(real_scratch_buffer, complex_scratch_buffer, &mut [])
             .unwrap();
 
-        // The resulting buffer needs to be normalized and written to `self.padded_ir_fft`. That way
-        // we don't need to do anything but multiplying and writing the results back when
+        // The resulting buffer needs to be normalized and written to `self.target_padded_ir_fft`.
+        // That way we don't need to do anything but multiplying and writing the results back when
         // processing.
         let normalization_factor = 1.0 / FFT_SIZE as f32;
         for (filter_bin, target_bin) in complex_scratch_buffer
             .iter()
-            .zip(self.padded_ir_fft.iter_mut())
+            .zip(self.target_padded_ir_fft.iter_mut())
         {
             *target_bin = *filter_bin * normalization_factor;
         }
+
+        self.crossfade_pending.fill(true);
+    }
+
+    /// Use the same coefficients as the last `recompute_coefficients()` call on `other`. The next
+    /// period is crossfaded to the new filter, just like when recomputing the coefficients.
+    pub fn copy_coefficients_from(&mut self, other: &FftFirFilter) {
+        self.target_padded_ir_fft = other.target_padded_ir_fft;
+        self.crossfade_pending.fill(true);
     }
 
-    /// Reset the internal filter state.
+    /// Reset the internal filter state. Any pending crossfade is skipped, and the coefficients from
+    /// the last `recompute_coefficients()` call are used immediately.
     pub fn reset(&mut self) {
         for buffer in &mut self.unapplied_padding_buffers {
             buffer.fill(0.0);
         }
+
+        self.padded_ir_fft = self.target_padded_ir_fft;
+        self.crossfade_pending.fill(false);
+    }
+}
+
+impl FirWindow {
+    /// Fill `window` with this window function.
+    fn fill(self, window: &mut [f32]) {
+        match self {
+            FirWindow::Blackman => util::window::blackman_in_place(window),
+            FirWindow::BlackmanHarris => util::window::blackman_harris_in_place(window),
+            FirWindow::Hann => util::window::hann_in_place(window),
+            FirWindow::Kaiser { beta } => util::window::kaiser_in_place(window, beta),
+        }
     }
 }
 
 impl<const N: usize> FirCoefficients<N> {
     /// A somewhat crude but very functional and relatively fast way create linear phase FIR
-    /// **low-pass** filter that matches the frequency response of a fourth order biquad low-pass
-    /// filter. As in, this matches the frequency response magnitudes of applying those biquads to a
-    /// signal twice. This only works for low-pass filters, as the function normalizes the result to
-    /// hae unity gain at the DC bin. The algorithm works as follows:
+    /// **low-pass** filter that matches the frequency response of a series of biquad low-pass
+    /// filters applied twice. As in, this matches the frequency response magnitudes of applying
+    /// those biquads to a signal twice, so a single second order Butterworth filter results in the
+    /// LR24 crossover's low-pass response. This only works for low-pass filters, as the function
+    /// normalizes the result to hae unity gain at the DC bin. The algorithm works as follows:
     ///
-    /// - An impulse function (so all zeroes except for the first element) of length `FILTER_LEN / 2
-    ///   + 1` is filtered with the biquad.
-    /// - The biquad's state is reset, and the impulse response is filtered in the opposite
+    /// - An impulse function (so all zeroes except for the first element) of length `num_taps / 2 +
+    ///   1` is filtered with the biquads.
+    /// - The biquads' states are reset, and the impulse response is filtered in the opposite
     ///   direction.
     /// - At this point the bidirectionally filtered impulse response contains the **right** half of
     ///   a truncated linear phase FIR kernel.
@@ -192,8 +355,8 @@ impl<const N: usize> FirCoefficients<N> {
     /// the post-processing work slightly by windowing and normalizing this bidirectionally filtered
     /// impulse response instead.
     ///
-    /// - A half Blackman window is applied to the impulse response. Since this is the right half,
-    ///   this starts at unity gain for the first sample and then tapers off towards the right.
+    /// - The right half of `window` is applied to the impulse response. Since this is the right
+    ///   half, this starts at unity gain for the first sample and then tapers off towards the right.
     /// - The impulse response is then normalized such that the final linear-phase FIR kernel has a
     ///   sum of 1.0. Since it will be symmetrical around the IRs first sample, the would-be final
     ///   sum can be computed as `ir.sum() * 2 - ir[0]`.
@@ -201,46 +364,59 @@ impl<const N: usize> FirCoefficients<N> {
     /// Lastly the linear phase FIR filter simply needs to be constructed from this right half:
     ///
     /// - This bidirectionally filtered impulse response is then reversed, and placed at the start
-    ///   of the `FILTER_LEN` size FIR coefficient array.
+    ///   of the `num_taps` size FIR kernel.
     /// - The non-reversed bidirectionally filtered impulse response is copied to the second half of
-    ///   the coefficients. (one of the copies doesn't need to include the centermost coefficient)
+    ///   the kernel. (one of the copies doesn't need to include the centermost coefficient)
     ///
-    /// The corresponding high-pass filter can be computed through spectral inversion.
-    pub fn design_fourth_order_linear_phase_low_pass_from_biquad(
-        biquad_coefs: BiquadCoefficients<f32>,
+    /// `num_taps` must be odd and at most `N`. The kernel is placed at the start of the
+    /// coefficients and the remaining coefficients are zero, so the filter's latency is `num_taps /
+    /// 2` samples. The corresponding high-pass filter can be computed through spectral inversion
+    /// around that same center coefficient.
+    pub fn design_linear_phase_low_pass_from_biquads(
+        biquad_coefs: &[BiquadCoefficients<f32>],
+        num_taps: usize,
+        window: FirWindow,
     ) -> Self {
-        // Rust doesn't allow you to define this as a constant
-        let center_idx = N / 2;
+        nih_debug_assert!(num_taps <= N);
+        nih_debug_assert!(num_taps % 2 == 1);
 
-        // We'll start with an impulse (at exactly half of this odd sized buffer)...
-        let mut impulse_response = [0.0; N];
+        let center_idx = num_taps / 2;
+
+        // We'll start with an impulse (at exactly half of this odd sized kernel)...
+        let mut coefficients = [0.0; N];
+        let impulse_response = &mut coefficients[..num_taps];
         impulse_response[center_idx] = 1.0;
 
-        // ...and filter that in both directions
+        // ...and filter that in both directions. The filters are linear and time-invariant, so they
+        // can be applied one after the other.
         let mut biquad = Biquad::default();
-        biquad.coefficients = biquad_coefs;
-        for sample in impulse_response.iter_mut().skip(center_idx - 1) {
-            *sample = biquad.process(*sample);
+        for coefficients in biquad_coefs {
+            biquad.coefficients = *coefficients;
+            biquad.reset();
+            for sample in impulse_response.iter_mut().skip(center_idx - 1) {
+                *sample = biquad.process(*sample);
+            }
         }
 
-        biquad.reset();
-        for sample in impulse_response.iter_mut().skip(center_idx - 1).rev() {
-            *sample = biquad.process(*sample);
+        for coefficients in biquad_coefs {
+            biquad.coefficients = *coefficients;
+            biquad.reset();
+            for sample in impulse_response.iter_mut().skip(center_idx - 1).rev() {
+                *sample = biquad.process(*sample);
+            }
         }
 
         // Now the right half of `impulse_response` contains a truncated right half of the
         // linear-phase FIR filter. We can apply the window function here, and then fianlly
         // normalize it so that the the final FIR filter kernel sums to 1.
 
-        // Adopted from `nih_plug::util::window`. We only end up applying the right half of the
-        // window, starting at the top of the window.
-        let blackman_scale_1 = (2.0 * f32::consts::PI) / (N - 1) as f32;
-        let blackman_scale_2 = blackman_scale_1 * 2.0;
-        for (sample_idx, sample) in impulse_response.iter_mut().enumerate().skip(center_idx - 1) {
-            let cos_1 = (blackman_scale_1 * sample_idx as f32).cos();
-            let cos_2 = (blackman_scale_2 * sample_idx as f32).cos();
-            *sample *= 0.42 - (0.5 * cos_1) + (0.08 * cos_2);
-        }
+        // We only end up applying the right half of the window, starting at the top of the window
+        let mut window_function = [0.0; N];
+        window.fill(&mut window_function[..num_taps]);
+        util::window::multiply_with_window(
+            &mut impulse_response[center_idx..],
+            &window_function[center_idx..num_taps],
+        );
 
         // Since this final filter will be symmetrical around `impulse_response[CENTER_IDX]`, we
         // can simply normalize based on that fact:
@@ -248,17 +424,17 @@ impl<const N: usize> FirCoefficients<N> {
             * 2.0)
             - impulse_response[center_idx];
         let would_be_impulse_response_recip = would_be_impulse_response_sum.recip();
-        for sample in &mut impulse_response {
+        for sample in impulse_response.iter_mut() {
             *sample *= would_be_impulse_response_recip;
         }
 
         // And finally we can simply copy the right half of the filter kernel to the left half
         // around the `CENTER_IDX`.
-        for source_idx in center_idx + 1..N {
+        for source_idx in center_idx + 1..num_taps {
             let target_idx = center_idx - (source_idx - center_idx);
             impulse_response[target_idx] = impulse_response[source_idx];
         }
 
-        Self(impulse_response)
+        Self(coefficients)
     }
 }
@@ -16,16 +16,48 @@
 
 use nih_plug::buffer::ChannelSamples;
 use nih_plug::debug::*;
+#[cfg(feature = "simd")]
 use std::simd::f32x2;
+#[cfg(all(feature = "simd", feature = "f64_filters"))]
+use std::simd::f64x2;
+#[cfg(feature = "simd")]
+use std::simd::num::SimdFloat;
 
-use self::biquad::{Biquad, BiquadCoefficients, NEUTRAL_Q};
-use crate::NUM_BANDS;
+#[cfg(not(feature = "simd"))]
+use self::biquad::f32x2;
+#[cfg(all(not(feature = "simd"), feature = "f64_filters"))]
+use self::biquad::f64x2;
+use self::biquad::{Biquad, BiquadCoefficients, BUTTERWORTH_4_QS, LR12_Q, NEUTRAL_Q};
+use crate::{NUM_BANDS, NUM_CHANNELS};
 
 pub mod biquad;
 
+/// The type the IIR crossover's filters use for their coefficients and state. The plugin's inputs
+/// and outputs are always `f32`, but with the `f64_filters` feature the filters run with double
+/// precision. With `f32`, the filter coefficients for crossover frequencies far below the sample
+/// rate can't be represented accurately enough, which noticeably changes the crossovers' slopes.
+#[cfg(not(feature = "f64_filters"))]
+type FilterSamples = f32x2;
+#[cfg(feature = "f64_filters")]
+type FilterSamples = f64x2;
+
+/// The crossover frequencies for each channel, indexed by `[channel_idx][crossover_idx]`. Both
+/// channels use the same frequencies unless the plugin's stereo link option is disabled. The left
+/// and right channels are processed in separate SIMD lanes, so they can use different filter
+/// coefficients at no extra processing cost.
+pub type ChannelFrequencies = [[f32; NUM_BANDS - 1]; NUM_CHANNELS as usize];
+
+/// The maximum number of biquads in a crossover's low-pass and high-pass sections. The LR48
+/// crossovers need four.
+const MAX_STAGES: usize = 4;
+/// The maximum number of all-pass filters needed to compensate the lower bands for a single
+/// crossover's phase shift. The LR48 crossovers need two.
+const MAX_ALL_PASS_STAGES: usize = 2;
+
 #[derive(Debug)]
 pub struct IirCrossover {
-    /// The kind of crossover to use. `.update_filters()` must be called after changing this.
+    /// The kind of crossover to use. `.update()` must be called after changing this through
+    /// `.set_mode()`.
     mode: IirCrossoverType,
 
     /// The crossovers. Depending on the number of bands argument passed to `.process()` one to four
@@ -33,14 +65,31 @@ pub struct IirCrossover {
     crossovers: [Crossover; NUM_BANDS - 1],
     /// Used to compensate the earlier bands for the phase shift introduced in the higher bands.
     all_passes: AllPassCascade,
+    /// The crossover frequencies the filters are currently configured for. Used to only recompute
+    /// the coefficients for the crossovers that changed in `.update_modulated()`.
+    frequencies: ChannelFrequencies,
 }
 
 /// The type of IIR crossover to use.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum IirCrossoverType {
+    /// Clean crossover with 12 dB/octave slopes. Uses a single $q = 0.5$ filter per crossover,
+    /// which is equivalent to two first order Butterworth filters in series. The high-passed
+    /// signal's polarity is inverted, since the bands would otherwise cancel out at the crossover
+    /// frequency. This means that every other band has an inverted polarity.
+    LinkwitzRiley12,
     /// Clean crossover with 24 dB/octave slopes and one period of delay in the power band. Stacks
     /// two Butterworth-style (i.e. $q = \frac{\sqrt{2}}{2}$) filters per crossover.
     LinkwitzRiley24,
+    /// Clean crossover with 48 dB/octave slopes. Stacks two fourth order Butterworth filters, so
+    /// four biquads, per crossover.
+    LinkwitzRiley48,
+    /// A single fourth order Butterworth filter per crossover. The slopes are just as steep as the
+    /// LR24 crossover's, but the filters have only 3 dB of attenuation at the crossover frequency,
+    /// so the bands sum to a 3 dB boost around the crossover frequencies. The lower bands' phase
+    /// shift is compensated for with a single all-pass filter, which only approximately matches
+    /// the phase shift of the higher bands.
+    Butterworth24,
 }
 
 /// A single crossover using multiple biquads in series to get steeper slopes. This can do both the
@@ -49,10 +98,22 @@ pub enum IirCrossoverType {
 struct Crossover {
     /// Filters for the low-pass section of the crossover. Not all filters may be used dependign on
     /// the crossover type.
-    lp_filters: [Biquad<f32x2>; 2],
+    lp_filters: [Biquad<FilterSamples>; MAX_STAGES],
     /// Filters for the high-pass section of the crossover. Not all filters may be used dependign on
     /// the crossover type.
-    hp_filters: [Biquad<f32x2>; 2],
+    hp_filters: [Biquad<FilterSamples>; MAX_STAGES],
+}
+
+/// The coefficients for a single crossover's filters, and for the all-pass filters that
+/// compensate the lower bands for that crossover's phase shift. Only the first
+/// [`IirCrossoverType::num_stages()`] low-pass and high-pass coefficients and the first
+/// [`IirCrossoverType::num_all_pass_stages()`] all-pass coefficients are used, the rest are
+/// identity filters.
+#[derive(Debug, Clone, Copy)]
+struct CrossoverCoefficients<T> {
+    lp: [BiquadCoefficients<T>; MAX_STAGES],
+    hp: [BiquadCoefficients<T>; MAX_STAGES],
+    ap: [BiquadCoefficients<T>; MAX_ALL_PASS_STAGES],
 }
 
 /// The crossover is super simple and feeds the low-passed result to the next band output while
@@ -60,16 +121,17 @@ struct Crossover {
 /// have had more filters applied to them, the lower bands need to have their phase response
 /// adjusted to match the higher bands. So for the LR24 crossovers, low-passed band `n` will get a
 /// second order all-pass for the frequencies corresponding to crossovers `n + 1..NUM_CROSSOVERS`
-/// applied to it.
+/// applied to it. The other crossover types use one or two all-pass filters per crossover instead,
+/// see [`IirCrossoverType::num_all_pass_stages()`].
 #[derive(Debug, Default)]
 struct AllPassCascade {
     /// The aforementioned all-pass filters. This is indexed by `[crossover_idx][0..num_bands -
-    /// crossover_index - 1]`. Ergo, if there are three crossovers, then the low-pass section from
-    /// the first crossover needs to have `[0][0]` and `[0][1]` applied to it. The last band doesn't
-    /// need any compensation, hence the `NUM_BANDS - 2`. The outer array is equal to the number of
-    /// crossovers. It will never contain any filters, but this makes the code a bit nicer by
-    /// needing an explicit check for this.
-    ap_filters: [[Biquad<f32x2>; NUM_BANDS - 2]; NUM_BANDS - 1],
+    /// crossover_index - 1][stage_idx]`. Ergo, if there are three crossovers, then the low-pass
+    /// section from the first crossover needs to have `[0][0]` and `[0][1]` applied to it. The last
+    /// band doesn't need any compensation, hence the `NUM_BANDS - 2`. The outer array is equal to
+    /// the number of crossovers. It will never contain any filters, but this makes the code a bit
+    /// nicer by needing an explicit check for this.
+    ap_filters: [[[Biquad<FilterSamples>; MAX_ALL_PASS_STAGES]; NUM_BANDS - 2]; NUM_BANDS - 1],
 
     /// The number of activate bands. Only coefficients for used bands are computed in `ap_filters`.
     num_bands: usize,
@@ -84,6 +146,22 @@ impl IirCrossover {
             mode,
             crossovers: Default::default(),
             all_passes: Default::default(),
+            frequencies: [[0.0; NUM_BANDS - 1]; NUM_CHANNELS as usize],
+        }
+    }
+
+    /// The crossover frequencies the filters are currently configured for.
+    pub fn frequencies(&self) -> ChannelFrequencies {
+        self.frequencies
+    }
+
+    /// Change the kind of crossover to use. The filters are reset when the mode changes since the
+    /// different modes use a different number of filters. `.update()` needs to be called
+    /// afterwards to compute the filter coefficients for the new mode.
+    pub fn set_mode(&mut self, mode: IirCrossoverType) {
+        if mode != self.mode {
+            self.mode = mode;
+            self.reset();
         }
     }
 
@@ -102,59 +180,96 @@ impl IirCrossover {
         // be unsound
         assert!(main_io.len() == 2);
 
-        let mut samples: f32x2 = unsafe { main_io.to_simd_unchecked() };
-        match self.mode {
-            IirCrossoverType::LinkwitzRiley24 => {
-                for (crossover_idx, (crossover, band_channel_samples)) in self
-                    .crossovers
-                    .iter_mut()
-                    .zip(band_outputs.iter_mut())
-                    .take(num_bands - 1)
-                    .enumerate()
-                {
-                    let (lp_samples, hp_samples) = crossover.process_lr24(samples);
-
-                    // The low-pass result needs to have the same phase shift applied to it that
-                    // higher bands would get
-                    let lp_samples = self.all_passes.compensate_lr24(lp_samples, crossover_idx);
-
-                    unsafe { band_channel_samples.from_simd_unchecked(lp_samples) };
-                    samples = hp_samples;
-                }
+        let num_stages = self.mode.num_stages();
+        let num_all_pass_stages = self.mode.num_all_pass_stages();
+        let mut samples: FilterSamples = unsafe { read_samples(main_io) }.cast();
+        for (crossover_idx, (crossover, band_channel_samples)) in self
+            .crossovers
+            .iter_mut()
+            .zip(band_outputs.iter_mut())
+            .take(num_bands - 1)
+            .enumerate()
+        {
+            let (lp_samples, hp_samples) = crossover.process(samples, num_stages);
 
-                // And the final high-passed result should be written to the last band
-                unsafe { band_outputs[num_bands - 1].from_simd_unchecked(samples) };
-            }
+            // The low-pass result needs to have the same phase shift applied to it that higher
+            // bands would get
+            let lp_samples =
+                self.all_passes
+                    .compensate(lp_samples, crossover_idx, num_all_pass_stages);
+
+            unsafe { write_samples(band_channel_samples, lp_samples.cast::<f32>()) };
+            samples = hp_samples;
         }
+
+        // And the final high-passed result should be written to the last band
+        unsafe { write_samples(&mut band_outputs[num_bands - 1], samples.cast::<f32>()) };
     }
 
     /// Update the crossover frequencies for all filters. `num_bands` is assumed to be in `[2,
     /// NUM_BANDS]`.
-    pub fn update(
+    pub fn update(&mut self, sample_rate: f32, num_bands: usize, frequencies: ChannelFrequencies) {
+        // NOTE: Currently we don't actually need to make sure that the frequencies are monotonic
+
+        self.all_passes.num_bands = num_bands;
+        for crossover_idx in 0..num_bands - 1 {
+            self.update_crossover(
+                sample_rate,
+                crossover_idx,
+                crossover_frequency(&frequencies, crossover_idx),
+            );
+        }
+
+        self.frequencies = frequencies;
+    }
+
+    /// The same as `.update()`, but only recomputes the coefficients for the crossovers whose
+    /// frequencies have changed since the last update. This is cheap enough to be called every
+    /// sample while the crossover frequencies are being modulated. The low-pass, high-pass, and
+    /// compensating all-pass filters for a crossover are always updated together, so the bands
+    /// still sum back to an all-passed version of the input at every instantaneous frequency.
+    pub fn update_modulated(
         &mut self,
         sample_rate: f32,
         num_bands: usize,
-        frequencies: [f32; NUM_BANDS - 1],
+        frequencies: ChannelFrequencies,
     ) {
-        // NOTE: Currently we don't actually need to make sure that the frequencies are monotonic
+        // Changing the number of bands changes the layout of the all-pass cascade
+        if num_bands != self.all_passes.num_bands {
+            self.update(sample_rate, num_bands, frequencies);
+            return;
+        }
 
-        match self.mode {
-            IirCrossoverType::LinkwitzRiley24 => {
-                for (crossover, frequency) in self
-                    .crossovers
-                    .iter_mut()
-                    .zip(frequencies)
-                    .take(num_bands - 1)
-                {
-                    let lp_coefs = BiquadCoefficients::lowpass(sample_rate, frequency, NEUTRAL_Q);
-                    let hp_coefs = BiquadCoefficients::highpass(sample_rate, frequency, NEUTRAL_Q);
-                    crossover.update_coefficients(lp_coefs, hp_coefs);
-                }
+        for crossover_idx in 0..num_bands - 1 {
+            let channel_frequencies = crossover_frequency(&frequencies, crossover_idx);
+            if channel_frequencies == crossover_frequency(&self.frequencies, crossover_idx) {
+                continue;
+            }
+
+            self.update_crossover(sample_rate, crossover_idx, channel_frequencies);
+            for (channel_frequencies, frequency) in
+                self.frequencies.iter_mut().zip(channel_frequencies)
+            {
+                channel_frequencies[crossover_idx] = frequency;
             }
         }
+    }
 
+    /// Compute the low-pass, high-pass, and all-pass coefficients for crossover `crossover_idx` in
+    /// one go using the left and right channel's frequencies for that crossover.
+    fn update_crossover(
+        &mut self,
+        sample_rate: f32,
+        crossover_idx: usize,
+        channel_frequencies: [f32; 2],
+    ) {
+        let coefficients = channel_coefficients(channel_frequencies, |f| {
+            self.mode.coefficients(sample_rate, f)
+        });
+
+        self.crossovers[crossover_idx].update_coefficients(coefficients.lp, coefficients.hp);
         self.all_passes
-            .update_coefficients(sample_rate, num_bands, &frequencies);
+            .set_crossover_coefficients(crossover_idx, coefficients.ap);
     }
 
     /// Reset the internal filter state for all crossovers.
@@ -165,19 +280,121 @@ impl IirCrossover {
 
         self.all_passes.reset();
     }
+
+    /// Reset only the filter state that is used once band `band_idx` becomes active. These are the
+    /// crossover that splits the band off from the band below it, and the all-pass filters that
+    /// compensate the lower bands for that crossover's phase shift. None of these filters are used
+    /// while the band is inactive, so this can be used to clear stale state when the number of
+    /// bands increases without causing a discontinuity in the bands that were already active. The
+    /// first band is always active, so this does nothing when `band_idx == 0`.
+    pub fn reset_band(&mut self, band_idx: usize) {
+        nih_debug_assert!(band_idx < NUM_BANDS);
+        if band_idx == 0 {
+            return;
+        }
+
+        let crossover_idx = band_idx - 1;
+        self.crossovers[crossover_idx].reset();
+        self.all_passes.reset_crossover(crossover_idx);
+    }
+}
+
+impl IirCrossoverType {
+    /// The number of biquads in each of a crossover's low-pass and high-pass sections.
+    pub fn num_stages(self) -> usize {
+        match self {
+            IirCrossoverType::LinkwitzRiley12 => 1,
+            IirCrossoverType::LinkwitzRiley24 | IirCrossoverType::Butterworth24 => 2,
+            IirCrossoverType::LinkwitzRiley48 => 4,
+        }
+    }
+
+    /// The number of all-pass filters needed to compensate the lower bands for a single
+    /// crossover's phase shift.
+    pub fn num_all_pass_stages(self) -> usize {
+        match self {
+            IirCrossoverType::LinkwitzRiley12
+            | IirCrossoverType::LinkwitzRiley24
+            | IirCrossoverType::Butterworth24 => 1,
+            IirCrossoverType::LinkwitzRiley48 => 2,
+        }
+    }
+
+    /// Compute the coefficients for a crossover at `frequency`. For the Linkwitz-Riley crossovers
+    /// the low-passed and the high-passed signals sum up to the all-pass filters' response.
+    fn coefficients(self, sample_rate: f32, frequency: f32) -> CrossoverCoefficients<f64> {
+        let mut coefficients: CrossoverCoefficients<f64> = CrossoverCoefficients {
+            lp: [BiquadCoefficients::identity(); MAX_STAGES],
+            hp: [BiquadCoefficients::identity(); MAX_STAGES],
+            ap: [BiquadCoefficients::identity(); MAX_ALL_PASS_STAGES],
+        };
+
+        match self {
+            IirCrossoverType::LinkwitzRiley12 => {
+                let [lp_coefs, hp_coefs, _] = BiquadCoefficients::<f64>::lowpass_highpass_allpass(
+                    sample_rate,
+                    frequency,
+                    LR12_Q,
+                );
+                coefficients.lp[0] = lp_coefs;
+                coefficients.hp[0] = hp_coefs.inverted();
+                coefficients.ap[0] =
+                    BiquadCoefficients::first_order_allpass(sample_rate, frequency);
+            }
+            IirCrossoverType::LinkwitzRiley24 => {
+                let [lp_coefs, hp_coefs, ap_coefs] =
+                    BiquadCoefficients::lowpass_highpass_allpass(sample_rate, frequency, NEUTRAL_Q);
+                coefficients.lp[..2].fill(lp_coefs);
+                coefficients.hp[..2].fill(hp_coefs);
+                coefficients.ap[0] = ap_coefs;
+            }
+            IirCrossoverType::LinkwitzRiley48 => {
+                // Both fourth order Butterworth filters consist of these two sections
+                for (stage_idx, q) in BUTTERWORTH_4_QS.into_iter().enumerate() {
+                    let [lp_coefs, hp_coefs, ap_coefs] =
+                        BiquadCoefficients::lowpass_highpass_allpass(sample_rate, frequency, q);
+                    coefficients.lp[stage_idx] = lp_coefs;
+                    coefficients.lp[stage_idx + 2] = lp_coefs;
+                    coefficients.hp[stage_idx] = hp_coefs;
+                    coefficients.hp[stage_idx + 2] = hp_coefs;
+                    coefficients.ap[stage_idx] = ap_coefs;
+                }
+            }
+            IirCrossoverType::Butterworth24 => {
+                for (stage_idx, q) in BUTTERWORTH_4_QS.into_iter().enumerate() {
+                    let [lp_coefs, hp_coefs, _] =
+                        BiquadCoefficients::lowpass_highpass_allpass(sample_rate, frequency, q);
+                    coefficients.lp[stage_idx] = lp_coefs;
+                    coefficients.hp[stage_idx] = hp_coefs;
+                }
+
+                // The low-passed and high-passed signals are in phase, but their phase shift is
+                // only half that of the all-pass filter corresponding to the fourth order
+                // Butterworth filter. A single second order all-pass filter with the average of
+                // the two sections' Q values matches that phase shift to within a couple degrees.
+                let q = (BUTTERWORTH_4_QS[0] + BUTTERWORTH_4_QS[1]) / 2.0;
+                coefficients.ap[0] = BiquadCoefficients::allpass(sample_rate, frequency, q);
+            }
+        }
+
+        coefficients
+    }
 }
 
 impl Crossover {
-    /// Process left and right audio samples through two low-pass and two high-pass filter stages.
-    /// The resulting tuple contains the low-passed and the high-passed samples. Used for the
-    /// Linkwitz-Riley 24 dB/octave crossover.
-    pub fn process_lr24(&mut self, samples: f32x2) -> (f32x2, f32x2) {
+    /// Process left and right audio samples through the first `num_stages` low-pass and high-pass
+    /// filter stages. The resulting tuple contains the low-passed and the high-passed samples.
+    pub fn process(
+        &mut self,
+        samples: FilterSamples,
+        num_stages: usize,
+    ) -> (FilterSamples, FilterSamples) {
         let mut low_passed = samples;
-        for filter in &mut self.lp_filters[..2] {
+        for filter in &mut self.lp_filters[..num_stages] {
             low_passed = filter.process(low_passed)
         }
         let mut high_passed = samples;
-        for filter in &mut self.hp_filters[..2] {
+        for filter in &mut self.hp_filters[..num_stages] {
             high_passed = filter.process(high_passed)
         }
 
@@ -187,14 +404,14 @@ impl Crossover {
     /// Update the coefficients for all filters in the crossover.
     pub fn update_coefficients(
         &mut self,
-        lp_coefs: BiquadCoefficients<f32x2>,
-        hp_coefs: BiquadCoefficients<f32x2>,
+        lp_coefs: [BiquadCoefficients<FilterSamples>; MAX_STAGES],
+        hp_coefs: [BiquadCoefficients<FilterSamples>; MAX_STAGES],
     ) {
-        for filter in &mut self.lp_filters {
-            filter.coefficients = lp_coefs;
+        for (filter, coefs) in self.lp_filters.iter_mut().zip(lp_coefs) {
+            filter.coefficients = coefs;
         }
-        for filter in &mut self.hp_filters {
-            filter.coefficients = hp_coefs;
+        for (filter, coefs) in self.hp_filters.iter_mut().zip(hp_coefs) {
+            filter.coefficients = coefs;
         }
     }
 
@@ -210,9 +427,14 @@ impl Crossover {
 }
 
 impl AllPassCascade {
-    /// Compensate lower bands for the additional phase shift introduced in higher bands when using
-    /// LR24 filters to split those bands.
-    pub fn compensate_lr24(&mut self, lp_samples: f32x2, band_idx: usize) -> f32x2 {
+    /// Compensate lower bands for the additional phase shift introduced in higher bands. Every
+    /// crossover above the band is compensated for with `num_stages` all-pass filters.
+    pub fn compensate(
+        &mut self,
+        lp_samples: FilterSamples,
+        band_idx: usize,
+        num_stages: usize,
+    ) -> FilterSamples {
         // The all-pass filters are set up based on the crossover that produced the low-passed
         // samples
         let crossover_idx = band_idx;
@@ -222,52 +444,314 @@ impl AllPassCascade {
         // `self.num_bands` were 4 then it would additionally also be filtered by
         // `self.ap_filters[0][1]`.
         let mut compensated = lp_samples;
-        for filter in &mut self.ap_filters[crossover_idx][..self.num_bands - band_idx - 2] {
-            compensated = filter.process(compensated)
+        for filters in &mut self.ap_filters[crossover_idx][..self.num_bands - band_idx - 2] {
+            for filter in &mut filters[..num_stages] {
+                compensated = filter.process(compensated)
+            }
         }
 
         compensated
     }
 
-    /// Update the coefficients for all filters in the cascade. For every active band, this adds up
-    /// to `num_bands - band_idx - 1` filters. The filter state of course cannot be shared between
-    /// bands, but the coefficients along the matrix's diagonals are identical.
-    pub fn update_coefficients(
+    /// Set the coefficients for all all-pass filters that compensate for crossover
+    /// `crossover_idx`. The first crossover doesn't need to be compensated for since all output
+    /// bands go through it, so this does nothing when `crossover_idx == 0`. The filter state of
+    /// course cannot be shared between bands, but the coefficients along the matrix's diagonals
+    /// are identical.
+    pub fn set_crossover_coefficients(
         &mut self,
-        sample_rate: f32,
-        num_bands: usize,
-        frequencies: &[f32; NUM_BANDS - 1],
+        crossover_idx: usize,
+        ap_coefs: [BiquadCoefficients<FilterSamples>; MAX_ALL_PASS_STAGES],
     ) {
-        self.num_bands = num_bands;
-
-        // All output bands go through the first filter, so we don't compensate for that. `band_idx`
-        // starts at 1
-        for (crossover_idx, crossover_frequency) in
-            frequencies.iter().enumerate().take(num_bands - 1).skip(1)
-        {
-            let ap_coefs =
-                BiquadCoefficients::allpass(sample_rate, *crossover_frequency, NEUTRAL_Q);
-
-            // This sets the coefficients in a diagonal pattern. If `crossover_idx == 2`, then this
-            // will set the coefficients for these filters:
-            // ```
-            // [_, x, ...] // Crossover 1 filters
-            // [x, ...]    // Crossover 2 filters
-            // ...
-            // ```
-            for target_crossover_idx in 0..crossover_idx {
-                self.ap_filters[target_crossover_idx][crossover_idx - target_crossover_idx - 1]
-                    .coefficients = ap_coefs;
+        // This sets the coefficients in a diagonal pattern. If `crossover_idx == 2`, then this
+        // will set the coefficients for these filters:
+        // ```
+        // [_, x, ...] // Crossover 1 filters
+        // [x, ...]    // Crossover 2 filters
+        // ...
+        // ```
+        for target_crossover_idx in 0..crossover_idx {
+            let filters = &mut self.ap_filters[target_crossover_idx]
+                [crossover_idx - target_crossover_idx - 1];
+            for (filter, coefs) in filters.iter_mut().zip(ap_coefs) {
+                filter.coefficients = coefs;
             }
         }
     }
 
     /// Reset the internal filter state.
     pub fn reset(&mut self) {
-        for filters in &mut self.ap_filters {
-            for filter in filters.iter_mut() {
+        for filter in self.ap_filters.iter_mut().flatten().flatten() {
+            filter.reset();
+        }
+    }
+
+    /// Reset the state of the all-pass filters that compensate for crossover `crossover_idx`. These
+    /// are the same filters [`set_crossover_coefficients()`][Self::set_crossover_coefficients()]
+    /// sets the coefficients for.
+    pub fn reset_crossover(&mut self, crossover_idx: usize) {
+        for target_crossover_idx in 0..crossover_idx {
+            for filter in
+                &mut self.ap_filters[target_crossover_idx][crossover_idx - target_crossover_idx - 1]
+            {
                 filter.reset();
             }
         }
     }
 }
+
+/// Get the frequencies of crossover `crossover_idx` for the left and right channels.
+fn crossover_frequency(frequencies: &ChannelFrequencies, crossover_idx: usize) -> [f32; 2] {
+    [frequencies[0][crossover_idx], frequencies[1][crossover_idx]]
+}
+
+/// Compute a crossover's filter coefficients for each channel's frequency using `compute` and
+/// combine them into vectors. When both channels use the same frequency, the coefficients are only
+/// computed once.
+fn channel_coefficients(
+    [left_frequency, right_frequency]: [f32; 2],
+    compute: impl Fn(f32) -> CrossoverCoefficients<f64>,
+) -> CrossoverCoefficients<FilterSamples> {
+    let left_coefs = compute(left_frequency);
+    let right_coefs = if left_frequency == right_frequency {
+        left_coefs
+    } else {
+        compute(right_frequency)
+    };
+
+    CrossoverCoefficients {
+        lp: combine_channels(left_coefs.lp, right_coefs.lp),
+        hp: combine_channels(left_coefs.hp, right_coefs.hp),
+        ap: combine_channels(left_coefs.ap, right_coefs.ap),
+    }
+}
+
+/// Combine the left and right channels' coefficients for a series of filters into vectors.
+fn combine_channels<const N: usize>(
+    left_coefs: [BiquadCoefficients<f64>; N],
+    right_coefs: [BiquadCoefficients<f64>; N],
+) -> [BiquadCoefficients<FilterSamples>; N] {
+    std::array::from_fn(|idx| {
+        BiquadCoefficients::from_f64_channels([left_coefs[idx], right_coefs[idx]])
+    })
+}
+
+/// Read a stereo sample into a vector.
+///
+/// # Safety
+///
+/// `channel_samples` must contain at least two channels.
+#[cfg(feature = "simd")]
+#[inline(always)]
+unsafe fn read_samples(channel_samples: &ChannelSamples) -> f32x2 {
+    channel_samples.to_simd_unchecked::<2>()
+}
+
+/// Read a stereo sample into a vector.
+///
+/// # Safety
+///
+/// `channel_samples` must contain at least two channels.
+#[cfg(not(feature = "simd"))]
+#[inline(always)]
+unsafe fn read_samples(channel_samples: &ChannelSamples) -> f32x2 {
+    f32x2::from_array([
+        channel_samples.get_unchecked(0),
+        channel_samples.get_unchecked(1),
+    ])
+}
+
+/// Write a vector back to a stereo sample.
+///
+/// # Safety
+///
+/// `channel_samples` must contain at least two channels.
+#[cfg(feature = "simd")]
+#[inline(always)]
+unsafe fn write_samples(channel_samples: &mut ChannelSamples, samples: f32x2) {
+    channel_samples.from_simd_unchecked(samples);
+}
+
+/// Write a vector back to a stereo sample.
+///
+/// # Safety
+///
+/// `channel_samples` must contain at least two channels.
+#[cfg(not(feature = "simd"))]
+#[inline(always)]
+unsafe fn write_samples(channel_samples: &mut ChannelSamples, samples: f32x2) {
+    let [left, right] = samples.to_array();
+    *channel_samples.get_unchecked_mut(0) = left;
+    *channel_samples.get_unchecked_mut(1) = right;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crossover::test_util::{process_iir, test_signal};
+    use realfft::num_complex::Complex32;
+
+    const SAMPLE_RATE: f32 = 44_100.0;
+    const FREQUENCIES: [f32; NUM_BANDS - 1] = [100.0, 1_000.0, 5_000.0, 15_000.0];
+
+    /// Process `num_samples` samples of the test signal and return each band's channels.
+    fn process_signal(
+        crossover: &mut IirCrossover,
+        num_bands: usize,
+        num_samples: usize,
+    ) -> Vec<Vec<Vec<f32>>> {
+        process_iir(crossover, num_bands, &test_signal(num_samples))
+    }
+
+    #[test]
+    fn reset_band_keeps_active_bands() {
+        let mut crossover = IirCrossover::new(IirCrossoverType::LinkwitzRiley24);
+        let mut reference = IirCrossover::new(IirCrossoverType::LinkwitzRiley24);
+        for crossover in [&mut crossover, &mut reference] {
+            crossover.update(SAMPLE_RATE, NUM_BANDS, [FREQUENCIES; 2]);
+            process_signal(crossover, NUM_BANDS, 512);
+        }
+
+        // The last band is not used with four bands, so resetting it should not affect the
+        // other bands in any way
+        for crossover in [&mut crossover, &mut reference] {
+            crossover.update(SAMPLE_RATE, NUM_BANDS - 1, [FREQUENCIES; 2]);
+        }
+        crossover.reset_band(NUM_BANDS - 1);
+        let outputs = process_signal(&mut crossover, NUM_BANDS - 1, 512);
+        let expected_outputs = process_signal(&mut reference, NUM_BANDS - 1, 512);
+        assert_eq!(outputs[..NUM_BANDS - 1], expected_outputs[..NUM_BANDS - 1]);
+
+        // But once the band is used again, it starts from a clean state
+        for crossover in [&mut crossover, &mut reference] {
+            crossover.update(SAMPLE_RATE, NUM_BANDS, [FREQUENCIES; 2]);
+        }
+        let outputs = process_signal(&mut crossover, NUM_BANDS, 1);
+        let expected_outputs = process_signal(&mut reference, NUM_BANDS, 1);
+        assert_ne!(outputs[NUM_BANDS - 1], expected_outputs[NUM_BANDS - 1]);
+    }
+
+    #[test]
+    fn bands_sum_to_all_pass() {
+        for mode in [
+            IirCrossoverType::LinkwitzRiley12,
+            IirCrossoverType::LinkwitzRiley24,
+            IirCrossoverType::LinkwitzRiley48,
+        ] {
+            let coefficients = mode.coefficients(SAMPLE_RATE, 1_000.0);
+            for frequency in [100.0, 1_000.0, 10_000.0] {
+                let response = |coefficients: &[BiquadCoefficients<f64>]| {
+                    coefficients
+                        .iter()
+                        .map(|coefs| coefs.frequency_response(SAMPLE_RATE, frequency))
+                        .product::<Complex32>()
+                };
+                let lp_response = response(&coefficients.lp[..mode.num_stages()]);
+                let hp_response = response(&coefficients.hp[..mode.num_stages()]);
+                let ap_response = response(&coefficients.ap[..mode.num_all_pass_stages()]);
+
+                assert!(
+                    (lp_response + hp_response - ap_response).norm() < 1e-4,
+                    "{mode:?} at {frequency} Hz"
+                );
+            }
+        }
+
+        // The Butterworth crossover's bands instead sum to a 3 dB boost at the crossover frequency
+        let mode = IirCrossoverType::Butterworth24;
+        let coefficients = mode.coefficients(SAMPLE_RATE, 1_000.0);
+        let response = coefficients.lp[..mode.num_stages()]
+            .iter()
+            .map(|coefs| coefs.frequency_response(SAMPLE_RATE, 1_000.0))
+            .product::<Complex32>()
+            + coefficients.hp[..mode.num_stages()]
+                .iter()
+                .map(|coefs| coefs.frequency_response(SAMPLE_RATE, 1_000.0))
+                .product::<Complex32>();
+        assert!((response.norm() - std::f32::consts::SQRT_2).abs() < 1e-3);
+    }
+
+    #[test]
+    fn summed_bands_are_flat() {
+        const NUM_NOISE_SAMPLES: usize = 4096;
+        // The filters' tails need to have decayed by the end of the signal so the band outputs
+        // are the linear convolution of the noise with the filters' impulse responses
+        const FFT_SIZE: usize = 16384;
+
+        // A simple xorshift generator is enough to get deterministic white noise. The noise is
+        // followed by silence so the FFTs below capture the filters' entire response.
+        let mut state = 0x1234_5678u32;
+        let noise: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                if i < NUM_NOISE_SAMPLES {
+                    (state as f32 / u32::MAX as f32) * 2.0 - 1.0
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        let fft = realfft::RealFftPlanner::<f32>::new().plan_fft_forward(FFT_SIZE);
+        let spectrum = |signal: &[f32]| {
+            let mut input = signal.to_vec();
+            let mut output = fft.make_output_vec();
+            fft.process(&mut input, &mut output).unwrap();
+            output
+        };
+        let noise_spectrum = spectrum(&noise);
+
+        for mode in [
+            IirCrossoverType::LinkwitzRiley12,
+            IirCrossoverType::LinkwitzRiley24,
+            IirCrossoverType::LinkwitzRiley48,
+        ] {
+            let mut crossover = IirCrossover::new(mode);
+            crossover.update(SAMPLE_RATE, NUM_BANDS, [FREQUENCIES; 2]);
+            let outputs = process_iir(&mut crossover, NUM_BANDS, &noise);
+
+            let summed: Vec<f32> = (0..FFT_SIZE)
+                .map(|i| outputs.iter().map(|band| band[0][i]).sum())
+                .collect();
+            let summed_spectrum = spectrum(&summed);
+
+            // The sum is an all-passed version of the input, so only the phase may differ
+            for (bin_idx, (summed_bin, noise_bin)) in
+                summed_spectrum.iter().zip(&noise_spectrum).enumerate()
+            {
+                let magnitude = summed_bin.norm() / noise_bin.norm();
+                assert!(
+                    (magnitude - 1.0).abs() < 1e-2,
+                    "{mode:?} has a magnitude of {magnitude} in bin {bin_idx}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unlinked_channels() {
+        const RIGHT_FREQUENCIES: [f32; NUM_BANDS - 1] = [200.0, 2_000.0, 7_000.0, 12_000.0];
+
+        // Every channel should be split exactly like it would be if both channels used that
+        // channel's frequencies
+        let mut crossover = IirCrossover::new(IirCrossoverType::LinkwitzRiley24);
+        let mut left_reference = IirCrossover::new(IirCrossoverType::LinkwitzRiley24);
+        let mut right_reference = IirCrossover::new(IirCrossoverType::LinkwitzRiley24);
+        crossover.update(SAMPLE_RATE, NUM_BANDS, [FREQUENCIES, RIGHT_FREQUENCIES]);
+        left_reference.update(SAMPLE_RATE, NUM_BANDS, [FREQUENCIES; 2]);
+        right_reference.update(SAMPLE_RATE, NUM_BANDS, [RIGHT_FREQUENCIES; 2]);
+
+        let outputs = process_signal(&mut crossover, NUM_BANDS, 512);
+        let left_outputs = process_signal(&mut left_reference, NUM_BANDS, 512);
+        let right_outputs = process_signal(&mut right_reference, NUM_BANDS, 512);
+        for ((band_outputs, left_band_outputs), right_band_outputs) in
+            outputs.iter().zip(&left_outputs).zip(&right_outputs)
+        {
+            assert_eq!(band_outputs[0], left_band_outputs[0]);
+            assert_eq!(band_outputs[1], right_band_outputs[1]);
+            assert_ne!(band_outputs[0], band_outputs[1]);
+        }
+    }
+}
@@ -15,12 +15,23 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use nih_plug::debug::*;
+use realfft::num_complex::{Complex32, Complex64};
 
-use std::f32::consts;
+use std::f64::consts;
 use std::ops::{Add, Mul, Sub};
-use std::simd::f32x2;
+#[cfg(feature = "simd")]
+use std::simd::{f32x2, f64x2};
+
+#[cfg(not(feature = "simd"))]
+pub use self::scalar::{f32x2, f64x2};
 
 pub const NEUTRAL_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+/// The Q value for a second order filter that behaves like two first order Butterworth filters in
+/// series. Used for the LR12 crossovers.
+pub const LR12_Q: f32 = 0.5;
+/// The Q values for the two second order sections that make up a fourth order Butterworth filter.
+/// Used for the LR48 and Butterworth 24 dB/octave crossovers.
+pub const BUTTERWORTH_4_QS: [f32; 2] = [0.541_196_1, 1.306_563];
 
 /// A simple biquad filter with functions for generating coefficients for second order low-pass and
 /// high-pass filters. Since these filters have 3 dB of attenuation at the center frequency, we'll
@@ -29,7 +40,9 @@ pub const NEUTRAL_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
 ///
 /// Based on <https://en.wikipedia.org/wiki/Digital_biquad_filter#Transposed_direct_forms>.
 ///
-/// The type parameter T  should be either an `f32` or a SIMD type.
+/// The type parameter T  should be either an `f32`, an `f64`, or a SIMD vector of those. Using `f64`
+/// for the filter state is more accurate at very low frequencies relative to the sample rate, since
+/// the poles then get very close to the unit circle.
 #[derive(Clone, Copy, Debug)]
 pub struct Biquad<T> {
     pub coefficients: BiquadCoefficients<T>,
@@ -40,7 +53,7 @@ pub struct Biquad<T> {
 /// The coefficients `[b0, b1, b2, a1, a2]` for [`Biquad`]. These coefficients are all
 /// prenormalized, i.e. they have been divided by `a0`.
 ///
-/// The type parameter T  should be either an `f32` or a SIMD type.
+/// The type parameter T  should be either an `f32`, an `f64`, or a SIMD vector of those.
 #[derive(Clone, Copy, Debug)]
 pub struct BiquadCoefficients<T> {
     b0: T,
@@ -50,11 +63,24 @@ pub struct BiquadCoefficients<T> {
     a2: T,
 }
 
-/// Either an `f32` or some SIMD vector type of `f32`s that can be used with our biquads.
+/// Either an `f32`, an `f64`, or some SIMD vector type of those that can be used with our biquads.
 pub trait SimdType:
     Mul<Output = Self> + Sub<Output = Self> + Add<Output = Self> + Copy + Sized
 {
-    fn from_f32(value: f32) -> Self;
+    /// Convert a scalar to this type, rounding it if this is an `f32` type. The coefficients are
+    /// always computed with `f64` precision.
+    fn from_f64(value: f64) -> Self;
+
+    /// Get the value from the first lane. Used when a vector is known to contain the same value in
+    /// every lane.
+    fn first_lane(self) -> f64;
+}
+
+/// A two lane [`SimdType`] that holds a stereo signal, with one channel per lane.
+pub trait StereoSimdType: SimdType {
+    /// Create a vector containing `left` in the first lane and `right` in the second lane, rounding
+    /// the values if this is an `f32` type.
+    fn from_f64_channels(left: f64, right: f64) -> Self;
 }
 
 impl<T: SimdType> Default for Biquad<T> {
@@ -62,8 +88,8 @@ impl<T: SimdType> Default for Biquad<T> {
     fn default() -> Self {
         Self {
             coefficients: BiquadCoefficients::identity(),
-            s1: T::from_f32(0.0),
-            s2: T::from_f32(0.0),
+            s1: T::from_f64(0.0),
+            s2: T::from_f64(0.0),
         }
     }
 }
@@ -82,26 +108,41 @@ impl<T: SimdType> Biquad<T> {
     /// Reset the state to zero, useful after making making large, non-interpolatable changes to the
     /// filter coefficients.
     pub fn reset(&mut self) {
-        self.s1 = T::from_f32(0.0);
-        self.s2 = T::from_f32(0.0);
+        self.s1 = T::from_f64(0.0);
+        self.s2 = T::from_f64(0.0);
     }
 }
 
 impl<T: SimdType> BiquadCoefficients<T> {
     /// Convert scalar coefficients into the correct vector type.
-    pub fn from_f32s(scalar: BiquadCoefficients<f32>) -> Self {
+    pub fn from_f64s(scalar: BiquadCoefficients<f64>) -> Self {
+        Self {
+            b0: T::from_f64(scalar.b0),
+            b1: T::from_f64(scalar.b1),
+            b2: T::from_f64(scalar.b2),
+            a1: T::from_f64(scalar.a1),
+            a2: T::from_f64(scalar.a2),
+        }
+    }
+
+    /// Combine scalar coefficients for the left and right channels into a single vector so both
+    /// channels can be filtered with different coefficients at the same time.
+    pub fn from_f64_channels([left, right]: [BiquadCoefficients<f64>; 2]) -> Self
+    where
+        T: StereoSimdType,
+    {
         Self {
-            b0: T::from_f32(scalar.b0),
-            b1: T::from_f32(scalar.b1),
-            b2: T::from_f32(scalar.b2),
-            a1: T::from_f32(scalar.a1),
-            a2: T::from_f32(scalar.a2),
+            b0: T::from_f64_channels(left.b0, right.b0),
+            b1: T::from_f64_channels(left.b1, right.b1),
+            b2: T::from_f64_channels(left.b2, right.b2),
+            a1: T::from_f64_channels(left.a1, right.a1),
+            a2: T::from_f64_channels(left.a2, right.a2),
         }
     }
 
     /// Filter coefficients that would cause the sound to be passed through as is.
     pub fn identity() -> Self {
-        Self::from_f32s(BiquadCoefficients {
+        Self::from_f64s(BiquadCoefficients {
             b0: 1.0,
             b1: 0.0,
             b2: 0.0,
@@ -119,9 +160,9 @@ impl<T: SimdType> BiquadCoefficients<T> {
         nih_debug_assert!(frequency < sample_rate / 2.0);
         nih_debug_assert!(q > 0.0);
 
-        let omega0 = consts::TAU * (frequency / sample_rate);
+        let omega0 = consts::TAU * (frequency as f64 / sample_rate as f64);
         let cos_omega0 = omega0.cos();
-        let alpha = omega0.sin() / (2.0 * q);
+        let alpha = omega0.sin() / (2.0 * q as f64);
 
         // We'll prenormalize everything with a0
         let a0 = 1.0 + alpha;
@@ -131,7 +172,7 @@ impl<T: SimdType> BiquadCoefficients<T> {
         let a1 = (-2.0 * cos_omega0) / a0;
         let a2 = (1.0 - alpha) / a0;
 
-        Self::from_f32s(BiquadCoefficients { b0, b1, b2, a1, a2 })
+        Self::from_f64s(BiquadCoefficients { b0, b1, b2, a1, a2 })
     }
 
     /// Compute the coefficients for a high-pass filter.
@@ -143,9 +184,9 @@ impl<T: SimdType> BiquadCoefficients<T> {
         nih_debug_assert!(frequency < sample_rate / 2.0);
         nih_debug_assert!(q > 0.0);
 
-        let omega0 = consts::TAU * (frequency / sample_rate);
+        let omega0 = consts::TAU * (frequency as f64 / sample_rate as f64);
         let cos_omega0 = omega0.cos();
-        let alpha = omega0.sin() / (2.0 * q);
+        let alpha = omega0.sin() / (2.0 * q as f64);
 
         // We'll prenormalize everything with a0
         let a0 = 1.0 + alpha;
@@ -155,7 +196,7 @@ impl<T: SimdType> BiquadCoefficients<T> {
         let a1 = (-2.0 * cos_omega0) / a0;
         let a2 = (1.0 - alpha) / a0;
 
-        Self::from_f32s(BiquadCoefficients { b0, b1, b2, a1, a2 })
+        Self::from_f64s(BiquadCoefficients { b0, b1, b2, a1, a2 })
     }
 
     /// Compute the coefficients for an all-pass filter.
@@ -167,9 +208,9 @@ impl<T: SimdType> BiquadCoefficients<T> {
         nih_debug_assert!(frequency < sample_rate / 2.0);
         nih_debug_assert!(q > 0.0);
 
-        let omega0 = consts::TAU * (frequency / sample_rate);
+        let omega0 = consts::TAU * (frequency as f64 / sample_rate as f64);
         let cos_omega0 = omega0.cos();
-        let alpha = omega0.sin() / (2.0 * q);
+        let alpha = omega0.sin() / (2.0 * q as f64);
 
         // We'll prenormalize everything with a0
         let a0 = 1.0 + alpha;
@@ -179,20 +220,413 @@ impl<T: SimdType> BiquadCoefficients<T> {
         let a1 = (-2.0 * cos_omega0) / a0;
         let a2 = (1.0 - alpha) / a0;
 
-        Self::from_f32s(BiquadCoefficients { b0, b1, b2, a1, a2 })
+        Self::from_f64s(BiquadCoefficients { b0, b1, b2, a1, a2 })
+    }
+
+    /// Compute the coefficients for a first order low-pass filter. Filtering a signal with this in
+    /// both directions results in the same frequency response magnitudes as the LR12 crossover's
+    /// low-pass section.
+    pub fn first_order_lowpass(sample_rate: f32, frequency: f32) -> Self {
+        nih_debug_assert!(sample_rate > 0.0);
+        nih_debug_assert!(frequency > 0.0);
+        nih_debug_assert!(frequency < sample_rate / 2.0);
+
+        // This is the bilinear transform of `1 / (s + 1)`, prewarped at the cutoff frequency
+        let k = (consts::PI * (frequency as f64 / sample_rate as f64)).tan();
+        let b0 = k / (k + 1.0);
+        let a1 = (k - 1.0) / (k + 1.0);
+
+        Self::from_f64s(BiquadCoefficients {
+            b0,
+            b1: b0,
+            b2: 0.0,
+            a1,
+            a2: 0.0,
+        })
+    }
+
+    /// Compute the coefficients for a first order all-pass filter. This is the phase shift the
+    /// bands of an LR12 crossover sum up to.
+    pub fn first_order_allpass(sample_rate: f32, frequency: f32) -> Self {
+        nih_debug_assert!(sample_rate > 0.0);
+        nih_debug_assert!(frequency > 0.0);
+        nih_debug_assert!(frequency < sample_rate / 2.0);
+
+        // This is the bilinear transform of `(1 - s) / (1 + s)`, prewarped at the cutoff frequency
+        let k = (consts::PI * (frequency as f64 / sample_rate as f64)).tan();
+        let a1 = (k - 1.0) / (k + 1.0);
+
+        Self::from_f64s(BiquadCoefficients {
+            b0: a1,
+            b1: 1.0,
+            b2: 0.0,
+            a1,
+            a2: 0.0,
+        })
+    }
+
+    /// Compute the coefficients for a low-pass, a high-pass, and an all-pass filter at the same
+    /// frequency, in that order. These filters share most of their intermediate values, so this is
+    /// cheaper than calling [`lowpass()`][Self::lowpass()], [`highpass()`][Self::highpass()], and
+    /// [`allpass()`][Self::allpass()] separately when the crossovers are updated every sample.
+    pub fn lowpass_highpass_allpass(sample_rate: f32, frequency: f32, q: f32) -> [Self; 3] {
+        nih_debug_assert!(sample_rate > 0.0);
+        nih_debug_assert!(frequency > 0.0);
+        nih_debug_assert!(frequency < sample_rate / 2.0);
+        nih_debug_assert!(q > 0.0);
+
+        let omega0 = consts::TAU * (frequency as f64 / sample_rate as f64);
+        let cos_omega0 = omega0.cos();
+        let alpha = omega0.sin() / (2.0 * q as f64);
+
+        // The denominators are the same for all three filters
+        let a0 = 1.0 + alpha;
+        let a1 = (-2.0 * cos_omega0) / a0;
+        let a2 = (1.0 - alpha) / a0;
+
+        let lp_b0 = ((1.0 - cos_omega0) / 2.0) / a0;
+        let hp_b0 = ((1.0 + cos_omega0) / 2.0) / a0;
+
+        [
+            Self::from_f64s(BiquadCoefficients {
+                b0: lp_b0,
+                b1: lp_b0 * 2.0,
+                b2: lp_b0,
+                a1,
+                a2,
+            }),
+            Self::from_f64s(BiquadCoefficients {
+                b0: hp_b0,
+                b1: hp_b0 * -2.0,
+                b2: hp_b0,
+                a1,
+                a2,
+            }),
+            Self::from_f64s(BiquadCoefficients {
+                b0: a2,
+                b1: a1,
+                b2: 1.0,
+                a1,
+                a2,
+            }),
+        ]
+    }
+
+    /// Evaluate the filter's transfer function at `frequency`. The magnitude of the result is the
+    /// filter's gain at that frequency, and its argument is the phase shift in radians. For SIMD
+    /// coefficients this uses the first lane since the functions above set the same coefficients
+    /// for every lane. The response is evaluated with `f64` precision regardless of `T`, so for
+    /// `f32` coefficients this includes the error caused by rounding the coefficients.
+    pub fn frequency_response(&self, sample_rate: f32, frequency: f32) -> Complex32 {
+        nih_debug_assert!(sample_rate > 0.0);
+        nih_debug_assert!(frequency >= 0.0);
+
+        // This is `H(z) = (b0 + b1 z^-1 + b2 z^-2) / (1 + a1 z^-1 + a2 z^-2)` evaluated at
+        // `z = e^(j * omega)`
+        let omega = consts::TAU * (frequency as f64 / sample_rate as f64);
+        let z1 = Complex64::from_polar(1.0, -omega);
+        let z2 = z1 * z1;
+
+        let numerator =
+            self.b0.first_lane() + (z1 * self.b1.first_lane()) + (z2 * self.b2.first_lane());
+        let denominator = 1.0 + (z1 * self.a1.first_lane()) + (z2 * self.a2.first_lane());
+        let response = numerator / denominator;
+
+        Complex32::new(response.re as f32, response.im as f32)
+    }
+}
+
+impl BiquadCoefficients<f64> {
+    /// The same filter, but with the output's polarity inverted.
+    pub fn inverted(self) -> Self {
+        Self {
+            b0: -self.b0,
+            b1: -self.b1,
+            b2: -self.b2,
+            ..self
+        }
     }
 }
 
 impl SimdType for f32 {
     #[inline(always)]
-    fn from_f32(value: f32) -> Self {
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    #[inline(always)]
+    fn first_lane(self) -> f64 {
+        self as f64
+    }
+}
+
+impl SimdType for f64 {
+    #[inline(always)]
+    fn from_f64(value: f64) -> Self {
         value
     }
+
+    #[inline(always)]
+    fn first_lane(self) -> f64 {
+        self
+    }
 }
 
 impl SimdType for f32x2 {
     #[inline(always)]
-    fn from_f32(value: f32) -> Self {
-        f32x2::splat(value)
+    fn from_f64(value: f64) -> Self {
+        f32x2::splat(value as f32)
+    }
+
+    #[inline(always)]
+    fn first_lane(self) -> f64 {
+        self[0] as f64
+    }
+}
+
+impl SimdType for f64x2 {
+    #[inline(always)]
+    fn from_f64(value: f64) -> Self {
+        f64x2::splat(value)
+    }
+
+    #[inline(always)]
+    fn first_lane(self) -> f64 {
+        self[0]
+    }
+}
+
+impl StereoSimdType for f32x2 {
+    #[inline(always)]
+    fn from_f64_channels(left: f64, right: f64) -> Self {
+        f32x2::from_array([left as f32, right as f32])
+    }
+}
+
+impl StereoSimdType for f64x2 {
+    #[inline(always)]
+    fn from_f64_channels(left: f64, right: f64) -> Self {
+        f64x2::from_array([left, right])
+    }
+}
+
+/// Scalar stand-ins for the `std::simd` vector types used by the IIR crossover. These are used when
+/// the `simd` feature is disabled so the crossover can be compiled with a stable compiler. They
+/// only implement the parts of the `std::simd` API the crossover needs.
+#[cfg(not(feature = "simd"))]
+mod scalar {
+    use std::ops::{Add, Index, Mul, Sub};
+
+    /// Two lanes of either `f32` or `f64` values that are processed one by one.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct Lanes2<T>([T; 2]);
+
+    #[allow(non_camel_case_types)]
+    pub type f32x2 = Lanes2<f32>;
+    #[allow(non_camel_case_types)]
+    pub type f64x2 = Lanes2<f64>;
+
+    /// A type that can be stored in [`Lanes2`].
+    pub trait Lane: Copy {
+        fn from_f64(value: f64) -> Self;
+        fn to_f64(self) -> f64;
+    }
+
+    impl Lane for f32 {
+        #[inline(always)]
+        fn from_f64(value: f64) -> Self {
+            value as f32
+        }
+
+        #[inline(always)]
+        fn to_f64(self) -> f64 {
+            self as f64
+        }
+    }
+
+    impl Lane for f64 {
+        #[inline(always)]
+        fn from_f64(value: f64) -> Self {
+            value
+        }
+
+        #[inline(always)]
+        fn to_f64(self) -> f64 {
+            self
+        }
+    }
+
+    impl<T: Lane> Lanes2<T> {
+        #[inline(always)]
+        pub fn splat(value: T) -> Self {
+            Self([value; 2])
+        }
+
+        #[inline(always)]
+        pub fn from_array(array: [T; 2]) -> Self {
+            Self(array)
+        }
+
+        #[inline(always)]
+        pub fn to_array(self) -> [T; 2] {
+            self.0
+        }
+
+        /// Convert the lanes to another floating point type, like `Simd::cast()`.
+        #[inline(always)]
+        pub fn cast<U: Lane>(self) -> Lanes2<U> {
+            Lanes2(self.0.map(|value| U::from_f64(value.to_f64())))
+        }
+    }
+
+    impl<T> Index<usize> for Lanes2<T> {
+        type Output = T;
+
+        #[inline(always)]
+        fn index(&self, index: usize) -> &T {
+            &self.0[index]
+        }
+    }
+
+    impl<T: Add<Output = T> + Copy> Add for Lanes2<T> {
+        type Output = Self;
+
+        #[inline(always)]
+        fn add(self, rhs: Self) -> Self {
+            Self([self.0[0] + rhs.0[0], self.0[1] + rhs.0[1]])
+        }
+    }
+
+    impl<T: Sub<Output = T> + Copy> Sub for Lanes2<T> {
+        type Output = Self;
+
+        #[inline(always)]
+        fn sub(self, rhs: Self) -> Self {
+            Self([self.0[0] - rhs.0[0], self.0[1] - rhs.0[1]])
+        }
+    }
+
+    impl<T: Mul<Output = T> + Copy> Mul for Lanes2<T> {
+        type Output = Self;
+
+        #[inline(always)]
+        fn mul(self, rhs: Self) -> Self {
+            Self([self.0[0] * rhs.0[0], self.0[1] * rhs.0[1]])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 44_100.0;
+
+    fn gain_db(response: Complex32) -> f32 {
+        20.0 * response.norm().log10()
+    }
+
+    #[test]
+    fn lowpass_cutoff() {
+        let coefficients: BiquadCoefficients<f32> =
+            BiquadCoefficients::lowpass(SAMPLE_RATE, 1000.0, NEUTRAL_Q);
+
+        let response = coefficients.frequency_response(SAMPLE_RATE, 1000.0);
+        assert!((gain_db(response) + 3.0103).abs() < 1e-2);
+
+        let dc_response = coefficients.frequency_response(SAMPLE_RATE, 0.0);
+        assert!(gain_db(dc_response).abs() < 1e-3);
+    }
+
+    #[test]
+    fn linkwitz_riley_cutoff() {
+        // The LR24 crossovers are two of these filters in series, so the responses are multiplied
+        let coefficients: BiquadCoefficients<f32x2> =
+            BiquadCoefficients::lowpass(SAMPLE_RATE, 1000.0, NEUTRAL_Q);
+
+        let response = coefficients.frequency_response(SAMPLE_RATE, 1000.0);
+        assert!((gain_db(response * response) + 6.0206).abs() < 2e-2);
+    }
+
+    #[test]
+    fn combined_coefficients_match() {
+        let [lp, hp, ap]: [BiquadCoefficients<f64>; 3] =
+            BiquadCoefficients::lowpass_highpass_allpass(SAMPLE_RATE, 1234.0, NEUTRAL_Q);
+        let expected = [
+            BiquadCoefficients::lowpass(SAMPLE_RATE, 1234.0, NEUTRAL_Q),
+            BiquadCoefficients::highpass(SAMPLE_RATE, 1234.0, NEUTRAL_Q),
+            BiquadCoefficients::allpass(SAMPLE_RATE, 1234.0, NEUTRAL_Q),
+        ];
+
+        for (coefficients, expected) in [lp, hp, ap].iter().zip(expected.iter()) {
+            for frequency in [100.0, 1234.0, 10_000.0] {
+                let response = coefficients.frequency_response(SAMPLE_RATE, frequency);
+                let expected_response = expected.frequency_response(SAMPLE_RATE, frequency);
+                assert!((response - expected_response).norm() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn allpass_unity_gain() {
+        let coefficients: BiquadCoefficients<f32> =
+            BiquadCoefficients::allpass(SAMPLE_RATE, 1000.0, NEUTRAL_Q);
+
+        for frequency in [20.0, 500.0, 1000.0, 5000.0, 20_000.0] {
+            let response = coefficients.frequency_response(SAMPLE_RATE, frequency);
+            assert!((response.norm() - 1.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn first_order_filters() {
+        let lowpass: BiquadCoefficients<f64> =
+            BiquadCoefficients::first_order_lowpass(SAMPLE_RATE, 1000.0);
+        let allpass: BiquadCoefficients<f64> =
+            BiquadCoefficients::first_order_allpass(SAMPLE_RATE, 1000.0);
+
+        let response = lowpass.frequency_response(SAMPLE_RATE, 1000.0);
+        assert!((gain_db(response) + 3.0103).abs() < 1e-2);
+        for frequency in [20.0, 1000.0, 20_000.0] {
+            let response = allpass.frequency_response(SAMPLE_RATE, frequency);
+            assert!((response.norm() - 1.0).abs() < 1e-4);
+        }
+
+        // The second order filter with `LR12_Q` is the first order filter applied twice
+        let squared: BiquadCoefficients<f64> =
+            BiquadCoefficients::lowpass(SAMPLE_RATE, 1000.0, LR12_Q);
+        for frequency in [100.0, 1000.0, 10_000.0] {
+            let response = lowpass.frequency_response(SAMPLE_RATE, frequency);
+            let expected_response = squared.frequency_response(SAMPLE_RATE, frequency);
+            assert!((response * response - expected_response).norm() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn f64_low_frequency_accuracy() {
+        // At 5 Hz and 192 kHz the poles are so close to the unit circle that rounding the
+        // coefficients to `f32` throws off the low-pass filter's response
+        const HIGH_SAMPLE_RATE: f32 = 192_000.0;
+        let f32_coefficients: BiquadCoefficients<f32> =
+            BiquadCoefficients::lowpass(HIGH_SAMPLE_RATE, 5.0, NEUTRAL_Q);
+        let f64_coefficients: BiquadCoefficients<f64> =
+            BiquadCoefficients::lowpass(HIGH_SAMPLE_RATE, 5.0, NEUTRAL_Q);
+
+        for frequency in [1.0f32, 5.0] {
+            // This is the analog second order Butterworth response, which the bilinear transform
+            // matches closely at these frequencies
+            let expected_gain_db = -10.0 * (1.0 + (frequency / 5.0).powi(4)).log10();
+            let f32_error =
+                (gain_db(f32_coefficients.frequency_response(HIGH_SAMPLE_RATE, frequency))
+                    - expected_gain_db)
+                    .abs();
+            let f64_error =
+                (gain_db(f64_coefficients.frequency_response(HIGH_SAMPLE_RATE, frequency))
+                    - expected_gain_db)
+                    .abs();
+
+            assert!(f64_error < 1e-3, "{f64_error} dB off at {frequency} Hz");
+            assert!(f32_error > 0.1, "{f32_error} dB off at {frequency} Hz");
+        }
     }
 }
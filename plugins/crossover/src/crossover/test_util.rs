@@ -0,0 +1,109 @@
+// Crossover: clean crossovers as a multi-out plugin
+// Copyright (C) 2022-2024 Robbert van der Helm
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Fixtures shared between the crossover tests.
+
+use nih_plug::buffer::Buffer;
+
+use super::fir::FirCrossover;
+use super::iir::IirCrossover;
+use crate::{NUM_BANDS, NUM_CHANNELS};
+
+/// A test signal containing both a low and a high frequency component, so every band gets some
+/// signal.
+pub fn test_signal(num_samples: usize) -> Vec<f32> {
+    (0..num_samples)
+        .map(|i| (i as f32 * 0.05).sin() + (i as f32 * 0.7).sin())
+        .collect()
+}
+
+/// Silent channels for every band's output.
+pub fn silent_band_channels(num_samples: usize) -> Vec<Vec<Vec<f32>>> {
+    vec![vec![vec![0.0; num_samples]; NUM_CHANNELS as usize]; NUM_BANDS]
+}
+
+/// Point `buffer` to `channels`. All channels should have the same length.
+pub fn set_buffer_slices<'a>(buffer: &mut Buffer<'a>, channels: &'a mut [Vec<f32>]) {
+    let num_samples = channels.first().map_or(0, Vec::len);
+    unsafe {
+        buffer.set_slices(num_samples, |slices| {
+            *slices = channels
+                .iter_mut()
+                .map(|channel| channel.as_mut_slice())
+                .collect();
+        });
+    }
+}
+
+/// Create a buffer for every band pointing to that band's channels.
+pub fn band_buffers(band_channels: &mut [Vec<Vec<f32>>]) -> [Buffer<'_>; NUM_BANDS] {
+    let mut band_buffers: [Buffer; NUM_BANDS] = Default::default();
+    for (band_buffer, channels) in band_buffers.iter_mut().zip(band_channels) {
+        set_buffer_slices(band_buffer, channels);
+    }
+
+    band_buffers
+}
+
+/// Split `signal` into bands with an IIR crossover, using it as the input for every channel.
+/// Returns the channels for each band.
+pub fn process_iir(
+    crossover: &mut IirCrossover,
+    num_bands: usize,
+    signal: &[f32],
+) -> Vec<Vec<Vec<f32>>> {
+    let mut main_channels = vec![signal.to_vec(); NUM_CHANNELS as usize];
+    let mut band_channels = silent_band_channels(signal.len());
+
+    let mut main_buffer = Buffer::default();
+    set_buffer_slices(&mut main_buffer, &mut main_channels);
+    let mut band_buffers = band_buffers(&mut band_channels);
+    let mut band_iters: Vec<_> = band_buffers
+        .iter_mut()
+        .map(|buffer| buffer.iter_samples())
+        .collect();
+    for main_channel_samples in main_buffer.iter_samples() {
+        let bands = std::array::from_fn(|band_idx| band_iters[band_idx].next().unwrap());
+        crossover.process(num_bands, &main_channel_samples, bands);
+    }
+
+    band_channels
+}
+
+/// Split `signal` into bands with a linear-phase FIR crossover, processing it as the first
+/// channel. Returns each band's output.
+pub fn process_fir(
+    crossover: &mut FirCrossover,
+    num_bands: usize,
+    signal: &[f32],
+) -> Vec<Vec<f32>> {
+    let mut band_outputs = vec![vec![0.0; signal.len()]; NUM_BANDS];
+    let mut band_slices: Vec<&mut [f32]> = band_outputs
+        .iter_mut()
+        .map(|band| band.as_mut_slice())
+        .collect();
+    let [band_1, band_2, band_3, band_4, band_5] = &mut band_slices[..] else {
+        unreachable!()
+    };
+    crossover.process(
+        num_bands,
+        signal,
+        [band_1, band_2, band_3, band_4, band_5],
+        0,
+    );
+
+    band_outputs
+}
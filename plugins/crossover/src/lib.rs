@@ -16,11 +16,9 @@
 
 #![cfg_attr(feature = "simd", feature(portable_simd))]
 
-#[cfg(not(feature = "simd"))]
-compile_error!("Compiling without SIMD support is currently not supported");
-
-use crossover::fir::{FirCrossover, FirCrossoverType};
-use crossover::iir::{IirCrossover, IirCrossoverType};
+use crossover::fir::filter::FirWindow;
+use crossover::fir::{FirCrossover, FirCrossoverType, FirSharpness};
+use crossover::iir::{ChannelFrequencies, IirCrossover, IirCrossoverType};
 use nih_plug::prelude::*;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -37,27 +35,104 @@ pub const NUM_BANDS: usize = 5;
 const MIN_CROSSOVER_FREQUENCY: f32 = 40.0;
 const MAX_CROSSOVER_FREQUENCY: f32 = 20_000.0;
 
+/// The time it takes to fade from the held outputs back to the crossover's outputs after freezing
+/// has been disabled. The filters resume from the state they were in when they were frozen, so
+/// this prevents a jump in the output.
+const UNFREEZE_CROSSFADE_MS: f32 = 10.0;
+/// The time it takes for the held outputs to fade out to silence after freezing. Holding the last
+/// output sample forever would result in a constant DC offset.
+const FREEZE_FADE_MS: f32 = 100.0;
+
 pub struct Crossover {
     params: Arc<CrossoverParams>,
 
     buffer_config: BufferConfig,
 
-    /// Provides the LR24 crossover.
+    /// Provides the IIR crossovers.
     iir_crossover: IirCrossover,
-    /// Provides the linear-phase LR24 crossover.
-    fir_crossover: FirCrossover,
+    /// Provides the linear-phase crossovers. There is one crossover per channel so the channels
+    /// can use different crossover frequencies when they are not stereo linked. While linked, the
+    /// right channel's crossover copies the left channel's filters instead of designing them again.
+    fir_crossovers: [FirCrossover; NUM_CHANNELS as usize],
     /// Set when the number of bands has changed and the filters must be updated.
     should_update_filters: Arc<AtomicBool>,
+    /// The number of bands the crossovers processed in the previous block. When the number of
+    /// bands increases, only the newly activated bands are reset.
+    active_num_bands: usize,
+
+    /// The last output sample for each band and channel. These are faded out while the crossover
+    /// is frozen, in which case they contain the faded out values from the end of the last block.
+    held_outputs: [[f32; NUM_CHANNELS as usize]; NUM_BANDS],
+    /// Whether the previous block was processed while the crossover was frozen.
+    frozen: bool,
+    /// The number of samples left in the fade from `held_outputs` to silence while frozen.
+    freeze_fade_samples_remaining: u32,
+    /// The number of samples left in the crossfade from `held_outputs` back to the crossover's
+    /// outputs after unfreezing.
+    unfreeze_crossfade_samples_remaining: u32,
 }
 
 #[derive(Params)]
+#[snapshot(CrossoverParamsSnapshot)]
 struct CrossoverParams {
     /// The number of bands between 2 and 5
     #[id = "bandcnt"]
+    #[group = "Global"]
     pub num_bands: IntParam,
 
-    // We'll only provide frequency controls, as gain, panning, solo, mute etc. is all already
-    // provided by Bitwig's UI
+    /// The crossover frequencies for both channels. While the channels are stereo linked, the first
+    /// set of frequencies is used for both channels. When they're unlinked, the first set only
+    /// affects the left channel and the right channel uses the second set. The second set is marked
+    /// as inactive while linked so hosts can grey them out.
+    #[nested(group = "Crossovers")]
+    pub crossovers: LinkedParams<CrossoverFreqParams>,
+
+    // Having this parameter first or after the number of bands makes more sense, but this way the
+    // band control plus the four crossovers fits exactly in Bitwig's parameter list
+    #[id = "xovtyp"]
+    #[group = "Global"]
+    pub crossover_type: EnumParam<CrossoverType>,
+    /// Trades the linear-phase crossover's slopes for less pre-ringing and latency. Only used with
+    /// the linear-phase crossover type.
+    #[id = "lpsharp"]
+    #[group = "Global"]
+    pub linear_phase_sharpness: EnumParam<LinearPhaseSharpness>,
+    /// The window function used to design the linear-phase crossover's filters. The automatic
+    /// option picks the window that fits the sharpness setting.
+    #[id = "lpwindow"]
+    #[group = "Global"]
+    pub linear_phase_window: EnumParam<LinearPhaseWindow>,
+    /// The Kaiser window's beta parameter. Only used when the Kaiser window is selected.
+    #[id = "lpkaiser"]
+    #[group = "Global"]
+    pub linear_phase_kaiser_beta: FloatParam,
+
+    /// Stop processing and hold the last output sample for each band while fading it out to
+    /// silence. The filter state is left untouched while frozen, and processing resumes from that
+    /// state after unfreezing.
+    #[id = "freeze"]
+    #[group = "Global"]
+    pub freeze: BoolParam,
+    /// Recompute the IIR crossovers' filter coefficients every sample, ramping the crossover
+    /// frequencies from their values at the end of the previous block to the new values over the
+    /// course of the block. This makes fast modulation of the crossover frequencies sound smooth,
+    /// at the cost of computing the coefficients for every crossover that's being modulated on
+    /// every sample instead of only while a parameter is being smoothed. The linear-phase
+    /// crossover always updates its filters once per block.
+    #[id = "audiomod"]
+    #[group = "Global"]
+    pub audio_rate_modulation: BoolParam,
+
+    /// Gain, mute, solo, and polarity controls for each band's output. The parameter IDs get the
+    /// band number as a suffix, e.g. `gain_1`.
+    #[nested(array, group = "Band")]
+    pub bands: [BandParams; NUM_BANDS],
+}
+
+/// The crossover frequencies for a single channel.
+#[derive(Params)]
+#[snapshot(CrossoverFreqParamsSnapshot)]
+struct CrossoverFreqParams {
     #[id = "xov1fq"]
     pub crossover_1_freq: FloatParam,
     #[id = "xov2fq"]
@@ -66,15 +141,27 @@ struct CrossoverParams {
     pub crossover_3_freq: FloatParam,
     #[id = "xov4fq"]
     pub crossover_4_freq: FloatParam,
+}
 
-    // Having this parameter first or after the number of bands makes more sense, but this way the
-    // band control plus the four crossovers fits exactly in Bitwig's parameter list
-    #[id = "xovtyp"]
-    pub crossover_type: EnumParam<CrossoverType>,
+/// The controls for a single band's output. These are applied after the signal has been split.
+#[derive(Params)]
+struct BandParams {
+    #[id = "gain"]
+    pub gain: FloatParam,
+    #[id = "mute"]
+    pub mute: BoolParam,
+    /// When any of the bands are soloed, only the soloed bands are output. Muting a band silences
+    /// it even when it's soloed.
+    #[id = "solo"]
+    pub solo: BoolParam,
+    /// Inverts the band's polarity, for instance to null test a band against the input.
+    #[id = "invert"]
+    pub invert: BoolParam,
 }
 
 // The `non_exhaustive` is to prevent adding cases for latency compensation when adding more types
-// later
+// later. New types are added to the end so existing automation keeps selecting the same type. The
+// Butterworth crossover is IIR only, see `FirCrossoverType` for why.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
 #[non_exhaustive]
 enum CrossoverType {
@@ -84,6 +171,109 @@ enum CrossoverType {
     #[id = "lr24-lp"]
     #[name = "LR24 (LP)"]
     LinkwitzRiley24LinearPhase,
+    #[id = "lr12"]
+    #[name = "LR12"]
+    LinkwitzRiley12,
+    #[id = "lr12-lp"]
+    #[name = "LR12 (LP)"]
+    LinkwitzRiley12LinearPhase,
+    #[id = "lr48"]
+    #[name = "LR48"]
+    LinkwitzRiley48,
+    #[id = "lr48-lp"]
+    #[name = "LR48 (LP)"]
+    LinkwitzRiley48LinearPhase,
+    #[id = "bw24"]
+    #[name = "BW24"]
+    Butterworth24,
+}
+
+/// The crossover that implements a [`CrossoverType`]. Only the linear-phase FIR crossovers
+/// introduce latency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FilterType {
+    Iir(IirCrossoverType),
+    Fir(FirCrossoverType),
+}
+
+impl CrossoverType {
+    /// Which crossover should be used for this crossover type.
+    fn filter_type(self) -> FilterType {
+        match self {
+            CrossoverType::LinkwitzRiley12 => FilterType::Iir(IirCrossoverType::LinkwitzRiley12),
+            CrossoverType::LinkwitzRiley24 => FilterType::Iir(IirCrossoverType::LinkwitzRiley24),
+            CrossoverType::LinkwitzRiley48 => FilterType::Iir(IirCrossoverType::LinkwitzRiley48),
+            CrossoverType::Butterworth24 => FilterType::Iir(IirCrossoverType::Butterworth24),
+            CrossoverType::LinkwitzRiley12LinearPhase => {
+                FilterType::Fir(FirCrossoverType::LinkwitzRiley12LinearPhase)
+            }
+            CrossoverType::LinkwitzRiley24LinearPhase => {
+                FilterType::Fir(FirCrossoverType::LinkwitzRiley24LinearPhase)
+            }
+            CrossoverType::LinkwitzRiley48LinearPhase => {
+                FilterType::Fir(FirCrossoverType::LinkwitzRiley48LinearPhase)
+            }
+        }
+    }
+}
+
+/// See [`FirSharpness`] for the tradeoffs between these options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+enum LinearPhaseSharpness {
+    #[id = "sharp"]
+    Sharp,
+    #[id = "balanced"]
+    Balanced,
+    #[id = "low-pre-ringing"]
+    #[name = "Low Pre-Ringing"]
+    LowPreRinging,
+}
+
+/// See [`FirWindow`] for the tradeoffs between these options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+enum LinearPhaseWindow {
+    #[id = "auto"]
+    Auto,
+    #[id = "blackman"]
+    Blackman,
+    #[id = "blackman-harris"]
+    #[name = "Blackman-Harris"]
+    BlackmanHarris,
+    #[id = "hann"]
+    Hann,
+    #[id = "kaiser"]
+    Kaiser,
+}
+
+/// Clear the main output. This should be silent as the signal is already evenly split over the
+/// bands.
+fn silence_main_output(buffer: &mut Buffer) {
+    for channel in buffer.as_slice() {
+        channel.fill(0.0);
+    }
+}
+
+/// Whether each band should be output given the bands' mute and solo states. When any of the bands
+/// in use is soloed, only the soloed bands are output. Muted bands are never output, even when
+/// they're soloed.
+fn audible_bands(
+    num_bands: usize,
+    mutes: [bool; NUM_BANDS],
+    solos: [bool; NUM_BANDS],
+) -> [bool; NUM_BANDS] {
+    let any_soloed = solos[..num_bands].iter().any(|solo| *solo);
+
+    std::array::from_fn(|band_idx| {
+        band_idx < num_bands && !mutes[band_idx] && (!any_soloed || solos[band_idx])
+    })
+}
+
+/// The default frequency for a crossover. The crossovers are spaced logarithmically so that with
+/// all bands enabled each band covers the same number of octaves.
+fn default_crossover_frequency(crossover_idx: usize) -> f32 {
+    let position = (crossover_idx + 1) as f32 / NUM_BANDS as f32;
+
+    MIN_CROSSOVER_FREQUENCY * (MAX_CROSSOVER_FREQUENCY / MIN_CROSSOVER_FREQUENCY).powf(position)
 }
 
 impl CrossoverParams {
@@ -96,6 +286,26 @@ impl CrossoverParams {
         let crossover_smoothing_style = SmoothingStyle::Logarithmic(100.0);
         let crossover_value_to_string = formatters::v2s_f32_hz_then_khz(0);
         let crossover_string_to_value = formatters::s2v_f32_hz_then_khz();
+        let crossover_freq_param = |name: &str, crossover_idx: usize| {
+            let default_frequency = default_crossover_frequency(crossover_idx);
+
+            FloatParam::new(name, default_frequency, crossover_range)
+                // Modulation isn't smoothed, so the filters need to be updated when it changes
+                .with_callback({
+                    let should_update_filters = should_update_filters.clone();
+
+                    Arc::new(move |_| should_update_filters.store(true, Ordering::Relaxed))
+                })
+                .with_smoother(crossover_smoothing_style.clone())
+                .with_value_to_string(crossover_value_to_string.clone())
+                .with_string_to_value(crossover_string_to_value.clone())
+        };
+        let crossover_freq_params = |name_suffix: &str| CrossoverFreqParams {
+            crossover_1_freq: crossover_freq_param(&format!("Crossover 1{name_suffix}"), 0),
+            crossover_2_freq: crossover_freq_param(&format!("Crossover 2{name_suffix}"), 1),
+            crossover_3_freq: crossover_freq_param(&format!("Crossover 3{name_suffix}"), 2),
+            crossover_4_freq: crossover_freq_param(&format!("Crossover 4{name_suffix}"), 3),
+        };
 
         Self {
             num_bands: IntParam::new(
@@ -106,33 +316,153 @@ impl CrossoverParams {
                     max: NUM_BANDS as i32,
                 },
             )
+            .with_value_to_string(formatters::v2s_i32_with_unit("band", "bands"))
+            .with_string_to_value(formatters::s2v_i32_with_unit("band", "bands"))
             .with_callback({
                 let should_update_filters = should_update_filters.clone();
 
                 Arc::new(move |_| should_update_filters.store(true, Ordering::Relaxed))
             }),
 
-            // TODO: More sensible default frequencies
-            crossover_1_freq: FloatParam::new("Crossover 1", 200.0, crossover_range)
-                .with_smoother(crossover_smoothing_style.clone())
-                .with_value_to_string(crossover_value_to_string.clone())
-                .with_string_to_value(crossover_string_to_value.clone()),
-            crossover_2_freq: FloatParam::new("Crossover 2", 1000.0, crossover_range)
-                .with_smoother(crossover_smoothing_style.clone())
-                .with_value_to_string(crossover_value_to_string.clone())
-                .with_string_to_value(crossover_string_to_value.clone()),
-            crossover_3_freq: FloatParam::new("Crossover 3", 5000.0, crossover_range)
-                .with_smoother(crossover_smoothing_style.clone())
-                .with_value_to_string(crossover_value_to_string.clone())
-                .with_string_to_value(crossover_string_to_value.clone()),
-            crossover_4_freq: FloatParam::new("Crossover 4", 10000.0, crossover_range)
-                .with_smoother(crossover_smoothing_style)
-                .with_value_to_string(crossover_value_to_string)
-                .with_string_to_value(crossover_string_to_value),
-
-            crossover_type: EnumParam::new("Type", CrossoverType::LinkwitzRiley24).with_callback(
-                Arc::new(move |_| should_update_filters.store(true, Ordering::Relaxed)),
-            ),
+            crossovers: LinkedParams::new(
+                BoolParam::new("Stereo Link", true).with_callback({
+                    let should_update_filters = should_update_filters.clone();
+
+                    Arc::new(move |_| should_update_filters.store(true, Ordering::Relaxed))
+                }),
+                crossover_freq_params(""),
+                crossover_freq_params(" (R)"),
+            )
+            .with_second_group("Right"),
+
+            crossover_type: EnumParam::new("Type", CrossoverType::LinkwitzRiley24).with_callback({
+                let should_update_filters = should_update_filters.clone();
+
+                Arc::new(move |_| should_update_filters.store(true, Ordering::Relaxed))
+            }),
+            linear_phase_sharpness: EnumParam::new("LP Sharpness", LinearPhaseSharpness::Sharp)
+                .with_callback({
+                    let should_update_filters = should_update_filters.clone();
+
+                    Arc::new(move |_| should_update_filters.store(true, Ordering::Relaxed))
+                }),
+            linear_phase_window: EnumParam::new("LP Window", LinearPhaseWindow::Auto)
+                .with_callback({
+                    let should_update_filters = should_update_filters.clone();
+
+                    Arc::new(move |_| should_update_filters.store(true, Ordering::Relaxed))
+                }),
+            linear_phase_kaiser_beta: FloatParam::new(
+                "LP Kaiser Beta",
+                8.6,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 20.0,
+                },
+            )
+            .with_step_size(0.1)
+            .with_callback({
+                let should_update_filters = should_update_filters.clone();
+
+                Arc::new(move |_| should_update_filters.store(true, Ordering::Relaxed))
+            }),
+
+            freeze: BoolParam::new("Freeze", false),
+            audio_rate_modulation: BoolParam::new("Audio-Rate Modulation", false),
+
+            bands: std::array::from_fn(|band_idx| BandParams::new(band_idx + 1)),
+        }
+    }
+
+    /// The crossover frequency parameters for each channel, indexed by
+    /// `[channel_idx][crossover_idx]`.
+    fn crossover_freqs(&self) -> [[&FloatParam; NUM_BANDS - 1]; NUM_CHANNELS as usize] {
+        self.crossovers.both().map(CrossoverFreqParams::freqs)
+    }
+
+    /// The current smoothed crossover frequencies for both channels, without advancing the
+    /// smoothers. When the channels are stereo linked the right channel uses the left channel's
+    /// frequencies.
+    fn crossover_freqs_snapshot(&self, stereo_link: bool) -> ChannelFrequencies {
+        let [left_frequencies, right_frequencies] = self
+            .crossovers
+            .both()
+            .map(|params| params.snapshot().freqs());
+        if stereo_link {
+            [left_frequencies, left_frequencies]
+        } else {
+            [left_frequencies, right_frequencies]
+        }
+    }
+
+    /// Pairs every crossover frequency parameter with whether it's currently in use. The
+    /// crossovers for unused bands and the right channel's crossovers while the channels are
+    /// linked are inactive.
+    fn crossover_freqs_active(
+        &self,
+        num_bands: usize,
+        stereo_link: bool,
+    ) -> impl Iterator<Item = (&FloatParam, bool)> {
+        self.crossover_freqs()
+            .into_iter()
+            .enumerate()
+            .flat_map(move |(channel_idx, params)| {
+                params
+                    .into_iter()
+                    .enumerate()
+                    .map(move |(crossover_idx, param)| {
+                        let active =
+                            crossover_idx + 1 < num_bands && (channel_idx == 0 || !stereo_link);
+
+                        (param, active)
+                    })
+            })
+    }
+}
+
+impl CrossoverFreqParams {
+    /// The crossover frequency parameters in order.
+    fn freqs(&self) -> [&FloatParam; NUM_BANDS - 1] {
+        [
+            &self.crossover_1_freq,
+            &self.crossover_2_freq,
+            &self.crossover_3_freq,
+            &self.crossover_4_freq,
+        ]
+    }
+}
+
+impl CrossoverFreqParamsSnapshot {
+    /// The crossover frequencies in order.
+    fn freqs(&self) -> [f32; NUM_BANDS - 1] {
+        [
+            self.crossover_1_freq,
+            self.crossover_2_freq,
+            self.crossover_3_freq,
+            self.crossover_4_freq,
+        ]
+    }
+}
+
+impl BandParams {
+    fn new(band_number: usize) -> Self {
+        Self {
+            gain: FloatParam::new(
+                format!("Band {band_number} Gain"),
+                util::db_to_gain(0.0),
+                FloatRange::Skewed {
+                    min: util::db_to_gain(-30.0),
+                    max: util::db_to_gain(30.0),
+                    factor: FloatRange::gain_skew_factor(-30.0, 30.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(50.0))
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_gain_to_db(2))
+            .with_string_to_value(formatters::s2v_f32_gain_to_db()),
+            mute: BoolParam::new(format!("Band {band_number} Mute"), false),
+            solo: BoolParam::new(format!("Band {band_number} Solo"), false),
+            invert: BoolParam::new(format!("Band {band_number} Invert"), false),
         }
     }
 }
@@ -152,8 +482,16 @@ impl Default for Crossover {
             },
 
             iir_crossover: IirCrossover::new(IirCrossoverType::LinkwitzRiley24),
-            fir_crossover: FirCrossover::new(FirCrossoverType::LinkwitzRiley24LinearPhase),
+            fir_crossovers: std::array::from_fn(|_| {
+                FirCrossover::new(FirCrossoverType::LinkwitzRiley24LinearPhase)
+            }),
             should_update_filters,
+            active_num_bands: NUM_BANDS,
+
+            held_outputs: [[0.0; NUM_CHANNELS as usize]; NUM_BANDS],
+            frozen: false,
+            freeze_fade_samples_remaining: 0,
+            unfreeze_crossfade_samples_remaining: 0,
         }
     }
 }
@@ -201,14 +539,20 @@ impl Plugin for Crossover {
         self.buffer_config = *buffer_config;
 
         // Make sure the filter states match the current parameters
-        self.update_filters(1);
+        let stereo_link = self.params.crossovers.link.value();
+        self.update_filters(1, stereo_link);
 
-        // The FIR filters are linear-phase and introduce latency
-        match self.params.crossover_type.value() {
-            CrossoverType::LinkwitzRiley24 => (),
-            CrossoverType::LinkwitzRiley24LinearPhase => {
-                context.set_latency_samples(self.fir_crossover.latency())
-            }
+        // The latency is also reported when switching back to the IIR filters, since the host may
+        // still have the old FIR latency
+        context.set_latency_samples(self.latency_samples(self.params.crossover_type.value()));
+
+        // Only the outputs for the bands that are actually in use are exposed to the host, and the
+        // crossover frequencies for the unused bands are greyed out
+        let num_bands = self.params.num_bands.value() as usize;
+        self.active_num_bands = num_bands;
+        context.set_active_aux_output_ports(num_bands);
+        for (param, active) in self.params.crossover_freqs_active(num_bands, stereo_link) {
+            context.set_param_active(param, active);
         }
 
         true
@@ -216,7 +560,13 @@ impl Plugin for Crossover {
 
     fn reset(&mut self) {
         self.iir_crossover.reset();
-        self.fir_crossover.reset();
+        for fir_crossover in &mut self.fir_crossovers {
+            fir_crossover.reset();
+        }
+
+        self.held_outputs = [[0.0; NUM_CHANNELS as usize]; NUM_BANDS];
+        self.freeze_fade_samples_remaining = 0;
+        self.unfreeze_crossfade_samples_remaining = 0;
     }
 
     fn process(
@@ -225,30 +575,112 @@ impl Plugin for Crossover {
         aux: &mut AuxiliaryBuffers,
         context: &mut impl ProcessContext<Self>,
     ) -> ProcessStatus {
-        // Right now both crossover types only do 24 dB/octave Linkwitz-Riley style crossovers
-        match self.params.crossover_type.value() {
-            CrossoverType::LinkwitzRiley24 => {
-                context.set_latency_samples(0);
+        // The snapshots contain the parameter values at the start of the block, so the discrete
+        // parameters stay the same for the entire block. The crossover frequencies are smoothed, so
+        // the filters still advance the smoothers themselves. Their snapshot is where the ramp
+        // starts for filters that haven't been configured yet.
+        let params = self.params.snapshot();
+        let num_bands = params.num_bands as usize;
+        // The stereo link toggle is part of the nested crossover parameters, so it's not included
+        // in the main snapshot. It's read once here so the entire block uses the same value.
+        let stereo_link = self.params.crossovers.link.value();
+        let crossover_freqs = self.params.crossover_freqs_snapshot(stereo_link);
 
-                self.process_iir(buffer, aux);
-            }
-            CrossoverType::LinkwitzRiley24LinearPhase => {
-                context.set_latency_samples(self.fir_crossover.latency());
+        // These only notify the host when the number of bands actually changes
+        context.set_active_aux_output_ports(num_bands);
+        for (param, active) in self.params.crossover_freqs_active(num_bands, stereo_link) {
+            context.set_param_active(param, active);
+        }
 
-                self.process_fir(buffer, aux);
+        // While frozen the crossovers are not touched at all so their state is kept intact
+        if params.freeze {
+            if !self.frozen {
+                self.freeze_fade_samples_remaining = self.freeze_fade_length() as u32;
             }
+
+            self.process_frozen(buffer, aux);
+            self.apply_band_controls(aux, num_bands);
+            self.frozen = true;
+
+            return ProcessStatus::Normal;
+        } else if self.frozen {
+            self.frozen = false;
+            self.unfreeze_crossfade_samples_remaining = self.unfreeze_crossfade_length() as u32;
         }
 
+        match params.crossover_type.filter_type() {
+            FilterType::Iir(_) => self.process_iir(
+                buffer,
+                aux,
+                num_bands,
+                stereo_link,
+                params.audio_rate_modulation.then_some(crossover_freqs),
+            ),
+            FilterType::Fir(_) => self.process_fir(buffer, aux, num_bands, stereo_link),
+        }
+
+        // The FIR latency depends on the sharpness, which may have just been changed when updating
+        // the filters
+        context.set_latency_samples(self.latency_samples(params.crossover_type));
+
+        if self.unfreeze_crossfade_samples_remaining > 0 {
+            self.crossfade_from_held_outputs(aux);
+        }
+        self.store_held_outputs(aux);
+        self.apply_band_controls(aux, num_bands);
+
         ProcessStatus::Normal
     }
+
+    fn tail_length_samples(&self) -> Option<u32> {
+        // The IIR filters' tails are short enough to be ignored, but the linear-phase filters delay
+        // the signal
+        match self.params.crossover_type.value().filter_type() {
+            FilterType::Iir(_) => None,
+            FilterType::Fir(_) => Some(self.fir_crossovers[0].tail_length()),
+        }
+    }
 }
 
 impl Crossover {
+    /// The latency for `crossover_type`. Only the linear-phase FIR filters introduce latency, and
+    /// that latency depends on the filters' current sharpness.
+    fn latency_samples(&self, crossover_type: CrossoverType) -> u32 {
+        match crossover_type.filter_type() {
+            FilterType::Iir(_) => 0,
+            FilterType::Fir(_) => self.fir_crossovers[0].latency(),
+        }
+    }
+
     /// Takes care of all of the boilerplate in zipping the outputs together to get a nice iterator
     /// friendly and SIMD-able interface for the processing function. Prevents having to branch per
     /// sample. The closure receives an input sample and it should write the output samples for each
     /// band to the array.
-    fn process_iir(&mut self, buffer: &mut Buffer, aux: &mut AuxiliaryBuffers) {
+    ///
+    /// With audio rate modulation enabled the crossover frequencies are ramped from their current
+    /// values to the new values over the course of the block, and the filters are updated every
+    /// sample. In that case `audio_rate_modulation` contains the snapshotted frequencies from the
+    /// start of the block.
+    fn process_iir(
+        &mut self,
+        buffer: &mut Buffer,
+        aux: &mut AuxiliaryBuffers,
+        num_bands: usize,
+        stereo_link: bool,
+        audio_rate_modulation: Option<ChannelFrequencies>,
+    ) {
+        let mut frequency_ramp = audio_rate_modulation.map(|start_frequencies| {
+            self.crossover_frequency_ramp(
+                num_bands,
+                stereo_link,
+                start_frequencies,
+                buffer.samples(),
+            )
+        });
+        // Resetting the IIR filters doesn't touch their coefficients, so this can be done before
+        // updating them
+        self.reset_activated_bands(num_bands);
+
         let aux_outputs = &mut aux.outputs;
         let (band_1_buffer, aux_outputs) = aux_outputs.split_first_mut().unwrap();
         let (band_2_buffer, aux_outputs) = aux_outputs.split_first_mut().unwrap();
@@ -284,31 +716,52 @@ impl Crossover {
             ];
 
             // Only update the filters when needed
-            if self.should_update_filters() {
-                self.update_filters(1);
-            }
-
-            self.iir_crossover.process(
-                self.params.num_bands.value() as usize,
-                &main_channel_samples,
-                bands,
-            );
+            match &mut frequency_ramp {
+                Some((frequencies, step_factors)) => {
+                    for (frequency, step_factor) in frequencies
+                        .iter_mut()
+                        .flatten()
+                        .zip(step_factors.iter().flatten())
+                    {
+                        *frequency *= step_factor;
+                    }
 
-            // The main output should be silent as the signal is already evenly split over the other
-            // bands
-            for sample in main_channel_samples {
-                *sample = 0.0;
+                    self.iir_crossover.update_modulated(
+                        self.buffer_config.sample_rate,
+                        num_bands,
+                        *frequencies,
+                    );
+                }
+                None => {
+                    if self.should_update_filters() {
+                        self.update_filters(1, stereo_link);
+                    }
+                }
             }
+
+            self.iir_crossover
+                .process(num_bands, &main_channel_samples, bands);
         }
+
+        // The main output is only cleared after processing the entire block since it's also the
+        // crossover's input
+        silence_main_output(buffer);
     }
 
     /// `process_iir()`, but for the linear-phase FIR crossovers. This processes an entire channel
     /// at once instead of processing per-sample since we use FFT convolution.
-    fn process_fir(&mut self, buffer: &mut Buffer, aux: &mut AuxiliaryBuffers) {
+    fn process_fir(
+        &mut self,
+        buffer: &mut Buffer,
+        aux: &mut AuxiliaryBuffers,
+        num_bands: usize,
+        stereo_link: bool,
+    ) {
         // In theory we could do smoothing in between processed blocks, but this hsould be fine
         if self.should_update_filters() {
-            self.update_filters(buffer.samples() as u32);
+            self.update_filters(buffer.samples() as u32, stereo_link);
         }
+        self.reset_activated_bands(num_bands);
 
         let aux_outputs = &mut aux.outputs;
         let (band_1_buffer, aux_outputs) = aux_outputs.split_first_mut().unwrap();
@@ -328,16 +781,138 @@ impl Crossover {
                 &mut band_5_buffer.as_slice()[channel_idx],
             ];
 
-            self.fir_crossover.process(
-                self.params.num_bands.value() as usize,
-                main_io,
-                band_outputs,
-                channel_idx,
-            );
+            self.fir_crossovers[channel_idx].process(num_bands, main_io, band_outputs, channel_idx);
+        }
+
+        silence_main_output(buffer);
+    }
+
+    /// Reset the filter state for the bands that were not active in the previous block. Those bands
+    /// may still contain state from when they were last used. The bands that were already active
+    /// are left alone so they don't jump. With the FIR crossover this must be called after
+    /// updating the filters for the new number of bands.
+    fn reset_activated_bands(&mut self, num_bands: usize) {
+        for band_idx in self.active_num_bands..num_bands {
+            self.iir_crossover.reset_band(band_idx);
+            for fir_crossover in &mut self.fir_crossovers {
+                fir_crossover.reset_band(band_idx);
+            }
+        }
+
+        self.active_num_bands = num_bands;
+    }
+
+    /// Output the held output samples for each band while the crossover is frozen. These are faded
+    /// out to silence, and the fade continues in the next block if it doesn't fit in this one.
+    fn process_frozen(&mut self, buffer: &mut Buffer, aux: &mut AuxiliaryBuffers) {
+        let samples_remaining = self.freeze_fade_samples_remaining as usize;
+        // The gains are relative to the held outputs at the start of the block
+        let held_gain = |sample_idx: usize| {
+            if samples_remaining == 0 {
+                0.0
+            } else {
+                samples_remaining.saturating_sub(sample_idx) as f32 / samples_remaining as f32
+            }
+        };
+
+        let mut num_samples = 0;
+        for (band_buffer, held_outputs) in aux.outputs.iter_mut().zip(self.held_outputs.iter_mut())
+        {
+            num_samples = band_buffer.samples();
+            for (sample_idx, channel_samples) in band_buffer.iter_samples().enumerate() {
+                let gain = held_gain(sample_idx);
+                for (sample, held_output) in channel_samples.into_iter().zip(held_outputs.iter()) {
+                    *sample = *held_output * gain;
+                }
+            }
+
+            // This way the crossfade after unfreezing starts from the faded out value
+            for held_output in held_outputs {
+                *held_output *= held_gain(num_samples);
+            }
+        }
+
+        self.freeze_fade_samples_remaining = samples_remaining.saturating_sub(num_samples) as u32;
+
+        // The main output should be silent, just like when the crossover isn't frozen
+        silence_main_output(buffer);
+    }
+
+    /// The length of the fade to silence after freezing, in samples.
+    fn freeze_fade_length(&self) -> f32 {
+        (FREEZE_FADE_MS / 1000.0 * self.buffer_config.sample_rate).round()
+    }
+
+    /// The length of the crossfade after unfreezing, in samples.
+    fn unfreeze_crossfade_length(&self) -> f32 {
+        (UNFREEZE_CROSSFADE_MS / 1000.0 * self.buffer_config.sample_rate).round()
+    }
+
+    /// Fade from the held output samples to the crossover's outputs after unfreezing. The fade
+    /// continues in the next block if it doesn't fit in this one.
+    fn crossfade_from_held_outputs(&mut self, aux: &mut AuxiliaryBuffers) {
+        let crossfade_length = self.unfreeze_crossfade_length();
+        let samples_remaining = self.unfreeze_crossfade_samples_remaining as usize;
+
+        let mut num_samples = 0;
+        for (band_buffer, held_outputs) in aux.outputs.iter_mut().zip(self.held_outputs.iter()) {
+            num_samples = band_buffer.samples();
+            for (sample_idx, channel_samples) in band_buffer
+                .iter_samples()
+                .enumerate()
+                .take(samples_remaining)
+            {
+                let held_gain = (samples_remaining - sample_idx) as f32 / crossfade_length;
+                for (sample, held_output) in channel_samples.into_iter().zip(held_outputs) {
+                    *sample = (*sample * (1.0 - held_gain)) + (*held_output * held_gain);
+                }
+            }
+        }
+
+        self.unfreeze_crossfade_samples_remaining =
+            samples_remaining.saturating_sub(num_samples) as u32;
+    }
+
+    /// Store each band's last output sample so they can be held when the crossover gets frozen.
+    fn store_held_outputs(&mut self, aux: &mut AuxiliaryBuffers) {
+        for (band_buffer, held_outputs) in aux.outputs.iter_mut().zip(self.held_outputs.iter_mut())
+        {
+            for (channel, held_output) in band_buffer.as_slice().iter().zip(held_outputs) {
+                if let Some(last_sample) = channel.last() {
+                    *held_output = *last_sample;
+                }
+            }
+        }
+    }
+
+    /// Apply the per-band gain, mute, solo, and polarity controls to the bands' outputs. This
+    /// happens after the held outputs have been stored, so freezing holds the crossover's own
+    /// outputs and the controls still work while frozen. The main output is left silent.
+    fn apply_band_controls(&self, aux: &mut AuxiliaryBuffers, num_bands: usize) {
+        let bands = &self.params.bands;
+        let audible = audible_bands(
+            num_bands,
+            std::array::from_fn(|band_idx| bands[band_idx].mute.value()),
+            std::array::from_fn(|band_idx| bands[band_idx].solo.value()),
+        );
 
-            // The main output should be silent as the signal is already evenly split over the other
-            // bands
-            main_io.fill(0.0);
+        for ((band_buffer, band_params), audible) in
+            aux.outputs.iter_mut().zip(bands.iter()).zip(audible)
+        {
+            let polarity = if band_params.invert.value() {
+                -1.0
+            } else {
+                1.0
+            };
+
+            // The gain is still smoothed for silent bands so it doesn't jump when they're unmuted
+            for channel_samples in band_buffer.iter_samples() {
+                let gain = band_params.gain.smoothed.next() * polarity;
+                let gain = if audible { gain } else { 0.0 };
+                for sample in channel_samples {
+                    *sample *= gain;
+                }
+            }
         }
     }
 
@@ -347,36 +922,161 @@ impl Crossover {
         // Technically this would only require a &self since `should_update_filters` has interior
         // mutability, but with the current setup this doesn't cause any problems and makes the
         // former a bit more obvious
+        let params = &self.params;
         self.should_update_filters
             .compare_exchange(true, false, Ordering::Relaxed, Ordering::Relaxed)
             .is_ok()
-            || self.params.crossover_1_freq.smoothed.is_smoothing()
-            || self.params.crossover_2_freq.smoothed.is_smoothing()
-            || self.params.crossover_3_freq.smoothed.is_smoothing()
-            || self.params.crossover_4_freq.smoothed.is_smoothing()
+            || params
+                .crossover_freqs()
+                .iter()
+                .flatten()
+                .any(|param| param.smoothed_unmodulated.is_smoothing())
+    }
+
+    /// Compute the starting frequencies and the per-sample multiplication factors for ramping the
+    /// IIR crossover's frequencies to the current (smoothed and modulated) parameter values over
+    /// `block_len` samples. The frequencies are ramped exponentially since that matches how
+    /// frequencies are perceived. This advances the parameter smoothers to the end of the block.
+    /// `start_frequencies` are the frequencies at the start of the block, which are used when the
+    /// filters haven't been configured yet.
+    fn crossover_frequency_ramp(
+        &mut self,
+        num_bands: usize,
+        stereo_link: bool,
+        start_frequencies: ChannelFrequencies,
+        block_len: usize,
+    ) -> (ChannelFrequencies, ChannelFrequencies) {
+        let target_frequencies = self.next_crossover_frequencies(block_len as u32, stereo_link);
+
+        // Changing the number of bands or the crossover type still requires all filters to be
+        // updated. The ramp then starts from the frequencies the filters are currently set to. The
+        // IIR filters have not been configured yet after initializing or switching from the FIR
+        // crossover, in which case the ramp starts at the frequencies from the start of the block
+        // instead.
+        if self.should_update_filters() {
+            if let FilterType::Iir(mode) = self.params.crossover_type.value().filter_type() {
+                self.iir_crossover.set_mode(mode);
+            }
+
+            let current_frequencies = self.iir_crossover.frequencies();
+            let frequencies = if current_frequencies
+                .iter()
+                .flatten()
+                .any(|frequency| *frequency <= 0.0)
+            {
+                start_frequencies
+            } else {
+                current_frequencies
+            };
+            self.iir_crossover
+                .update(self.buffer_config.sample_rate, num_bands, frequencies);
+        }
+
+        let mut frequencies = self.iir_crossover.frequencies();
+        let mut step_factors = [[1.0; NUM_BANDS - 1]; NUM_CHANNELS as usize];
+        for ((frequency, target_frequency), step_factor) in frequencies
+            .iter_mut()
+            .flatten()
+            .zip(target_frequencies.into_iter().flatten())
+            .zip(step_factors.iter_mut().flatten())
+        {
+            // The filters may not have been configured yet. Tiny differences caused by rounding
+            // errors in the previous ramp are ignored so the coefficients are not recomputed every
+            // sample while the frequency stays the same.
+            let ratio = target_frequency / *frequency;
+            if *frequency <= 0.0 {
+                *frequency = target_frequency;
+            } else if block_len > 0 && (ratio - 1.0).abs() > 1e-5 {
+                *step_factor = ratio.powf((block_len as f32).recip());
+            }
+        }
+
+        (frequencies, step_factors)
+    }
+
+    /// The next crossover frequencies for each channel after advancing the parameters' smoothers by
+    /// `step_size` samples. When the channels are stereo linked the right channel uses the left
+    /// channel's frequencies.
+    fn next_crossover_frequencies(&self, step_size: u32, stereo_link: bool) -> ChannelFrequencies {
+        // Automation is smoothed, but the host's modulation is applied on top of that as is. The
+        // right channel's smoothers are always advanced so they don't jump when unlinking.
+        let mut crossover_frequencies = self
+            .params
+            .crossover_freqs()
+            .map(|params| params.map(|param| param.smoothed_modulated_value_step(step_size)));
+        if stereo_link {
+            crossover_frequencies[1] = crossover_frequencies[0];
+        }
+
+        crossover_frequencies
     }
 
     /// Update the filter coefficients for the crossovers. The step size can be used when the filter
     /// coefficietns aren't updated every sample.
-    fn update_filters(&mut self, step_size: u32) {
-        let crossover_frequencies = [
-            self.params.crossover_1_freq.smoothed.next_step(step_size),
-            self.params.crossover_2_freq.smoothed.next_step(step_size),
-            self.params.crossover_3_freq.smoothed.next_step(step_size),
-            self.params.crossover_4_freq.smoothed.next_step(step_size),
-        ];
-
-        match self.params.crossover_type.value() {
-            CrossoverType::LinkwitzRiley24 => self.iir_crossover.update(
-                self.buffer_config.sample_rate,
-                self.params.num_bands.value() as usize,
-                crossover_frequencies,
-            ),
-            CrossoverType::LinkwitzRiley24LinearPhase => self.fir_crossover.update(
-                self.buffer_config.sample_rate,
-                self.params.num_bands.value() as usize,
-                crossover_frequencies,
-            ),
+    fn update_filters(&mut self, step_size: u32, stereo_link: bool) {
+        let crossover_frequencies = self.next_crossover_frequencies(step_size, stereo_link);
+
+        match self.params.crossover_type.value().filter_type() {
+            FilterType::Iir(mode) => {
+                self.iir_crossover.set_mode(mode);
+                self.iir_crossover.update(
+                    self.buffer_config.sample_rate,
+                    self.params.num_bands.value() as usize,
+                    crossover_frequencies,
+                );
+            }
+            FilterType::Fir(mode) => {
+                let sharpness = self.fir_sharpness();
+                let window = self.fir_window();
+                let [left_crossover, right_crossover] = &mut self.fir_crossovers;
+                let [left_frequencies, right_frequencies] = crossover_frequencies;
+
+                left_crossover.set_mode(mode);
+                left_crossover.update(
+                    self.buffer_config.sample_rate,
+                    self.params.num_bands.value() as usize,
+                    left_frequencies,
+                    sharpness,
+                    window,
+                );
+
+                // Designing the filters is expensive, so while the channels are linked the right
+                // channel reuses the left channel's filters
+                if stereo_link {
+                    right_crossover.update_from(left_crossover);
+                } else {
+                    right_crossover.set_mode(mode);
+                    right_crossover.update(
+                        self.buffer_config.sample_rate,
+                        self.params.num_bands.value() as usize,
+                        right_frequencies,
+                        sharpness,
+                        window,
+                    );
+                }
+            }
+        }
+    }
+
+    /// The sharpness for the linear-phase crossover based on the current parameter values.
+    fn fir_sharpness(&self) -> FirSharpness {
+        match self.params.linear_phase_sharpness.value() {
+            LinearPhaseSharpness::Sharp => FirSharpness::Sharp,
+            LinearPhaseSharpness::Balanced => FirSharpness::Balanced,
+            LinearPhaseSharpness::LowPreRinging => FirSharpness::LowPreRinging,
+        }
+    }
+
+    /// The window function for the linear-phase crossover based on the current parameter values.
+    fn fir_window(&self) -> FirWindow {
+        match self.params.linear_phase_window.value() {
+            LinearPhaseWindow::Auto => self.fir_sharpness().default_window(),
+            LinearPhaseWindow::Blackman => FirWindow::Blackman,
+            LinearPhaseWindow::BlackmanHarris => FirWindow::BlackmanHarris,
+            LinearPhaseWindow::Hann => FirWindow::Hann,
+            LinearPhaseWindow::Kaiser => FirWindow::Kaiser {
+                beta: self.params.linear_phase_kaiser_beta.value(),
+            },
         }
     }
 }
@@ -398,13 +1098,21 @@ impl ClapPlugin for Crossover {
             section.add_page("Main", |page| {
                 page.add_param(&self.params.num_bands);
                 page.add_param(&self.params.crossover_type);
+                page.add_param(&self.params.freeze);
                 page.add_spacer();
+                for param in self.params.crossovers.first.freqs() {
+                    page.add_param(param);
+                }
+            });
+            section.add_page("Right Channel", |page| {
+                page.add_param(&self.params.crossovers.link);
                 page.add_spacer();
-                page.add_param(&self.params.crossover_1_freq);
-                page.add_param(&self.params.crossover_2_freq);
-                page.add_param(&self.params.crossover_3_freq);
-                page.add_param(&self.params.crossover_4_freq);
-            })
+                page.add_spacer();
+                page.add_spacer();
+                for param in self.params.crossovers.second.freqs() {
+                    page.add_param(param);
+                }
+            });
         })
     }
 }
@@ -417,3 +1125,159 @@ impl Vst3Plugin for Crossover {
 
 nih_export_clap!(Crossover);
 nih_export_vst3!(Crossover);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crossover::test_util::{
+        band_buffers, process_iir, set_buffer_slices, silent_band_channels, test_signal,
+    };
+
+    const SAMPLE_RATE: f32 = 44_100.0;
+    const NUM_SAMPLES: usize = 512;
+
+    #[test]
+    fn process_iir_silences_main_output() {
+        let mut crossover = Crossover::default();
+        crossover.buffer_config.sample_rate = SAMPLE_RATE;
+        crossover.update_filters(1, true);
+        let num_bands = crossover.params.num_bands.value() as usize;
+
+        let signal = test_signal(NUM_SAMPLES);
+        let mut main_channels = vec![signal.clone(); NUM_CHANNELS as usize];
+        let mut band_channels = silent_band_channels(NUM_SAMPLES);
+        {
+            let mut main_buffer = Buffer::default();
+            set_buffer_slices(&mut main_buffer, &mut main_channels);
+            let mut band_buffers = band_buffers(&mut band_channels);
+
+            let mut aux = AuxiliaryBuffers {
+                inputs: &mut [],
+                outputs: &mut band_buffers,
+            };
+            crossover.process_iir(&mut main_buffer, &mut aux, num_bands, true, None);
+        }
+
+        // The bands should be the same as when processing the signal with the crossover directly
+        let mut reference = IirCrossover::new(IirCrossoverType::LinkwitzRiley24);
+        reference.update(
+            SAMPLE_RATE,
+            num_bands,
+            crossover.iir_crossover.frequencies(),
+        );
+        let reference_band_channels = process_iir(&mut reference, num_bands, &signal);
+
+        assert_eq!(band_channels, reference_band_channels);
+        for channel in main_channels {
+            assert!(channel.iter().all(|sample| *sample == 0.0));
+        }
+    }
+
+    #[test]
+    fn frozen_outputs_fade_to_silence() {
+        let mut crossover = Crossover::default();
+        crossover.buffer_config.sample_rate = SAMPLE_RATE;
+        crossover.held_outputs = [[0.5, -0.25]; NUM_BANDS];
+        crossover.freeze_fade_samples_remaining = crossover.freeze_fade_length() as u32;
+
+        // The fade takes multiple blocks, and it should continue where the previous block left off
+        let mut previous_magnitudes = crossover.held_outputs.map(|band| band.map(f32::abs));
+        let num_blocks = (crossover.freeze_fade_length() as usize).div_ceil(NUM_SAMPLES) + 1;
+        for block_idx in 0..num_blocks {
+            let mut main_channels = vec![vec![0.0; NUM_SAMPLES]; NUM_CHANNELS as usize];
+            let mut band_channels = silent_band_channels(NUM_SAMPLES);
+            {
+                let mut main_buffer = Buffer::default();
+                set_buffer_slices(&mut main_buffer, &mut main_channels);
+                let mut band_buffers = band_buffers(&mut band_channels);
+
+                let mut aux = AuxiliaryBuffers {
+                    inputs: &mut [],
+                    outputs: &mut band_buffers,
+                };
+                crossover.process_frozen(&mut main_buffer, &mut aux);
+            }
+
+            for (channels, previous_magnitudes) in
+                band_channels.iter().zip(&mut previous_magnitudes)
+            {
+                for (channel, previous_magnitude) in channels.iter().zip(previous_magnitudes) {
+                    for sample in channel {
+                        assert!(sample.abs() <= *previous_magnitude);
+                        *previous_magnitude = sample.abs();
+                    }
+                }
+            }
+
+            // After the fade the bands should not output any DC offset
+            if block_idx == num_blocks - 1 {
+                for channel in band_channels.iter().flatten() {
+                    assert!(channel.iter().all(|sample| *sample == 0.0));
+                }
+            }
+        }
+
+        assert_eq!(crossover.freeze_fade_samples_remaining, 0);
+    }
+
+    #[test]
+    fn latency_follows_crossover_type() {
+        let mut crossover = Crossover::default();
+        crossover.buffer_config.sample_rate = SAMPLE_RATE;
+        assert!(crossover.params.crossover_type.set_from_id("lr24-lp"));
+        crossover.update_filters(1, true);
+
+        let sharp_latency = crossover.latency_samples(CrossoverType::LinkwitzRiley24LinearPhase);
+        assert!(sharp_latency > 0);
+        assert_eq!(crossover.latency_samples(CrossoverType::LinkwitzRiley24), 0);
+
+        // Switching back to the IIR filters must clear the FIR latency regardless of the sharpness
+        assert!(crossover
+            .params
+            .linear_phase_sharpness
+            .set_from_id("low-pre-ringing"));
+        crossover.update_filters(1, true);
+        let low_pre_ringing_latency =
+            crossover.latency_samples(CrossoverType::LinkwitzRiley24LinearPhase);
+        assert!(low_pre_ringing_latency > 0 && low_pre_ringing_latency < sharp_latency);
+        assert_eq!(crossover.latency_samples(CrossoverType::LinkwitzRiley24), 0);
+    }
+
+    #[test]
+    fn solos_and_mutes() {
+        let mut mutes = [false; NUM_BANDS];
+        let mut solos = [false; NUM_BANDS];
+        assert_eq!(
+            audible_bands(3, mutes, solos),
+            [true, true, true, false, false]
+        );
+
+        mutes[0] = true;
+        assert_eq!(
+            audible_bands(3, mutes, solos),
+            [false, true, true, false, false]
+        );
+
+        solos[1] = true;
+        solos[2] = true;
+        assert_eq!(
+            audible_bands(3, mutes, solos),
+            [false, true, true, false, false]
+        );
+
+        // Muting a band silences it even when it's soloed
+        solos[0] = true;
+        mutes[2] = true;
+        assert_eq!(
+            audible_bands(3, mutes, solos),
+            [false, true, false, false, false]
+        );
+
+        // Soloing a band that isn't in use shouldn't silence the other bands
+        let solos = [false, false, false, true, false];
+        assert_eq!(
+            audible_bands(3, mutes, solos),
+            [false, true, false, false, false]
+        );
+    }
+}
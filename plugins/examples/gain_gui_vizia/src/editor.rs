@@ -1,9 +1,9 @@
 use atomic_float::AtomicF32;
 use nih_plug::prelude::{util, Editor};
 use nih_plug_vizia::vizia::prelude::*;
+use nih_plug_vizia::widgets::util::atomic_value_lens;
 use nih_plug_vizia::widgets::*;
 use nih_plug_vizia::{assets, create_vizia_editor, ViziaState, ViziaTheming};
-use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -19,7 +19,7 @@ impl Model for Data {}
 
 // Makes sense to also define this here, makes it a bit easier to keep track of
 pub(crate) fn default_state() -> Arc<ViziaState> {
-    ViziaState::new(|| (200, 150))
+    ViziaState::new(|| (200, 170))
 }
 
 pub(crate) fn create(
@@ -51,12 +51,17 @@ pub(crate) fn create(
 
             PeakMeter::new(
                 cx,
-                Data::peak_meter
-                    .map(|peak_meter| util::gain_to_db(peak_meter.load(Ordering::Relaxed))),
+                atomic_value_lens(Data::peak_meter, util::gain_to_db),
                 Some(Duration::from_millis(600)),
             )
             // This is how adding padding works in vizia
             .top(Pixels(10.0));
+            Label::new(
+                cx,
+                atomic_value_lens(Data::peak_meter, |peak_meter| {
+                    format!("{:.1} dBFS", util::gain_to_db(peak_meter))
+                }),
+            );
         })
         .row_between(Pixels(0.0))
         .child_left(Stretch(1.0))
@@ -25,6 +25,12 @@ pub struct AudioIOLayout {
     /// The plugin's additional outputs, if it has any. Use the [`new_nonzero_u32()`] function to
     /// construct these values until const `Option::unwrap()` gets stabilized
     /// (<https://github.com/rust-lang/rust/issues/67441>).
+    ///
+    /// Every entry is exposed to the host as a separate output port with its own channel count and
+    /// name, after the main output port. For CLAP these are separate audio ports, and for VST3
+    /// these are separate auxiliary audio buses. The names are taken from
+    /// [`PortNames::aux_outputs`]. The plugin can hide the trailing ports using
+    /// [`InitContext::set_active_aux_output_ports()`][crate::prelude::InitContext::set_active_aux_output_ports()].
     pub aux_output_ports: &'static [NonZeroU32],
 
     /// Optional names for the audio ports. Defining these can be useful for plugins with multiple
@@ -74,6 +80,39 @@ pub struct PortNames {
     pub aux_outputs: &'static [&'static str],
 }
 
+/// A description of one of the audio ports in an [`AudioIOLayout`]. Returned by
+/// [`AudioIOLayout::buses()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BusInfo {
+    /// The port's name. Either generated or taken from the layout's [`PortNames`].
+    pub name: String,
+    /// Whether this is an input or an output port.
+    pub direction: BusDirection,
+    /// Whether this is the main port or an auxiliary port.
+    pub role: BusRole,
+    /// The number of channels in this port.
+    pub num_channels: u32,
+}
+
+/// Whether a port described by a [`BusInfo`] receives or produces audio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BusDirection {
+    /// The port receives audio from the host.
+    Input,
+    /// The port sends audio to the host.
+    Output,
+}
+
+/// The role of a port described by a [`BusInfo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BusRole {
+    /// The main input or output port.
+    Main,
+    /// An auxiliary port. For inputs these are sidechain inputs, and for outputs these are the
+    /// additional outputs defined in [`AudioIOLayout::aux_output_ports`].
+    Auxiliary,
+}
+
 /// Configuration for (the host's) audio buffers.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct BufferConfig {
@@ -157,7 +196,7 @@ impl AudioIOLayout {
 
     /// The name for the main output port. Either generated or taken from the `names` field.
     pub fn main_output_name(&self) -> String {
-        self.names.main_input.unwrap_or("Output").to_owned()
+        self.names.main_output.unwrap_or("Output").to_owned()
     }
 
     /// The name for the auxiliary input port with the given index. Either generated or taken from
@@ -187,6 +226,52 @@ impl AudioIOLayout {
             }
         }
     }
+
+    /// Describe all of the layout's audio ports. This contains the main input port followed by
+    /// the auxiliary input ports, and then the main output port followed by the auxiliary output
+    /// ports. Main ports are omitted if the layout doesn't have them. The ports are listed in the
+    /// same order the plugin APIs expose them to the host.
+    pub fn buses(&self) -> Vec<BusInfo> {
+        let main_input = self.main_input_channels.map(|num_channels| BusInfo {
+            name: self.main_input_name(),
+            direction: BusDirection::Input,
+            role: BusRole::Main,
+            num_channels: num_channels.get(),
+        });
+        let aux_inputs = self
+            .aux_input_ports
+            .iter()
+            .enumerate()
+            .map(|(idx, num_channels)| BusInfo {
+                name: self.aux_input_name(idx).unwrap(),
+                direction: BusDirection::Input,
+                role: BusRole::Auxiliary,
+                num_channels: num_channels.get(),
+            });
+        let main_output = self.main_output_channels.map(|num_channels| BusInfo {
+            name: self.main_output_name(),
+            direction: BusDirection::Output,
+            role: BusRole::Main,
+            num_channels: num_channels.get(),
+        });
+        let aux_outputs = self
+            .aux_output_ports
+            .iter()
+            .enumerate()
+            .map(|(idx, num_channels)| BusInfo {
+                name: self.aux_output_name(idx).unwrap(),
+                direction: BusDirection::Output,
+                role: BusRole::Auxiliary,
+                num_channels: num_channels.get(),
+            });
+
+        main_input
+            .into_iter()
+            .chain(aux_inputs)
+            .chain(main_output)
+            .chain(aux_outputs)
+            .collect()
+    }
 }
 
 impl PortNames {
@@ -202,3 +287,49 @@ impl PortNames {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_all_buses() {
+        const LAYOUT: AudioIOLayout = AudioIOLayout {
+            main_input_channels: NonZeroU32::new(2),
+            main_output_channels: NonZeroU32::new(2),
+            aux_input_ports: &[new_nonzero_u32(1)],
+            aux_output_ports: &[new_nonzero_u32(2); 2],
+            names: PortNames {
+                main_output: Some("The Void"),
+                aux_outputs: &["Band 1"],
+                ..PortNames::const_default()
+            },
+        };
+
+        let buses = LAYOUT.buses();
+        let summary: Vec<_> = buses
+            .iter()
+            .map(|bus| (bus.name.as_str(), bus.direction, bus.role, bus.num_channels))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Input", BusDirection::Input, BusRole::Main, 2),
+                (
+                    "Sidechain Input",
+                    BusDirection::Input,
+                    BusRole::Auxiliary,
+                    1
+                ),
+                ("The Void", BusDirection::Output, BusRole::Main, 2),
+                ("Band 1", BusDirection::Output, BusRole::Auxiliary, 2),
+                (
+                    "Auxiliary Output 2",
+                    BusDirection::Output,
+                    BusRole::Auxiliary,
+                    2
+                ),
+            ]
+        );
+    }
+}
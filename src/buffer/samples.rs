@@ -2,6 +2,8 @@
 
 use std::marker::PhantomData;
 
+#[cfg(feature = "simd")]
+use std::simd::num::SimdFloat;
 #[cfg(feature = "simd")]
 use std::simd::{LaneCount, Simd, SupportedLaneCount};
 
@@ -163,6 +165,101 @@ impl<'slice, 'sample> ChannelSamples<'slice, 'sample> {
             .get_unchecked_mut(self.current_sample)
     }
 
+    /// Compute the sum of all channel values for this sample. Uses SIMD when the `simd` feature is
+    /// enabled and there are at most eight channels.
+    #[inline]
+    pub fn sum(&self) -> f32 {
+        #[cfg(feature = "simd")]
+        match self.len() {
+            0..=2 => return self.to_simd_padded::<2>().reduce_sum(),
+            3..=4 => return self.to_simd_padded::<4>().reduce_sum(),
+            5..=8 => return self.to_simd_padded::<8>().reduce_sum(),
+            _ => (),
+        }
+
+        (0..self.len())
+            .map(|channel_idx| unsafe { self.get_unchecked(channel_idx) })
+            .sum()
+    }
+
+    /// Compute the sum of the squares of all channel values for this sample. This is the
+    /// per-sample energy term used for RMS metering. Uses SIMD when the `simd` feature is enabled
+    /// and there are at most eight channels.
+    #[inline]
+    pub fn sum_squares(&self) -> f32 {
+        #[cfg(feature = "simd")]
+        match self.len() {
+            0..=2 => {
+                let values = self.to_simd_padded::<2>();
+                return (values * values).reduce_sum();
+            }
+            3..=4 => {
+                let values = self.to_simd_padded::<4>();
+                return (values * values).reduce_sum();
+            }
+            5..=8 => {
+                let values = self.to_simd_padded::<8>();
+                return (values * values).reduce_sum();
+            }
+            _ => (),
+        }
+
+        (0..self.len())
+            .map(|channel_idx| {
+                let value = unsafe { self.get_unchecked(channel_idx) };
+                value * value
+            })
+            .sum()
+    }
+
+    /// Compute the largest absolute channel value for this sample, useful for peak metering.
+    /// Returns 0.0 if there are no channels. Uses SIMD when the `simd` feature is enabled and there
+    /// are at most eight channels.
+    #[inline]
+    pub fn max_abs(&self) -> f32 {
+        #[cfg(feature = "simd")]
+        match self.len() {
+            // The zero padding can't affect the result since absolute values are never negative
+            0..=2 => return self.to_simd_padded::<2>().abs().reduce_max(),
+            3..=4 => return self.to_simd_padded::<4>().abs().reduce_max(),
+            5..=8 => return self.to_simd_padded::<8>().abs().reduce_max(),
+            _ => (),
+        }
+
+        (0..self.len()).fold(0.0f32, |max, channel_idx| {
+            max.max(unsafe { self.get_unchecked(channel_idx) }.abs())
+        })
+    }
+
+    /// Read a channel's value for this sample without borrowing it mutably. Useful for scalar
+    /// fallbacks of code that otherwise uses the SIMD conversion functions.
+    ///
+    /// # Safety
+    ///
+    /// `channel_index` must be in the range `0..Self::len()`.
+    #[inline]
+    pub unsafe fn get_unchecked(&self, channel_index: usize) -> f32 {
+        *(*self.buffers)
+            .get_unchecked(channel_index)
+            .get_unchecked(self.current_sample)
+    }
+
+    /// Gather this sample's channel data into a SIMD vector, padding the unused lanes with zeroes.
+    /// This never reads more than `self.len()` channels.
+    #[cfg(feature = "simd")]
+    #[inline]
+    fn to_simd_padded<const LANES: usize>(&self) -> Simd<f32, LANES>
+    where
+        LaneCount<LANES>: SupportedLaneCount,
+    {
+        let mut values = [0.0; LANES];
+        for (channel_idx, value) in values.iter_mut().enumerate().take(self.len()) {
+            *value = unsafe { self.get_unchecked(channel_idx) };
+        }
+
+        Simd::from_array(values)
+    }
+
     /// Get a SIMD vector containing the channel data for this buffer. If `LANES > channels.len()`
     /// then this will be padded with zeroes. If `LANES < channels.len()` then this won't contain
     /// all values.
@@ -248,3 +345,86 @@ impl<'slice, 'sample> ChannelSamples<'slice, 'sample> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::Buffer;
+
+    /// Run `check` on every sample of a buffer with `num_channels` channels filled with a
+    /// deterministic mix of positive and negative values.
+    fn with_test_buffer(num_channels: usize, check: impl Fn(&[f32], f32, f32, f32)) {
+        const NUM_SAMPLES: usize = 16;
+
+        let mut real_buffers: Vec<Vec<f32>> = (0..num_channels)
+            .map(|channel_idx| {
+                (0..NUM_SAMPLES)
+                    .map(|sample_idx| {
+                        let value = ((channel_idx * 7 + sample_idx * 3) % 11) as f32 / 5.0 - 1.0;
+                        if (channel_idx + sample_idx) % 2 == 0 {
+                            value
+                        } else {
+                            -value * 0.5
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let expected_frames: Vec<Vec<f32>> = (0..NUM_SAMPLES)
+            .map(|sample_idx| {
+                real_buffers
+                    .iter()
+                    .map(|channel| channel[sample_idx])
+                    .collect()
+            })
+            .collect();
+
+        let mut buffer = Buffer::default();
+        unsafe {
+            buffer.set_slices(NUM_SAMPLES, |output_slices| {
+                *output_slices = real_buffers
+                    .iter_mut()
+                    .map(|channel| channel.as_mut_slice())
+                    .collect();
+            })
+        };
+
+        for (channel_samples, frame) in buffer.iter_samples().zip(expected_frames.iter()) {
+            check(
+                frame,
+                channel_samples.sum(),
+                channel_samples.sum_squares(),
+                channel_samples.max_abs(),
+            );
+        }
+    }
+
+    fn check_against_scalar(num_channels: usize) {
+        with_test_buffer(num_channels, |frame, sum, sum_squares, max_abs| {
+            approx::assert_relative_eq!(sum, frame.iter().sum::<f32>(), epsilon = 1e-6);
+            approx::assert_relative_eq!(
+                sum_squares,
+                frame.iter().map(|value| value * value).sum::<f32>(),
+                epsilon = 1e-6
+            );
+            assert_eq!(
+                max_abs,
+                frame.iter().fold(0.0f32, |max, value| max.max(value.abs()))
+            );
+        });
+    }
+
+    #[test]
+    fn test_reductions_mono() {
+        check_against_scalar(1);
+    }
+
+    #[test]
+    fn test_reductions_stereo() {
+        check_against_scalar(2);
+    }
+
+    #[test]
+    fn test_reductions_eight_channels() {
+        check_against_scalar(8);
+    }
+}
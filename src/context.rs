@@ -27,3 +27,32 @@ impl Display for PluginApi {
         }
     }
 }
+
+/// The optional features the host negotiated with the plugin. This can be queried through
+/// [`InitContext::host_features()`][init::InitContext::host_features()] and
+/// [`ProcessContext::host_features()`][process::ProcessContext::host_features()] so the plugin can
+/// adapt its behavior to the host instead of assuming that every feature is available. Only
+/// features the host explicitly announces can be detected, so a field being `false` means that the
+/// host did not announce the feature, not necessarily that it won't work.
+///
+/// - **CLAP:** Every field corresponds to one of the CLAP host extensions the host provides.
+/// - **VST3:** These are all communicated through the host's component handler. If the host
+///   provided one by the time the plugin got activated, then latency, audio port, and parameter
+///   info changes are supported. Note names and voice info are CLAP-only features.
+/// - **Standalone:** There is no host, so none of these features are supported.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HostFeatures {
+    /// Whether the host can be notified of latency changes made using `set_latency_samples()`.
+    pub latency_changes: bool,
+    /// Whether the host can be notified of audio port changes made using
+    /// `set_active_aux_output_ports()`.
+    pub audio_port_changes: bool,
+    /// Whether the host can be notified of parameter info changes made using `set_param_active()`.
+    pub param_info_changes: bool,
+    /// Whether the host can be notified of note name changes made using `note_names_changed()`.
+    pub note_name_changes: bool,
+    /// Whether the host can be notified of voice capacity changes made using
+    /// `set_current_voice_capacity()`. Hosts that support polyphonic modulation need this, so
+    /// plugins can skip setting up per-voice modulation state if this is `false`.
+    pub voice_info: bool,
+}
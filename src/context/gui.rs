@@ -3,7 +3,7 @@
 use std::sync::Arc;
 
 use super::PluginApi;
-use crate::prelude::{Param, ParamPtr, Plugin, PluginState};
+use crate::prelude::{BufferConfig, Param, ParamPtr, Plugin, PluginState};
 
 /// Callbacks the plugin can make when the user interacts with its GUI such as updating parameter
 /// values. This is passed to the plugin during [`Editor::spawn()`][crate::prelude::Editor::spawn()]. All of
@@ -18,6 +18,10 @@ pub trait GuiContext: Send + Sync + 'static {
     /// about screen.
     fn plugin_api(&self) -> PluginApi;
 
+    /// Get the plugin's [`Plugin::NAME`][crate::prelude::Plugin::NAME]. The editor integrations use
+    /// this as the default window title.
+    fn plugin_name(&self) -> &'static str;
+
     /// Ask the host to resize the editor window to the size specified by
     /// [`Editor::size()`][crate::prelude::Editor::size()]. This will return false if the host
     /// somehow didn't like this and rejected the resize, in which case the window should revert to
@@ -26,6 +30,25 @@ pub trait GuiContext: Send + Sync + 'static {
     /// TODO: Host->Plugin resizing has not been implemented yet
     fn request_resize(&self) -> bool;
 
+    /// Get the plugin's current buffer configuration, containing the sample rate, the maximum
+    /// buffer size, and the processing mode. This can be used to label frequency axes or to size
+    /// analysis FFTs. Returns `None` if the plugin has not yet been initialized. This always
+    /// returns the most recent configuration, so it reflects sample rate changes made by the host
+    /// while the editor is open.
+    fn buffer_config(&self) -> Option<BufferConfig>;
+
+    /// Get the plugin's smoothed CPU usage as a fraction of the real-time budget. A value of 0.5
+    /// means that the plugin's [`process()`][crate::prelude::Plugin::process()] function took half
+    /// as long as the duration of the audio it processed, and values above 1.0 will cause dropouts.
+    /// Returns `None` unless [`Plugin::MEASURE_CPU_USAGE`][crate::prelude::Plugin::MEASURE_CPU_USAGE]
+    /// is enabled and the plugin has processed audio.
+    fn cpu_usage(&self) -> Option<f32>;
+
+    /// Ask the host to query the plugin's note names again, for instance after the user picked a
+    /// different kit in the editor. See
+    /// [`ClapPlugin::note_names()`][crate::prelude::ClapPlugin::note_names()].
+    fn note_names_changed(&self);
+
     /// Inform the host a parameter will be automated. Create a [`ParamSetter`] and use
     /// [`ParamSetter::begin_set_parameter()`] instead for a safe, user friendly API.
     ///
@@ -63,6 +86,47 @@ pub trait GuiContext: Send + Sync + 'static {
     /// host. If the plugin is currently processing audio, then the parameter values will be
     /// restored at the end of the current processing cycle.
     fn set_state(&self, state: PluginState);
+
+    /// Serialize the plugin's current state to a JSON string. This uses the same format as the
+    /// state stored by the host, but without compression, so it can be copied to the clipboard and
+    /// shared as plain text. Load the string again using
+    /// [`set_state_string()`][Self::set_state_string()].
+    fn get_state_string(&self) -> String {
+        // Serializing a `PluginState` cannot fail since all of its map keys are strings
+        serde_json::to_string_pretty(&self.get_state()).unwrap_or_default()
+    }
+
+    /// Restore the state from a string created by [`get_state_string()`][Self::get_state_string()].
+    /// The string is validated before anything is loaded. This returns an error describing the
+    /// problem if the string does not contain a valid state, or if the state does not contain any
+    /// of this plugin's parameters or persistent fields. Like with
+    /// [`set_state()`][Self::set_state()], parameters in the state that the plugin does not know
+    /// about are ignored.
+    fn set_state_string(&self, state: &str) -> Result<(), String> {
+        let state: PluginState = serde_json::from_str(state.trim())
+            .map_err(|err| format!("Could not parse the state: {err}"))?;
+
+        // This catches states copied from other plugins
+        let current_state = self.get_state();
+        let has_known_params = state
+            .params
+            .keys()
+            .any(|param_id| current_state.params.contains_key(param_id));
+        let has_known_fields = state
+            .fields
+            .keys()
+            .any(|field_id| current_state.fields.contains_key(field_id));
+        let has_no_state = current_state.params.is_empty() && current_state.fields.is_empty();
+        if !(has_known_params || has_known_fields || has_no_state) {
+            return Err(String::from(
+                "The state does not contain any of this plugin's parameters",
+            ));
+        }
+
+        self.set_state(state);
+
+        Ok(())
+    }
 }
 
 /// An way to run background tasks from the plugin's GUI, equivalent to the
@@ -180,4 +244,14 @@ impl<'a> ParamSetter<'a> {
     pub fn end_set_parameter<P: Param>(&self, param: &P) {
         unsafe { self.raw_context.raw_end_set_parameter(param.as_ptr()) };
     }
+
+    /// Reset a parameter to its default value. This is a complete automation gesture, so unlike
+    /// [`set_parameter()`][Self::set_parameter()] this should not be surrounded by calls to
+    /// [`begin_set_parameter()`][Self::begin_set_parameter()] and
+    /// [`end_set_parameter()`][Self::end_set_parameter()].
+    pub fn reset_parameter<P: Param>(&self, param: &P) {
+        self.begin_set_parameter(param);
+        self.set_parameter(param, param.default_plain_value());
+        self.end_set_parameter(param);
+    }
 }
@@ -1,7 +1,7 @@
 //! A context passed during plugin initialization.
 
-use super::PluginApi;
-use crate::prelude::Plugin;
+use super::{HostFeatures, PluginApi};
+use crate::prelude::{Param, Plugin};
 
 /// Callbacks the plugin can make while it is being initialized. This is passed to the plugin during
 /// [`Plugin::initialize()`][crate::plugin::Plugin::initialize()].
@@ -14,6 +14,12 @@ pub trait InitContext<P: Plugin> {
     /// Get the current plugin API.
     fn plugin_api(&self) -> PluginApi;
 
+    /// Get the optional features the host supports. See [`HostFeatures`] for which features can be
+    /// detected with which plugin API. Defaults to no features being supported.
+    fn host_features(&self) -> HostFeatures {
+        HostFeatures::default()
+    }
+
     /// Run a task directly on this thread. This ensures that the task has finished executing before
     /// the plugin finishes initializing.
     ///
@@ -34,4 +40,28 @@ pub trait InitContext<P: Plugin> {
     /// runtime allows the host to better optimize polyphonic modulation, or to switch to strictly
     /// monophonic modulation when dropping the capacity down to 1.
     fn set_current_voice_capacity(&self, capacity: u32);
+
+    /// Only report the first `num_ports` auxiliary output ports to the host as active. This is
+    /// useful for plugins like multiband splitters where the number of used outputs depends on a
+    /// parameter. The remaining ports are still present in
+    /// [`AuxiliaryBuffers::outputs`][crate::prelude::AuxiliaryBuffers::outputs], but they are
+    /// backed by scratch buffers whose contents are discarded. CLAP hosts will see the inactive
+    /// ports disappear, and VST3 hosts will see them as inactive busses. This may cause audio
+    /// playback to be restarted. Values larger than the number of auxiliary output ports activate
+    /// all ports. The standalone wrapper ignores this.
+    fn set_active_aux_output_ports(&self, num_ports: usize);
+
+    /// Mark a parameter as active or inactive. Inactive parameters are reported to the host as
+    /// read-only so it can grey them out, which is useful for parameters that don't do anything
+    /// with the current values of other parameters. This does not affect the parameter's value,
+    /// and the plugin's own editor can still change it. All parameters start out active. CLAP
+    /// hosts are asked to rescan the parameters the next time the plugin is deactivated, so this
+    /// may cause audio playback to be restarted, and VST3 hosts are notified that the parameter
+    /// info has changed. The host is only notified when the state actually changes. The
+    /// standalone wrapper ignores this.
+    fn set_param_active(&self, param: &impl Param, active: bool);
+
+    /// Ask the host to query the plugin's note names again once the plugin has been initialized.
+    /// See [`ClapPlugin::note_names()`][crate::prelude::ClapPlugin::note_names()].
+    fn note_names_changed(&self);
 }
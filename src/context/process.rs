@@ -1,7 +1,7 @@
 //! A context passed during the process function.
 
-use super::PluginApi;
-use crate::prelude::{Plugin, PluginNoteEvent};
+use super::{HostFeatures, PluginApi};
+use crate::prelude::{Param, Plugin, PluginNoteEvent};
 
 /// Contains both context data and callbacks the plugin can use during processing. Most notably this
 /// is how a plugin sends and receives note events, gets transport information, and accesses
@@ -16,6 +16,12 @@ pub trait ProcessContext<P: Plugin> {
     /// Get the current plugin API.
     fn plugin_api(&self) -> PluginApi;
 
+    /// Get the optional features the host supports. See [`HostFeatures`] for which features can be
+    /// detected with which plugin API. Defaults to no features being supported.
+    fn host_features(&self) -> HostFeatures {
+        HostFeatures::default()
+    }
+
     /// Execute a task on a background thread using `[Plugin::task_executor]`. This allows you to
     /// defer expensive tasks for later without blocking either the process function or the GUI
     /// thread. As long as creating the `task` is realtime-safe, this operation is too.
@@ -92,6 +98,31 @@ pub trait ProcessContext<P: Plugin> {
     /// monophonic modulation when dropping the capacity down to 1.
     fn set_current_voice_capacity(&self, capacity: u32);
 
+    /// Only report the first `num_ports` auxiliary output ports to the host as active. This is
+    /// useful for plugins like multiband splitters where the number of used outputs depends on a
+    /// parameter. The remaining ports are still present in
+    /// [`AuxiliaryBuffers::outputs`][crate::prelude::AuxiliaryBuffers::outputs], but they are
+    /// backed by scratch buffers whose contents are discarded. CLAP hosts will see the inactive
+    /// ports disappear, and VST3 hosts will see them as inactive busses. This may cause audio
+    /// playback to be restarted. Values larger than the number of auxiliary output ports activate
+    /// all ports. The standalone wrapper ignores this.
+    fn set_active_aux_output_ports(&self, num_ports: usize);
+
+    /// Mark a parameter as active or inactive. Inactive parameters are reported to the host as
+    /// read-only so it can grey them out, which is useful for parameters that don't do anything
+    /// with the current values of other parameters. This does not affect the parameter's value,
+    /// and the plugin's own editor can still change it. All parameters start out active. CLAP
+    /// hosts are asked to rescan the parameters the next time the plugin is deactivated, so this
+    /// may cause audio playback to be restarted, and VST3 hosts are notified that the parameter
+    /// info has changed. The host is only notified when the state actually changes. The
+    /// standalone wrapper ignores this.
+    fn set_param_active(&self, param: &impl Param, active: bool);
+
+    /// Ask the host to query the plugin's note names again. The request is handled on the main
+    /// thread, so this is realtime-safe. See
+    /// [`ClapPlugin::note_names()`][crate::prelude::ClapPlugin::note_names()].
+    fn note_names_changed(&self);
+
     // TODO: Add this, this works similar to [GuiContext::set_parameter] but it adds the parameter
     //       change to a queue (or directly to the VST3 plugin's parameter output queues) instead of
     //       using main thread host automation (and all the locks involved there).
@@ -336,4 +367,109 @@ impl Transport {
             (_, _, _, _) => None,
         }
     }
+
+    /// The offset in samples from the start of the current block to the next beat, if that beat
+    /// falls within the block. Beats are quarter notes, like in
+    /// [`pos_beats()`][Self::pos_beats()]. This returns `Some(0)` if the block starts exactly on a
+    /// beat, and `None` if the next beat starts at or after the end of the block or if the
+    /// position or tempo are not known. Useful for quantizing events to the beat.
+    pub fn next_beat_boundary_offset(&self, block_len: usize) -> Option<usize> {
+        let pos_beats = self.pos_beats()?;
+
+        self.next_boundary_offset(pos_beats.floor(), 1.0, block_len)
+    }
+
+    /// The offset in samples from the start of the current block to the start of the next bar, if
+    /// that bar starts within the block. This returns `Some(0)` if the block starts exactly on a
+    /// bar, and `None` if the next bar starts at or after the end of the block or if the position,
+    /// tempo, or time signature are not known. Useful for starting patterns on the next bar.
+    pub fn next_bar_boundary_offset(&self, block_len: usize) -> Option<usize> {
+        let bar_start_pos_beats = self.bar_start_pos_beats()?;
+        let quarter_note_bar_length =
+            self.time_sig_numerator? as f64 / self.time_sig_denominator? as f64 * 4.0;
+
+        self.next_boundary_offset(bar_start_pos_beats, quarter_note_bar_length, block_len)
+    }
+
+    /// Compute the offset in samples to the end of a period of `period_beats` quarter notes that
+    /// started at `period_start_beats`. The current position counts as a boundary when it's within
+    /// half a sample of the period's start.
+    fn next_boundary_offset(
+        &self,
+        period_start_beats: f64,
+        period_beats: f64,
+        block_len: usize,
+    ) -> Option<usize> {
+        let pos_beats = self.pos_beats()?;
+        let samples_per_beat = 60.0 / self.tempo? * self.sample_rate as f64;
+
+        let period_samples = period_beats * samples_per_beat;
+        let samples_since_start = (pos_beats - period_start_beats) * samples_per_beat;
+        let samples_until_boundary = period_samples - samples_since_start;
+
+        // The position may be slightly off because of rounding errors, so a position that lies
+        // just before or just after a boundary should be treated as being exactly on it
+        let offset = if samples_since_start.round() <= 0.0 {
+            0
+        } else {
+            samples_until_boundary.round().max(0.0) as usize
+        };
+
+        if offset < block_len {
+            Some(offset)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A transport at 120 BPM in 4/4 at 48 kHz, so a beat lasts 24000 samples and a bar lasts 96000
+    /// samples.
+    fn transport_at(pos_samples: i64) -> Transport {
+        let mut transport = Transport::new(48_000.0);
+        transport.tempo = Some(120.0);
+        transport.time_sig_numerator = Some(4);
+        transport.time_sig_denominator = Some(4);
+        transport.pos_samples = Some(pos_samples);
+
+        transport
+    }
+
+    #[test]
+    fn boundary_at_block_start() {
+        let transport = transport_at(96_000);
+        assert_eq!(transport.next_beat_boundary_offset(512), Some(0));
+        assert_eq!(transport.next_bar_boundary_offset(512), Some(0));
+    }
+
+    #[test]
+    fn boundary_in_block() {
+        let transport = transport_at(24_000 - 100);
+        assert_eq!(transport.next_beat_boundary_offset(512), Some(100));
+        assert_eq!(transport.next_bar_boundary_offset(512), None);
+
+        let transport = transport_at(96_000 - 511);
+        assert_eq!(transport.next_beat_boundary_offset(512), Some(511));
+        assert_eq!(transport.next_bar_boundary_offset(512), Some(511));
+    }
+
+    #[test]
+    fn boundary_at_block_end() {
+        // The boundary starts on the first sample of the next block
+        let transport = transport_at(96_000 - 512);
+        assert_eq!(transport.next_beat_boundary_offset(512), None);
+        assert_eq!(transport.next_bar_boundary_offset(512), None);
+    }
+
+    #[test]
+    fn unknown_position() {
+        let mut transport = transport_at(0);
+        transport.pos_samples = None;
+        assert_eq!(transport.next_beat_boundary_offset(512), None);
+        assert_eq!(transport.next_bar_boundary_offset(512), None);
+    }
 }
@@ -8,6 +8,7 @@ use std::sync::Arc;
 use crate::prelude::GuiContext;
 
 /// An editor for a [`Plugin`][crate::prelude::Plugin].
+#[allow(unused_variables)]
 pub trait Editor: Send {
     /// Create an instance of the plugin's editor and embed it in the parent window. As explained in
     /// [`Plugin::editor()`][crate::prelude::Plugin::editor()], you can then read the parameter
@@ -57,17 +58,32 @@ pub trait Editor: Send {
     /// Called whenever a specific parameter's value has changed while the editor is open. You don't
     /// need to do anything with this, but this can be used to force a redraw when the host sends a
     /// new value for a parameter or when a parameter change sent to the host gets processed.
-    fn param_value_changed(&self, id: &str, normalized_value: f32);
+    ///
+    /// This calls [`param_values_changed()`][Self::param_values_changed()] by default.
+    fn param_value_changed(&self, id: &str, normalized_value: f32) {
+        self.param_values_changed();
+    }
 
     /// Called whenever a specific parameter's monophonic modulation value has changed while the
     /// editor is open.
-    fn param_modulation_changed(&self, id: &str, modulation_offset: f32);
+    ///
+    /// This calls [`param_values_changed()`][Self::param_values_changed()] by default.
+    fn param_modulation_changed(&self, id: &str, modulation_offset: f32) {
+        self.param_values_changed();
+    }
 
     /// Called whenever one or more parameter values or modulations have changed while the editor is
     /// open. This may be called in place of [`param_value_changed()`][Self::param_value_changed()]
     /// when multiple parameter values hcange at the same time. For example, when a preset is
-    /// loaded.
-    fn param_values_changed(&self);
+    /// loaded. Since the other two parameter notification functions call this function by default,
+    /// a custom editor only needs to implement this to know when it should redraw. This does
+    /// nothing by default.
+    ///
+    /// These notifications are sent from the host's main thread, which is not necessarily the
+    /// thread the editor's window or render loop runs on. Implementations should do as little work
+    /// as possible here, like setting an atomic flag that the editor checks before drawing the next
+    /// frame.
+    fn param_values_changed(&self) {}
 
     // TODO: Reconsider adding a tick function here for the Linux `IRunLoop`. To keep this platform
     //       and API agnostic, add a way to ask the GuiContext if the wrapper already provides a
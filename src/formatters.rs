@@ -310,6 +310,88 @@ pub fn s2v_i32_note_formatter() -> Arc<dyn Fn(&str) -> Option<i32> + Send + Sync
     })
 }
 
+/// Turns an integer MIDI note number into a note name like [`v2s_i32_note_formatter()`], but with
+/// a configurable octave number for middle C (MIDI note 60). Some hosts and hardware call middle C
+/// `C4` while others call it `C3`, so `middle_c_octave` should usually be either 4 or 3. Useful for
+/// root note and key parameters.
+pub fn v2s_i32_note_name(middle_c_octave: i32) -> Arc<dyn Fn(i32) -> String + Send + Sync> {
+    Arc::new(move |value| {
+        let note_name = util::NOTES[value.rem_euclid(12) as usize];
+        let octave = value.div_euclid(12) - 5 + middle_c_octave;
+        format!("{note_name}{octave}")
+    })
+}
+
+/// Parse a note name to a MIDI note number using the inverse mapping from
+/// [`v2s_i32_note_name()`]. `middle_c_octave` should match the value passed to that function. In
+/// addition to the sharps produced by the formatter, this also accepts flats (`Bb3`, `E♭4`) and
+/// spaces between the note name and the octave number.
+pub fn s2v_i32_note_name(middle_c_octave: i32) -> Arc<dyn Fn(&str) -> Option<i32> + Send + Sync> {
+    Arc::new(move |string| {
+        let mut chars = string.trim().chars().peekable();
+
+        let mut note_id = match chars.next()?.to_ascii_uppercase() {
+            'C' => 0,
+            'D' => 2,
+            'E' => 4,
+            'F' => 5,
+            'G' => 7,
+            'A' => 9,
+            'B' => 11,
+            _ => return None,
+        };
+        // The note letter may be followed by any number of accidentals. Since the note letter has
+        // already been consumed, a lowercase `b` here is always a flat.
+        while let Some(accidental) = chars.next_if(|c| matches!(c, '#' | '♯' | 'b' | '♭')) {
+            note_id += match accidental {
+                '#' | '♯' => 1,
+                _ => -1,
+            };
+        }
+
+        let octave: i32 = chars.collect::<String>().trim().parse().ok()?;
+
+        // With middle C at octave 4, 60 = C4, 48 = C3, and 0 = C-1
+        Some(note_id + (12 * (octave - middle_c_octave + 5)))
+    })
+}
+
+/// Format an integer followed by a unit that is pluralized when needed, like `1 band` and
+/// `3 bands`. Useful for discrete counts like the number of voices, bands, or taps. The singular
+/// form is only used for 1 and -1.
+pub fn v2s_i32_with_unit(
+    singular: &'static str,
+    plural: &'static str,
+) -> Arc<dyn Fn(i32) -> String + Send + Sync> {
+    Arc::new(move |value| {
+        let unit = if value.unsigned_abs() == 1 {
+            singular
+        } else {
+            plural
+        };
+        format!("{value} {unit}")
+    })
+}
+
+/// Parse a string in the same format as [`v2s_i32_with_unit()`]. The unit is optional, and both
+/// the singular and the plural forms are accepted regardless of the number.
+pub fn s2v_i32_with_unit(
+    singular: &'static str,
+    plural: &'static str,
+) -> Arc<dyn Fn(&str) -> Option<i32> + Send + Sync> {
+    Arc::new(move |string| {
+        let string = string.trim();
+        // The singular form is often a prefix of the plural form, so the plural needs to be checked
+        // first
+        let number = [plural, singular]
+            .into_iter()
+            .find_map(|unit| strip_suffix_ignore_ascii_case(string, unit))
+            .unwrap_or(string);
+
+        number.trim().parse().ok()
+    })
+}
+
 /// Display 'Bypassed' or 'Not Bypassed' depending on whether the parameter is true or false.
 /// 'Enabled' would have also been a possibility here, but that could be a bit confusing.
 pub fn v2s_bool_bypass() -> Arc<dyn Fn(bool) -> String + Send + Sync> {
@@ -336,6 +418,16 @@ pub fn s2v_bool_bypass() -> Arc<dyn Fn(&str) -> Option<bool> + Send + Sync> {
     })
 }
 
+/// Remove `suffix` from the end of `string`, ignoring the case of ASCII characters.
+fn strip_suffix_ignore_ascii_case<'a>(string: &'a str, suffix: &str) -> Option<&'a str> {
+    let split_idx = string.len().checked_sub(suffix.len())?;
+    if string.is_char_boundary(split_idx) && string[split_idx..].eq_ignore_ascii_case(suffix) {
+        Some(&string[..split_idx])
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -352,6 +444,54 @@ mod tests {
         assert_eq!("0.01", v2s(0.009));
     }
 
+    #[test]
+    fn i32_note_name_middle_c() {
+        let v2s_c4 = v2s_i32_note_name(4);
+        let v2s_c3 = v2s_i32_note_name(3);
+
+        assert_eq!("C4", v2s_c4(60));
+        assert_eq!("A4", v2s_c4(69));
+        assert_eq!("C-1", v2s_c4(0));
+        assert_eq!("G9", v2s_c4(127));
+
+        assert_eq!("C3", v2s_c3(60));
+        assert_eq!("A#2", v2s_c3(58));
+        assert_eq!("C-2", v2s_c3(0));
+        assert_eq!("G8", v2s_c3(127));
+    }
+
+    #[test]
+    fn i32_note_name_roundtrip() {
+        for middle_c_octave in [3, 4] {
+            let v2s = v2s_i32_note_name(middle_c_octave);
+            let s2v = s2v_i32_note_name(middle_c_octave);
+
+            for note in 0..=127 {
+                let string = v2s(note);
+                assert_eq!(Some(note), s2v(&string), "Unexpected: {note} -> {string}");
+            }
+        }
+    }
+
+    #[test]
+    fn s2v_i32_note_name_accidentals() {
+        let s2v = s2v_i32_note_name(4);
+
+        assert_eq!(Some(70), s2v("A#4"));
+        assert_eq!(Some(70), s2v("Bb4"));
+        assert_eq!(Some(70), s2v("bb4"));
+        assert_eq!(Some(70), s2v("B♭4"));
+        assert_eq!(Some(70), s2v("A♯ 4"));
+        assert_eq!(Some(59), s2v("Cb4"));
+        assert_eq!(Some(60), s2v(" c 4 "));
+        assert_eq!(Some(1), s2v("Db-1"));
+
+        assert_eq!(None, s2v(""));
+        assert_eq!(None, s2v("H4"));
+        assert_eq!(None, s2v("C"));
+        assert_eq!(None, s2v("C#x"));
+    }
+
     // More of these validators could use tests, but this one in particular is tricky and I noticed
     // an issue where it didn't roundtrip correctly
     #[test]
@@ -371,4 +511,30 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn i32_with_unit_roundtrip() {
+        let v2s = v2s_i32_with_unit("band", "bands");
+        let s2v = s2v_i32_with_unit("band", "bands");
+
+        assert_eq!(v2s(1), "1 band");
+        assert_eq!(v2s(2), "2 bands");
+        assert_eq!(v2s(0), "0 bands");
+        assert_eq!(v2s(i32::MIN), format!("{} bands", i32::MIN));
+        for value in [-2, -1, 0, 1, 2, 5] {
+            assert_eq!(s2v(&v2s(value)), Some(value));
+        }
+    }
+
+    #[test]
+    fn s2v_i32_with_unit_optional_unit() {
+        let s2v = s2v_i32_with_unit("band", "bands");
+
+        assert_eq!(s2v("3"), Some(3));
+        assert_eq!(s2v(" 4Bands "), Some(4));
+        assert_eq!(s2v("1 BAND"), Some(1));
+        assert_eq!(s2v("2 band"), Some(2));
+        assert_eq!(s2v("bands"), None);
+        assert_eq!(s2v("2 voices"), None);
+    }
 }
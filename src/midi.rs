@@ -1,6 +1,7 @@
 //! Constants and definitions surrounding MIDI support.
 
 use midi_consts::channel_event as midi;
+use std::ops::Range;
 
 use self::sysex::SysExMessage;
 use crate::prelude::Plugin;
@@ -67,8 +68,10 @@ pub enum NoteEvent<S> {
         /// 128 levels available in MIDI.
         velocity: f32,
     },
-    /// A note off event, available on [`MidiConfig::Basic`] and up. Bitwig Studio does not provide
-    /// a voice ID for this event.
+    /// A note off event, available on [`MidiConfig::Basic`] and up. Some hosts, like Bitwig Studio,
+    /// do not provide a voice ID for this event. In that case the wrapper fills in the voice ID of
+    /// the note on event for the same channel and key. The voice ID is left empty if multiple
+    /// overlapping notes are playing on that key, and the event then applies to all of them.
     NoteOff {
         timing: u32,
         /// A unique identifier for this note, if available. Using this to refer to a note is
@@ -84,7 +87,8 @@ pub enum NoteEvent<S> {
     },
     /// A note choke event, available on [`MidiConfig::Basic`] and up. When the host sends this to
     /// the plugin, it indicates that a voice or all sound associated with a note should immediately
-    /// stop playing.
+    /// stop playing. Missing voice IDs are filled in the same way as for
+    /// [`NoteOff`][Self::NoteOff] events.
     Choke {
         timing: u32,
         /// A unique identifier for this note, if available. Using this to refer to a note is
@@ -623,7 +627,6 @@ impl<S: SysExMessage> NoteEvent<S> {
 
     /// Subtract a sample offset from this event's timing, needed to compensate for the block
     /// splitting in the VST3 wrapper implementation because all events have to be read upfront.
-    #[cfg_attr(not(feature = "vst3"), allow(dead_code))]
     pub(crate) fn subtract_timing(&mut self, samples: u32) {
         match self {
             NoteEvent::NoteOn { timing, .. } => *timing -= samples,
@@ -648,6 +651,26 @@ impl<S: SysExMessage> NoteEvent<S> {
     }
 }
 
+/// Get the events from `events` that belong to the sub-block `block`, with their timings rebased so
+/// that sample `block.start` in the original buffer becomes sample 0. Events outside of the range
+/// are skipped. This is useful when splitting a buffer into smaller blocks and processing those
+/// blocks individually, since the events' timings are always relative to the start of the block
+/// being processed.
+pub fn events_in_block<S: SysExMessage>(
+    events: &[NoteEvent<S>],
+    block: Range<u32>,
+) -> impl Iterator<Item = NoteEvent<S>> + '_ {
+    events
+        .iter()
+        .filter(move |event| block.contains(&event.timing()))
+        .map(move |event| {
+            let mut event = event.clone();
+            event.subtract_timing(block.start);
+
+            event
+        })
+}
+
 #[cfg(test)]
 mod tests {
     pub use super::*;
@@ -749,6 +772,47 @@ mod tests {
         assert_eq!(roundtrip_basic_event(event), event);
     }
 
+    mod events_in_block {
+        use super::*;
+
+        fn note_on(timing: u32) -> NoteEvent<()> {
+            NoteEvent::NoteOn {
+                timing,
+                voice_id: None,
+                channel: 0,
+                note: 60,
+                velocity: 1.0,
+            }
+        }
+
+        fn timings(events: &[NoteEvent<()>], block: Range<u32>) -> Vec<u32> {
+            events_in_block(events, block)
+                .map(|event| event.timing())
+                .collect()
+        }
+
+        #[test]
+        fn rebases_timings() {
+            let events = [note_on(0), note_on(10), note_on(15), note_on(31)];
+
+            assert_eq!(timings(&events, 0..16), [0, 10, 15]);
+            assert_eq!(timings(&events, 16..32), [15]);
+            assert_eq!(timings(&events, 10..11), [0]);
+        }
+
+        #[test]
+        fn boundaries() {
+            let events = [note_on(7), note_on(8), note_on(15), note_on(16)];
+
+            // The start is inclusive and the end is exclusive
+            assert_eq!(timings(&events, 8..16), [0, 7]);
+            assert_eq!(timings(&events, 0..8), [7]);
+            assert_eq!(timings(&events, 16..17), [0]);
+            assert!(timings(&events, 8..8).is_empty());
+            assert!(timings(&events, 17..64).is_empty());
+        }
+    }
+
     mod sysex {
         use super::*;
 
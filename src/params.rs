@@ -20,6 +20,7 @@ mod float;
 mod integer;
 
 pub mod internals;
+pub mod linked;
 pub mod persist;
 pub mod range;
 pub mod smoothing;
@@ -118,6 +119,15 @@ pub trait Param: Display + Debug + sealed::Sealed {
     /// Studio use modulation.
     fn unmodulated_normalized_value(&self) -> f32;
 
+    /// Get the unnormalized value this parameter's smoother is currently at. For parameters
+    /// without a smoother, or when the smoother is not currently smoothing, this is the same as
+    /// [`modulated_plain_value()`][Self::modulated_plain_value()]. Unlike the smoother's `next()`
+    /// function this does not advance the smoother.
+    #[inline]
+    fn smoothed_plain_value(&self) -> Self::Plain {
+        self.modulated_plain_value()
+    }
+
     /// Get the unnormalized default value for this parameter.
     fn default_plain_value(&self) -> Self::Plain;
 
@@ -249,6 +259,14 @@ pub(crate) trait ParamMut: Param {
 /// with the `#[persist = "key"]` attribute containing types that can be serialized and deserialized
 /// with [Serde](https://serde.rs/).
 ///
+/// ## `#[group = "group name"]`
+///
+/// Parameters with an `#[id = "stable"]` attribute can also be given a `#[group = "group name"]`
+/// attribute to place them in a group without having to move them to a separate struct. Hosts that
+/// show parameters in a tree, like Cubase, use these groups to organize larger parameter lists.
+/// Parameters without a group attribute stay at the top level. Parameters from the same group don't
+/// need to be declared next to each other.
+///
 /// ## `#[nested]`, `#[nested(group_name = "group name")]`
 ///
 /// Finally, the `Params` object may include parameters from other objects. Setting a group name is
@@ -275,6 +293,16 @@ pub(crate) trait ParamMut: Param {
 /// parameter will belong to the group `Foo {array_index + 1}`, and it will have the renamed
 /// parameter ID `bar_{array_index + 1}`. The same thing applies to persistent field keys.
 ///
+/// ## `#[snapshot(FooParamsSnapshot)]`
+///
+/// This attribute goes on the struct itself instead of on one of its fields. It makes the derive
+/// macro generate a `FooParamsSnapshot` struct with a field for every `#[id = "stable"]` parameter
+/// containing that parameter's plain value, along with a `snapshot()` method on the parameters
+/// struct that fills it with each parameter's
+/// [`smoothed_plain_value()`][Param::smoothed_plain_value()]. Taking a snapshot at the start of a
+/// block lets the rest of the `process()` function read the parameter values from plain local
+/// fields. Parameters from `#[nested]` structs are not included.
+///
 /// # Safety
 ///
 /// This implementation is safe when using from the wrapper because the plugin's returned `Params`
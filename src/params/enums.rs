@@ -45,6 +45,79 @@ pub use nih_plug_derive::Enum;
 ///
 /// You can safely move from not using IDs to using IDs without breaking patches, but you cannot go
 /// back to not using IDs after that.
+///
+/// The index used for a variant can also be pinned with the `#[value = N]` attribute. Variants
+/// without this attribute use their declaration order index. This keeps the indices stable when
+/// reordering variants or inserting new ones in between existing variants. The indices are also
+/// the parameter's plain values, so they need to be unique and they need to range from zero to the
+/// number of variants minus one. The variants are listed in the order of their indices.
+///
+/// ```ignore
+/// #[derive(Enum)]
+/// enum Foo {
+///     #[value = 1]
+///     Bar,
+///     #[value = 2]
+///     Baz,
+///     #[value = 0]
+///     Qux,
+/// }
+/// ```
+///
+/// Explicit discriminants are ignored by default. With the `#[use_discriminants]` attribute on the
+/// enum, integer literal discriminants are treated the same way as the `#[value = N]` attribute.
+/// This makes it possible to use the same values as, for instance, an existing protocol, as long
+/// as those values range from zero to the number of variants minus one:
+///
+/// ```ignore
+/// #[derive(Enum)]
+/// #[use_discriminants]
+/// #[repr(u8)]
+/// enum Foo {
+///     Bar = 1,
+///     Baz = 0,
+/// }
+/// ```
+///
+/// Variants marked with `#[skip]` are not included in the parameter. They won't show up in the
+/// host's list of options and [`from_index()`][Self::from_index()] never returns them, but the
+/// variant can still be used in the code. Converting a skipped variant to an index results in the
+/// index of the closest preceding variant that isn't skipped, or the first variant after it if
+/// there is none. This can be used to hide deprecated options without having to remove them:
+///
+/// ```ignore
+/// #[derive(Enum)]
+/// enum Foo {
+///     Bar,
+///     #[skip]
+///     OldBar,
+///     Baz,
+/// }
+/// ```
+///
+/// The derive macro also adds a `VARIANTS_LEN` constant and a `const fn all() -> &'static [Self]`
+/// function to the enum. These contain the number of variants and all variants in index order,
+/// without the skipped variants.
+///
+/// If the display names need to be computed at runtime, for instance because they should be
+/// translated, then a `#[name_fn = path::to::function]` attribute can be used instead of the
+/// `#[name = "..."]` attribute. On a variant the function takes no arguments, and on the enum
+/// itself it takes the variant as its only argument. Both return a `String`. The static names from
+/// [`variants()`][Self::variants()] are still used for the variants without a function, and they
+/// can still be used when parsing text back to a parameter value:
+///
+/// ```ignore
+/// #[derive(Enum)]
+/// #[name_fn = translate_foo]
+/// enum Foo {
+///     Bar,
+///     #[name_fn = translate_baz]
+///     Baz,
+/// }
+///
+/// fn translate_foo(value: Foo) -> String { /* ... */ }
+/// fn translate_baz() -> String { /* ... */ }
+/// ```
 pub trait Enum {
     /// The human readable names for the variants. These are displayed in the GUI or parameter list,
     /// and also used for parsing text back to a parameter value. The length of this slice
@@ -65,6 +138,15 @@ pub trait Enum {
     /// [`variants()`][Self::variants()]. This must always return a value. If the index is out of
     /// range, return the first variant.
     fn from_index(index: usize) -> Self;
+
+    /// The display name for the variant with the same index in [`variants()`][Self::variants()].
+    /// This can be overridden to compute the names at runtime. This defaults to the static name
+    /// from [`variants()`][Self::variants()]. If the index is out of range, return the first
+    /// variant's name.
+    fn variant_name(index: usize) -> String {
+        let variants = Self::variants();
+        variants.get(index).unwrap_or(&variants[0]).to_string()
+    }
 }
 
 /// An [`IntParam`]-backed categorical parameter that allows convenient conversion to and from a
@@ -87,6 +169,9 @@ pub struct EnumParamInner {
     pub(crate) inner: IntParam,
     /// The human readable variant names, obtained from [Enum::variants()].
     variants: &'static [&'static str],
+    /// Computes the displayed variant names, obtained from [Enum::variant_name()]. These are the
+    /// same as `variants` unless the names are computed at runtime.
+    variant_name: fn(usize) -> String,
     /// Stable identifiers for the enum variants, obtained from [Enum::ids()]. These are optional,
     /// but if they are set (they're either not set for any variant, or set for all variants) then
     /// these identifiers are used when saving enum parameter values to the state. Otherwise the
@@ -105,7 +190,7 @@ impl Display for EnumParamInner {
         write!(
             f,
             "{}",
-            self.variants[self.inner.modulated_plain_value() as usize]
+            (self.variant_name)(self.inner.modulated_plain_value() as usize)
         )
     }
 }
@@ -267,14 +352,15 @@ impl Param for EnumParamInner {
 
     fn normalized_value_to_string(&self, normalized: f32, _include_unit: bool) -> String {
         let index = self.preview_plain(normalized);
-        self.variants[index as usize].to_string()
+        (self.variant_name)(index as usize)
     }
 
     fn string_to_normalized_value(&self, string: &str) -> Option<f32> {
         let string = string.trim();
-        self.variants
-            .iter()
-            .position(|variant| variant == &string)
+        // The static names are also accepted in case the displayed names are computed at runtime
+        (0..self.len())
+            .position(|idx| (self.variant_name)(idx) == string)
+            .or_else(|| self.variants.iter().position(|variant| variant == &string))
             .map(|idx| self.preview_normalized(idx as i32))
     }
 
@@ -351,6 +437,7 @@ impl<T: Enum + PartialEq + 'static> EnumParam<T> {
                     },
                 ),
                 variants,
+                variant_name: T::variant_name,
                 ids,
             },
             _marker: PhantomData,
@@ -32,6 +32,11 @@ pub struct FloatParam {
     /// An optional smoother that will automatically interpolate between the new automation values
     /// set by the host.
     pub smoothed: Smoother<f32>,
+    /// A second smoother with the same style as `smoothed` that only smooths the **unmodulated**
+    /// value. This is used by [`smoothed_modulated_value()`][Self::smoothed_modulated_value()] so
+    /// the host's modulation can be applied on top of the smoothed automation without being
+    /// smoothed itself.
+    pub smoothed_unmodulated: Smoother<f32>,
 
     /// Flags to control the parameter's behavior. See [`ParamFlags`].
     flags: ParamFlags,
@@ -135,6 +140,15 @@ impl Param for FloatParam {
         self.unmodulated_normalized_value.load(Ordering::Relaxed)
     }
 
+    #[inline]
+    fn smoothed_plain_value(&self) -> Self::Plain {
+        if self.smoothed.is_smoothing() {
+            self.smoothed.previous_value()
+        } else {
+            self.modulated_plain_value()
+        }
+    }
+
     #[inline]
     fn default_plain_value(&self) -> Self::Plain {
         self.default
@@ -258,9 +272,13 @@ impl ParamMut for FloatParam {
     fn update_smoother(&self, sample_rate: f32, reset: bool) {
         if reset {
             self.smoothed.reset(self.modulated_plain_value());
+            self.smoothed_unmodulated
+                .reset(self.unmodulated_plain_value());
         } else {
             self.smoothed
                 .set_target(sample_rate, self.modulated_plain_value());
+            self.smoothed_unmodulated
+                .set_target(sample_rate, self.unmodulated_plain_value());
         }
     }
 }
@@ -279,6 +297,7 @@ impl FloatParam {
             modulation_offset: AtomicF32::new(0.0),
             default,
             smoothed: Smoother::none(),
+            smoothed_unmodulated: Smoother::none(),
 
             flags: ParamFlags::default(),
             value_changed: None,
@@ -300,6 +319,45 @@ impl FloatParam {
         self.modulated_plain_value()
     }
 
+    /// The parameter's effective value for the next sample, combining automation, monophonic
+    /// modulation, and smoothing. This advances [`smoothed_unmodulated`][Self::smoothed_unmodulated]
+    /// by one step, so it should be called once per sample. The value is computed as follows:
+    ///
+    /// 1. The unmodulated value set through automation is smoothed using the parameter's smoothing
+    ///    style.
+    /// 2. The host's current monophonic modulation offset is added to the smoothed value in the
+    ///    normalized `[0, 1]` range. The modulation itself is not smoothed, since the host already
+    ///    sends sample accurate modulation values.
+    /// 3. The result is clamped to `[0, 1]` and converted back to a plain value.
+    ///
+    /// This is an alternative to [`smoothed`][Self::smoothed], which smooths the modulated value
+    /// and thus also delays changes in modulation. A plugin should use either one or the other.
+    #[inline]
+    pub fn smoothed_modulated_value(&self) -> f32 {
+        self.apply_modulation(self.smoothed_unmodulated.next())
+    }
+
+    /// The same as [`smoothed_modulated_value()`][Self::smoothed_modulated_value()], but advances
+    /// the smoother by `steps` steps at once. Useful when expensive computations like filter
+    /// coefficients are only updated every `steps` samples.
+    #[inline]
+    pub fn smoothed_modulated_value_step(&self, steps: u32) -> f32 {
+        self.apply_modulation(self.smoothed_unmodulated.next_step(steps))
+    }
+
+    /// Apply the current monophonic modulation offset to an unmodulated plain value.
+    #[inline]
+    fn apply_modulation(&self, unmodulated_value: f32) -> f32 {
+        let modulation_offset = self.modulation_offset.load(Ordering::Relaxed);
+        if modulation_offset == 0.0 {
+            unmodulated_value
+        } else {
+            self.range.unnormalize(
+                (self.range.normalize(unmodulated_value) + modulation_offset).clamp(0.0, 1.0),
+            )
+        }
+    }
+
     /// The range of valid plain values for this parameter.
     #[inline]
     pub fn range(&self) -> FloatRange {
@@ -342,6 +400,7 @@ impl FloatParam {
             "Logarithmic smoothing does not work with ranges that go through zero"
         );
 
+        self.smoothed_unmodulated = Smoother::new(style.clone());
         self.smoothed = Smoother::new(style);
         self
     }
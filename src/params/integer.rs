@@ -127,6 +127,15 @@ impl Param for IntParam {
         self.unmodulated_normalized_value.load(Ordering::Relaxed)
     }
 
+    #[inline]
+    fn smoothed_plain_value(&self) -> Self::Plain {
+        if self.smoothed.is_smoothing() {
+            self.smoothed.previous_value()
+        } else {
+            self.modulated_plain_value()
+        }
+    }
+
     #[inline]
     fn default_plain_value(&self) -> Self::Plain {
         self.default
@@ -0,0 +1,251 @@
+//! Pairs of parameter sets that can be linked together, like the left and right channel settings
+//! for a stereo effect.
+
+use std::collections::BTreeMap;
+
+use super::internals::ParamPtr;
+use super::{BoolParam, Param, Params};
+
+/// Two copies of the same parameters with a toggle that links them together. While linked, the
+/// first set of parameters controls both sides, for instance both channels of a stereo signal, and
+/// the second set is unused. Unlinking them allows both sides to be controlled independently, like
+/// for an EQ with separate left and right channel settings.
+///
+/// This is added to a [`Params`] struct with the `#[nested]` attribute. The link toggle gets the
+/// parameter ID `link` and it's listed in between the two sets of parameters. The first set of
+/// parameters keeps its own parameter IDs, and the second set's parameter IDs get a `_2` suffix.
+/// The same applies to persistent field keys. This means that `T` cannot contain a parameter with
+/// the ID `link`, and none of its parameter IDs or persistent field keys can be another one of its
+/// IDs or keys with a `_2` suffix, since those would collide with the second set's. Because the
+/// first set's IDs don't change, existing parameters can be turned into a linked pair without
+/// breaking old presets. Use `#[nested(id_prefix = "foo")]` if a plugin has more than one linked
+/// pair.
+///
+/// The second set of parameters doesn't do anything while the pair is linked, so those parameters
+/// should be marked as inactive using
+/// [`InitContext::set_param_active()`][crate::prelude::InitContext::set_param_active()] and
+/// [`ProcessContext::set_param_active()`][crate::prelude::ProcessContext::set_param_active()].
+/// This lets hosts grey them out. Only the plugin knows which parameters `T` contains, so this is
+/// not done automatically.
+pub struct LinkedParams<T: Params> {
+    /// When this is enabled, the first set of parameters is used for both sides.
+    pub link: BoolParam,
+    /// The parameters for the first side, and for both sides while linked.
+    pub first: T,
+    /// The parameters for the second side. These are only used while unlinked.
+    pub second: T,
+
+    /// The group the second set of parameters is placed in, if any. See
+    /// [`with_second_group()`][Self::with_second_group()].
+    second_group: Option<String>,
+}
+
+impl<T: Params> LinkedParams<T> {
+    /// Create a new linked pair of parameters. Both sets of parameters are created separately so
+    /// they can have different names, like `Gain (L)` and `Gain (R)`.
+    pub fn new(link: BoolParam, first: T, second: T) -> Self {
+        Self {
+            link,
+            first,
+            second,
+
+            second_group: None,
+        }
+    }
+
+    /// Place the second set of parameters in a group with this name. Hosts that show parameters in
+    /// a tree then list them separately from the first set. Any groups within `T` become subgroups
+    /// of this group.
+    pub fn with_second_group(mut self, group: impl Into<String>) -> Self {
+        self.second_group = Some(group.into());
+        self
+    }
+
+    /// Get the parameters that should be used for side `idx`, which is either 0 or 1. The link
+    /// state is passed explicitly so it can come from a snapshot taken at the start of the block.
+    pub fn get(&self, idx: usize, linked: bool) -> &T {
+        nih_debug_assert!(idx < 2);
+
+        if idx == 0 || linked {
+            &self.first
+        } else {
+            &self.second
+        }
+    }
+
+    /// Get both sets of parameters, regardless of whether they are linked. This can be used to
+    /// advance the second set's smoothers while linked so they don't jump after unlinking.
+    pub fn both(&self) -> [&T; 2] {
+        [&self.first, &self.second]
+    }
+}
+
+unsafe impl<T: Params> Params for LinkedParams<T> {
+    fn param_map(&self) -> Vec<(String, ParamPtr, String)> {
+        let mut param_map = self.first.param_map();
+        param_map.push((String::from("link"), self.link.as_ptr(), String::new()));
+        param_map.extend(self.second.param_map().into_iter().map(
+            |(param_id, param_ptr, group)| {
+                let group = match (&self.second_group, group.is_empty()) {
+                    (Some(second_group), true) => second_group.clone(),
+                    (Some(second_group), false) => format!("{second_group}/{group}"),
+                    (None, _) => group,
+                };
+
+                (format!("{param_id}_2"), param_ptr, group)
+            },
+        ));
+
+        param_map
+    }
+
+    fn serialize_fields(&self) -> BTreeMap<String, String> {
+        let mut serialized = self.first.serialize_fields();
+        for (key, value) in self.second.serialize_fields() {
+            let key = format!("{key}_2");
+            nih_debug_assert!(
+                !serialized.contains_key(&key),
+                "The persistent field key '{}' is used by both sets of linked parameters",
+                key
+            );
+
+            serialized.insert(key, value);
+        }
+
+        serialized
+    }
+
+    fn deserialize_fields(&self, serialized: &BTreeMap<String, String>) {
+        // The second set's keys are `T`'s own keys with a `_2` suffix. Those are taken out first so
+        // the first set only receives its own keys.
+        let mut first_serialized = serialized.clone();
+        let mut second_serialized = BTreeMap::new();
+        for key in self.second.serialize_fields().into_keys() {
+            if let Some(value) = first_serialized.remove(&format!("{key}_2")) {
+                second_serialized.insert(key, value);
+            }
+        }
+
+        self.first.deserialize_fields(&first_serialized);
+        self.second.deserialize_fields(&second_serialized);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::params::range::FloatRange;
+    use crate::params::FloatParam;
+
+    struct GainParams {
+        gain: FloatParam,
+    }
+
+    unsafe impl Params for GainParams {
+        fn param_map(&self) -> Vec<(String, ParamPtr, String)> {
+            vec![(
+                String::from("gain"),
+                self.gain.as_ptr(),
+                String::from("Gain"),
+            )]
+        }
+    }
+
+    fn gain_params(name: &str) -> GainParams {
+        GainParams {
+            gain: FloatParam::new(name, 1.0, FloatRange::Linear { min: 0.0, max: 2.0 }),
+        }
+    }
+
+    /// Only contains a persistent field with the key `state`.
+    struct StateParams {
+        state: Mutex<String>,
+    }
+
+    unsafe impl Params for StateParams {
+        fn param_map(&self) -> Vec<(String, ParamPtr, String)> {
+            Vec::new()
+        }
+
+        fn serialize_fields(&self) -> BTreeMap<String, String> {
+            BTreeMap::from([(String::from("state"), self.state.lock().unwrap().clone())])
+        }
+
+        fn deserialize_fields(&self, serialized: &BTreeMap<String, String>) {
+            // Both sets should only receive their own keys
+            assert_eq!(serialized.keys().collect::<Vec<_>>(), ["state"]);
+            *self.state.lock().unwrap() = serialized["state"].clone();
+        }
+    }
+
+    fn state_params(state: &str) -> StateParams {
+        StateParams {
+            state: Mutex::new(String::from(state)),
+        }
+    }
+
+    #[test]
+    fn param_map() {
+        let params = LinkedParams::new(
+            BoolParam::new("Link", true),
+            gain_params("Gain (L)"),
+            gain_params("Gain (R)"),
+        )
+        .with_second_group("Right");
+
+        let param_map: Vec<(String, String)> = params
+            .param_map()
+            .into_iter()
+            .map(|(param_id, _, group)| (param_id, group))
+            .collect();
+        assert_eq!(
+            param_map,
+            [
+                (String::from("gain"), String::from("Gain")),
+                (String::from("link"), String::new()),
+                (String::from("gain_2"), String::from("Right/Gain")),
+            ]
+        );
+    }
+
+    #[test]
+    fn persistent_fields() {
+        let params = LinkedParams::new(
+            BoolParam::new("Link", true),
+            state_params("left"),
+            state_params("right"),
+        );
+        let serialized = params.serialize_fields();
+        assert_eq!(
+            serialized,
+            BTreeMap::from([
+                (String::from("state"), String::from("left")),
+                (String::from("state_2"), String::from("right")),
+            ])
+        );
+
+        let restored_params = LinkedParams::new(
+            BoolParam::new("Link", true),
+            state_params(""),
+            state_params(""),
+        );
+        restored_params.deserialize_fields(&serialized);
+        assert_eq!(*restored_params.first.state.lock().unwrap(), "left");
+        assert_eq!(*restored_params.second.state.lock().unwrap(), "right");
+    }
+
+    #[test]
+    fn get() {
+        let params = LinkedParams::new(
+            BoolParam::new("Link", true),
+            gain_params("Gain (L)"),
+            gain_params("Gain (R)"),
+        );
+
+        assert_eq!(params.get(0, false).gain.name(), "Gain (L)");
+        assert_eq!(params.get(1, false).gain.name(), "Gain (R)");
+        assert_eq!(params.get(1, true).gain.name(), "Gain (L)");
+    }
+}
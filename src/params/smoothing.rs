@@ -73,6 +73,19 @@ pub struct SmootherIter<'a, T: Smoothable> {
     smoother: &'a Smoother<T>,
 }
 
+/// A snapshot of a [`Smoother`]'s internal state, returned by [`Smoother::debug_info()`]. Useful
+/// for logging or displaying which parameters are still being smoothed while debugging a plugin.
+#[cfg(feature = "smoother_debug")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmootherDebugInfo<T> {
+    /// The value for the current sample, as returned by [`Smoother::previous_value()`].
+    pub current: T,
+    /// The value the smoother is smoothing towards.
+    pub target: T,
+    /// The number of steps of smoothing left to take, as returned by [`Smoother::steps_left()`].
+    pub steps_left: i32,
+}
+
 impl SmoothingStyle {
     /// Compute the number of steps to reach the target value based on the sample rate and this
     /// smoothing style's duration.
@@ -353,6 +366,18 @@ impl<T: Smoothable> Smoother<T> {
         T::from_f32(self.current.load(Ordering::Relaxed))
     }
 
+    /// Get a snapshot of the smoother's current value, target value, and the number of steps left.
+    /// Only available with the `smoother_debug` feature, since this is meant for diagnosing why a
+    /// parameter keeps smoothing and not for use in a plugin's DSP code.
+    #[cfg(feature = "smoother_debug")]
+    pub fn debug_info(&self) -> SmootherDebugInfo<T> {
+        SmootherDebugInfo {
+            current: self.previous_value(),
+            target: T::atomic_load(&self.target),
+            steps_left: self.steps_left(),
+        }
+    }
+
     /// Produce smoothed values for an entire block of audio. This is useful when iterating the same
     /// block of audio multiple times. For instance when summing voices for a synthesizer.
     /// `block_values[..block_len]` will be filled with the smoothed values. This is simply a
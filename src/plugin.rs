@@ -117,6 +117,12 @@ pub trait Plugin: Default + Send + 'static {
     /// to do offline processing.
     const HARD_REALTIME_ONLY: bool = false;
 
+    /// If enabled, the wrapper measures how long each [`process()`][Self::process()] call takes
+    /// compared to the duration of the audio being processed. The smoothed result can be read from
+    /// the editor through [`GuiContext::cpu_usage()`][crate::prelude::GuiContext::cpu_usage()].
+    /// This adds a small amount of overhead to every process call, so it's disabled by default.
+    const MEASURE_CPU_USAGE: bool = false;
+
     /// The plugin's SysEx message type if it supports sending or receiving MIDI SysEx messages, or
     /// `()` if it does not. This type can be a struct or enum wrapping around one or more message
     /// types, and the [`SysExMessage`] trait is then used to convert between this type and basic
@@ -201,6 +207,7 @@ pub trait Plugin: Default + Send + 'static {
     ///   times in rapid succession. It may thus be useful to check if the initialization work for
     ///   the current bufffer and audio IO configurations has already been performed first.
     /// - If the plugin fails to initialize for whatever reason, then this should return `false`.
+    ///   Implement [`try_initialize()`][Self::try_initialize()] instead to also tell the user why.
     fn initialize(
         &mut self,
         audio_io_layout: &AudioIOLayout,
@@ -210,6 +217,24 @@ pub trait Plugin: Default + Send + 'static {
         true
     }
 
+    /// The same as [`initialize()`][Self::initialize()], but with a reason for why the plugin could
+    /// not be initialized, like an unsupported sample rate. The reason is logged with
+    /// [`nih_error!()`][crate::nih_error!()], and the standalone wrapper includes it in its error
+    /// message. CLAP and VST3 don't have a way to pass this reason to the host. Only implement one
+    /// of these two functions. The default implementation calls `initialize()`.
+    fn try_initialize(
+        &mut self,
+        audio_io_layout: &AudioIOLayout,
+        buffer_config: &BufferConfig,
+        context: &mut impl InitContext<Self>,
+    ) -> Result<(), String> {
+        if self.initialize(audio_io_layout, buffer_config, context) {
+            Ok(())
+        } else {
+            Err(String::from("Plugin::initialize() returned false"))
+        }
+    }
+
     /// Clear internal state such as filters and envelopes. This is always called after
     /// [`initialize()`][Self::initialize()], and it may also be called at any other time from the
     /// audio thread. You should thus not do any allocations in this function.
@@ -243,6 +268,21 @@ pub trait Plugin: Default + Send + 'static {
         context: &mut impl ProcessContext<Self>,
     ) -> ProcessStatus;
 
+    /// The length of the plugin's tail in samples, or `None` if the plugin stops producing sound
+    /// as soon as its input becomes silent. Effects like reverbs and delays, or plugins with
+    /// linear-phase filters, should return how long they continue to produce output after the input
+    /// stops so the host keeps processing them, for instance when bouncing a track. This is
+    /// reported through CLAP's tail extension and VST3's `getTailSamples()`.
+    ///
+    /// This is queried after every [`process()`][Self::process()] call, so it should be cheap to
+    /// compute and it must not allocate. It does not change the status returned by `process()`, so
+    /// plugins returning [`ProcessStatus::Normal`] can still be put to sleep by the host. Returning
+    /// [`ProcessStatus::Tail`] or [`ProcessStatus::KeepAlive`] from `process()` takes precedence
+    /// over this value.
+    fn tail_length_samples(&self) -> Option<u32> {
+        None
+    }
+
     /// Called when the plugin is deactivated. The host will call
     /// [`initialize()`][Self::initialize()] again before the plugin resumes processing audio. These
     /// two functions will not be called when the host only temporarily stops processing audio. You
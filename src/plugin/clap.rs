@@ -20,11 +20,40 @@ pub trait ClapPlugin: Plugin {
     /// If set, this informs the host about the plugin's capabilities for polyphonic modulation.
     const CLAP_POLY_MODULATION_CONFIG: Option<PolyModulationConfig> = None;
 
+    /// If set, the host can query the names returned by [`note_names()`][Self::note_names()]
+    /// through the [note name
+    /// extension](https://github.com/free-audio/clap/blob/main/include/clap/ext/note-name.h) and
+    /// display them in its piano roll. This is useful for drum machines and multisamplers. The
+    /// extension is not exposed if this is not set.
+    const CLAP_NOTE_NAMES: bool = false;
+
     /// This function can be implemented to define plugin-specific [remote control
     /// pages](https://github.com/free-audio/clap/blob/main/include/clap/ext/draft/remote-controls.h)
     /// that the host can use to provide better hardware mapping for a plugin. See the linked
     /// extension for more information.
     fn remote_controls(&self, context: &mut impl RemoteControlsContext) {}
+
+    /// The names for individual notes if [`CLAP_NOTE_NAMES`][Self::CLAP_NOTE_NAMES] is set, like
+    /// the names of the sounds in a drum kit. This is called from the main thread whenever the host
+    /// queries the note names. When the names change, the plugin should call
+    /// `note_names_changed()` on the [`InitContext`][crate::prelude::InitContext],
+    /// [`ProcessContext`][crate::prelude::ProcessContext], or
+    /// [`GuiContext`][crate::prelude::GuiContext] so the host queries them again. Those calls don't
+    /// do anything for other plugin APIs, or if `CLAP_NOTE_NAMES` is not set.
+    fn note_names(&self) -> Vec<NoteName> {
+        Vec::new()
+    }
+}
+
+/// The name for a single note, as returned from [`ClapPlugin::note_names()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteName {
+    /// The name that should be displayed for the note.
+    pub name: String,
+    /// The MIDI note number this name applies to.
+    pub note: u8,
+    /// The MIDI channel this name applies to, or `None` if it applies to all channels.
+    pub channel: Option<u8>,
 }
 
 /// Configuration for the plugin's polyphonic modulation options, if it supports .
@@ -1,8 +1,36 @@
 //! General conversion functions and utilities.
 
+#[cfg(feature = "convolver")]
+mod convolver;
+mod envelope;
+mod fixed_block;
+mod lookahead;
+mod meter;
+mod param_mirror;
+mod pitch;
+mod redraw;
+pub mod saturation;
+mod shared_cache;
 mod stft;
 pub mod window;
 
+#[cfg(feature = "simd")]
+use std::simd::cmp::SimdPartialOrd;
+#[cfg(feature = "simd")]
+use std::simd::num::{SimdFloat, SimdInt, SimdUint};
+#[cfg(feature = "simd")]
+use std::simd::{LaneCount, Simd, StdFloat, SupportedLaneCount};
+
+#[cfg(feature = "convolver")]
+pub use convolver::Convolver;
+pub use envelope::{DetectionMode, EnvelopeFollower};
+pub use fixed_block::FixedBlockAdapter;
+pub use lookahead::Lookahead;
+pub use meter::PeakMeterLevels;
+pub use param_mirror::ParamMirror;
+pub use pitch::{Pitch, PitchDetector};
+pub use redraw::RedrawFlag;
+pub use shared_cache::SharedCache;
 pub use stft::StftHelper;
 
 pub const MINUS_INFINITY_DB: f32 = -100.0;
@@ -79,6 +107,125 @@ pub fn gain_to_db_fast_epsilon(gain: f32) -> f32 {
     f32::max(gain, MINUS_INFINITY_GAIN).ln() * CONVERSION_FACTOR
 }
 
+/// The same as [`db_to_gain()`], but for a SIMD vector of decibel values like an `f32x2`. This uses
+/// the standard library's vectorized `exp()`, so the results are accurate but this may not be much
+/// faster than converting the values one by one. See [`db_to_gain_fast_simd()`] for an
+/// approximation that's fully vectorized.
+#[cfg(feature = "simd")]
+#[inline]
+pub fn db_to_gain_simd<const N: usize>(dbs: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const CONVERSION_FACTOR: f32 = std::f32::consts::LN_10 / 20.0;
+    let gain = (dbs * Simd::splat(CONVERSION_FACTOR)).exp();
+
+    dbs.simd_gt(Simd::splat(MINUS_INFINITY_DB))
+        .select(gain, Simd::splat(0.0))
+}
+
+/// The same as [`gain_to_db()`], but for a SIMD vector of gain ratios like an `f32x2`. This uses
+/// the standard library's vectorized `ln()`. See [`gain_to_db_fast_simd()`] for an approximation
+/// that's fully vectorized.
+#[cfg(feature = "simd")]
+#[inline]
+pub fn gain_to_db_simd<const N: usize>(gain: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const CONVERSION_FACTOR: f32 = std::f32::consts::LOG10_E * 20.0;
+    gain.simd_max(Simd::splat(MINUS_INFINITY_GAIN)).ln() * Simd::splat(CONVERSION_FACTOR)
+}
+
+/// A fully vectorized approximation of [`db_to_gain_simd()`] using a polynomial approximation of
+/// `exp2()`. The relative error is below 1e-5 for the entire range of decibel values that can be
+/// represented as an `f32` gain ratio. Like [`db_to_gain_fast()`], this does not treat values
+/// below [`MINUS_INFINITY_DB`] as 0.0 gain.
+#[cfg(feature = "simd")]
+#[inline]
+pub fn db_to_gain_fast_simd<const N: usize>(dbs: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const CONVERSION_FACTOR: f32 = std::f32::consts::LOG2_10 / 20.0;
+    exp2_approx(dbs * Simd::splat(CONVERSION_FACTOR))
+}
+
+/// A fully vectorized approximation of [`gain_to_db_simd()`] using a polynomial approximation of
+/// `log2()`. The absolute error is below 1e-4 dB.
+#[cfg(feature = "simd")]
+#[inline]
+pub fn gain_to_db_fast_simd<const N: usize>(gain: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const CONVERSION_FACTOR: f32 = std::f32::consts::LOG10_2 * 20.0;
+    log2_approx(gain.simd_max(Simd::splat(MINUS_INFINITY_GAIN))) * Simd::splat(CONVERSION_FACTOR)
+}
+
+/// Approximate `2^x`. The exponent is split into an integer part, which is written directly to the
+/// result's exponent bits, and a fractional part in `[-0.5, 0.5]` that's approximated using a
+/// Taylor polynomial.
+#[cfg(feature = "simd")]
+#[inline]
+fn exp2_approx<const N: usize>(x: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // This keeps the integer part within the range of normal `f32` exponents
+    let x = x.simd_clamp(Simd::splat(-126.0), Simd::splat(126.0));
+    let integer_part = x.round();
+    let fractional_part = x - integer_part;
+
+    // These are the Taylor series coefficients for `2^x`, which are `ln(2)^n / n!`
+    let f = fractional_part;
+    let fractional_pow = Simd::splat(1.0)
+        + f * (Simd::splat(0.693_147_2)
+            + f * (Simd::splat(0.240_226_5)
+                + f * (Simd::splat(0.055_504_1)
+                    + f * (Simd::splat(0.009_618_1) + f * Simd::splat(0.001_333_4)))));
+    let integer_pow = Simd::<f32, N>::from_bits(
+        ((integer_part.cast::<i32>() + Simd::splat(127)) << Simd::splat(23)).cast::<u32>(),
+    );
+
+    fractional_pow * integer_pow
+}
+
+/// Approximate `log2(x)` for positive normal values. The value's exponent bits are used directly,
+/// and the logarithm of the mantissa is approximated using the series for `ln((1 + t) / (1 - t))`.
+#[cfg(feature = "simd")]
+#[inline]
+fn log2_approx<const N: usize>(x: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let bits = x.to_bits();
+    let exponent = (bits >> Simd::splat(23)).cast::<i32>() - Simd::splat(127);
+    let mantissa =
+        Simd::<f32, N>::from_bits((bits & Simd::splat(0x007f_ffff)) | Simd::splat(0x3f80_0000));
+
+    // The series converges much faster when the mantissa is centered around 1, so mantissas in
+    // `[sqrt(2), 2)` are halved and the exponent is incremented to compensate. Converting a mask
+    // to integers results in -1 for set lanes.
+    let needs_halving = mantissa.simd_ge(Simd::splat(std::f32::consts::SQRT_2));
+    let mantissa = needs_halving.select(mantissa * Simd::splat(0.5), mantissa);
+    let exponent = exponent - needs_halving.to_int();
+
+    let t = (mantissa - Simd::splat(1.0)) / (mantissa + Simd::splat(1.0));
+    let t2 = t * t;
+    let ln_mantissa = Simd::splat(2.0)
+        * t
+        * (Simd::splat(1.0)
+            + t2 * (Simd::splat(1.0 / 3.0)
+                + t2 * (Simd::splat(1.0 / 5.0) + t2 * Simd::splat(1.0 / 7.0))));
+
+    exponent.cast::<f32>() + (ln_mantissa * Simd::splat(std::f32::consts::LOG2_E))
+}
+
+/// The standard tuning reference used by [`midi_note_to_freq()`], [`f32_midi_note_to_freq()`], and
+/// [`freq_to_midi_note()`].
+pub const A4_FREQ: f32 = 440.0;
+
 /// Convert a MIDI note ID to a frequency at A4 = 440 Hz equal temperament and middle C = note 60 =
 /// C4.
 #[inline]
@@ -91,7 +238,14 @@ pub fn midi_note_to_freq(note: u8) -> f32 {
 /// cents.
 #[inline]
 pub fn f32_midi_note_to_freq(note: f32) -> f32 {
-    2.0f32.powf((note - 69.0) / 12.0) * 440.0
+    f32_midi_note_to_freq_with_a4(note, A4_FREQ)
+}
+
+/// The same as [`f32_midi_note_to_freq()`], but with a custom tuning reference. `a4_freq` is the
+/// frequency of MIDI note 69, A4.
+#[inline]
+pub fn f32_midi_note_to_freq_with_a4(note: f32, a4_freq: f32) -> f32 {
+    semitones_to_ratio(note - 69.0) * a4_freq
 }
 
 /// The inverse of [`f32_midi_note_to_freq()`]. This returns a fractional note number. Round to a
@@ -99,7 +253,28 @@ pub fn f32_midi_note_to_freq(note: f32) -> f32 {
 /// number of cents.
 #[inline]
 pub fn freq_to_midi_note(freq: f32) -> f32 {
-    ((freq / 440.0).log2() * 12.0) + 69.0
+    freq_to_midi_note_with_a4(freq, A4_FREQ)
+}
+
+/// The same as [`freq_to_midi_note()`], but with a custom tuning reference. `a4_freq` is the
+/// frequency of MIDI note 69, A4.
+#[inline]
+pub fn freq_to_midi_note_with_a4(freq: f32, a4_freq: f32) -> f32 {
+    ratio_to_semitones(freq / a4_freq) + 69.0
+}
+
+/// Convert an interval in (fractional) semitones to a frequency ratio in equal temperament. 12
+/// semitones results in a ratio of 2.0, and -12 semitones results in a ratio of 0.5.
+#[inline]
+pub fn semitones_to_ratio(semitones: f32) -> f32 {
+    2.0f32.powf(semitones / 12.0)
+}
+
+/// The inverse of [`semitones_to_ratio()`]. Converts a frequency ratio to a fractional number of
+/// semitones.
+#[inline]
+pub fn ratio_to_semitones(ratio: f32) -> f32 {
+    ratio.log2() * 12.0
 }
 
 #[cfg(test)]
@@ -193,4 +368,81 @@ mod tests {
             approx::assert_relative_eq!(gain_to_db(-2.0), gain_to_db_fast(-2.0), epsilon = 1e-7);
         }
     }
+
+    #[cfg(feature = "simd")]
+    mod simd_db_gain_conversion {
+        use super::super::*;
+        use std::simd::f32x2;
+
+        #[test]
+        fn test_db_to_gain_simd() {
+            let gain = db_to_gain_simd(f32x2::from_array([3.0, -100.0]));
+            approx::assert_relative_eq!(gain[0], db_to_gain(3.0), epsilon = 1e-6);
+            assert_eq!(gain[1], 0.0);
+        }
+
+        #[test]
+        fn test_gain_to_db_simd() {
+            let dbs = gain_to_db_simd(f32x2::from_array([0.25, 0.0]));
+            approx::assert_relative_eq!(dbs[0], gain_to_db(0.25), epsilon = 1e-5);
+            approx::assert_relative_eq!(dbs[1], MINUS_INFINITY_DB, epsilon = 1e-4);
+        }
+
+        #[test]
+        fn test_db_to_gain_fast_simd_error() {
+            for i in -1500..=1500 {
+                let db = i as f32 / 10.0;
+                let gain = db_to_gain_fast_simd(f32x2::splat(db))[0];
+                approx::assert_relative_eq!(gain, db_to_gain_fast(db), max_relative = 1e-5);
+            }
+        }
+
+        #[test]
+        fn test_gain_to_db_fast_simd_error() {
+            for i in 0..=10000 {
+                let gain = i as f32 / 1000.0;
+                let db = gain_to_db_fast_simd(f32x2::splat(gain))[0];
+                approx::assert_abs_diff_eq!(db, gain_to_db(gain), epsilon = 1e-4);
+            }
+        }
+    }
+
+    mod note_freq_conversion {
+        use super::super::*;
+
+        #[test]
+        fn test_midi_note_to_freq_a4() {
+            assert_eq!(midi_note_to_freq(69), 440.0);
+            assert_eq!(midi_note_to_freq(81), 880.0);
+            assert_eq!(midi_note_to_freq(57), 220.0);
+        }
+
+        #[test]
+        fn test_midi_note_to_freq_middle_c() {
+            approx::assert_relative_eq!(midi_note_to_freq(60), 261.62558, epsilon = 1e-3);
+        }
+
+        #[test]
+        fn test_freq_to_midi_note() {
+            assert_eq!(freq_to_midi_note(440.0), 69.0);
+            assert_eq!(freq_to_midi_note(880.0), 81.0);
+            approx::assert_relative_eq!(freq_to_midi_note(261.62558), 60.0, epsilon = 1e-4);
+        }
+
+        #[test]
+        fn test_custom_a4() {
+            assert_eq!(f32_midi_note_to_freq_with_a4(69.0, 432.0), 432.0);
+            assert_eq!(f32_midi_note_to_freq_with_a4(81.0, 432.0), 864.0);
+            assert_eq!(freq_to_midi_note_with_a4(864.0, 432.0), 81.0);
+        }
+
+        #[test]
+        fn test_semitones_ratio() {
+            assert_eq!(semitones_to_ratio(12.0), 2.0);
+            assert_eq!(semitones_to_ratio(-12.0), 0.5);
+            approx::assert_relative_eq!(semitones_to_ratio(7.0), 1.4983071, epsilon = 1e-6);
+            assert_eq!(ratio_to_semitones(2.0), 12.0);
+            approx::assert_relative_eq!(ratio_to_semitones(1.4983071), 7.0, epsilon = 1e-5);
+        }
+    }
 }
@@ -0,0 +1,444 @@
+//! Partitioned FFT convolution for long impulse responses.
+
+use realfft::num_complex::Complex32;
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+use std::sync::Arc;
+
+use super::stft::StftInputMut;
+
+/// Convolves audio with an impulse response using uniformly partitioned overlap-add FFT
+/// convolution. The impulse response is split into partitions of `block_size` samples, and the
+/// input is processed in blocks of the same size. This makes it possible to convolve with long
+/// impulse responses like reverbs or cabinet simulations at a fraction of the cost of direct
+/// convolution. Every channel is convolved with the same impulse response.
+///
+/// The convolution introduces exactly one block of latency, so the plugin needs to report
+/// [`latency_samples()`][Self::latency_samples()] to the host using
+/// [`InitContext::set_latency_samples()`][crate::prelude::InitContext::set_latency_samples()].
+/// Smaller block sizes reduce the latency at the cost of needing more partitions and thus more
+/// CPU time. Powers of two are the most efficient block sizes. The latency can also be removed
+/// entirely by convolving the first partition directly, see
+/// [`with_direct_first_partition()`][Self::with_direct_first_partition()].
+pub struct Convolver {
+    block_size: usize,
+    /// The first `block_size` samples of the impulse response, if the first partition is convolved
+    /// directly. The partitioned FFT convolution then only handles the rest of the impulse
+    /// response. See [`with_direct_first_partition()`][Self::with_direct_first_partition()].
+    direct_impulse_response: Option<Vec<f32>>,
+    /// The number of partitions in the current impulse response. This is at most
+    /// `ir_partitions.len()`.
+    num_active_partitions: usize,
+
+    /// The forward FFT for `2 * block_size` real samples.
+    r2c_plan: Arc<dyn RealToComplex<f32>>,
+    /// The inverse FFT for `2 * block_size` real samples.
+    c2r_plan: Arc<dyn ComplexToReal<f32>>,
+
+    /// The spectra of the zero padded impulse response partitions, normalized so the inverse FFT
+    /// does not need to be normalized anymore.
+    ir_partitions: Vec<Vec<Complex32>>,
+    /// The spectra of the most recent zero padded input blocks for every channel, indexed by
+    /// `[channel_idx][partition_idx]`. This is used as a ring buffer, with the most recent block
+    /// being stored at `input_spectra_pos`.
+    input_spectra: Vec<Vec<Vec<Complex32>>>,
+    /// The index in the inner vectors of `input_spectra` the most recent input spectrum is stored
+    /// at.
+    input_spectra_pos: usize,
+
+    /// The input samples for the block that is currently being filled, one vector per channel.
+    /// The samples after `current_pos` are still from the previous block, so this also serves as
+    /// the input history for the direct convolution.
+    input_blocks: Vec<Vec<f32>>,
+    /// The output samples for the last processed block. These are written back to the buffer while
+    /// the next input block is being filled.
+    output_blocks: Vec<Vec<f32>>,
+    /// The second half of the last convolved block for every channel. This is added to the next
+    /// block's output.
+    overlap_blocks: Vec<Vec<f32>>,
+    /// The current position in `input_blocks` and `output_blocks`.
+    current_pos: usize,
+
+    /// Scratch buffers for the FFT operations. These are stored here so processing audio doesn't
+    /// need to allocate.
+    real_scratch: Vec<f32>,
+    complex_scratch: Vec<Complex32>,
+    spectrum_accumulator: Vec<Complex32>,
+    r2c_scratch: Vec<Complex32>,
+    c2r_scratch: Vec<Complex32>,
+}
+
+impl Convolver {
+    /// Create a convolver for `num_channels` channels that processes audio in blocks of
+    /// `block_size` samples, and that can hold impulse responses of up to
+    /// `max_impulse_response_len` samples. The impulse response is initially silent, so
+    /// [`set_impulse_response()`][Self::set_impulse_response()] needs to be called before this
+    /// produces any output. This allocates, so it should be called from the plugin's
+    /// `initialize()` function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_channels == 0 || block_size == 0 || max_impulse_response_len == 0`.
+    pub fn new(num_channels: usize, block_size: usize, max_impulse_response_len: usize) -> Self {
+        assert_ne!(num_channels, 0);
+        assert_ne!(block_size, 0);
+        assert_ne!(max_impulse_response_len, 0);
+
+        let fft_size = block_size * 2;
+        let num_bins = block_size + 1;
+        let num_partitions = max_impulse_response_len.div_ceil(block_size);
+
+        let mut planner = RealFftPlanner::new();
+        let r2c_plan = planner.plan_fft_forward(fft_size);
+        let c2r_plan = planner.plan_fft_inverse(fft_size);
+        let r2c_scratch = r2c_plan.make_scratch_vec();
+        let c2r_scratch = c2r_plan.make_scratch_vec();
+
+        Self {
+            block_size,
+            direct_impulse_response: None,
+            num_active_partitions: 0,
+
+            r2c_plan,
+            c2r_plan,
+
+            ir_partitions: vec![vec![Complex32::default(); num_bins]; num_partitions],
+            input_spectra: vec![
+                vec![vec![Complex32::default(); num_bins]; num_partitions];
+                num_channels
+            ],
+            input_spectra_pos: 0,
+
+            input_blocks: vec![vec![0.0; block_size]; num_channels],
+            output_blocks: vec![vec![0.0; block_size]; num_channels],
+            overlap_blocks: vec![vec![0.0; block_size]; num_channels],
+            current_pos: 0,
+
+            real_scratch: vec![0.0; fft_size],
+            complex_scratch: vec![Complex32::default(); num_bins],
+            spectrum_accumulator: vec![Complex32::default(); num_bins],
+            r2c_scratch,
+            c2r_scratch,
+        }
+    }
+
+    /// Convolve the first `block_size` samples of the impulse response directly instead of using
+    /// FFTs. This removes the convolver's latency entirely, but the direct convolution's CPU usage
+    /// grows linearly with the block size, so this should only be used with small block sizes. Like
+    /// [`new()`][Self::new()], this allocates and it resets the impulse response.
+    pub fn with_direct_first_partition(mut self) -> Self {
+        self.direct_impulse_response = Some(vec![0.0; self.block_size]);
+        self.num_active_partitions = 0;
+        self.reset();
+
+        self
+    }
+
+    /// The number of channels this convolver was configured for.
+    pub fn num_channels(&self) -> usize {
+        self.input_blocks.len()
+    }
+
+    /// The size of the blocks and impulse response partitions.
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// The amount of latency introduced by the convolution. This is equal to the block size, or
+    /// zero if the first partition is convolved directly.
+    pub fn latency_samples(&self) -> u32 {
+        if self.direct_impulse_response.is_some() {
+            0
+        } else {
+            self.block_size as u32
+        }
+    }
+
+    /// Change the impulse response. This does not allocate, so it can be called from the audio
+    /// thread, but it does need to compute an FFT for every partition. The audio that has already
+    /// been processed is not affected, and the new impulse response is used starting from the next
+    /// block. An empty impulse response silences the output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the impulse response is longer than the maximum length passed to
+    /// [`new()`][Self::new()].
+    pub fn set_impulse_response(&mut self, impulse_response: &[f32]) {
+        assert!(
+            impulse_response.len().div_ceil(self.block_size) <= self.ir_partitions.len(),
+            "The impulse response is longer than the convolver's maximum impulse response length"
+        );
+
+        // With direct convolution the first partition is not part of the FFT convolution. The
+        // FFT convolution's one block of latency then lines up with the second partition.
+        let fft_impulse_response = match &mut self.direct_impulse_response {
+            Some(direct_impulse_response) => {
+                let (first_partition, rest) =
+                    impulse_response.split_at(self.block_size.min(impulse_response.len()));
+                direct_impulse_response.fill(0.0);
+                direct_impulse_response[..first_partition.len()].copy_from_slice(first_partition);
+
+                rest
+            }
+            None => impulse_response,
+        };
+        let num_partitions = fft_impulse_response.len().div_ceil(self.block_size);
+
+        // The inverse FFT is not normalized, so the normalization is folded into the impulse
+        // response instead
+        let normalization_factor = (self.real_scratch.len() as f32).recip();
+        for (partition, ir_partition) in fft_impulse_response
+            .chunks(self.block_size)
+            .zip(self.ir_partitions.iter_mut())
+        {
+            self.real_scratch.fill(0.0);
+            for (scratch_sample, ir_sample) in self.real_scratch.iter_mut().zip(partition) {
+                *scratch_sample = *ir_sample * normalization_factor;
+            }
+
+            self.r2c_plan
+                .process_with_scratch(&mut self.real_scratch, ir_partition, &mut self.r2c_scratch)
+                .unwrap();
+        }
+
+        self.num_active_partitions = num_partitions;
+    }
+
+    /// Clear the buffered input and output. The next block of output will be silent. This should
+    /// be called from the plugin's `reset()` function.
+    pub fn reset(&mut self) {
+        for block in self
+            .input_blocks
+            .iter_mut()
+            .chain(&mut self.output_blocks)
+            .chain(&mut self.overlap_blocks)
+        {
+            block.fill(0.0);
+        }
+        for spectrum in self.input_spectra.iter_mut().flatten() {
+            spectrum.fill(Complex32::default());
+        }
+
+        self.input_spectra_pos = 0;
+        self.current_pos = 0;
+    }
+
+    /// Convolve the audio in `buffer` with the impulse response in place. The output is delayed by
+    /// [`latency_samples()`][Self::latency_samples()] samples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer does not have the same number of channels as this convolver.
+    pub fn process<B>(&mut self, buffer: &mut B)
+    where
+        B: StftInputMut + ?Sized,
+    {
+        assert_eq!(buffer.num_channels(), self.input_blocks.len());
+
+        let buffer_len = buffer.num_samples();
+        let mut already_processed_samples = 0;
+        while already_processed_samples < buffer_len {
+            let samples_to_process =
+                (self.block_size - self.current_pos).min(buffer_len - already_processed_samples);
+
+            for (channel_idx, (input_block, output_block)) in self
+                .input_blocks
+                .iter_mut()
+                .zip(&self.output_blocks)
+                .enumerate()
+            {
+                for offset in 0..samples_to_process {
+                    // SAFETY: `channel_idx` is bounded by the assertion above, and the sample
+                    //         index is bounded by `buffer_len`
+                    let sample = unsafe {
+                        buffer.get_sample_unchecked_mut(
+                            channel_idx,
+                            already_processed_samples + offset,
+                        )
+                    };
+                    let pos = self.current_pos + offset;
+                    input_block[pos] = *sample;
+                    *sample = output_block[pos];
+                    if let Some(direct_impulse_response) = &self.direct_impulse_response {
+                        *sample +=
+                            direct_convolution_sample(direct_impulse_response, input_block, pos);
+                    }
+                }
+            }
+
+            already_processed_samples += samples_to_process;
+            self.current_pos += samples_to_process;
+
+            if self.current_pos == self.block_size {
+                self.process_blocks();
+                self.current_pos = 0;
+            }
+        }
+    }
+
+    /// Convolve the filled input blocks and write the results to the output blocks.
+    fn process_blocks(&mut self) {
+        let num_partitions = self.ir_partitions.len();
+        self.input_spectra_pos = (self.input_spectra_pos + 1) % num_partitions;
+
+        for (((input_block, output_block), overlap_block), input_spectra) in self
+            .input_blocks
+            .iter()
+            .zip(self.output_blocks.iter_mut())
+            .zip(self.overlap_blocks.iter_mut())
+            .zip(self.input_spectra.iter_mut())
+        {
+            self.real_scratch[..self.block_size].copy_from_slice(input_block);
+            self.real_scratch[self.block_size..].fill(0.0);
+            self.r2c_plan
+                .process_with_scratch(
+                    &mut self.real_scratch,
+                    &mut input_spectra[self.input_spectra_pos],
+                    &mut self.r2c_scratch,
+                )
+                .unwrap();
+
+            // Partition `n` of the impulse response is applied to the input block from `n` blocks
+            // ago
+            self.spectrum_accumulator.fill(Complex32::default());
+            for (partition_idx, ir_partition) in self
+                .ir_partitions
+                .iter()
+                .take(self.num_active_partitions)
+                .enumerate()
+            {
+                let input_spectrum = &input_spectra
+                    [(self.input_spectra_pos + num_partitions - partition_idx) % num_partitions];
+                for ((accumulator_bin, input_bin), ir_bin) in self
+                    .spectrum_accumulator
+                    .iter_mut()
+                    .zip(input_spectrum)
+                    .zip(ir_partition)
+                {
+                    *accumulator_bin += input_bin * ir_bin;
+                }
+            }
+
+            // The inverse FFT modifies its input, so the accumulated spectrum is copied first
+            self.complex_scratch
+                .copy_from_slice(&self.spectrum_accumulator);
+            self.c2r_plan
+                .process_with_scratch(
+                    &mut self.complex_scratch,
+                    &mut self.real_scratch,
+                    &mut self.c2r_scratch,
+                )
+                .unwrap();
+
+            // The second half of the convolved block overlaps with the next block
+            for ((output_sample, overlap_sample), convolved_sample) in output_block
+                .iter_mut()
+                .zip(overlap_block.iter())
+                .zip(&self.real_scratch[..self.block_size])
+            {
+                *output_sample = convolved_sample + overlap_sample;
+            }
+            overlap_block.copy_from_slice(&self.real_scratch[self.block_size..]);
+        }
+    }
+}
+
+/// Compute a single output sample by directly convolving `impulse_response` with the input samples
+/// in `input_block`, where the most recent input sample is stored at `pos`. `input_block` is a ring
+/// buffer of the last `impulse_response.len()` input samples.
+fn direct_convolution_sample(impulse_response: &[f32], input_block: &[f32], pos: usize) -> f32 {
+    let (newer_samples, older_samples) = input_block.split_at(pos + 1);
+    newer_samples
+        .iter()
+        .rev()
+        .chain(older_samples.iter().rev())
+        .zip(impulse_response)
+        .map(|(input_sample, ir_sample)| input_sample * ir_sample)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Convolve `input` with `impulse_response` without using FFTs.
+    fn direct_convolution(input: &[f32], impulse_response: &[f32]) -> Vec<f32> {
+        (0..input.len())
+            .map(|output_idx| {
+                impulse_response
+                    .iter()
+                    .enumerate()
+                    .take(output_idx + 1)
+                    .map(|(ir_idx, ir_sample)| ir_sample * input[output_idx - ir_idx])
+                    .sum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_direct_convolution() {
+        const HOST_BLOCK_SIZES: [usize; 6] = [7, 64, 1, 29, 100, 13];
+
+        let impulse_response: Vec<f32> = (0..37)
+            .map(|i| (i as f32 * 0.7).sin() * 0.9f32.powi(i))
+            .collect();
+        let total_len: usize = HOST_BLOCK_SIZES.iter().sum();
+        let input: Vec<f32> = (0..total_len)
+            .map(|i| (i as f32 * 0.3).sin() + (i as f32 * 1.9).cos() * 0.5)
+            .collect();
+        let expected = direct_convolution(&input, &impulse_response);
+
+        // This covers impulse responses that are both longer and shorter than a single partition
+        for (block_size, direct_first_partition) in [8, 16, 64]
+            .into_iter()
+            .flat_map(|block_size| [(block_size, false), (block_size, true)])
+        {
+            let mut convolver = Convolver::new(2, block_size, impulse_response.len());
+            let latency = if direct_first_partition {
+                convolver = convolver.with_direct_first_partition();
+                0
+            } else {
+                block_size
+            };
+            convolver.set_impulse_response(&impulse_response);
+            assert_eq!(convolver.latency_samples(), latency as u32);
+
+            let mut left = input.clone();
+            let mut right: Vec<f32> = input.iter().map(|sample| -sample).collect();
+            let mut start = 0;
+            for host_block_size in HOST_BLOCK_SIZES {
+                let end = start + host_block_size;
+                let mut slices = [&mut left[start..end], &mut right[start..end]];
+                convolver.process(&mut slices[..]);
+
+                start = end;
+            }
+
+            for (sample_idx, (left_sample, right_sample)) in left.iter().zip(&right).enumerate() {
+                let expected_sample = sample_idx
+                    .checked_sub(latency)
+                    .map_or(0.0, |delayed_idx| expected[delayed_idx]);
+                assert!(
+                    (left_sample - expected_sample).abs() < 1e-4,
+                    "Block size {block_size}, direct first partition {direct_first_partition}, \
+                     sample {sample_idx}: {left_sample} != {expected_sample}"
+                );
+                assert!((right_sample + expected_sample).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn reset_clears_tail() {
+        let mut convolver = Convolver::new(1, 4, 12);
+        convolver.set_impulse_response(&[1.0; 12]);
+
+        let mut samples = [1.0; 8];
+        convolver.process(&mut [&mut samples[..]][..]);
+        assert_eq!(samples[..4], [0.0; 4]);
+
+        convolver.reset();
+        let mut samples = [0.0; 16];
+        convolver.process(&mut [&mut samples[..]][..]);
+        assert_eq!(samples, [0.0; 16]);
+    }
+}
@@ -0,0 +1,224 @@
+//! Envelope followers for dynamics processing.
+
+use crate::buffer::ChannelSamples;
+
+/// How an [`EnvelopeFollower`] measures the input signal's level.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DetectionMode {
+    /// Follow the rectified signal. Reacts quickly to transients.
+    #[default]
+    Peak,
+    /// Follow the signal's mean square and return its square root. This is closer to perceived
+    /// loudness, but reacts more slowly to transients.
+    Rms,
+}
+
+/// An envelope follower with separate attack and release times, as used in compressors and gates.
+/// The attack time is used while the input's level rises above the envelope and the release time
+/// is used while it falls below. Both times are the one-pole filter's time constant, so after the
+/// attack time has elapsed a step from 0 to 1 will have reached 63.2% of its final value.
+#[derive(Debug, Clone)]
+pub struct EnvelopeFollower {
+    mode: DetectionMode,
+    sample_rate: f32,
+    attack_ms: f32,
+    release_ms: f32,
+
+    /// For each sample, the envelope becomes `(state * t) + (input * (1.0 - t))`. This is `t`
+    /// while the input is above the envelope.
+    attack_retain_t: f32,
+    /// `attack_retain_t`, but for when the input is below the envelope.
+    release_retain_t: f32,
+
+    /// The filter's state. This is the squared envelope when using [`DetectionMode::Rms`].
+    state: f32,
+}
+
+impl EnvelopeFollower {
+    /// Create a new envelope follower for a sample rate with attack and release times in
+    /// milliseconds. A time of 0 ms makes that stage instant.
+    pub fn new(sample_rate: f32, attack_ms: f32, release_ms: f32, mode: DetectionMode) -> Self {
+        let mut envelope_follower = Self {
+            mode,
+            sample_rate,
+            attack_ms,
+            release_ms,
+
+            attack_retain_t: 0.0,
+            release_retain_t: 0.0,
+
+            state: 0.0,
+        };
+        envelope_follower.update_coefficients();
+
+        envelope_follower
+    }
+
+    /// Change the sample rate. The attack and release times stay the same.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.update_coefficients();
+    }
+
+    /// Change the attack time in milliseconds.
+    pub fn set_attack_ms(&mut self, attack_ms: f32) {
+        self.attack_ms = attack_ms;
+        self.update_coefficients();
+    }
+
+    /// Change the release time in milliseconds.
+    pub fn set_release_ms(&mut self, release_ms: f32) {
+        self.release_ms = release_ms;
+        self.update_coefficients();
+    }
+
+    /// Change the detection mode. This resets the envelope since the two modes store their state
+    /// differently.
+    pub fn set_mode(&mut self, mode: DetectionMode) {
+        if mode != self.mode {
+            self.mode = mode;
+            self.reset();
+        }
+    }
+
+    /// Reset the envelope back to zero.
+    pub fn reset(&mut self) {
+        self.state = 0.0;
+    }
+
+    /// Return the current/previously returned envelope value.
+    pub fn current(&self) -> f32 {
+        match self.mode {
+            DetectionMode::Peak => self.state,
+            DetectionMode::Rms => self.state.sqrt(),
+        }
+    }
+
+    /// Process a single sample and return the new envelope value.
+    #[inline]
+    pub fn process(&mut self, sample: f32) -> f32 {
+        self.process_rectified(sample.abs())
+    }
+
+    /// Process a single sample for all channels, returning the new envelope value. The channels are
+    /// linked by following the loudest channel, so the same envelope can be applied to all
+    /// channels without shifting the stereo image.
+    #[inline]
+    pub fn process_linked(&mut self, channel_samples: &ChannelSamples) -> f32 {
+        self.process_rectified(channel_samples.max_abs())
+    }
+
+    /// Advance the envelope using an already rectified input level.
+    #[inline]
+    fn process_rectified(&mut self, level: f32) -> f32 {
+        let input = match self.mode {
+            DetectionMode::Peak => level,
+            DetectionMode::Rms => level * level,
+        };
+
+        let t = if input > self.state {
+            self.attack_retain_t
+        } else {
+            self.release_retain_t
+        };
+        self.state = (self.state * t) + (input * (1.0 - t));
+
+        self.current()
+    }
+
+    fn update_coefficients(&mut self) {
+        self.attack_retain_t = retain_t(self.sample_rate, self.attack_ms);
+        self.release_retain_t = retain_t(self.sample_rate, self.release_ms);
+    }
+}
+
+/// The amount of the previous filter state retained each sample for a one-pole filter with a time
+/// constant of `time_ms` milliseconds.
+fn retain_t(sample_rate: f32, time_ms: f32) -> f32 {
+    if time_ms <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / (time_ms / 1000.0 * sample_rate)).exp()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+
+    const SAMPLE_RATE: f32 = 1000.0;
+
+    #[test]
+    fn attack_time_constant() {
+        // At 1 kHz, 10 ms is 10 samples
+        let mut envelope_follower =
+            EnvelopeFollower::new(SAMPLE_RATE, 10.0, 100.0, DetectionMode::Peak);
+        let mut envelope = 0.0;
+        for _ in 0..10 {
+            envelope = envelope_follower.process(1.0);
+        }
+
+        approx::assert_relative_eq!(envelope, 1.0 - (-1.0f32).exp(), epsilon = 1e-4);
+    }
+
+    #[test]
+    fn release_time_constant() {
+        let mut envelope_follower =
+            EnvelopeFollower::new(SAMPLE_RATE, 0.0, 10.0, DetectionMode::Peak);
+        assert_eq!(envelope_follower.process(-1.0), 1.0);
+
+        let mut envelope = 0.0;
+        for _ in 0..10 {
+            envelope = envelope_follower.process(0.0);
+        }
+
+        approx::assert_relative_eq!(envelope, (-1.0f32).exp(), epsilon = 1e-4);
+    }
+
+    #[test]
+    fn sample_rate_independent() {
+        let mut slow = EnvelopeFollower::new(SAMPLE_RATE, 10.0, 10.0, DetectionMode::Peak);
+        let mut fast = EnvelopeFollower::new(SAMPLE_RATE * 4.0, 10.0, 10.0, DetectionMode::Peak);
+        for _ in 0..10 {
+            slow.process(1.0);
+        }
+        for _ in 0..40 {
+            fast.process(1.0);
+        }
+
+        approx::assert_relative_eq!(slow.current(), fast.current(), epsilon = 1e-4);
+    }
+
+    #[test]
+    fn rms_square_wave() {
+        let mut peak = EnvelopeFollower::new(SAMPLE_RATE, 1.0, 1000.0, DetectionMode::Peak);
+        let mut rms = EnvelopeFollower::new(SAMPLE_RATE, 1000.0, 1000.0, DetectionMode::Rms);
+        for i in 0..20000 {
+            let sample = if i % 4 < 2 { 0.5 } else { -0.5 };
+            peak.process(sample);
+            rms.process(sample);
+        }
+
+        approx::assert_relative_eq!(peak.current(), 0.5, epsilon = 1e-3);
+        approx::assert_relative_eq!(rms.current(), 0.5, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn linked_follows_loudest_channel() {
+        let mut left = vec![0.25; 4];
+        let mut right = vec![-0.75; 4];
+        let mut buffer = Buffer::default();
+        unsafe {
+            buffer.set_slices(4, |output_slices| {
+                *output_slices = vec![&mut left, &mut right];
+            })
+        };
+
+        let mut envelope_follower =
+            EnvelopeFollower::new(SAMPLE_RATE, 0.0, 0.0, DetectionMode::Peak);
+        for channel_samples in buffer.iter_samples() {
+            assert_eq!(envelope_follower.process_linked(&channel_samples), 0.75);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Processing audio in fixed size blocks regardless of the host's buffer sizes.
+
+use super::stft::StftInputMut;
+
+/// Buffers audio so it can be processed in blocks of a constant size, even when the host sends
+/// buffers of varying sizes. This is useful for algorithms that can only work on a fixed number of
+/// samples at a time, like FFT based processing without overlap. Every
+/// [`process()`][Self::process()] call copies the input to an internal block and writes the
+/// previously processed block back to the buffer. Whenever the internal block has been filled, the
+/// callback is run to process it in place.
+///
+/// This introduces exactly one block of latency, so the plugin needs to report
+/// [`latency_samples()`][Self::latency_samples()] to the host using
+/// [`InitContext::set_latency_samples()`][crate::prelude::InitContext::set_latency_samples()]. The
+/// first block of output after creating the adapter or calling [`reset()`][Self::reset()] is
+/// silent. Samples from a partially filled block are kept until the next `process()` call fills up
+/// the rest of the block.
+pub struct FixedBlockAdapter {
+    /// The input samples for the block that is currently being filled, one vector per channel.
+    input_blocks: Vec<Vec<f32>>,
+    /// The last processed block. These samples are written back to the host's buffer while the
+    /// next block is being filled.
+    output_blocks: Vec<Vec<f32>>,
+
+    /// The current position in both blocks. Whenever this reaches the block size, the input
+    /// block is processed and the two blocks are swapped.
+    current_pos: usize,
+}
+
+impl FixedBlockAdapter {
+    /// Create an adapter for `num_channels` channels that processes audio in blocks of `block_size`
+    /// samples. This allocates, so it should be called from the plugin's `initialize()` function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_channels == 0 || block_size == 0`.
+    pub fn new(num_channels: usize, block_size: usize) -> Self {
+        assert_ne!(num_channels, 0);
+        assert_ne!(block_size, 0);
+
+        Self {
+            input_blocks: vec![vec![0.0; block_size]; num_channels],
+            output_blocks: vec![vec![0.0; block_size]; num_channels],
+
+            current_pos: 0,
+        }
+    }
+
+    /// The number of channels this adapter was configured for.
+    pub fn num_channels(&self) -> usize {
+        self.input_blocks.len()
+    }
+
+    /// The size of the blocks passed to the process callback.
+    pub fn block_size(&self) -> usize {
+        self.input_blocks[0].len()
+    }
+
+    /// The amount of latency introduced when processing audio through this adapter. This is always
+    /// equal to the block size.
+    pub fn latency_samples(&self) -> u32 {
+        self.block_size() as u32
+    }
+
+    /// Clear the buffered input and output. The next block of output will be silent. This should
+    /// be called from the plugin's `reset()` function.
+    pub fn reset(&mut self) {
+        for block in self.input_blocks.iter_mut().chain(&mut self.output_blocks) {
+            block.fill(0.0);
+        }
+        self.current_pos = 0;
+    }
+
+    /// Process the audio in `buffer` in fixed size blocks. `process_cb` receives one vector per
+    /// channel, each containing exactly [`block_size()`][Self::block_size()] samples, and it should
+    /// process those samples in place. The vectors must not be resized. The callback may be called
+    /// zero or more times depending on the size of `buffer`, and the processed samples are written
+    /// back to `buffer` one block later.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer does not have the same number of channels as this adapter.
+    pub fn process<B, F>(&mut self, buffer: &mut B, mut process_cb: F)
+    where
+        B: StftInputMut + ?Sized,
+        F: FnMut(&mut [Vec<f32>]),
+    {
+        assert_eq!(buffer.num_channels(), self.input_blocks.len());
+
+        let buffer_len = buffer.num_samples();
+        let block_size = self.block_size();
+        let mut already_processed_samples = 0;
+        while already_processed_samples < buffer_len {
+            let samples_to_process =
+                (block_size - self.current_pos).min(buffer_len - already_processed_samples);
+
+            // Copy the input from `buffer` to the input block while copying the last block's
+            // result back to `buffer`
+            for (channel_idx, (input_block, output_block)) in self
+                .input_blocks
+                .iter_mut()
+                .zip(&self.output_blocks)
+                .enumerate()
+            {
+                for offset in 0..samples_to_process {
+                    // SAFETY: `channel_idx` is bounded by the assertion above, and the sample
+                    //         index is bounded by `buffer_len`
+                    let sample = unsafe {
+                        buffer.get_sample_unchecked_mut(
+                            channel_idx,
+                            already_processed_samples + offset,
+                        )
+                    };
+                    input_block[self.current_pos + offset] = *sample;
+                    *sample = output_block[self.current_pos + offset];
+                }
+            }
+
+            already_processed_samples += samples_to_process;
+            self.current_pos += samples_to_process;
+
+            // The processed input block becomes the next output block, and the old output block has
+            // already been written back so it can be reused for the next input
+            if self.current_pos == block_size {
+                process_cb(&mut self.input_blocks);
+                nih_debug_assert!(
+                    self.input_blocks
+                        .iter()
+                        .all(|block| block.len() == block_size),
+                    "The process callback must not resize the blocks"
+                );
+
+                std::mem::swap(&mut self.input_blocks, &mut self.output_blocks);
+                self.current_pos = 0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prime_sized_host_blocks() {
+        const BLOCK_SIZE: usize = 16;
+        const HOST_BLOCK_SIZES: [usize; 8] = [7, 13, 1, 29, 3, 17, 11, 19];
+
+        let mut adapter = FixedBlockAdapter::new(2, BLOCK_SIZE);
+        assert_eq!(adapter.latency_samples(), BLOCK_SIZE as u32);
+
+        let total_len: usize = HOST_BLOCK_SIZES.iter().sum();
+        let mut left: Vec<f32> = (1..=total_len).map(|i| i as f32).collect();
+        let mut right: Vec<f32> = left.iter().map(|sample| -sample).collect();
+        let expected_left: Vec<f32> = (0..total_len)
+            .map(|i| i.checked_sub(BLOCK_SIZE).map_or(0.0, |i| left[i] * 2.0))
+            .collect();
+        let expected_right: Vec<f32> = expected_left.iter().map(|sample| -sample).collect();
+
+        let mut num_blocks = 0;
+        let mut start = 0;
+        for host_block_size in HOST_BLOCK_SIZES {
+            let end = start + host_block_size;
+            let mut slices = [&mut left[start..end], &mut right[start..end]];
+            adapter.process(&mut slices[..], |blocks| {
+                assert_eq!(blocks.len(), 2);
+                for block in blocks {
+                    assert_eq!(block.len(), BLOCK_SIZE);
+                    for sample in block.iter_mut() {
+                        *sample *= 2.0;
+                    }
+                }
+
+                num_blocks += 1;
+            });
+
+            start = end;
+        }
+
+        assert_eq!(num_blocks, total_len / BLOCK_SIZE);
+        assert_eq!(left, expected_left);
+        assert_eq!(right, expected_right);
+    }
+
+    #[test]
+    fn reset_clears_partial_blocks() {
+        let mut adapter = FixedBlockAdapter::new(1, 4);
+        let mut samples = [1.0; 6];
+        adapter.process(&mut [&mut samples[..]][..], |_| ());
+        assert_eq!(samples, [0.0, 0.0, 0.0, 0.0, 1.0, 1.0]);
+
+        adapter.reset();
+        let mut samples = [0.5; 6];
+        adapter.process(&mut [&mut samples[..]][..], |_| ());
+        assert_eq!(samples, [0.0, 0.0, 0.0, 0.0, 0.5, 0.5]);
+    }
+}
@@ -0,0 +1,183 @@
+//! Lookahead delays for dynamics processors.
+
+use std::collections::VecDeque;
+
+use crate::buffer::ChannelSamples;
+
+/// Delays audio by a configurable number of samples so a dynamics processor can react to the
+/// signal before it reaches the output, like in a lookahead limiter. For every sample
+/// [`process()`][Self::process()] returns the highest level between the sample that's currently
+/// being output and the undelayed input sample. Computing the gain reduction from this level means
+/// that the gain reduction is already fully applied by the time a peak reaches the output.
+///
+/// The delay introduces latency, so the plugin needs to report
+/// [`latency_samples()`][Self::latency_samples()] to the host using
+/// [`InitContext::set_latency_samples()`][crate::prelude::InitContext::set_latency_samples()].
+#[derive(Debug, Clone)]
+pub struct Lookahead {
+    /// A ring buffer per channel.
+    delay_lines: Vec<Vec<f32>>,
+    /// The position in `delay_lines` of the oldest sample, which is the next sample to be output.
+    pos: usize,
+    /// The current delay in samples. Only the first `lookahead_samples` samples of the delay lines
+    /// are used.
+    lookahead_samples: usize,
+    /// The length of the delay lines.
+    max_lookahead_samples: usize,
+
+    /// The candidates for the highest level within the lookahead window as `(sample_idx, level)`
+    /// pairs. The levels are sorted in decreasing order, so the first entry contains the maximum.
+    /// This has enough capacity for the largest possible window so it never needs to reallocate.
+    peaks: VecDeque<(u64, f32)>,
+    /// The number of samples processed since the last reset.
+    sample_idx: u64,
+}
+
+impl Lookahead {
+    /// Create a lookahead delay for `num_channels` channels that can delay the signal by up to
+    /// `max_lookahead_samples` samples. The delay is initially set to the maximum. This allocates,
+    /// so it should be called from the plugin's `initialize()` function.
+    pub fn new(num_channels: usize, max_lookahead_samples: usize) -> Self {
+        Self {
+            delay_lines: vec![vec![0.0; max_lookahead_samples]; num_channels],
+            pos: 0,
+            lookahead_samples: max_lookahead_samples,
+            max_lookahead_samples,
+
+            peaks: VecDeque::with_capacity(max_lookahead_samples + 1),
+            sample_idx: 0,
+        }
+    }
+
+    /// Change the delay in samples. This resets the delay lines if the delay changes. The delay is
+    /// capped at the maximum passed to [`new()`][Self::new()].
+    pub fn set_lookahead_samples(&mut self, samples: usize) {
+        nih_debug_assert!(
+            samples <= self.max_lookahead_samples,
+            "The lookahead cannot exceed {} samples",
+            self.max_lookahead_samples
+        );
+
+        let samples = samples.min(self.max_lookahead_samples);
+        if samples != self.lookahead_samples {
+            self.lookahead_samples = samples;
+            self.reset();
+        }
+    }
+
+    /// The current delay in samples, to be reported to the host as the plugin's latency.
+    pub fn latency_samples(&self) -> u32 {
+        self.lookahead_samples as u32
+    }
+
+    /// Clear the delay lines and the detection window.
+    pub fn reset(&mut self) {
+        for delay_line in &mut self.delay_lines {
+            delay_line.fill(0.0);
+        }
+        self.pos = 0;
+
+        self.peaks.clear();
+        self.sample_idx = 0;
+    }
+
+    /// Delay a single sample for all channels, replacing the samples with the delayed samples. The
+    /// returned detection level is the highest absolute sample value across all channels from the
+    /// delayed sample up to and including the undelayed input sample.
+    #[inline]
+    pub fn process(&mut self, channel_samples: &mut ChannelSamples) -> f32 {
+        nih_debug_assert!(channel_samples.len() <= self.delay_lines.len());
+
+        // Levels that are lower than the new level can never be the maximum again, so the maximum
+        // within the window is always at the front of the queue
+        let level = channel_samples.max_abs();
+        while let Some(&(_, peak)) = self.peaks.back() {
+            if peak > level {
+                break;
+            }
+            self.peaks.pop_back();
+        }
+        self.peaks.push_back((self.sample_idx, level));
+
+        let window_start = self
+            .sample_idx
+            .saturating_sub(self.lookahead_samples as u64);
+        while let Some(&(peak_idx, _)) = self.peaks.front() {
+            if peak_idx >= window_start {
+                break;
+            }
+            self.peaks.pop_front();
+        }
+        self.sample_idx += 1;
+
+        if self.lookahead_samples > 0 {
+            for (sample, delay_line) in channel_samples.iter_mut().zip(&mut self.delay_lines) {
+                *sample = std::mem::replace(&mut delay_line[self.pos], *sample);
+            }
+
+            self.pos += 1;
+            if self.pos == self.lookahead_samples {
+                self.pos = 0;
+            }
+        }
+
+        self.peaks[0].1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+
+    #[test]
+    fn delay_and_detection_alignment() {
+        let mut left = vec![0.0; 10];
+        let mut right = vec![0.0; 10];
+        left[2] = 0.5;
+        right[2] = -1.0;
+        left[4] = 0.25;
+        let mut buffer = Buffer::default();
+        unsafe {
+            buffer.set_slices(10, |output_slices| {
+                *output_slices = vec![&mut left, &mut right];
+            })
+        };
+
+        let mut lookahead = Lookahead::new(2, 8);
+        lookahead.set_lookahead_samples(3);
+        assert_eq!(lookahead.latency_samples(), 3);
+
+        let levels: Vec<f32> = buffer
+            .iter_samples()
+            .map(|mut channel_samples| lookahead.process(&mut channel_samples))
+            .collect();
+
+        // The loudest peak is detected as soon as it enters the delay line and is held until it
+        // has been output. The quieter peak takes over after that.
+        assert_eq!(levels, [0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.25, 0.25, 0.0, 0.0]);
+        assert_eq!(left, [0.0, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.25, 0.0, 0.0]);
+        assert_eq!(right, [0.0, 0.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn no_lookahead_passes_through() {
+        let mut samples = vec![0.5, -0.25, 0.0];
+        let mut buffer = Buffer::default();
+        unsafe {
+            buffer.set_slices(3, |output_slices| {
+                *output_slices = vec![&mut samples];
+            })
+        };
+
+        let mut lookahead = Lookahead::new(1, 4);
+        lookahead.set_lookahead_samples(0);
+        let levels: Vec<f32> = buffer
+            .iter_samples()
+            .map(|mut channel_samples| lookahead.process(&mut channel_samples))
+            .collect();
+
+        assert_eq!(levels, [0.5, 0.25, 0.0]);
+        assert_eq!(samples, [0.5, -0.25, 0.0]);
+    }
+}
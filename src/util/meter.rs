@@ -0,0 +1,205 @@
+//! Peak meter levels that can be shared between the audio thread and the editor.
+
+use atomic_float::AtomicF32;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::buffer::Buffer;
+
+/// The default time it takes for a meter to fall by 12 dB after the signal becomes silent.
+const DEFAULT_DECAY_MS: f32 = 150.0;
+
+/// Per-channel peak levels for metering. This generalizes the single `Arc<AtomicF32>` used in the
+/// GUI examples to any number of channels. The audio thread updates the levels through a shared
+/// reference using [`update()`][Self::update()] or [`update_channel()`][Self::update_channel()],
+/// and the editor reads them with [`level()`][Self::level()]. Wrap this in an `Arc` to share it.
+///
+/// The levels are stored as linear gain values. When the signal becomes quieter than the current
+/// level, the level falls off exponentially instead of immediately following the signal.
+///
+/// Every channel also has a clip indicator that latches when a sample exceeds the clip threshold,
+/// which defaults to 0 dBFS. It stays set until it's cleared using
+/// [`reset_clip_indicators()`][Self::reset_clip_indicators()], usually when the user clicks on it.
+#[derive(Debug)]
+pub struct PeakMeterLevels {
+    /// The current level for each channel, as linear gain.
+    levels: Box<[AtomicF32]>,
+    /// Whether a channel contained a sample above `clip_threshold` since the clip indicators were
+    /// last reset.
+    clipped: Box<[AtomicBool]>,
+    /// Samples with an absolute value above this linear gain value set the clip indicator.
+    clip_threshold: AtomicF32,
+    /// The amount of the previous level that's retained for every sample the level falls off.
+    decay_weight: AtomicF32,
+}
+
+impl PeakMeterLevels {
+    /// Create levels for `num_channels` channels. The levels start out silent. Call
+    /// [`set_sample_rate()`][Self::set_sample_rate()] from the plugin's `initialize()` function
+    /// before updating the levels.
+    pub fn new(num_channels: usize) -> Self {
+        Self {
+            levels: (0..num_channels).map(|_| AtomicF32::new(0.0)).collect(),
+            clipped: (0..num_channels).map(|_| AtomicBool::new(false)).collect(),
+            clip_threshold: AtomicF32::new(1.0),
+            decay_weight: AtomicF32::new(0.0),
+        }
+    }
+
+    /// Set the sample rate, using the default decay time of 150 milliseconds.
+    pub fn set_sample_rate(&self, sample_rate: f32) {
+        self.set_decay(sample_rate, DEFAULT_DECAY_MS);
+    }
+
+    /// Set the sample rate and the time in milliseconds it takes for the levels to fall by 12 dB
+    /// once the signal becomes silent.
+    pub fn set_decay(&self, sample_rate: f32, decay_ms: f32) {
+        let decay_weight = 0.25f64.powf((sample_rate as f64 * decay_ms as f64 / 1000.0).recip());
+        self.decay_weight
+            .store(decay_weight as f32, Ordering::Relaxed);
+    }
+
+    /// Set the linear gain value samples need to exceed to set the clip indicator. Defaults to 1.0,
+    /// or 0 dBFS. Use [`util::db_to_gain()`][crate::util::db_to_gain()] to set the threshold in
+    /// decibels.
+    pub fn set_clip_threshold(&self, threshold: f32) {
+        self.clip_threshold.store(threshold, Ordering::Relaxed);
+    }
+
+    /// The number of channels these levels were created for.
+    pub fn num_channels(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Get a channel's current level as linear gain. Returns 0 if `channel_idx` is out of bounds.
+    pub fn level(&self, channel_idx: usize) -> f32 {
+        self.levels
+            .get(channel_idx)
+            .map(|level| level.load(Ordering::Relaxed))
+            .unwrap_or(0.0)
+    }
+
+    /// Whether a channel has clipped since its clip indicator was last reset. Returns `false` if
+    /// `channel_idx` is out of bounds.
+    pub fn clipped(&self, channel_idx: usize) -> bool {
+        self.clipped
+            .get(channel_idx)
+            .map(|clipped| clipped.load(Ordering::Relaxed))
+            .unwrap_or(false)
+    }
+
+    /// Whether any channel has clipped since the clip indicators were last reset.
+    pub fn any_clipped(&self) -> bool {
+        self.clipped
+            .iter()
+            .any(|clipped| clipped.load(Ordering::Relaxed))
+    }
+
+    /// Update the levels using the samples in `buffer`. Channels beyond the number of channels
+    /// these levels were created for are ignored. This does not allocate.
+    pub fn update(&self, buffer: &Buffer) {
+        for (channel_idx, channel_samples) in buffer.as_slice_immutable().iter().enumerate() {
+            self.update_channel(channel_idx, channel_samples);
+        }
+    }
+
+    /// Update a single channel's level using a block of samples for that channel. Does nothing if
+    /// `channel_idx` is out of bounds.
+    pub fn update_channel(&self, channel_idx: usize, samples: &[f32]) {
+        let Some(level) = self.levels.get(channel_idx) else {
+            return;
+        };
+        if samples.is_empty() {
+            return;
+        }
+
+        let peak = samples
+            .iter()
+            .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        if peak > self.clip_threshold.load(Ordering::Relaxed) {
+            self.clipped[channel_idx].store(true, Ordering::Relaxed);
+        }
+
+        let current_level = level.load(Ordering::Relaxed);
+        let new_level = if peak > current_level {
+            peak
+        } else {
+            let decay_weight = self
+                .decay_weight
+                .load(Ordering::Relaxed)
+                .powi(samples.len() as i32);
+            (current_level * decay_weight) + (peak * (1.0 - decay_weight))
+        };
+
+        level.store(new_level, Ordering::Relaxed);
+    }
+
+    /// Clear a single channel's clip indicator. Does nothing if `channel_idx` is out of bounds.
+    pub fn reset_clip_indicator(&self, channel_idx: usize) {
+        if let Some(clipped) = self.clipped.get(channel_idx) {
+            clipped.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Clear all clip indicators.
+    pub fn reset_clip_indicators(&self) {
+        for clipped in self.clipped.iter() {
+            clipped.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Reset all levels back to silence and clear the clip indicators.
+    pub fn reset(&self) {
+        for level in self.levels.iter() {
+            level.store(0.0, Ordering::Relaxed);
+        }
+        self.reset_clip_indicators();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attack_is_instant() {
+        let levels = PeakMeterLevels::new(2);
+        levels.set_sample_rate(1000.0);
+        levels.update_channel(0, &[0.1, -0.5, 0.25]);
+        levels.update_channel(1, &[0.2]);
+
+        assert_eq!(levels.level(0), 0.5);
+        assert_eq!(levels.level(1), 0.2);
+        assert_eq!(levels.level(2), 0.0);
+    }
+
+    #[test]
+    fn decays_by_12_db() {
+        // At 1 kHz, 150 ms is 150 samples
+        let levels = PeakMeterLevels::new(1);
+        levels.set_sample_rate(1000.0);
+        levels.update_channel(0, &[1.0]);
+        levels.update_channel(0, &[0.0; 100]);
+        levels.update_channel(0, &[0.0; 50]);
+
+        approx::assert_relative_eq!(levels.level(0), 0.25, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn clip_indicator_latches() {
+        let levels = PeakMeterLevels::new(2);
+        levels.set_sample_rate(1000.0);
+        levels.update_channel(0, &[1.0, -0.5]);
+        assert!(!levels.any_clipped());
+
+        levels.update_channel(0, &[-1.5]);
+        levels.update_channel(0, &[0.0; 100]);
+        assert!(levels.clipped(0));
+        assert!(!levels.clipped(1));
+
+        levels.reset_clip_indicators();
+        levels.set_clip_threshold(0.5);
+        levels.update_channel(1, &[0.75]);
+        assert!(!levels.clipped(0));
+        assert!(levels.clipped(1));
+    }
+}
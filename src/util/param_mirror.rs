@@ -0,0 +1,79 @@
+//! Copies of smoothed parameter values that the editor can read without locking.
+
+use std::fmt::Debug;
+
+use crate::params::smoothing::{Smoothable, Smoother};
+
+/// A copy of a parameter's smoothed value that's updated from the plugin's `process()` function,
+/// so the editor can show the value that's currently audible instead of the parameter's target
+/// value. This is useful for displaying smoothed frequencies on a filter or crossover display, or
+/// for metering a parameter. Wrap this in an `Arc` and share it with the editor.
+///
+/// The smoother itself changes every sample while a block is being processed, so reading it
+/// directly from the GUI thread would result in arbitrary values from the middle of a block.
+/// Calling [`update()`][Self::update()] once at the end of every block instead means the editor
+/// always sees the value the smoother had at the end of the last processed block. The mirrored
+/// value thus lags behind the audio by up to one block.
+pub struct ParamMirror<T: Smoothable> {
+    value: T::Atomic,
+}
+
+impl<T: Smoothable + Debug> Debug for ParamMirror<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParamMirror")
+            .field("value", &self.load())
+            .finish()
+    }
+}
+
+impl<T: Smoothable> ParamMirror<T> {
+    /// Create a mirror with an initial value. This is usually the parameter's default value.
+    pub fn new(value: T) -> Self {
+        Self {
+            value: T::atomic_new(value),
+        }
+    }
+
+    /// Copy the smoother's current value, as returned by
+    /// [`Smoother::previous_value()`]. Call this after processing a block of audio.
+    #[inline]
+    pub fn update(&self, smoother: &Smoother<T>) {
+        self.store(smoother.previous_value());
+    }
+
+    /// Store a value directly. Useful for mirroring values that are derived from one or more
+    /// parameters.
+    #[inline]
+    pub fn store(&self, value: T) {
+        T::atomic_store(&self.value, value);
+    }
+
+    /// Get the last mirrored value.
+    #[inline]
+    pub fn load(&self) -> T {
+        T::atomic_load(&self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::smoothing::SmoothingStyle;
+
+    #[test]
+    fn mirrors_smoothed_value() {
+        // At 1 kHz, 10 ms is 10 samples
+        let smoother: Smoother<f32> = Smoother::new(SmoothingStyle::Linear(10.0));
+        smoother.reset(0.0);
+        smoother.set_target(1000.0, 1.0);
+
+        let mirror = ParamMirror::new(0.0);
+        for _ in 0..5 {
+            smoother.next();
+        }
+        assert_eq!(mirror.load(), 0.0);
+
+        mirror.update(&smoother);
+        approx::assert_relative_eq!(mirror.load(), 0.5, epsilon = 1e-6);
+    }
+}
@@ -0,0 +1,265 @@
+//! Monophonic pitch detection.
+
+use crate::buffer::Buffer;
+
+/// The default threshold for [`PitchDetector::set_threshold()`]. This is the value suggested in
+/// the YIN paper.
+const DEFAULT_THRESHOLD: f32 = 0.15;
+
+/// A pitch detected by [`PitchDetector::detect()`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pitch {
+    /// The detected fundamental frequency in Hertz.
+    pub frequency: f32,
+    /// How periodic the signal is at this frequency, between 0 and 1. Clean periodic signals like
+    /// sine waves have a confidence close to 1, while noisy signals have a lower confidence.
+    pub confidence: f32,
+}
+
+/// Detects the fundamental frequency of a monophonic signal using the YIN algorithm. This can be
+/// used to implement tuners or pitch tracking effects. Samples are accumulated into a window that
+/// is long enough to contain two periods of the lowest frequency that should be detected, and
+/// [`detect()`][Self::detect()] analyzes the most recent window. Everything is allocated up front,
+/// so after creating the detector in the plugin's `initialize()` function, all other functions can
+/// be called from the audio thread.
+///
+/// The analysis takes time proportional to the square of the window's length, so it should not be
+/// run for every sample. Running it once per processing cycle or once every couple of milliseconds
+/// is usually more than enough for tuners.
+#[derive(Debug, Clone)]
+pub struct PitchDetector {
+    sample_rate: f32,
+    /// The detected frequencies must have a period of at least this many samples. This is derived
+    /// from the maximum frequency.
+    min_period: usize,
+    /// The detected frequencies must have a period of at most this many samples. This is derived
+    /// from the minimum frequency. The window contains twice this many samples.
+    max_period: usize,
+    /// The normalized difference threshold a period needs to fall below to be considered the
+    /// fundamental period.
+    threshold: f32,
+
+    /// A ring buffer containing the last `2 * max_period` samples.
+    window: Vec<f32>,
+    /// The index in `window` the next sample will be written to. This is also the oldest sample in
+    /// the window.
+    window_pos: usize,
+
+    /// The window with the oldest sample at the start. Used as scratch space during the analysis.
+    linear_window: Vec<f32>,
+    /// The cumulative mean normalized difference function for every period between 0 and
+    /// `max_period`, inclusive.
+    differences: Vec<f32>,
+}
+
+impl PitchDetector {
+    /// Create a pitch detector that detects frequencies between `min_frequency` and
+    /// `max_frequency` Hertz. The lower the minimum frequency, the more samples need to be analyzed
+    /// and the longer it takes for the detector to react to pitch changes. This allocates, so it
+    /// should be called from the plugin's `initialize()` function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min_frequency` is not positive or if `max_frequency <= min_frequency`.
+    pub fn new(sample_rate: f32, min_frequency: f32, max_frequency: f32) -> Self {
+        assert!(min_frequency > 0.0);
+        assert!(max_frequency > min_frequency);
+
+        // The period is interpolated between neighbouring lags, so the minimum period should not
+        // be below 2 samples
+        let min_period = ((sample_rate / max_frequency).floor() as usize).max(2);
+        let max_period = ((sample_rate / min_frequency).ceil() as usize).max(min_period) + 1;
+        let window_size = max_period * 2;
+
+        Self {
+            sample_rate,
+            min_period,
+            max_period,
+            threshold: DEFAULT_THRESHOLD,
+
+            window: vec![0.0; window_size],
+            window_pos: 0,
+
+            linear_window: vec![0.0; window_size],
+            differences: vec![0.0; max_period + 1],
+        }
+    }
+
+    /// Set the threshold used to pick the fundamental period, between 0 and 1. Lower values make
+    /// the detector stricter, so noisy signals are less likely to result in a detected pitch but
+    /// [`detect()`][Self::detect()] will also return `None` more often. Defaults to 0.15.
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold.clamp(0.0, 1.0);
+    }
+
+    /// The number of samples analyzed by [`detect()`][Self::detect()]. A pitch can only be detected
+    /// after at least this many samples have been processed.
+    pub fn window_size(&self) -> usize {
+        self.window.len()
+    }
+
+    /// Clear the accumulated samples.
+    pub fn reset(&mut self) {
+        self.window.fill(0.0);
+        self.window_pos = 0;
+    }
+
+    /// Add a single sample to the analysis window.
+    pub fn process_sample(&mut self, sample: f32) {
+        self.window[self.window_pos] = sample;
+        self.window_pos = (self.window_pos + 1) % self.window.len();
+    }
+
+    /// Add all samples in `buffer` to the analysis window. If the buffer contains multiple channels,
+    /// then the average of those channels is analyzed.
+    pub fn process(&mut self, buffer: &Buffer) {
+        let channels = buffer.as_slice_immutable();
+        if channels.is_empty() {
+            return;
+        }
+
+        let channel_scale = (channels.len() as f32).recip();
+        for sample_idx in 0..buffer.samples() {
+            let sum: f32 = channels.iter().map(|channel| channel[sample_idx]).sum();
+            self.process_sample(sum * channel_scale);
+        }
+    }
+
+    /// Analyze the current window and return the detected pitch, if any. Returns `None` if the
+    /// signal is silent or not periodic enough within the configured frequency range.
+    pub fn detect(&mut self) -> Option<Pitch> {
+        // The ring buffer is copied to a linear buffer first so the difference function doesn't
+        // need to wrap around
+        let (newest, oldest) = self.window.split_at(self.window_pos);
+        self.linear_window[..oldest.len()].copy_from_slice(oldest);
+        self.linear_window[oldest.len()..].copy_from_slice(newest);
+
+        // This is the cumulative mean normalized difference function from the YIN paper, with the
+        // difference function being computed over the first half of the window
+        self.differences[0] = 1.0;
+        let mut difference_sum = 0.0;
+        for period in 1..=self.max_period {
+            let difference: f32 = self.linear_window[..self.max_period]
+                .iter()
+                .zip(&self.linear_window[period..period + self.max_period])
+                .map(|(sample, delayed_sample)| (sample - delayed_sample).powi(2))
+                .sum();
+
+            difference_sum += difference;
+            self.differences[period] = if difference_sum > f32::EPSILON {
+                difference * period as f32 / difference_sum
+            } else {
+                1.0
+            };
+        }
+
+        // The fundamental period is the first dip below the threshold. Without the threshold the
+        // global minimum would often be a multiple of the fundamental period.
+        let mut period = (self.min_period..self.max_period)
+            .find(|&period| self.differences[period] < self.threshold)?;
+        while period + 1 < self.max_period
+            && self.differences[period + 1] < self.differences[period]
+        {
+            period += 1;
+        }
+
+        // Parabolic interpolation is used to get a fractional period
+        let previous = self.differences[period - 1];
+        let current = self.differences[period];
+        let next = self.differences[period + 1];
+        let curvature = previous - (2.0 * current) + next;
+        let offset = if curvature > f32::EPSILON {
+            ((previous - next) / (2.0 * curvature)).clamp(-0.5, 0.5)
+        } else {
+            0.0
+        };
+
+        Some(Pitch {
+            frequency: self.sample_rate / (period as f32 + offset),
+            confidence: (1.0 - current).clamp(0.0, 1.0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48000.0;
+
+    fn process_signal(detector: &mut PitchDetector, signal: impl Fn(f32) -> f32) {
+        for sample_idx in 0..detector.window_size() {
+            detector.process_sample(signal(sample_idx as f32 / SAMPLE_RATE));
+        }
+    }
+
+    #[test]
+    fn detects_sine_tones() {
+        let mut detector = PitchDetector::new(SAMPLE_RATE, 50.0, 2000.0);
+        for frequency in [55.0, 110.0, 261.63, 440.0, 1234.5] {
+            process_signal(&mut detector, |time| {
+                (time * frequency * std::f32::consts::TAU).sin()
+            });
+
+            let pitch = detector.detect().unwrap();
+            assert!(
+                (pitch.frequency - frequency).abs() < frequency * 0.002,
+                "Detected {} Hz for a {frequency} Hz sine",
+                pitch.frequency
+            );
+            assert!(pitch.confidence > 0.95);
+        }
+    }
+
+    #[test]
+    fn detects_fundamental_of_harmonic_signal() {
+        // A sawtooth wave has strong harmonics, which should not be mistaken for the fundamental
+        const FREQUENCY: f32 = 220.0;
+        let mut detector = PitchDetector::new(SAMPLE_RATE, 50.0, 2000.0);
+        process_signal(&mut detector, |time| {
+            ((time * FREQUENCY).fract() * 2.0) - 1.0
+        });
+
+        let pitch = detector.detect().unwrap();
+        assert!((pitch.frequency - FREQUENCY).abs() < FREQUENCY * 0.005);
+    }
+
+    #[test]
+    fn silence_has_no_pitch() {
+        let mut detector = PitchDetector::new(SAMPLE_RATE, 50.0, 2000.0);
+        assert_eq!(detector.detect(), None);
+
+        process_signal(&mut detector, |time| {
+            (time * 440.0 * std::f32::consts::TAU).sin()
+        });
+        assert!(detector.detect().is_some());
+
+        detector.reset();
+        assert_eq!(detector.detect(), None);
+    }
+
+    #[test]
+    fn averages_buffer_channels() {
+        const NUM_SAMPLES: usize = 4000;
+        let mut detector = PitchDetector::new(SAMPLE_RATE, 50.0, 2000.0);
+        assert!(detector.window_size() <= NUM_SAMPLES);
+
+        // The right channel is silent, which should not affect the detected pitch
+        let mut left: Vec<f32> = (0..NUM_SAMPLES)
+            .map(|sample_idx| {
+                (sample_idx as f32 / SAMPLE_RATE * 330.0 * std::f32::consts::TAU).sin()
+            })
+            .collect();
+        let mut right = vec![0.0; NUM_SAMPLES];
+        let mut buffer = Buffer::default();
+        unsafe {
+            buffer.set_slices(NUM_SAMPLES, |output_slices| {
+                *output_slices = vec![&mut left, &mut right];
+            })
+        };
+        detector.process(&buffer);
+
+        let pitch = detector.detect().unwrap();
+        assert!((pitch.frequency - 330.0).abs() < 330.0 * 0.002);
+    }
+}
@@ -0,0 +1,62 @@
+//! Signalling the editor from the audio thread that it should redraw.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A flag the audio thread can raise to let the editor know that new data is available and that it
+/// should redraw, for instance after updating [`PeakMeterLevels`][super::PeakMeterLevels] or a
+/// spectrum analyzer's data. Wrap this in an `Arc` and share it with the editor.
+///
+/// Raising the flag only stores an atomic boolean. It never blocks, allocates, or wakes up another
+/// thread, so [`request_redraw()`][Self::request_redraw()] is safe to call from `process()`. The
+/// editor polls the flag at its own frame rate using [`take()`][Self::take()], and only repaints
+/// the affected widgets when the flag was raised. Multiple requests between two polls result in a
+/// single redraw.
+#[derive(Debug, Default)]
+pub struct RedrawFlag {
+    requested: AtomicBool,
+}
+
+impl RedrawFlag {
+    /// Create a flag that has not yet been raised.
+    pub const fn new() -> Self {
+        Self {
+            requested: AtomicBool::new(false),
+        }
+    }
+
+    /// Ask the editor to redraw. This is realtime-safe and can be called from the audio thread.
+    #[inline]
+    pub fn request_redraw(&self) {
+        self.requested.store(true, Ordering::Release);
+    }
+
+    /// Check whether a redraw has been requested since the last call, and lower the flag again.
+    /// This should be called from the editor's event loop.
+    #[inline]
+    pub fn take(&self) -> bool {
+        self.requested.swap(false, Ordering::Acquire)
+    }
+
+    /// Check whether a redraw has been requested without lowering the flag.
+    #[inline]
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::Acquire)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_are_coalesced() {
+        let flag = RedrawFlag::new();
+        assert!(!flag.take());
+
+        flag.request_redraw();
+        flag.request_redraw();
+        assert!(flag.is_requested());
+        assert!(flag.take());
+        assert!(!flag.take());
+    }
+}
@@ -0,0 +1,189 @@
+//! Waveshapers for saturation, distortion, and soft clipping. All curves are odd-symmetric, pass
+//! signals near zero through at unity gain, and saturate to `[-1, 1]`. The `drive` argument is a
+//! linear gain applied before the curve, so higher drive values result in more saturation.
+
+#[cfg(feature = "simd")]
+use std::simd::f32x2;
+#[cfg(feature = "simd")]
+use std::simd::num::SimdFloat;
+
+/// The input level at which [`cubic()`] reaches full saturation. Using 1.5 instead of 1.0 keeps the
+/// slope at zero equal to one while still reaching exactly ±1 at the knee.
+const CUBIC_KNEE: f32 = 1.5;
+/// The cubic term's coefficient for [`cubic()`], chosen so the curve's slope is zero at
+/// [`CUBIC_KNEE`]. This is `1 / (3 * CUBIC_KNEE^2)`.
+const CUBIC_COEFFICIENT: f32 = 4.0 / 27.0;
+
+/// One of the waveshaping curves from this module. Useful for letting the user pick a curve through
+/// an [`EnumParam`][crate::prelude::EnumParam].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SaturationCurve {
+    /// See [`tanh()`].
+    #[default]
+    Tanh,
+    /// See [`cubic()`].
+    Cubic,
+    /// See [`hard_clip()`].
+    HardClip,
+    /// See [`arctan()`].
+    Arctan,
+}
+
+impl SaturationCurve {
+    /// Apply this curve to a single sample.
+    #[inline]
+    pub fn process(self, sample: f32, drive: f32) -> f32 {
+        match self {
+            SaturationCurve::Tanh => tanh(sample, drive),
+            SaturationCurve::Cubic => cubic(sample, drive),
+            SaturationCurve::HardClip => hard_clip(sample, drive),
+            SaturationCurve::Arctan => arctan(sample, drive),
+        }
+    }
+
+    /// Apply this curve to a pair of samples, usually a stereo sample.
+    #[cfg(feature = "simd")]
+    #[inline]
+    pub fn process_f32x2(self, samples: f32x2, drive: f32) -> f32x2 {
+        match self {
+            SaturationCurve::Tanh => tanh_f32x2(samples, drive),
+            SaturationCurve::Cubic => cubic_f32x2(samples, drive),
+            SaturationCurve::HardClip => hard_clip_f32x2(samples, drive),
+            SaturationCurve::Arctan => arctan_f32x2(samples, drive),
+        }
+    }
+}
+
+/// Hyperbolic tangent saturation. Smooth and gradual, with the output approaching ±1
+/// asymptotically.
+#[inline]
+pub fn tanh(sample: f32, drive: f32) -> f32 {
+    (sample * drive).tanh()
+}
+
+/// A cubic soft clipper. This is cheaper than [`tanh()`] and leaves more of the signal untouched,
+/// but it reaches full saturation at an input level of 1.5 after which it behaves like a hard
+/// clipper. The curve's slope is zero at that point so there is no discontinuity in the slope.
+#[inline]
+pub fn cubic(sample: f32, drive: f32) -> f32 {
+    let x = (sample * drive).clamp(-CUBIC_KNEE, CUBIC_KNEE);
+    x - (CUBIC_COEFFICIENT * x * x * x)
+}
+
+/// A hard clipper that clamps the signal to `[-1, 1]`. This adds a lot of high frequency content
+/// and will alias, so it's mostly useful as a last resort safety clipper.
+#[inline]
+pub fn hard_clip(sample: f32, drive: f32) -> f32 {
+    (sample * drive).clamp(-1.0, 1.0)
+}
+
+/// Arctangent saturation, scaled so the output approaches ±1. This has a softer knee than
+/// [`tanh()`] and approaches full saturation much more slowly.
+#[inline]
+pub fn arctan(sample: f32, drive: f32) -> f32 {
+    (sample * drive * std::f32::consts::FRAC_PI_2).atan() * std::f32::consts::FRAC_2_PI
+}
+
+/// The same as [`tanh()`], but for a pair of samples.
+#[cfg(feature = "simd")]
+#[inline]
+pub fn tanh_f32x2(samples: f32x2, drive: f32) -> f32x2 {
+    // `std::simd` does not have a vectorized tanh
+    let [left, right] = samples.to_array();
+    f32x2::from_array([tanh(left, drive), tanh(right, drive)])
+}
+
+/// The same as [`cubic()`], but for a pair of samples.
+#[cfg(feature = "simd")]
+#[inline]
+pub fn cubic_f32x2(samples: f32x2, drive: f32) -> f32x2 {
+    let x = (samples * f32x2::splat(drive))
+        .simd_clamp(f32x2::splat(-CUBIC_KNEE), f32x2::splat(CUBIC_KNEE));
+    x - (f32x2::splat(CUBIC_COEFFICIENT) * x * x * x)
+}
+
+/// The same as [`hard_clip()`], but for a pair of samples.
+#[cfg(feature = "simd")]
+#[inline]
+pub fn hard_clip_f32x2(samples: f32x2, drive: f32) -> f32x2 {
+    (samples * f32x2::splat(drive)).simd_clamp(f32x2::splat(-1.0), f32x2::splat(1.0))
+}
+
+/// The same as [`arctan()`], but for a pair of samples.
+#[cfg(feature = "simd")]
+#[inline]
+pub fn arctan_f32x2(samples: f32x2, drive: f32) -> f32x2 {
+    // `std::simd` does not have a vectorized atan
+    let [left, right] = samples.to_array();
+    f32x2::from_array([arctan(left, drive), arctan(right, drive)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVES: [SaturationCurve; 4] = [
+        SaturationCurve::Tanh,
+        SaturationCurve::Cubic,
+        SaturationCurve::HardClip,
+        SaturationCurve::Arctan,
+    ];
+
+    #[test]
+    fn unity_gain_near_zero() {
+        for curve in CURVES {
+            approx::assert_relative_eq!(curve.process(1e-3, 1.0), 1e-3, epsilon = 1e-6);
+            approx::assert_relative_eq!(curve.process(1e-3, 4.0), 4e-3, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn bounded_and_symmetric() {
+        for curve in CURVES {
+            for i in 0..=1000 {
+                let sample = i as f32 / 100.0;
+                let output = curve.process(sample, 2.0);
+
+                assert!(
+                    output.abs() <= 1.0,
+                    "{curve:?} returned {output} for {sample}"
+                );
+                assert_eq!(curve.process(-sample, 2.0), -output);
+            }
+        }
+    }
+
+    #[test]
+    fn monotonic() {
+        for curve in CURVES {
+            let mut previous = curve.process(-3.0, 1.0);
+            for i in -299..=300 {
+                let output = curve.process(i as f32 / 100.0, 1.0);
+                assert!(output >= previous, "{curve:?} is not monotonic");
+                previous = output;
+            }
+        }
+    }
+
+    #[test]
+    fn cubic_continuous_at_knee() {
+        approx::assert_relative_eq!(cubic(CUBIC_KNEE, 1.0), 1.0);
+        approx::assert_relative_eq!(cubic(CUBIC_KNEE - 1e-3, 1.0), 1.0, epsilon = 1e-5);
+        assert_eq!(cubic(10.0, 1.0), 1.0);
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd_matches_scalar() {
+        for curve in CURVES {
+            for i in -200..=200 {
+                let left = i as f32 / 50.0;
+                let right = -left * 0.5;
+                let output = curve.process_f32x2(f32x2::from_array([left, right]), 1.5);
+
+                approx::assert_relative_eq!(output[0], curve.process(left, 1.5), epsilon = 1e-6);
+                approx::assert_relative_eq!(output[1], curve.process(right, 1.5), epsilon = 1e-6);
+            }
+        }
+    }
+}
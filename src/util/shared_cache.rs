@@ -0,0 +1,101 @@
+//! A process-wide cache for sharing immutable data between plugin instances.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Weak};
+
+/// Shares immutable data like designed filter kernels, wavetables, or FFT plans between all
+/// instances of a plugin that are loaded in the same process. Instances that need the data for the
+/// same key receive the same `Arc`, so the data is only computed and stored once. Define a cache as
+/// a `static`:
+///
+/// ```ignore
+/// static WAVETABLES: SharedCache<WavetableSettings, Wavetable> = SharedCache::new();
+///
+/// let wavetable = WAVETABLES.get_or_insert_with(settings, |settings| Wavetable::new(settings));
+/// ```
+///
+/// The cache only holds weak references. An entry is evicted once the last instance using it drops
+/// its `Arc`, so the cache never keeps data alive that no instance needs anymore.
+///
+/// This locks a mutex and may compute and allocate the data, so it must not be used from the audio
+/// thread. Query it from the plugin's constructor or from `initialize()` instead.
+pub struct SharedCache<K, V> {
+    /// The cached values. This is only initialized on first use so the cache can be created in a
+    /// `const` context.
+    entries: Mutex<Option<HashMap<K, Weak<V>>>>,
+}
+
+impl<K: Eq + Hash, V> Default for SharedCache<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Eq + Hash, V> SharedCache<K, V> {
+    /// Create an empty cache.
+    pub const fn new() -> Self {
+        Self {
+            entries: Mutex::new(None),
+        }
+    }
+
+    /// Get the value for `key`. If no other instance is currently using a value for this key, then
+    /// the value is computed using `f`. The cache stays locked while `f` runs, so instances that
+    /// request the same key at the same time wait for the value instead of computing it twice.
+    pub fn get_or_insert_with(&self, key: K, f: impl FnOnce(&K) -> V) -> Arc<V> {
+        let mut entries = self.entries.lock();
+        let entries = entries.get_or_insert_with(HashMap::new);
+
+        // Evicting entries here keeps the map from growing when the keys keep changing
+        entries.retain(|_, value| value.strong_count() > 0);
+        if let Some(value) = entries.get(&key).and_then(Weak::upgrade) {
+            return value;
+        }
+
+        let value = Arc::new(f(&key));
+        entries.insert(key, Arc::downgrade(&value));
+
+        value
+    }
+
+    /// The number of values that are currently in use by at least one instance.
+    pub fn len(&self) -> usize {
+        match &*self.entries.lock() {
+            Some(entries) => entries
+                .values()
+                .filter(|value| value.strong_count() > 0)
+                .count(),
+            None => 0,
+        }
+    }
+
+    /// Whether none of the cached values are currently in use.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_and_evicts() {
+        static CACHE: SharedCache<u32, Vec<u32>> = SharedCache::new();
+
+        let first = CACHE.get_or_insert_with(4, |n| (0..*n).collect());
+        let second = CACHE.get_or_insert_with(4, |_| panic!("The value should have been shared"));
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(*second, [0, 1, 2, 3]);
+        assert_eq!(CACHE.len(), 1);
+
+        drop(first);
+        drop(second);
+        assert!(CACHE.is_empty());
+
+        let recomputed = CACHE.get_or_insert_with(4, |n| vec![*n]);
+        assert_eq!(*recomputed, [4]);
+    }
+}
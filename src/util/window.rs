@@ -25,6 +25,32 @@ pub fn blackman_in_place(window: &mut [f32]) {
     }
 }
 
+/// A four-term Blackman-Harris window function. This has a wider main lobe but much lower side lobes
+/// than [`blackman()`].
+///
+/// <https://en.wikipedia.org/wiki/Window_function#Blackman%E2%80%93Harris_window>
+pub fn blackman_harris(size: usize) -> Vec<f32> {
+    let mut window = vec![0.0; size];
+    blackman_harris_in_place(&mut window);
+
+    window
+}
+
+/// The same as [`blackman_harris()`], but filling an existing slice instead.
+pub fn blackman_harris_in_place(window: &mut [f32]) {
+    let size = window.len();
+
+    let scale_1 = (2.0 * f32::consts::PI) / (size - 1) as f32;
+    let scale_2 = scale_1 * 2.0;
+    let scale_3 = scale_1 * 3.0;
+    for (i, sample) in window.iter_mut().enumerate() {
+        let cos_1 = (scale_1 * i as f32).cos();
+        let cos_2 = (scale_2 * i as f32).cos();
+        let cos_3 = (scale_3 * i as f32).cos();
+        *sample = 0.35875 - (0.48829 * cos_1) + (0.14128 * cos_2) - (0.01168 * cos_3);
+    }
+}
+
 /// A Hann window function.
 ///
 /// <https://en.wikipedia.org/wiki/Hann_function>
@@ -48,6 +74,31 @@ pub fn hann_in_place(window: &mut [f32]) {
     }
 }
 
+/// A Kaiser window function. `beta` controls the tradeoff between the main lobe's width and the
+/// side lobes' levels. A `beta` of 0 results in a rectangular window, and higher values result in a
+/// wider main lobe with lower side lobes. A `beta` of around 8.6 is comparable to a Blackman window.
+///
+/// <https://en.wikipedia.org/wiki/Kaiser_window>
+pub fn kaiser(size: usize, beta: f32) -> Vec<f32> {
+    let mut window = vec![0.0; size];
+    kaiser_in_place(&mut window, beta);
+
+    window
+}
+
+/// The same as [`kaiser()`], but filling an existing slice instead.
+pub fn kaiser_in_place(window: &mut [f32], beta: f32) {
+    let size = window.len();
+
+    // We want to scale `[0, size - 1]` to `[-1, 1]`
+    let scale = 2.0 / (size as f32 - 1.0);
+    let normalization_factor = bessel_i0(beta).recip();
+    for (i, sample) in window.iter_mut().enumerate() {
+        let x = (i as f32 * scale) - 1.0;
+        *sample = bessel_i0(beta * (1.0 - (x * x)).max(0.0).sqrt()) * normalization_factor;
+    }
+}
+
 /// Multiply a buffer with a window function.
 #[inline]
 pub fn multiply_with_window(buffer: &mut [f32], window_function: &[f32]) {
@@ -56,3 +107,51 @@ pub fn multiply_with_window(buffer: &mut [f32], window_function: &[f32]) {
         *sample *= window_sample;
     }
 }
+
+/// The zeroth order modified Bessel function of the first kind, used for [`kaiser()`]. Computed
+/// using its power series until the terms no longer contribute to the result.
+fn bessel_i0(x: f32) -> f32 {
+    let half_x = x / 2.0;
+
+    let mut result = 1.0;
+    let mut term = 1.0;
+    let mut k = 1.0;
+    loop {
+        term *= half_x / k;
+        let squared_term = term * term;
+        result += squared_term;
+
+        if squared_term < result * f32::EPSILON {
+            return result;
+        }
+
+        k += 1.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bessel_i0_values() {
+        approx::assert_relative_eq!(bessel_i0(0.0), 1.0);
+        approx::assert_relative_eq!(bessel_i0(1.0), 1.266_066, epsilon = 1e-5);
+        approx::assert_relative_eq!(bessel_i0(8.6), 750.461_2, max_relative = 1e-5);
+    }
+
+    #[test]
+    fn kaiser_shape() {
+        let window = kaiser(65, 8.6);
+        approx::assert_relative_eq!(window[32], 1.0);
+        for (i, sample) in window.iter().enumerate().take(32) {
+            approx::assert_relative_eq!(*sample, window[64 - i], epsilon = 1e-6);
+            assert!(*sample < window[i + 1]);
+        }
+
+        // Without any shaping this is just a rectangular window
+        for sample in kaiser(16, 0.0) {
+            approx::assert_relative_eq!(sample, 1.0);
+        }
+    }
+}
@@ -11,8 +11,9 @@ use std::sync::Arc;
 use super::wrapper::{OutputParamEvent, Task, Wrapper};
 use crate::event_loop::EventLoop;
 use crate::prelude::{
-    ClapPlugin, GuiContext, InitContext, ParamPtr, PluginApi, PluginNoteEvent, ProcessContext,
-    RemoteControlsContext, RemoteControlsPage, RemoteControlsSection, Transport,
+    BufferConfig, ClapPlugin, GuiContext, HostFeatures, InitContext, Param, ParamPtr, PluginApi,
+    PluginNoteEvent, ProcessContext, RemoteControlsContext, RemoteControlsPage,
+    RemoteControlsSection, Transport,
 };
 use crate::wrapper::util::strlcpy;
 
@@ -33,6 +34,12 @@ pub(crate) struct WrapperInitContext<'a, P: ClapPlugin> {
 pub(crate) struct PendingInitContextRequests {
     /// The value of the last `.set_latency_samples()` call.
     latency_changed: Cell<Option<u32>>,
+    /// The value of the last `.set_active_aux_output_ports()` call.
+    active_aux_output_ports_changed: Cell<Option<usize>>,
+    /// Whether a `.set_param_active()` call changed a parameter's active state.
+    param_info_changed: Cell<bool>,
+    /// Whether `.note_names_changed()` was called.
+    note_names_changed: Cell<bool>,
 }
 
 /// A [`ProcessContext`] implementation for the wrapper. This is a separate object so it can hold on
@@ -70,6 +77,16 @@ impl<P: ClapPlugin> Drop for WrapperInitContext<'_, P> {
         if let Some(samples) = self.pending_requests.latency_changed.take() {
             self.wrapper.set_latency_samples(samples)
         }
+        if let Some(num_ports) = self.pending_requests.active_aux_output_ports_changed.take() {
+            self.wrapper.set_active_aux_output_ports(num_ports)
+        }
+        if self.pending_requests.param_info_changed.take() {
+            let task_posted = self.wrapper.schedule_gui(Task::ParamInfoChanged);
+            nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+        }
+        if self.pending_requests.note_names_changed.take() {
+            self.wrapper.note_names_changed()
+        }
     }
 }
 
@@ -78,6 +95,10 @@ impl<P: ClapPlugin> InitContext<P> for WrapperInitContext<'_, P> {
         PluginApi::Clap
     }
 
+    fn host_features(&self) -> HostFeatures {
+        self.wrapper.host_features()
+    }
+
     fn execute(&self, task: P::BackgroundTask) {
         (self.wrapper.task_executor.lock())(task);
     }
@@ -90,6 +111,27 @@ impl<P: ClapPlugin> InitContext<P> for WrapperInitContext<'_, P> {
     fn set_current_voice_capacity(&self, capacity: u32) {
         self.wrapper.set_current_voice_capacity(capacity)
     }
+
+    fn set_active_aux_output_ports(&self, num_ports: usize) {
+        // See this struct's docstring
+        self.pending_requests
+            .active_aux_output_ports_changed
+            .set(Some(num_ports));
+    }
+
+    fn set_param_active(&self, param: &impl Param, active: bool) {
+        // See this struct's docstring. The active state is stored immediately, only the host
+        // notification is deferred.
+        if self.wrapper.update_param_active(param.as_ptr(), active) {
+            self.pending_requests.param_info_changed.set(true);
+        }
+    }
+
+    fn note_names_changed(&self) {
+        // See this struct's docstring. The host would otherwise query the note names while the
+        // plugin is still locked.
+        self.pending_requests.note_names_changed.set(true);
+    }
 }
 
 impl<P: ClapPlugin> ProcessContext<P> for WrapperProcessContext<'_, P> {
@@ -97,6 +139,10 @@ impl<P: ClapPlugin> ProcessContext<P> for WrapperProcessContext<'_, P> {
         PluginApi::Clap
     }
 
+    fn host_features(&self) -> HostFeatures {
+        self.wrapper.host_features()
+    }
+
     fn execute_background(&self, task: P::BackgroundTask) {
         let task_posted = self.wrapper.schedule_background(Task::PluginTask(task));
         nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
@@ -127,6 +173,18 @@ impl<P: ClapPlugin> ProcessContext<P> for WrapperProcessContext<'_, P> {
     fn set_current_voice_capacity(&self, capacity: u32) {
         self.wrapper.set_current_voice_capacity(capacity)
     }
+
+    fn set_active_aux_output_ports(&self, num_ports: usize) {
+        self.wrapper.set_active_aux_output_ports(num_ports)
+    }
+
+    fn set_param_active(&self, param: &impl Param, active: bool) {
+        self.wrapper.set_param_active(param.as_ptr(), active)
+    }
+
+    fn note_names_changed(&self) {
+        self.wrapper.note_names_changed()
+    }
 }
 
 impl<P: ClapPlugin> GuiContext for WrapperGuiContext<P> {
@@ -134,10 +192,26 @@ impl<P: ClapPlugin> GuiContext for WrapperGuiContext<P> {
         PluginApi::Clap
     }
 
+    fn plugin_name(&self) -> &'static str {
+        P::NAME
+    }
+
     fn request_resize(&self) -> bool {
         self.wrapper.request_resize()
     }
 
+    fn buffer_config(&self) -> Option<BufferConfig> {
+        self.wrapper.current_buffer_config()
+    }
+
+    fn cpu_usage(&self) -> Option<f32> {
+        self.wrapper.cpu_usage_meter.load()
+    }
+
+    fn note_names_changed(&self) {
+        self.wrapper.note_names_changed()
+    }
+
     // All of these functions are supposed to be called from the main thread, so we'll put some
     // trust in the caller and assume that this is indeed the case
     unsafe fn raw_begin_set_parameter(&self, param: ParamPtr) {
@@ -16,8 +16,9 @@ use clap_sys::events::{
     CLAP_TRANSPORT_IS_RECORDING, CLAP_TRANSPORT_IS_WITHIN_PRE_ROLL,
 };
 use clap_sys::ext::audio_ports::{
-    clap_audio_port_info, clap_plugin_audio_ports, CLAP_AUDIO_PORT_IS_MAIN, CLAP_EXT_AUDIO_PORTS,
-    CLAP_PORT_MONO, CLAP_PORT_STEREO,
+    clap_audio_port_info, clap_host_audio_ports, clap_plugin_audio_ports,
+    CLAP_AUDIO_PORTS_RESCAN_LIST, CLAP_AUDIO_PORT_IS_MAIN, CLAP_EXT_AUDIO_PORTS, CLAP_PORT_MONO,
+    CLAP_PORT_STEREO,
 };
 use clap_sys::ext::audio_ports_config::{
     clap_audio_ports_config, clap_plugin_audio_ports_config, CLAP_EXT_AUDIO_PORTS_CONFIG,
@@ -30,6 +31,9 @@ use clap_sys::ext::gui::{
     CLAP_WINDOW_API_COCOA, CLAP_WINDOW_API_WIN32, CLAP_WINDOW_API_X11,
 };
 use clap_sys::ext::latency::{clap_host_latency, clap_plugin_latency, CLAP_EXT_LATENCY};
+use clap_sys::ext::note_name::{
+    clap_host_note_name, clap_note_name, clap_plugin_note_name, CLAP_EXT_NOTE_NAME,
+};
 use clap_sys::ext::note_ports::{
     clap_note_port_info, clap_plugin_note_ports, CLAP_EXT_NOTE_PORTS, CLAP_NOTE_DIALECT_CLAP,
     CLAP_NOTE_DIALECT_MIDI,
@@ -38,7 +42,7 @@ use clap_sys::ext::params::{
     clap_host_params, clap_param_info, clap_plugin_params, CLAP_EXT_PARAMS,
     CLAP_PARAM_IS_AUTOMATABLE, CLAP_PARAM_IS_BYPASS, CLAP_PARAM_IS_HIDDEN,
     CLAP_PARAM_IS_MODULATABLE, CLAP_PARAM_IS_MODULATABLE_PER_NOTE_ID, CLAP_PARAM_IS_READONLY,
-    CLAP_PARAM_IS_STEPPED, CLAP_PARAM_RESCAN_VALUES,
+    CLAP_PARAM_IS_STEPPED, CLAP_PARAM_RESCAN_ALL, CLAP_PARAM_RESCAN_VALUES,
 };
 use clap_sys::ext::render::{
     clap_plugin_render, clap_plugin_render_mode, CLAP_EXT_RENDER, CLAP_RENDER_OFFLINE,
@@ -72,7 +76,7 @@ use std::mem;
 use std::num::NonZeroU32;
 use std::os::raw::c_char;
 use std::ptr::NonNull;
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
 use std::sync::{Arc, Weak};
 use std::thread::{self, ThreadId};
 use std::time::Duration;
@@ -83,17 +87,20 @@ use super::util::ClapPtr;
 use crate::event_loop::{BackgroundThread, EventLoop, MainThreadExecutor, TASK_QUEUE_CAPACITY};
 use crate::midi::MidiResult;
 use crate::prelude::{
-    AsyncExecutor, AudioIOLayout, AuxiliaryBuffers, BufferConfig, ClapPlugin, Editor, MidiConfig,
-    NoteEvent, ParamFlags, ParamPtr, Params, ParentWindowHandle, Plugin, PluginNoteEvent,
-    ProcessMode, ProcessStatus, SysExMessage, TaskExecutor, Transport,
+    AsyncExecutor, AudioIOLayout, AuxiliaryBuffers, BufferConfig, ClapPlugin, Editor, HostFeatures,
+    MidiConfig, NoteEvent, ParamFlags, ParamPtr, Params, ParentWindowHandle, Plugin,
+    PluginNoteEvent, ProcessMode, ProcessStatus, SysExMessage, TaskExecutor, Transport,
 };
 use crate::util::permit_alloc;
 use crate::wrapper::clap::context::RemoteControlPages;
 use crate::wrapper::clap::util::{read_stream, write_stream};
 use crate::wrapper::state::{self, PluginState};
 use crate::wrapper::util::buffer_management::{BufferManager, ChannelPointers};
+use crate::wrapper::util::cpu_usage::CpuUsageMeter;
+use crate::wrapper::util::note_ids::NoteIdTracker;
 use crate::wrapper::util::{
-    clamp_input_event_timing, clamp_output_event_timing, hash_param_id, process_wrapper, strlcpy,
+    clamp_input_event_timing, clamp_output_event_timing, hash_param_id, initialize_plugin,
+    process_wrapper, reported_tail_length, strlcpy,
 };
 
 /// How many output parameter changes we can store in our output parameter change queue. Storing
@@ -125,6 +132,9 @@ pub struct Wrapper<P: ClapPlugin> {
     /// APIs only deal in logical pixels.
     editor_scaling_factor: AtomicF32,
 
+    /// Whether the plugin is currently activated. The audio port list can only be changed while
+    /// the plugin is deactivated.
+    is_activated: AtomicBool,
     is_processing: AtomicBool,
     /// The current IO configuration, modified through the `clap_plugin_audio_ports_config`
     /// extension. Initialized to the plugin's first audio IO configuration.
@@ -134,6 +144,8 @@ pub struct Wrapper<P: ClapPlugin> {
     current_buffer_config: AtomicCell<Option<BufferConfig>>,
     /// The current audio processing mode. Set through the render extension. Defaults to realtime.
     pub current_process_mode: AtomicCell<ProcessMode>,
+    /// Measures the plugin's CPU usage if `P::MEASURE_CPU_USAGE` is enabled.
+    pub cpu_usage_meter: CpuUsageMeter,
     /// The incoming events for the plugin, if `P::MIDI_INPUT` is set to `MidiConfig::Basic` or
     /// higher.
     ///
@@ -143,11 +155,29 @@ pub struct Wrapper<P: ClapPlugin> {
     /// Stores any events the plugin has output during the current processing cycle, analogous to
     /// `input_events`.
     output_events: AtomicRefCell<VecDeque<PluginNoteEvent<P>>>,
+    /// Fills in the note IDs for note off and choke events that were sent without one.
+    note_id_tracker: AtomicRefCell<NoteIdTracker>,
     /// The last process status returned by the plugin. This is used for tail handling.
     last_process_status: AtomicCell<ProcessStatus>,
+    /// The plugin's [`Plugin::tail_length_samples()`] after the last process call. This is cached
+    /// so the tail extension doesn't need to lock the plugin.
+    last_tail_length: AtomicCell<Option<u32>>,
     /// The current latency in samples, as set by the plugin through the [`ProcessContext`]. Uses
     /// the latency extension.
     pub current_latency: AtomicU32,
+    /// The number of auxiliary output ports that should be exposed to the host, as set by the
+    /// plugin through the [`InitContext`] and the [`ProcessContext`]. This is clamped to the
+    /// number of auxiliary output ports in the current audio IO layout when it's read, and it
+    /// defaults to `usize::MAX` so all ports are exposed by default.
+    active_aux_output_ports: AtomicUsize,
+    /// Set when the active auxiliary output ports changed while the plugin was activated. The
+    /// host will then be asked to rescan the audio ports the next time the plugin gets
+    /// deactivated.
+    audio_ports_rescan_pending: AtomicBool,
+    /// Set when a parameter's active state changed while the plugin was activated. Changing a
+    /// parameter's flags requires a full parameter rescan, which the host may only do while the
+    /// plugin is deactivated.
+    params_rescan_pending: AtomicBool,
     /// A data structure that helps manage and create buffers for all of the plugin's inputs and
     /// outputs based on channel pointers provided by the host.
     buffer_manager: AtomicRefCell<BufferManager>,
@@ -176,6 +206,7 @@ pub struct Wrapper<P: ClapPlugin> {
     _plugin_descriptor: Box<PluginDescriptor>,
 
     clap_plugin_audio_ports: clap_plugin_audio_ports,
+    host_audio_ports: AtomicRefCell<Option<ClapPtr<clap_host_audio_ports>>>,
 
     clap_plugin_gui: clap_plugin_gui,
     host_gui: AtomicRefCell<Option<ClapPtr<clap_host_gui>>>,
@@ -183,6 +214,12 @@ pub struct Wrapper<P: ClapPlugin> {
     clap_plugin_latency: clap_plugin_latency,
     host_latency: AtomicRefCell<Option<ClapPtr<clap_host_latency>>>,
 
+    clap_plugin_note_name: clap_plugin_note_name,
+    host_note_name: AtomicRefCell<Option<ClapPtr<clap_host_note_name>>>,
+    /// The names returned by the plugin's `note_names()` function, converted to CLAP's format.
+    /// These are refreshed every time the host asks for the number of note names.
+    note_names: AtomicRefCell<Vec<clap_note_name>>,
+
     clap_plugin_note_ports: clap_plugin_note_ports,
 
     clap_plugin_params: clap_plugin_params,
@@ -211,6 +248,10 @@ pub struct Wrapper<P: ClapPlugin> {
     /// having to add a setter function to the parameter (or even worse, have it be completely
     /// untyped).
     pub param_ptr_to_hash: HashMap<ParamPtr, u32>,
+    /// Whether a parameter is currently active, indexed by the parameter's hash. Inactive
+    /// parameters are reported as read-only. Set by the plugin through the [`InitContext`] and the
+    /// [`ProcessContext`].
+    param_active_by_hash: HashMap<u32, AtomicBool>,
     /// For all polyphonically modulatable parameters, mappings from the parameter hash's hash to
     /// the parameter's poly modulation ID. These IDs are then passed to the plugin, so it can
     /// quickly refer to parameter by matching on constant IDs.
@@ -277,6 +318,12 @@ pub enum Task<P: Plugin> {
     LatencyChanged,
     /// Inform the host that the voice info has changed.
     VoiceInfoChanged,
+    /// Inform the host that the number of active auxiliary output ports has changed.
+    AudioPortsChanged,
+    /// Inform the host that one or more parameters' active states have changed.
+    ParamInfoChanged,
+    /// Inform the host that the plugin's note names have changed.
+    NoteNamesChanged,
     /// Tell the host that it should rescan the current parameter values.
     RescanParamValues,
 }
@@ -405,6 +452,47 @@ impl<P: ClapPlugin> MainThreadExecutor<Task<P>> for Wrapper<P> {
                 }
                 None => nih_debug_assert_failure!("Host does not support the latency extension"),
             },
+            Task::AudioPortsChanged => match &*self.host_audio_ports.borrow() {
+                Some(host_audio_ports) => {
+                    nih_debug_assert!(is_gui_thread);
+
+                    // The port list may only change while the plugin is deactivated, so if it's
+                    // currently active we'll request a restart and rescan the ports when the host
+                    // deactivates the plugin
+                    if self.is_activated.load(Ordering::SeqCst) {
+                        self.audio_ports_rescan_pending
+                            .store(true, Ordering::SeqCst);
+                        unsafe_clap_call! { &*self.host_callback=>request_restart(&*self.host_callback) };
+                    } else {
+                        unsafe_clap_call! { host_audio_ports=>rescan(&*self.host_callback, CLAP_AUDIO_PORTS_RESCAN_LIST) };
+                    }
+                }
+                None => {
+                    nih_debug_assert_failure!("Host does not support the audio-ports extension")
+                }
+            },
+            Task::ParamInfoChanged => match &*self.host_params.borrow() {
+                Some(host_params) => {
+                    nih_debug_assert!(is_gui_thread);
+
+                    // The parameter flags can only be rescanned while the plugin is deactivated,
+                    // so this works the same way as the audio ports rescan above
+                    if self.is_activated.load(Ordering::SeqCst) {
+                        self.params_rescan_pending.store(true, Ordering::SeqCst);
+                        unsafe_clap_call! { &*self.host_callback=>request_restart(&*self.host_callback) };
+                    } else {
+                        unsafe_clap_call! { host_params=>rescan(&*self.host_callback, CLAP_PARAM_RESCAN_ALL) };
+                    }
+                }
+                None => nih_debug_assert_failure!("The host does not support parameters? What?"),
+            },
+            Task::NoteNamesChanged => match &*self.host_note_name.borrow() {
+                Some(host_note_name) => {
+                    nih_debug_assert!(is_gui_thread);
+                    unsafe_clap_call! { host_note_name=>changed(&*self.host_callback) };
+                }
+                None => nih_debug_assert_failure!("Host does not support the note-name extension"),
+            },
             Task::VoiceInfoChanged => match &*self.host_voice_info.borrow() {
                 Some(host_voice_info) => {
                     nih_debug_assert!(is_gui_thread);
@@ -481,6 +569,10 @@ impl<P: ClapPlugin> Wrapper<P> {
             .iter()
             .map(|(_, hash, ptr, _)| (*ptr, *hash))
             .collect();
+        let param_active_by_hash = param_id_hashes_ptrs_groups
+            .iter()
+            .map(|(_, hash, _, _)| (*hash, AtomicBool::new(true)))
+            .collect();
         let poly_mod_ids_by_hash: HashMap<u32, u32> = param_id_hashes_ptrs_groups
             .iter()
             .filter_map(|(_, hash, ptr, _)| unsafe {
@@ -543,16 +635,23 @@ impl<P: ClapPlugin> Wrapper<P> {
             editor_handle: Mutex::new(None),
             editor_scaling_factor: AtomicF32::new(1.0),
 
+            is_activated: AtomicBool::new(false),
             is_processing: AtomicBool::new(false),
             current_audio_io_layout: AtomicCell::new(
                 P::AUDIO_IO_LAYOUTS.first().copied().unwrap_or_default(),
             ),
             current_buffer_config: AtomicCell::new(None),
             current_process_mode: AtomicCell::new(ProcessMode::Realtime),
+            cpu_usage_meter: CpuUsageMeter::default(),
             input_events: AtomicRefCell::new(VecDeque::with_capacity(512)),
             output_events: AtomicRefCell::new(VecDeque::with_capacity(512)),
+            note_id_tracker: AtomicRefCell::new(NoteIdTracker::default()),
             last_process_status: AtomicCell::new(ProcessStatus::Normal),
+            last_tail_length: AtomicCell::new(None),
             current_latency: AtomicU32::new(0),
+            active_aux_output_ports: AtomicUsize::new(usize::MAX),
+            audio_ports_rescan_pending: AtomicBool::new(false),
+            params_rescan_pending: AtomicBool::new(false),
             // This is initialized just before calling `Plugin::initialize()` so that during the
             // process call buffers can be initialized without any allocations
             buffer_manager: AtomicRefCell::new(BufferManager::for_audio_io_layout(
@@ -595,6 +694,7 @@ impl<P: ClapPlugin> Wrapper<P> {
                 count: Some(Self::ext_audio_ports_count),
                 get: Some(Self::ext_audio_ports_get),
             },
+            host_audio_ports: AtomicRefCell::new(None),
 
             clap_plugin_gui: clap_plugin_gui {
                 is_api_supported: Some(Self::ext_gui_is_api_supported),
@@ -620,6 +720,13 @@ impl<P: ClapPlugin> Wrapper<P> {
             },
             host_latency: AtomicRefCell::new(None),
 
+            clap_plugin_note_name: clap_plugin_note_name {
+                count: Some(Self::ext_note_name_count),
+                get: Some(Self::ext_note_name_get),
+            },
+            host_note_name: AtomicRefCell::new(None),
+            note_names: AtomicRefCell::new(Vec::new()),
+
             clap_plugin_note_ports: clap_plugin_note_ports {
                 count: Some(Self::ext_note_ports_count),
                 get: Some(Self::ext_note_ports_get),
@@ -640,6 +747,7 @@ impl<P: ClapPlugin> Wrapper<P> {
             param_group_by_hash,
             param_id_to_hash,
             param_ptr_to_hash,
+            param_active_by_hash,
             poly_mod_ids_by_hash,
             output_parameter_events: ArrayQueue::new(OUTPUT_EVENT_QUEUE_CAPACITY),
 
@@ -1488,11 +1596,15 @@ impl<P: ClapPlugin> Wrapper<P> {
                         // When splitting up the buffer for sample accurate automation all events
                         // should be relative to the block
                         timing,
-                        voice_id: if event.note_id != -1 {
-                            Some(event.note_id)
-                        } else {
-                            None
-                        },
+                        voice_id: self.note_id_tracker.borrow_mut().note_on(
+                            if event.note_id != -1 {
+                                Some(event.note_id)
+                            } else {
+                                None
+                            },
+                            event.channel as u8,
+                            event.key as u8,
+                        ),
                         channel: event.channel as u8,
                         note: event.key as u8,
                         velocity: event.velocity as f32,
@@ -1504,11 +1616,17 @@ impl<P: ClapPlugin> Wrapper<P> {
                     let event = &*(event as *const clap_event_note);
                     input_events.push_back(NoteEvent::NoteOff {
                         timing,
-                        voice_id: if event.note_id != -1 {
-                            Some(event.note_id)
-                        } else {
-                            None
-                        },
+                        // Some hosts don't send note IDs for note off events, so we'll fill them
+                        // in if we can
+                        voice_id: self.note_id_tracker.borrow_mut().note_off(
+                            if event.note_id != -1 {
+                                Some(event.note_id)
+                            } else {
+                                None
+                            },
+                            event.channel as u8,
+                            event.key as u8,
+                        ),
                         channel: event.channel as u8,
                         note: event.key as u8,
                         velocity: event.velocity as f32,
@@ -1520,11 +1638,15 @@ impl<P: ClapPlugin> Wrapper<P> {
                     let event = &*(event as *const clap_event_note);
                     input_events.push_back(NoteEvent::Choke {
                         timing,
-                        voice_id: if event.note_id != -1 {
-                            Some(event.note_id)
-                        } else {
-                            None
-                        },
+                        voice_id: self.note_id_tracker.borrow_mut().note_off(
+                            if event.note_id != -1 {
+                                Some(event.note_id)
+                            } else {
+                                None
+                            },
+                            event.channel as u8,
+                            event.key as u8,
+                        ),
                         // FIXME: These values are also allowed to be -1, we need to support that
                         channel: event.channel as u8,
                         note: event.key as u8,
@@ -1745,6 +1867,75 @@ impl<P: ClapPlugin> Wrapper<P> {
         }
     }
 
+    /// Get the current buffer configuration, if the plugin has been activated. The processing mode
+    /// can be changed by the host while the plugin is activated, so that's read separately.
+    pub fn current_buffer_config(&self) -> Option<BufferConfig> {
+        self.current_buffer_config
+            .load()
+            .map(|buffer_config| BufferConfig {
+                process_mode: self.current_process_mode.load(),
+                ..buffer_config
+            })
+    }
+
+    pub fn set_active_aux_output_ports(&self, num_ports: usize) {
+        // Same as the above, this only informs the host when the exposed ports actually change
+        let old_num_ports = self
+            .active_aux_output_ports
+            .swap(num_ports, Ordering::SeqCst);
+        let num_aux_output_ports = self.current_audio_io_layout.load().aux_output_ports.len();
+        if old_num_ports.min(num_aux_output_ports) != num_ports.min(num_aux_output_ports) {
+            let task_posted = self.schedule_gui(Task::AudioPortsChanged);
+            nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+        }
+    }
+
+    /// Update a parameter's active state. Returns `true` if the state changed, in which case the
+    /// host should be informed using [`Task::ParamInfoChanged`].
+    pub fn update_param_active(&self, param: ParamPtr, active: bool) -> bool {
+        match self
+            .param_ptr_to_hash
+            .get(&param)
+            .and_then(|hash| self.param_active_by_hash.get(hash))
+        {
+            Some(param_active) => param_active.swap(active, Ordering::SeqCst) != active,
+            None => {
+                nih_debug_assert_failure!("Unknown parameter: {:?}", param);
+                false
+            }
+        }
+    }
+
+    pub fn set_param_active(&self, param: ParamPtr, active: bool) {
+        if self.update_param_active(param, active) {
+            let task_posted = self.schedule_gui(Task::ParamInfoChanged);
+            nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+        }
+    }
+
+    /// The features the host supports. These are determined by the host extensions queried when
+    /// the plugin was initialized.
+    pub fn host_features(&self) -> HostFeatures {
+        HostFeatures {
+            latency_changes: self.host_latency.borrow().is_some(),
+            audio_port_changes: self.host_audio_ports.borrow().is_some(),
+            param_info_changes: self.host_params.borrow().is_some(),
+            note_name_changes: self.host_note_name.borrow().is_some(),
+            voice_info: self.host_voice_info.borrow().is_some(),
+        }
+    }
+
+    pub fn note_names_changed(&self) {
+        nih_debug_assert!(
+            P::CLAP_NOTE_NAMES,
+            "The note names can only change if 'ClapPlugin::CLAP_NOTE_NAMES' is set"
+        );
+        if P::CLAP_NOTE_NAMES {
+            let task_posted = self.schedule_gui(Task::NoteNamesChanged);
+            nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+        }
+    }
+
     pub fn set_current_voice_capacity(&self, capacity: u32) {
         match P::CLAP_POLY_MODULATION_CONFIG {
             Some(config) => {
@@ -1810,17 +2001,19 @@ impl<P: ClapPlugin> Wrapper<P> {
 
             // See above
             success = permit_alloc(|| {
-                plugin.initialize(&audio_io_layout, &buffer_config, &mut init_context)
+                initialize_plugin(
+                    &mut *plugin,
+                    &audio_io_layout,
+                    &buffer_config,
+                    &mut init_context,
+                )
             });
             if success {
                 process_wrapper(|| plugin.reset());
             }
         }
 
-        nih_debug_assert!(
-            success,
-            "Plugin returned false when reinitializing after loading state"
-        );
+        nih_debug_assert!(success, "Plugin failed to reinitialize after loading state");
 
         // Reinitialize the plugin after loading state so it can respond to the new parameter values
         let task_posted = self.schedule_gui(Task::ParameterValuesChanged);
@@ -1841,10 +2034,16 @@ impl<P: ClapPlugin> Wrapper<P> {
         let wrapper = &*((*plugin).plugin_data as *const Self);
 
         // We weren't allowed to query these in the constructor, so we need to do it now instead.
+        *wrapper.host_audio_ports.borrow_mut() = query_host_extension::<clap_host_audio_ports>(
+            &wrapper.host_callback,
+            CLAP_EXT_AUDIO_PORTS,
+        );
         *wrapper.host_gui.borrow_mut() =
             query_host_extension::<clap_host_gui>(&wrapper.host_callback, CLAP_EXT_GUI);
         *wrapper.host_latency.borrow_mut() =
             query_host_extension::<clap_host_latency>(&wrapper.host_callback, CLAP_EXT_LATENCY);
+        *wrapper.host_note_name.borrow_mut() =
+            query_host_extension::<clap_host_note_name>(&wrapper.host_callback, CLAP_EXT_NOTE_NAME);
         *wrapper.host_params.borrow_mut() =
             query_host_extension::<clap_host_params>(&wrapper.host_callback, CLAP_EXT_PARAMS);
         *wrapper.host_voice_info.borrow_mut() = query_host_extension::<clap_host_voice_info>(
@@ -1892,7 +2091,12 @@ impl<P: ClapPlugin> Wrapper<P> {
         // NOTE: This needs to be dropped after the `plugin` lock to avoid deadlocks
         let mut init_context = wrapper.make_init_context();
         let mut plugin = wrapper.plugin.lock();
-        if plugin.initialize(&audio_io_layout, &buffer_config, &mut init_context) {
+        if initialize_plugin(
+            &mut *plugin,
+            &audio_io_layout,
+            &buffer_config,
+            &mut init_context,
+        ) {
             // NOTE: `Plugin::reset()` is called in `clap_plugin::start_processing()` instead of in
             //       this function
 
@@ -1903,6 +2107,7 @@ impl<P: ClapPlugin> Wrapper<P> {
 
             // Also store this for later, so we can reinitialize the plugin after restoring state
             wrapper.current_buffer_config.store(Some(buffer_config));
+            wrapper.is_activated.store(true, Ordering::SeqCst);
 
             true
         } else {
@@ -1915,6 +2120,23 @@ impl<P: ClapPlugin> Wrapper<P> {
         let wrapper = &*((*plugin).plugin_data as *const Self);
 
         wrapper.plugin.lock().deactivate();
+        wrapper.is_activated.store(false, Ordering::SeqCst);
+
+        // If the active auxiliary output ports changed while the plugin was activated, then the
+        // host can now pick up the new port list
+        if wrapper
+            .audio_ports_rescan_pending
+            .swap(false, Ordering::SeqCst)
+        {
+            if let Some(host_audio_ports) = &*wrapper.host_audio_ports.borrow() {
+                unsafe_clap_call! { host_audio_ports=>rescan(&*wrapper.host_callback, CLAP_AUDIO_PORTS_RESCAN_LIST) };
+            }
+        }
+        if wrapper.params_rescan_pending.swap(false, Ordering::SeqCst) {
+            if let Some(host_params) = &*wrapper.host_params.borrow() {
+                unsafe_clap_call! { host_params=>rescan(&*wrapper.host_callback, CLAP_PARAM_RESCAN_ALL) };
+            }
+        }
     }
 
     unsafe extern "C" fn start_processing(plugin: *const clap_plugin) -> bool {
@@ -1925,10 +2147,12 @@ impl<P: ClapPlugin> Wrapper<P> {
 
         // Always reset the processing status when the plugin gets activated or deactivated
         wrapper.last_process_status.store(ProcessStatus::Normal);
+        wrapper.last_tail_length.store(None);
         wrapper.is_processing.store(true, Ordering::SeqCst);
 
         // To be consistent with the VST3 wrapper, we'll also reset the buffers here in addition to
         // the dedicated `reset()` function.
+        wrapper.note_id_tracker.borrow_mut().reset();
         process_wrapper(|| wrapper.plugin.lock().reset());
 
         true
@@ -1945,6 +2169,7 @@ impl<P: ClapPlugin> Wrapper<P> {
         check_null_ptr!((), plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
 
+        wrapper.note_id_tracker.borrow_mut().reset();
         process_wrapper(|| wrapper.plugin.lock().reset());
     }
 
@@ -2104,7 +2329,7 @@ impl<P: ClapPlugin> Wrapper<P> {
                                 .enumerate()
                             {
                                 let aux_output_idx = aux_output_no + aux_output_start_idx;
-                                if aux_output_idx > process.audio_outputs_count as usize {
+                                if aux_output_idx >= process.audio_outputs_count as usize {
                                     break;
                                 }
 
@@ -2245,8 +2470,14 @@ impl<P: ClapPlugin> Wrapper<P> {
                         outputs: buffers.aux_outputs,
                     };
                     let mut context = wrapper.make_process_context(transport);
-                    let result = plugin.process(buffers.main_buffer, &mut aux, &mut context);
+                    let result =
+                        wrapper
+                            .cpu_usage_meter
+                            .measure::<P, _>(block_len, sample_rate, || {
+                                plugin.process(buffers.main_buffer, &mut aux, &mut context)
+                            });
                     wrapper.last_process_status.store(result);
+                    wrapper.last_tail_length.store(plugin.tail_length_samples());
                     result
                 } else {
                     ProcessStatus::Normal
@@ -2321,6 +2552,8 @@ impl<P: ClapPlugin> Wrapper<P> {
             &wrapper.clap_plugin_gui as *const _ as *const c_void
         } else if id == CLAP_EXT_LATENCY {
             &wrapper.clap_plugin_latency as *const _ as *const c_void
+        } else if id == CLAP_EXT_NOTE_NAME && P::CLAP_NOTE_NAMES {
+            &wrapper.clap_plugin_note_name as *const _ as *const c_void
         } else if id == CLAP_EXT_NOTE_PORTS
             && (P::MIDI_INPUT >= MidiConfig::Basic || P::MIDI_OUTPUT >= MidiConfig::Basic)
         {
@@ -2463,7 +2696,11 @@ impl<P: ClapPlugin> Wrapper<P> {
             } else {
                 0
             };
-            let aux_ports = audio_io_layout.aux_output_ports.len();
+            // The plugin may only want to expose some of its auxiliary outputs
+            let aux_ports = audio_io_layout
+                .aux_output_ports
+                .len()
+                .min(wrapper.active_aux_output_ports.load(Ordering::SeqCst));
 
             (main_ports + aux_ports) as u32
         }
@@ -2836,6 +3073,51 @@ impl<P: ClapPlugin> Wrapper<P> {
         wrapper.current_latency.load(Ordering::SeqCst)
     }
 
+    unsafe extern "C" fn ext_note_name_count(plugin: *const clap_plugin) -> u32 {
+        check_null_ptr!(0, plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        // The host queries the individual names right after this, so this is where we'll fetch the
+        // plugin's current note names
+        let note_names = wrapper.plugin.lock().note_names();
+        let mut clap_note_names = wrapper.note_names.borrow_mut();
+        clap_note_names.clear();
+        clap_note_names.extend(note_names.into_iter().map(|note_name| {
+            let mut clap_note_name: clap_note_name = std::mem::zeroed();
+            strlcpy(&mut clap_note_name.name, &note_name.name);
+            // The plugin has at most one note input port, so the names apply to all ports
+            clap_note_name.port = -1;
+            clap_note_name.key = note_name.note as i16;
+            clap_note_name.channel = note_name
+                .channel
+                .map(|channel| channel as i16)
+                .unwrap_or(-1);
+
+            clap_note_name
+        }));
+
+        clap_note_names.len() as u32
+    }
+
+    unsafe extern "C" fn ext_note_name_get(
+        plugin: *const clap_plugin,
+        index: u32,
+        note_name: *mut clap_note_name,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, note_name);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        let clap_note_names = wrapper.note_names.borrow();
+        nih_debug_assert!((index as usize) < clap_note_names.len());
+        match clap_note_names.get(index as usize) {
+            Some(n) => {
+                *note_name = *n;
+                true
+            }
+            None => false,
+        }
+    }
+
     unsafe extern "C" fn ext_note_ports_count(_plugin: *const clap_plugin, is_input: bool) -> u32 {
         match is_input {
             true if P::MIDI_INPUT >= MidiConfig::Basic => 1,
@@ -2928,6 +3210,9 @@ impl<P: ClapPlugin> Wrapper<P> {
         if hidden {
             param_info.flags |= CLAP_PARAM_IS_HIDDEN | CLAP_PARAM_IS_READONLY;
         }
+        if !wrapper.param_active_by_hash[param_hash].load(Ordering::SeqCst) {
+            param_info.flags |= CLAP_PARAM_IS_READONLY;
+        }
         if is_bypass {
             param_info.flags |= CLAP_PARAM_IS_BYPASS
         }
@@ -3179,11 +3464,10 @@ impl<P: ClapPlugin> Wrapper<P> {
         check_null_ptr!(0, plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
 
-        match wrapper.last_process_status.load() {
-            ProcessStatus::Tail(samples) => samples,
-            ProcessStatus::KeepAlive => u32::MAX,
-            _ => 0,
-        }
+        reported_tail_length(
+            wrapper.last_process_status.load(),
+            wrapper.last_tail_length.load(),
+        )
     }
 
     unsafe extern "C" fn ext_voice_info_get(
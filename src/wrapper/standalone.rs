@@ -9,9 +9,11 @@ use self::wrapper::{Wrapper, WrapperError};
 use super::util::setup_logger;
 use crate::prelude::Plugin;
 
+mod automation;
 mod backend;
 mod config;
 mod context;
+mod input_file;
 mod wrapper;
 
 /// Open an NIH-plug plugin as a standalone application. If the plugin has an editor, this will open
@@ -71,6 +73,11 @@ pub fn nih_export_standalone_with_args<P: Plugin, Args: IntoIterator<Item = Stri
     )
     .unwrap_or_else(|err| err.exit());
 
+    if config.list_devices {
+        backend::list_devices();
+        return true;
+    }
+
     match config.backend {
         config::BackendType::Auto => {
             let result = backend::Jack::new::<P>(config.clone()).map(|backend| {
@@ -176,6 +183,22 @@ pub fn nih_export_standalone_with_args<P: Plugin, Args: IntoIterator<Item = Stri
 }
 
 fn run_wrapper<P: Plugin, B: Backend<P>>(backend: B, config: WrapperConfig) -> bool {
+    // The backend may not be able to use the requested sample rate and period size
+    let actual_config = backend.actual_config();
+    if actual_config.sample_rate != config.sample_rate
+        || actual_config.period_size != config.period_size
+    {
+        nih_log!(
+            "The audio backend is running at {} Hz with a period size of {} samples instead of \
+             the requested {} Hz and {} samples",
+            actual_config.sample_rate,
+            actual_config.period_size,
+            config.sample_rate,
+            config.period_size
+        );
+    }
+    let config = actual_config;
+
     let wrapper = match Wrapper::<P, _>::new(backend, config) {
         Ok(wrapper) => wrapper,
         Err(err) => {
@@ -196,8 +219,17 @@ fn run_wrapper<P: Plugin, B: Backend<P>>(backend: B, config: WrapperConfig) -> b
 
 fn print_error(error: WrapperError) {
     match error {
-        WrapperError::InitializationFailed => {
-            nih_error!("The plugin failed to initialize");
+        WrapperError::InitializationFailed(reason) => {
+            nih_error!("The plugin failed to initialize: {reason}");
+        }
+        WrapperError::ReadAutomationFailed => {
+            nih_error!("Could not read the automation file to replay");
+        }
+        WrapperError::WriteAutomationFailed => {
+            nih_error!("Could not write the recorded automation");
+        }
+        WrapperError::ReadInputFileFailed => {
+            nih_error!("Could not read the audio file to use as the input");
         }
     }
 }
@@ -0,0 +1,203 @@
+//! Recording and replaying parameter changes and note events for the standalone wrapper. This can
+//! be used to build reproducible test cases for a plugin's behavior under automation, especially
+//! when combined with the dummy backend.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::fs;
+use std::path::Path;
+
+use crate::midi::sysex::SysExMessage;
+use crate::midi::{MidiResult, NoteEvent};
+
+/// A recorded session's parameter changes and note events, stored as JSON. Parameters are
+/// referenced by their stable string IDs.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AutomationRecording {
+    /// The sample rate the session was recorded at. All event timings are in samples, so replaying
+    /// at a different sample rate will change the events' timings.
+    pub sample_rate: f32,
+    /// The recorded events, sorted by their timing.
+    pub events: Vec<AutomationEvent>,
+}
+
+/// A single recorded event. The timings are absolute sample positions, starting at the first
+/// sample processed by the plugin.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AutomationEvent {
+    /// A parameter was set to a new normalized value. The standalone wrapper only updates
+    /// parameters between processing cycles, so this is always aligned to the start of a block.
+    ParameterChange {
+        timing: u64,
+        param_id: String,
+        normalized_value: f32,
+    },
+    /// A note event was sent to the plugin. This is stored as MIDI since the standalone backends
+    /// only receive MIDI.
+    Midi { timing: u64, data: Vec<u8> },
+}
+
+/// Replays an [`AutomationRecording`] one block at a time.
+#[derive(Debug)]
+pub struct AutomationPlayer {
+    events: Vec<AutomationEvent>,
+    /// The index in `events` of the first event that has not yet been returned.
+    next_event_idx: usize,
+}
+
+impl AutomationRecording {
+    /// Create an empty recording for a session with the specified sample rate.
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            sample_rate,
+            events: Vec::with_capacity(4096),
+        }
+    }
+
+    /// Read a recording from a JSON file.
+    pub fn read(path: &Path) -> Result<Self> {
+        let json =
+            fs::read(path).with_context(|| format!("Could not read '{}'", path.display()))?;
+
+        serde_json::from_slice(&json)
+            .with_context(|| format!("Could not parse '{}' as an automation file", path.display()))
+    }
+
+    /// Write the recording to a JSON file, overwriting the file if it already exists.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_vec_pretty(self).context("Could not format as JSON")?;
+
+        fs::write(path, json).with_context(|| format!("Could not write '{}'", path.display()))
+    }
+}
+
+impl AutomationEvent {
+    /// Convert a note event from the block starting at `block_start` to a recorded MIDI event.
+    /// Returns `None` if the event doesn't have a MIDI equivalent.
+    pub fn from_note_event<S: SysExMessage>(block_start: u64, event: NoteEvent<S>) -> Option<Self> {
+        let timing = block_start + event.timing() as u64;
+        let data = match event.as_midi()? {
+            MidiResult::Basic(midi_data) => midi_data.to_vec(),
+            MidiResult::SysEx(padded_sysex_buffer, length) => {
+                // The SysEx buffer may contain padding
+                let padded_sysex_buffer = padded_sysex_buffer.borrow();
+                padded_sysex_buffer[..length.min(padded_sysex_buffer.len())].to_vec()
+            }
+        };
+
+        Some(AutomationEvent::Midi { timing, data })
+    }
+
+    /// The event's absolute timing in samples.
+    pub fn timing(&self) -> u64 {
+        match self {
+            AutomationEvent::ParameterChange { timing, .. } => *timing,
+            AutomationEvent::Midi { timing, .. } => *timing,
+        }
+    }
+}
+
+impl AutomationPlayer {
+    /// Prepare a recording for playback. The events are sorted by their timing in case the file
+    /// was edited by hand.
+    pub fn new(recording: AutomationRecording) -> Self {
+        let mut events = recording.events;
+        events.sort_by_key(AutomationEvent::timing);
+
+        Self {
+            events,
+            next_event_idx: 0,
+        }
+    }
+
+    /// Get all events that should be sent to the plugin before the block ending at the sample
+    /// position `block_end`. Events are only returned once.
+    pub fn next_block(&mut self, block_end: u64) -> &[AutomationEvent] {
+        let block_start_idx = self.next_event_idx;
+        while self.next_event_idx < self.events.len()
+            && self.events[self.next_event_idx].timing() < block_end
+        {
+            self.next_event_idx += 1;
+        }
+
+        &self.events[block_start_idx..self.next_event_idx]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameter_change(timing: u64) -> AutomationEvent {
+        AutomationEvent::ParameterChange {
+            timing,
+            param_id: String::from("gain"),
+            normalized_value: 0.5,
+        }
+    }
+
+    #[test]
+    fn player_splits_blocks() {
+        let mut player = AutomationPlayer::new(AutomationRecording {
+            sample_rate: 48000.0,
+            events: vec![
+                parameter_change(512),
+                parameter_change(0),
+                AutomationEvent::Midi {
+                    timing: 700,
+                    data: vec![0x90, 60, 100],
+                },
+            ],
+        });
+
+        assert_eq!(player.next_block(512), &[parameter_change(0)]);
+        assert_eq!(
+            player.next_block(1024),
+            &[
+                parameter_change(512),
+                AutomationEvent::Midi {
+                    timing: 700,
+                    data: vec![0x90, 60, 100],
+                },
+            ]
+        );
+        assert!(player.next_block(1536).is_empty());
+    }
+
+    #[test]
+    fn note_event_roundtrip() {
+        let event: NoteEvent<()> = NoteEvent::from_midi(12, &[0x91, 69, 64]).unwrap();
+        let recorded = AutomationEvent::from_note_event(1024, event).unwrap();
+
+        let AutomationEvent::Midi { timing, data } = &recorded else {
+            panic!("Expected a MIDI event, got {recorded:?}");
+        };
+        assert_eq!(*timing, 1036);
+        assert_eq!(
+            NoteEvent::<()>::from_midi(*timing as u32 - 1024, data),
+            Ok(event)
+        );
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let recording = AutomationRecording {
+            sample_rate: 44100.0,
+            events: vec![
+                parameter_change(0),
+                AutomationEvent::Midi {
+                    timing: 64,
+                    data: vec![0x80, 60, 0],
+                },
+            ],
+        };
+
+        let json = serde_json::to_string(&recording).unwrap();
+        assert_eq!(
+            serde_json::from_str::<AutomationRecording>(&json).unwrap(),
+            recording
+        );
+    }
+}
@@ -1,10 +1,11 @@
+use super::config::WrapperConfig;
 use crate::prelude::{AuxiliaryBuffers, PluginNoteEvent, Transport};
 
 mod cpal;
 mod dummy;
 mod jack;
 
-pub use self::cpal::CpalMidir;
+pub use self::cpal::{list_devices, CpalMidir};
 pub use self::dummy::Dummy;
 pub use self::jack::Jack;
 pub use crate::buffer::Buffer;
@@ -12,6 +13,11 @@ pub use crate::plugin::Plugin;
 
 /// An audio+MIDI backend for the standalone wrapper.
 pub trait Backend<P: Plugin>: 'static + Send + Sync {
+    /// The configuration the backend will actually run with. The sample rate and period size may
+    /// differ from the ones that were requested, for instance because JACK's are set by the JACK
+    /// server. The plugin should be initialized using these values.
+    fn actual_config(&self) -> WrapperConfig;
+
     /// Start processing audio and MIDI on this thread. The process callback will be called whenever
     /// there's a new block of audio to be processed. The process callback receives the audio
     /// buffers for the wrapped plugin's outputs. Any inputs will have already been copied to this
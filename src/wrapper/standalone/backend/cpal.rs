@@ -1,7 +1,7 @@
 use anyhow::{Context, Result};
 use cpal::{
-    traits::*, Device, FromSample, InputCallbackInfo, OutputCallbackInfo, Sample, SampleFormat,
-    Stream, StreamConfig,
+    traits::*, Device, FromSample, InputCallbackInfo, Sample, SampleFormat, Stream, StreamConfig,
+    SupportedStreamConfigRange,
 };
 use crossbeam::sync::{Parker, Unparker};
 use midir::{
@@ -12,7 +12,10 @@ use rtrb::RingBuffer;
 use std::borrow::Borrow;
 use std::num::NonZeroU32;
 use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::thread::ScopedJoinHandle;
+use std::time::{Duration, Instant};
 
 use super::super::config::WrapperConfig;
 use super::Backend;
@@ -31,7 +34,21 @@ pub struct CpalMidir {
     audio_io_layout: AudioIOLayout,
 
     input: Option<CpalDevice>,
-    output: CpalDevice,
+    /// Whether the plugin's sidechain inputs are read from the input device's additional channels,
+    /// which come after the main input's channels. This is only done when no separate sidechain
+    /// input devices have been specified and the input device has enough channels.
+    aux_inputs_from_input: bool,
+    /// The input devices for the plugin's sidechain inputs, in the same order as
+    /// `audio_io_layout.aux_input_ports`. There may be fewer devices than sidechain inputs, in
+    /// which case the remaining sidechain inputs receive silence.
+    aux_inputs: Vec<CpalDevice>,
+    /// The number of the plugin's auxiliary output ports that are written to the output device's
+    /// additional channels, which come after the main output's channels. The remaining auxiliary
+    /// outputs are discarded.
+    num_aux_outputs_on_output: usize,
+    /// This is `None` when the plugin does not have any main audio outputs. Processing is then
+    /// driven by a timer instead of by an audio output stream.
+    output: Option<CpalDevice>,
 
     midi_input: Mutex<Option<MidirInputDevice>>,
     midi_output: Mutex<Option<MidirOutputDevice>>,
@@ -92,6 +109,12 @@ enum MidiOutputTask<P: Plugin> {
 }
 
 impl<P: Plugin> Backend<P> for CpalMidir {
+    fn actual_config(&self) -> WrapperConfig {
+        // `new()` already replaced the requested settings with the ones the output device
+        // supports. Without an output stream the requested settings are used as is.
+        self.config.clone()
+    }
+
     fn run(
         &mut self,
         cb: impl FnMut(
@@ -104,82 +127,72 @@ impl<P: Plugin> Backend<P> for CpalMidir {
             + 'static
             + Send,
     ) {
-        // So this is a lot of fun. There are up to four separate streams here, all using their own
-        // callbacks. The audio output stream acts as the primary stream, and everything else either
-        // sends data to it or (in the case of the MIDI output stream) receives data from it using
-        // channels.
+        // So this is a lot of fun. There are at least four separate streams here, all using their
+        // own callbacks. The audio output stream acts as the primary stream, and everything else
+        // either sends data to it or (in the case of the MIDI output stream) receives data from it
+        // using channels.
         //
         // Audio input is read from the input device (if configured), and is send at a period at a
-        // time to the output stream in an interleaved format. Because of that the audio output
-        // stream is delayed for one period using a parker to you don't immediately get xruns. CPAL
-        // audio devices may also not accept floating point samples, so all of the actual audio
-        // handling and buffer management handles in the `build_*_data_callback()` functions defined
-        // below.
+        // time to the output stream in an interleaved format. Sidechain inputs work the same way,
+        // with one additional input stream per configured sidechain input device. Without those
+        // devices, the sidechain inputs may also be read from the input device's additional
+        // channels. Because of that the audio output stream is delayed for one period (or half a
+        // period with the `--low-latency` option) using a parker to you don't immediately get
+        // xruns. CPAL audio devices may also not accept floating point samples, so all of the
+        // actual audio handling and buffer management handles in the `build_*_data_callback()`
+        // functions defined below.
         //
         // MIDI input is parsed in the Midir callback and the events are sent over a callback to the
         // output audio thread where the process callback happens. If that process callback outputs
         // events then those are sent over another ringbuffer to a thread that handles MIDI output.
         // Both MIDI input and MIDI output are disabled by default.
         //
+        // Processing stops when the plugin's process callback returns `false` or when any of the
+        // streams runs into an error. At that point a shared flag is set so the output callback
+        // stops calling the process callback and only outputs silence, and this thread is unparked
+        // so it can drop the streams.
+        //
+        // Plugins without any main audio outputs, like pure MIDI processors, don't get an audio
+        // output stream. Instead the process callback is called from this thread at the same rate
+        // an output stream would have called it. An input stream is never created in that case.
+        //
         // The thread scope is needed to accomodate the midir MIDI output API. Outputting MIDI is
         // realtime unsafe, and to be able to output MIDI with midir you need to transform between
         // `MidiOutputPort` and `MidiOutputPortConnection` types by taking values out of an
         // `Option`.
         std::thread::scope(|s| {
-            let mut _input_stream: Option<Stream> = None;
+            // This thread needs to be blocked until audio processing ends as CPAL processes the
+            // streams on another thread instead of blocking
+            let should_stop = Arc::new(AtomicBool::new(false));
+            let parker = Parker::new();
+            let unparker = parker.unparker().clone();
+
+            // These need to be kept alive until processing ends
+            let mut input_streams: Vec<Stream> = Vec::with_capacity(self.aux_inputs.len() + 1);
+            let mut input_parkers: Vec<Parker> = Vec::with_capacity(self.aux_inputs.len() + 1);
             let mut input_rb_consumer: Option<rtrb::Consumer<f32>> = None;
             if let Some(input) = &self.input {
-                // Data is sent to the output data callback using a wait-free ring buffer
-                let (rb_producer, rb_consumer) = RingBuffer::new(
-                    self.output.config.channels as usize * self.config.period_size as usize,
-                );
+                let (stream, rb_consumer, parker) =
+                    self.start_input_stream(input, should_stop.clone(), unparker.clone());
+                input_streams.push(stream);
+                input_parkers.push(parker);
                 input_rb_consumer = Some(rb_consumer);
+            }
 
-                let input_parker = Parker::new();
-                let input_unparker = input_parker.unparker().clone();
-                let error_cb = {
-                    let input_unparker = input_unparker.clone();
-                    move |err| {
-                        nih_error!("Error during capture: {err:#}");
-                        input_unparker.clone().unpark();
-                    }
-                };
+            let mut aux_input_rb_consumers: Vec<rtrb::Consumer<f32>> =
+                Vec::with_capacity(self.aux_inputs.len());
+            for aux_input in &self.aux_inputs {
+                let (stream, rb_consumer, parker) =
+                    self.start_input_stream(aux_input, should_stop.clone(), unparker.clone());
+                input_streams.push(stream);
+                input_parkers.push(parker);
+                aux_input_rb_consumers.push(rb_consumer);
+            }
 
-                macro_rules! build_input_streams {
-                    ($sample_format:expr, $(($format:path, $primitive_type:ty)),*) => {
-                        match $sample_format {
-                            $($format => input.device.build_input_stream(
-                                &input.config,
-                                self.build_input_data_callback::<$primitive_type>(input_unparker, rb_producer),
-                                error_cb,
-                                None,
-                            ),)*
-                            format => todo!("Unsupported sample format {format}"),
-                        }
-                    }
-                }
-                let stream = build_input_streams!(
-                    input.sample_format,
-                    (SampleFormat::I8, i8),
-                    (SampleFormat::I16, i16),
-                    (SampleFormat::I32, i32),
-                    (SampleFormat::I64, i64),
-                    (SampleFormat::U8, u8),
-                    (SampleFormat::U16, u16),
-                    (SampleFormat::U32, u32),
-                    (SampleFormat::U64, u64),
-                    (SampleFormat::F32, f32),
-                    (SampleFormat::F64, f64)
-                )
-                .expect("Fatal error creating the capture stream");
-                stream
-                    .play()
-                    .expect("Fatal error trying to start the capture stream");
-                _input_stream = Some(stream);
-
-                // Playback is delayed one period if we're capturing audio so it has something to
-                // process
-                input_parker.park()
+            // Playback is delayed one period, or half a period in low latency mode, if we're
+            // capturing audio so every input has something to process
+            for input_parker in input_parkers {
+                input_parker.park();
             }
 
             // The output callback can read input events from this ringbuffer
@@ -276,61 +289,105 @@ impl<P: Plugin> Backend<P> for CpalMidir {
                     }
                 });
 
-            // This thread needs to be blocked until audio processing ends as CPAL processes the
-            // streams on another thread instead of blocking
-            let parker = Parker::new();
-            let unparker = parker.unparker().clone();
-            let error_cb = {
-                let unparker = unparker.clone();
-                move |err| {
-                    nih_error!("Error during playback: {err:#}");
-                    unparker.clone().unpark();
+            match &self.output {
+                Some(output) => {
+                    let error_cb = {
+                        let should_stop = should_stop.clone();
+                        let unparker = unparker.clone();
+                        move |err| {
+                            nih_error!("Error during playback: {err:#}");
+                            should_stop.store(true, Ordering::Relaxed);
+                            unparker.unpark();
+                        }
+                    };
+
+                    macro_rules! build_output_streams {
+                        ($sample_format:expr, $(($format:path, $primitive_type:ty)),*) => {
+                            match $sample_format {
+                                $($format => {
+                                    let mut process = self
+                                        .build_output_data_callback::<P, $primitive_type>(
+                                            input_rb_consumer,
+                                            aux_input_rb_consumers,
+                                            midi_input_rb_consumer,
+                                            // This is a MPMC crossbeam channel instead of an rtrb
+                                            // ringbuffer, and we also need it to terminate the
+                                            // thread
+                                            midi_output_rb_producer.clone(),
+                                            should_stop.clone(),
+                                            cb,
+                                        );
+
+                                    output.device.build_output_stream(
+                                        &output.config,
+                                        move |data, _info| {
+                                            // The stream keeps running until this thread drops
+                                            // it, but it only outputs silence from here on
+                                            if !process(data) {
+                                                unparker.unpark();
+                                            }
+                                        },
+                                        error_cb,
+                                        None,
+                                    )
+                                },)*
+                                format => todo!("Unsupported sample format {format}"),
+                            }
+                        }
+                    }
+                    let output_stream = build_output_streams!(
+                        output.sample_format,
+                        (SampleFormat::I8, i8),
+                        (SampleFormat::I16, i16),
+                        (SampleFormat::I32, i32),
+                        (SampleFormat::I64, i64),
+                        (SampleFormat::U8, u8),
+                        (SampleFormat::U16, u16),
+                        (SampleFormat::U32, u32),
+                        (SampleFormat::U64, u64),
+                        (SampleFormat::F32, f32),
+                        (SampleFormat::F64, f64)
+                    )
+                    .expect("Fatal error creating the output stream");
+
+                    // TODO: Wait a period before doing this when also reading the input
+                    output_stream
+                        .play()
+                        .expect("Fatal error trying to start the output stream");
+
+                    // Wait for the audio thread to exit, and then stop all streams right away
+                    parker.park();
+                    drop(output_stream);
+                    input_streams.clear();
                 }
-            };
+                None => {
+                    // Without an output stream there's nothing to synchronize to, so we'll call
+                    // the process callback once every period just like the dummy backend does
+                    let interval = Duration::from_secs_f32(
+                        self.config.period_size as f32 / self.config.sample_rate,
+                    );
+                    let mut process = self.build_output_data_callback::<P, f32>(
+                        input_rb_consumer,
+                        aux_input_rb_consumers,
+                        midi_input_rb_consumer,
+                        midi_output_rb_producer.clone(),
+                        should_stop.clone(),
+                        cb,
+                    );
+
+                    loop {
+                        let period_start = Instant::now();
+                        if !process(&mut []) {
+                            break;
+                        }
 
-            macro_rules! build_output_streams {
-                ($sample_format:expr, $(($format:path, $primitive_type:ty)),*) => {
-                    match $sample_format {
-                        $($format => self.output.device.build_output_stream(
-                            &self.output.config,
-                            self.build_output_data_callback::<P, $primitive_type>(
-                                unparker,
-                                input_rb_consumer,
-                                midi_input_rb_consumer,
-                                // This is a MPMC crossbeam channel instead of an rtrb ringbuffer, and we
-                                // also need it to terminate the thread
-                                midi_output_rb_producer.clone(),
-                                cb,
-                            ),
-                            error_cb,
-                            None,
-                        ),)*
-                        format => todo!("Unsupported sample format {format}"),
+                        let period_end = Instant::now();
+                        std::thread::sleep(
+                            (period_start + interval).saturating_duration_since(period_end),
+                        );
                     }
                 }
             }
-            let output_stream = build_output_streams!(
-                self.output.sample_format,
-                (SampleFormat::I8, i8),
-                (SampleFormat::I16, i16),
-                (SampleFormat::I32, i32),
-                (SampleFormat::I64, i64),
-                (SampleFormat::U8, u8),
-                (SampleFormat::U16, u16),
-                (SampleFormat::U32, u32),
-                (SampleFormat::U64, u64),
-                (SampleFormat::F32, f32),
-                (SampleFormat::F64, f64)
-            )
-            .expect("Fatal error creating the output stream");
-
-            // TODO: Wait a period before doing this when also reading the input
-            output_stream
-                .play()
-                .expect("Fatal error trying to start the output stream");
-
-            // Wait for the audio thread to exit
-            parker.park();
 
             // The Midir API requires us to take things out of Options and transform between these
             // structs
@@ -363,11 +420,24 @@ impl<P: Plugin> Backend<P> for CpalMidir {
 impl CpalMidir {
     /// Initialize the backend with the specified host. Returns an error if this failed for whatever
     /// reason.
-    pub fn new<P: Plugin>(config: WrapperConfig, cpal_host_id: cpal::HostId) -> Result<Self> {
+    pub fn new<P: Plugin>(mut config: WrapperConfig, cpal_host_id: cpal::HostId) -> Result<Self> {
         let audio_io_layout = config.audio_io_layout_or_exit::<P>();
         let host = cpal::host_from_id(cpal_host_id).context("The Audio API is unavailable")?;
+        let num_input_channels = audio_io_layout
+            .main_input_channels
+            .map(NonZeroU32::get)
+            .unwrap_or_default() as usize;
+        let num_output_channels = audio_io_layout
+            .main_output_channels
+            .map(NonZeroU32::get)
+            .unwrap_or_default() as usize;
 
-        if config.input_device.is_none() && audio_io_layout.main_input_channels.is_some() {
+        if num_output_channels == 0 {
+            nih_log!(
+                "The plugin does not have any main audio outputs. Processing will be driven by a \
+                 timer instead of by an audio output stream."
+            );
+        } else if config.input_device.is_none() && num_input_channels > 0 {
             nih_log!(
                 "Audio inputs are not connected automatically to prevent feedback. Use the \
                  '--input-device' option to choose an input device."
@@ -381,95 +451,171 @@ impl CpalMidir {
             nih_log!("Use the '--midi-output' option to select a MIDI output device.")
         }
 
-        // No input device is connected unless requested by the user to avoid feedback loops
-        let input_device = config
-            .input_device
-            .as_ref()
-            .map(|name| -> Result<Device> {
-                let device = host
-                    .input_devices()
-                    .context("No audio input devices available")?
-                    // `.name()` returns a `Result` with a non-Eq error type so you can't compare this
-                    // directly
-                    .find(|d| d.name().as_deref().map(|n| n == name).unwrap_or(false))
-                    .with_context(|| {
-                        // This is a bit awkward, but instead of adding a dedicated option we'll just
-                        // list all of the available devices in the error message when the chosen device
-                        // does not exist
-                        let mut message =
-                            format!("Unknown input device '{name}'. Available devices are:");
-                        for device_name in host.input_devices().unwrap().flat_map(|d| d.name()) {
-                            message.push_str(&format!("\n{device_name}"))
-                        }
+        // The output stream drives the processing, so its settings are negotiated first. The input
+        // stream then needs to use the exact same settings.
+        let mut num_aux_outputs_on_output = 0;
+        let output = if num_output_channels > 0 {
+            let output_device = match config.output_device.as_ref() {
+                Some(name) => select_device(
+                    host.output_devices()
+                        .context("No audio output devices available")?
+                        .collect(),
+                    "output",
+                    name,
+                )?,
+                None => host
+                    .default_output_device()
+                    .context("No default audio output device available")?,
+            };
 
-                        message
-                    })?;
+            // The auxiliary outputs are written to the device's channels after the main output's
+            // channels. If the device doesn't have enough channels for all of them, then as many
+            // whole auxiliary output ports as possible are used.
+            let mut negotiated_config = None;
+            for num_aux_outputs in (1..=audio_io_layout.aux_output_ports.len()).rev() {
+                let num_channels = num_output_channels
+                    + audio_io_layout.aux_output_ports[..num_aux_outputs]
+                        .iter()
+                        .map(|num_channels| num_channels.get() as usize)
+                        .sum::<usize>();
+                let result = negotiate_stream_config(
+                    output_device
+                        .supported_output_configs()
+                        .context("Could not get supported audio output configurations")?,
+                    "output",
+                    num_channels,
+                    config.sample_rate as u32,
+                    config.period_size,
+                    config.strict,
+                    config.strict || config.strict_buffer_size,
+                );
+                if let Ok(result) = result {
+                    num_aux_outputs_on_output = num_aux_outputs;
+                    negotiated_config = Some(result);
+                    break;
+                }
+            }
 
-                Ok(device)
-            })
-            .transpose()?;
+            let (output_config, output_sample_format) = match negotiated_config {
+                Some(negotiated_config) => negotiated_config,
+                None => negotiate_stream_config(
+                    output_device
+                        .supported_output_configs()
+                        .context("Could not get supported audio output configurations")?,
+                    "output",
+                    num_output_channels,
+                    config.sample_rate as u32,
+                    config.period_size,
+                    config.strict,
+                    config.strict || config.strict_buffer_size,
+                )?,
+            };
 
-        let output_device = match config.output_device.as_ref() {
-            Some(name) => host
-                .output_devices()
-                .context("No audio output devices available")?
-                .find(|d| d.name().as_deref().map(|n| n == name).unwrap_or(false))
-                .with_context(|| {
-                    let mut message =
-                        format!("Unknown output device '{name}'. Available devices are:");
-                    for device_name in host.output_devices().unwrap().flat_map(|d| d.name()) {
-                        message.push_str(&format!("\n{device_name}"))
-                    }
+            // If the device did not support the requested settings, then the rest of the wrapper
+            // should use the substituted settings instead
+            config.sample_rate = output_config.sample_rate.0 as f32;
+            if let cpal::BufferSize::Fixed(period_size) = output_config.buffer_size {
+                config.period_size = period_size;
+            }
 
-                    message
-                })?,
-            None => host
-                .default_output_device()
-                .context("No default audio output device available")?,
+            Some(CpalDevice {
+                device: output_device,
+                config: output_config,
+                sample_format: output_sample_format,
+            })
+        } else {
+            if config.output_device.is_some() {
+                nih_warn!(
+                    "Ignoring the audio output device since the plugin does not have any main \
+                     audio outputs"
+                );
+            }
+
+            None
         };
 
-        let requested_sample_rate = cpal::SampleRate(config.sample_rate as u32);
-        let requested_buffer_size = cpal::BufferSize::Fixed(config.period_size);
-        let num_input_channels = audio_io_layout
-            .main_input_channels
-            .map(NonZeroU32::get)
-            .unwrap_or_default() as usize;
+        // No input device is connected unless requested by the user to avoid feedback loops. Audio
+        // input is also only processed when there's an output stream driving the processing.
+        let input_device = if num_input_channels > 0 && num_output_channels > 0 {
+            config
+                .input_device
+                .as_ref()
+                .map(|name| find_input_device(&host, name))
+                .transpose()?
+        } else {
+            if config.input_device.is_some() {
+                nih_warn!(
+                    "Ignoring the audio input device since the plugin does not have both main \
+                     audio inputs and outputs"
+                );
+            }
+
+            None
+        };
+        let num_aux_input_channels: usize = audio_io_layout
+            .aux_input_ports
+            .iter()
+            .map(|num_channels| num_channels.get() as usize)
+            .sum();
+        let mut aux_inputs_from_input = false;
         let input = input_device
             .map(|device| -> Result<CpalDevice> {
-                let input_configs: Vec<_> = device
-                    .supported_input_configs()
-                    .context("Could not get supported audio input configurations")?
-                    .filter(|c| match c.buffer_size() {
-                        cpal::SupportedBufferSize::Range { min, max } => {
-                            c.channels() as usize == num_input_channels
-                                && (c.min_sample_rate()..=c.max_sample_rate())
-                                    .contains(&requested_sample_rate)
-                                && (min..=max).contains(&&config.period_size)
+                // Without separate sidechain input devices, the sidechain inputs are read from the
+                // input device's additional channels if it has enough of them
+                if num_aux_input_channels > 0 && config.aux_input_device.is_empty() {
+                    let num_channels = num_input_channels + num_aux_input_channels;
+                    let result = negotiate_stream_config(
+                        device
+                            .supported_input_configs()
+                            .context("Could not get supported audio input configurations")?,
+                        "input",
+                        num_channels,
+                        config.sample_rate as u32,
+                        config.period_size,
+                        true,
+                        true,
+                    );
+
+                    match result {
+                        Ok((input_config, input_sample_format)) => {
+                            aux_inputs_from_input = true;
+
+                            return Ok(CpalDevice {
+                                device,
+                                config: input_config,
+                                sample_format: input_sample_format,
+                            });
                         }
-                        cpal::SupportedBufferSize::Unknown => false,
-                    })
-                    .collect();
-                let input_config_range = input_configs
-                    .iter()
-                    // Prefer floating point samples to avoid conversions
-                    .find(|c| c.sample_format() == SampleFormat::F32)
-                    .or_else(|| input_configs.first())
-                    .cloned()
-                    .with_context(|| {
-                        format!(
-                            "The audio input device does not support {} audio channels at a \
-                             sample rate of {} Hz and a period size of {} samples",
-                            num_input_channels, config.sample_rate, config.period_size,
-                        )
-                    })?;
+                        Err(_) => {
+                            let max_num_channels = device
+                                .supported_input_configs()
+                                .ok()
+                                .and_then(|configs| configs.map(|config| config.channels()).max())
+                                .unwrap_or(0);
+
+                            nih_log!(
+                                "Could not read the sidechain inputs from the input device's \
+                                 additional channels. The main and sidechain inputs need \
+                                 {num_channels} channels with the output's sample rate and period \
+                                 size, and the device supports up to {max_num_channels} channels. \
+                                 The sidechain inputs will receive silence."
+                            );
+                        }
+                    }
+                }
 
-                // We already checked that these settings are valid
-                let input_config = StreamConfig {
-                    channels: input_config_range.channels(),
-                    sample_rate: requested_sample_rate,
-                    buffer_size: requested_buffer_size,
-                };
-                let input_sample_format = input_config_range.sample_format();
+                // The input has to match the output's settings, so there's no fallback here
+                let (input_config, input_sample_format) = negotiate_stream_config(
+                    device
+                        .supported_input_configs()
+                        .context("Could not get supported audio input configurations")?,
+                    "input",
+                    num_input_channels,
+                    config.sample_rate as u32,
+                    config.period_size,
+                    true,
+                    true,
+                )?;
 
                 Ok(CpalDevice {
                     device,
@@ -479,57 +625,93 @@ impl CpalMidir {
             })
             .transpose()?;
 
-        let num_output_channels = audio_io_layout
-            .main_output_channels
-            .map(NonZeroU32::get)
-            .unwrap_or_default() as usize;
-        let output = {
-            let output_configs: Vec<_> = output_device
-                .supported_output_configs()
-                .context("Could not get supported audio output configurations")?
-                .filter(|c| match c.buffer_size() {
-                    cpal::SupportedBufferSize::Range { min, max } => {
-                        c.channels() as usize == num_output_channels
-                            && (c.min_sample_rate()..=c.max_sample_rate())
-                                .contains(&requested_sample_rate)
-                            && (min..=max).contains(&&config.period_size)
-                    }
-                    cpal::SupportedBufferSize::Unknown => false,
-                })
-                .collect();
-            let output_config_range = output_configs
+        // Sidechain inputs can be read from additional input devices. These also need to match the
+        // output's settings. Like the main input, they're only processed when there's an output
+        // stream driving the processing.
+        let aux_inputs = if num_output_channels > 0 {
+            if config.aux_input_device.len() > audio_io_layout.aux_input_ports.len() {
+                anyhow::bail!(
+                    "{} sidechain input devices were specified, but the plugin only has {} \
+                     sidechain inputs",
+                    config.aux_input_device.len(),
+                    audio_io_layout.aux_input_ports.len()
+                );
+            }
+            if config.aux_input_device.len() < audio_io_layout.aux_input_ports.len()
+                && !aux_inputs_from_input
+            {
+                nih_log!(
+                    "Sidechain inputs without an input device receive silence. Use the \
+                     '--aux-input-device' option to choose an input device for each sidechain \
+                     input."
+                );
+            }
+
+            config
+                .aux_input_device
                 .iter()
-                .find(|c| c.sample_format() == SampleFormat::F32)
-                .or_else(|| output_configs.first())
-                .cloned()
-                .with_context(|| {
-                    format!(
-                        "The audio output device does not support {} audio channels at a sample \
-                         rate of {} Hz and a period size of {} samples",
-                        num_output_channels, config.sample_rate, config.period_size,
+                .zip(audio_io_layout.aux_input_ports)
+                .enumerate()
+                .map(|(port_idx, (name, num_channels))| -> Result<CpalDevice> {
+                    let device = find_input_device(&host, name)?;
+                    let (aux_input_config, aux_input_sample_format) = negotiate_stream_config(
+                        device
+                            .supported_input_configs()
+                            .context("Could not get supported audio input configurations")?,
+                        "sidechain input",
+                        num_channels.get() as usize,
+                        config.sample_rate as u32,
+                        config.period_size,
+                        true,
+                        true,
                     )
-                })?;
-            let output_config = StreamConfig {
-                channels: output_config_range.channels(),
-                sample_rate: requested_sample_rate,
-                buffer_size: requested_buffer_size,
-            };
-            let output_sample_format = output_config_range.sample_format();
+                    .with_context(|| {
+                        format!(
+                            "Could not use '{name}' for sidechain input {}",
+                            port_idx + 1
+                        )
+                    })?;
 
-            CpalDevice {
-                device: output_device,
-                config: output_config,
-                sample_format: output_sample_format,
+                    Ok(CpalDevice {
+                        device,
+                        config: aux_input_config,
+                        sample_format: aux_input_sample_format,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?
+        } else {
+            if !config.aux_input_device.is_empty() {
+                nih_warn!(
+                    "Ignoring the sidechain input devices since the plugin does not have any main \
+                     audio outputs"
+                );
             }
+
+            Vec::new()
         };
 
-        // There's no obvious way to do sidechain inputs and additional outputs with the CPAL
-        // backends like there is with JACK. So we'll just provide empty buffers instead.
-        if !audio_io_layout.aux_input_ports.is_empty() {
-            nih_warn!("Sidechain inputs are not supported with this audio backend");
-        }
-        if !audio_io_layout.aux_output_ports.is_empty() {
-            nih_warn!("Auxiliary outputs are not supported with this audio backend");
+        // Auxiliary outputs that don't fit on the output device still get buffers, but their
+        // contents are discarded
+        if num_aux_outputs_on_output < audio_io_layout.aux_output_ports.len() {
+            let discarded_ports = &audio_io_layout.aux_output_ports[num_aux_outputs_on_output..];
+            let num_discarded_channels: u32 = discarded_ports
+                .iter()
+                .map(|num_channels| num_channels.get())
+                .sum();
+            if num_output_channels == 0 {
+                nih_warn!(
+                    "Discarding the plugin's auxiliary outputs ({num_discarded_channels} channels) \
+                     since the plugin does not have any main audio outputs"
+                );
+            } else {
+                nih_warn!(
+                    "The audio output device does not have enough channels for all auxiliary \
+                     outputs. Discarding the last {} of the plugin's {} auxiliary outputs \
+                     ({num_discarded_channels} channels).",
+                    discarded_ports.len(),
+                    audio_io_layout.aux_output_ports.len()
+                );
+            }
         }
 
         let midi_input = match &config.midi_input {
@@ -619,6 +801,9 @@ impl CpalMidir {
             audio_io_layout,
 
             input,
+            aux_inputs_from_input,
+            aux_inputs,
+            num_aux_outputs_on_output,
             output,
 
             midi_input: Mutex::new(midi_input),
@@ -626,10 +811,86 @@ impl CpalMidir {
         })
     }
 
+    /// Create and start a capture stream for `input`. The captured samples are sent to the output
+    /// data callback in an interleaved format using the returned ring buffer, which fits a single
+    /// period, or half a period in low latency mode. The returned parker is unparked once the ring
+    /// buffer has been filled for the first time. If the stream runs into an error, then
+    /// `should_stop` is set and `unparker` is unparked to stop processing.
+    fn start_input_stream(
+        &self,
+        input: &CpalDevice,
+        should_stop: Arc<AtomicBool>,
+        unparker: Unparker,
+    ) -> (Stream, rtrb::Consumer<f32>, Parker) {
+        // Data is sent to the output data callback using a wait-free ring buffer. Playback starts
+        // once this buffer has been filled, so the size determines the added latency. In low
+        // latency mode the output callback will instead wait for the second half of the period's
+        // input while it's running, which causes underruns if that input arrives too late.
+        let rb_num_frames = if self.config.low_latency {
+            (self.config.period_size as usize / 2).max(1)
+        } else {
+            self.config.period_size as usize
+        };
+        let (rb_producer, rb_consumer) =
+            RingBuffer::new(input.config.channels as usize * rb_num_frames);
+
+        let input_parker = Parker::new();
+        let input_unparker = input_parker.unparker().clone();
+        let error_cb = {
+            let should_stop = should_stop.clone();
+            let input_unparker = input_unparker.clone();
+            move |err| {
+                nih_error!("Error during capture: {err:#}");
+                should_stop.store(true, Ordering::Relaxed);
+                // This thread may still be waiting for the first period to be captured
+                input_unparker.unpark();
+                unparker.unpark();
+            }
+        };
+
+        macro_rules! build_input_streams {
+            ($sample_format:expr, $(($format:path, $primitive_type:ty)),*) => {
+                match $sample_format {
+                    $($format => input.device.build_input_stream(
+                        &input.config,
+                        self.build_input_data_callback::<$primitive_type>(
+                            input_unparker,
+                            rb_producer,
+                            should_stop,
+                        ),
+                        error_cb,
+                        None,
+                    ),)*
+                    format => todo!("Unsupported sample format {format}"),
+                }
+            }
+        }
+        let stream = build_input_streams!(
+            input.sample_format,
+            (SampleFormat::I8, i8),
+            (SampleFormat::I16, i16),
+            (SampleFormat::I32, i32),
+            (SampleFormat::I64, i64),
+            (SampleFormat::U8, u8),
+            (SampleFormat::U16, u16),
+            (SampleFormat::U32, u32),
+            (SampleFormat::U64, u64),
+            (SampleFormat::F32, f32),
+            (SampleFormat::F64, f64)
+        )
+        .expect("Fatal error creating the capture stream");
+        stream
+            .play()
+            .expect("Fatal error trying to start the capture stream");
+
+        (stream, rb_consumer, input_parker)
+    }
+
     fn build_input_data_callback<T>(
         &self,
         input_unparker: Unparker,
         mut input_rb_producer: rtrb::Producer<f32>,
+        should_stop: Arc<AtomicBool>,
     ) -> impl FnMut(&[T], &InputCallbackInfo) + Send + 'static
     where
         T: Sample,
@@ -643,13 +904,20 @@ impl CpalMidir {
         move |data, _info| {
             for sample in data {
                 // If for whatever reason the input callback is fired twice before an output
-                // callback, then just spin on this until the push succeeds
-                while input_rb_producer.push(sample.to_sample()).is_err() {}
-            }
+                // callback, then just spin on this until the push succeeds. Nothing will read from
+                // the ring buffer anymore after processing has stopped.
+                while input_rb_producer.push(sample.to_sample()).is_err() {
+                    if should_stop.load(Ordering::Relaxed) {
+                        return;
+                    }
+                }
 
-            // The run function is blocked until a single period has been processed here. After this
-            // point output playback can start.
-            input_unparker.unpark();
+                // The run function is blocked until the ring buffer has been filled. After this
+                // point output playback can start.
+                if input_rb_producer.is_full() {
+                    input_unparker.unpark();
+                }
+            }
         }
     }
 
@@ -669,12 +937,20 @@ impl CpalMidir {
         }
     }
 
+    /// Build the function that processes a single period and writes the (interleaved) results to
+    /// the slice passed to it. Each frame contains the main output's channels followed by the
+    /// channels of the auxiliary outputs written to the output device. This slice is empty if the
+    /// plugin does not have any main outputs.
+    /// The function returns `false` when the plugin should stop processing. At that point
+    /// `should_stop` is set, and from then on the function only writes silence to `data` without
+    /// calling `cb` again. `should_stop` can also be set from elsewhere to stop processing.
     fn build_output_data_callback<P, T>(
         &self,
-        unparker: Unparker,
         mut input_rb_consumer: Option<rtrb::Consumer<f32>>,
+        mut aux_input_rb_consumers: Vec<rtrb::Consumer<f32>>,
         mut input_event_rb_consumer: Option<rtrb::Consumer<PluginNoteEvent<P>>>,
         mut output_event_rb_producer: Option<crossbeam::channel::Sender<MidiOutputTask<P>>>,
+        should_stop: Arc<AtomicBool>,
         mut cb: impl FnMut(
                 &mut Buffer,
                 &mut AuxiliaryBuffers,
@@ -684,7 +960,7 @@ impl CpalMidir {
             ) -> bool
             + 'static
             + Send,
-    ) -> impl FnMut(&mut [T], &OutputCallbackInfo) + Send + 'static
+    ) -> impl FnMut(&mut [T]) -> bool + Send + 'static
     where
         P: Plugin,
         T: Sample + FromSample<f32>,
@@ -702,11 +978,18 @@ impl CpalMidir {
             .main_input_channels
             .map(NonZeroU32::get)
             .unwrap_or(0) as usize;
+        let num_aux_outputs_on_output = self.num_aux_outputs_on_output;
+        let num_device_output_channels = num_output_channels
+            + self.audio_io_layout.aux_output_ports[..num_aux_outputs_on_output]
+                .iter()
+                .map(|num_channels| num_channels.get() as usize)
+                .sum::<usize>();
         let mut main_io_storage = vec![vec![0.0f32; buffer_size]; num_output_channels];
 
-        // This backend does not support auxiliary inputs and outputs, so in order to have the same
-        // behavior as the other backends we'll provide some dummy buffers that we'll zero out every
-        // time
+        // Sidechain inputs are filled from their input devices' ring buffers, or from the main
+        // input's ring buffer if they're read from the input device's additional channels. In
+        // order to have the same behavior as the other backends, sidechain inputs without a device
+        // and auxiliary outputs that don't fit on the output device still get dummy buffers.
         let mut aux_input_storage: Vec<Vec<Vec<f32>>> = Vec::new();
         for channel_count in self.audio_io_layout.aux_input_ports {
             aux_input_storage.push(vec![
@@ -753,8 +1036,16 @@ impl CpalMidir {
 
         // Can't borrow from `self` in the callback
         let config = self.config.clone();
+        let aux_inputs_from_input = self.aux_inputs_from_input;
         let mut num_processed_samples = 0usize;
-        move |data, _info| {
+        move |data| {
+            // The plugin may not be called anymore after it requested processing to stop, even if
+            // the stream hasn't been dropped yet
+            if should_stop.load(Ordering::Relaxed) {
+                data.fill(T::EQUILIBRIUM);
+                return false;
+            }
+
             let mut transport = Transport::new(config.sample_rate);
             transport.pos_samples = Some(num_processed_samples as i64);
             transport.tempo = Some(config.tempo as f64);
@@ -762,25 +1053,47 @@ impl CpalMidir {
             transport.time_sig_denominator = Some(config.timesig_denom as i32);
             transport.playing = true;
 
-            // If an input was configured, then the output buffer is filled with (interleaved) input
-            // samples. Otherwise it gets filled with silence. There is no need to zero out any of
-            // the other buffers. The `BufferManager` will copy the auxiliary input data to its own
-            // storage buffers because it cannot assume that these buffers are safe to write to.
-            // Because of that we'll never need to reinitialize these, and the output storage is
-            // write-only (with `BufferManager` always zeroing them out when creating the buffers).
+            // If an input was configured, then the output buffer is filled with input samples.
+            // Otherwise it gets filled with silence. There is no need to zero out any of the other
+            // buffers. The `BufferManager` will copy the auxiliary input data to its own storage
+            // buffers because it cannot assume that these buffers are safe to write to. Because of
+            // that we'll never need to reinitialize these, and the output storage is write-only
+            // (with `BufferManager` always zeroing them out when creating the buffers).
             match &mut input_rb_consumer {
                 Some(input_rb_consumer) => {
-                    for channel in main_io_storage.iter_mut() {
-                        for sample in channel {
-                            loop {
-                                // Keep spinning on this if the output callback somehow outpaces the
-                                // input callback
-                                if let Ok(input_sample) = input_rb_consumer.pop() {
-                                    *sample = input_sample;
-                                    break;
-                                }
+                    // Each interleaved frame contains the main input's channels, optionally
+                    // followed by the sidechain inputs' channels. Input channels without a
+                    // corresponding output channel are discarded.
+                    for sample_idx in 0..buffer_size {
+                        for channel_idx in 0..num_input_channels {
+                            let Some(input_sample) =
+                                pop_input_sample(input_rb_consumer, &should_stop)
+                            else {
+                                data.fill(T::EQUILIBRIUM);
+                                return false;
+                            };
+
+                            if let Some(channel) = main_io_storage.get_mut(channel_idx) {
+                                channel[sample_idx] = input_sample;
                             }
                         }
+
+                        if aux_inputs_from_input {
+                            for channel in aux_input_storage.iter_mut().flatten() {
+                                let Some(input_sample) =
+                                    pop_input_sample(input_rb_consumer, &should_stop)
+                                else {
+                                    data.fill(T::EQUILIBRIUM);
+                                    return false;
+                                };
+
+                                channel[sample_idx] = input_sample;
+                            }
+                        }
+                    }
+
+                    for channel in main_io_storage.iter_mut().skip(num_input_channels) {
+                        channel.fill(0.0);
                     }
                 }
                 None => {
@@ -790,6 +1103,26 @@ impl CpalMidir {
                 }
             }
 
+            // The sidechain inputs from separate devices are deinterleaved into their own storage
+            // buffers
+            for (aux_input_rb_consumer, input_storage) in aux_input_rb_consumers
+                .iter_mut()
+                .zip(aux_input_storage.iter_mut())
+            {
+                for sample_idx in 0..buffer_size {
+                    for channel in input_storage.iter_mut() {
+                        let Some(input_sample) =
+                            pop_input_sample(aux_input_rb_consumer, &should_stop)
+                        else {
+                            data.fill(T::EQUILIBRIUM);
+                            return false;
+                        };
+
+                        channel[sample_idx] = input_sample;
+                    }
+                }
+            }
+
             // Things may have been moved in between callbacks, so these pointers need to be set up
             // again on each invocation
             main_io_channel_pointers.get().clear();
@@ -866,9 +1199,10 @@ impl CpalMidir {
                     })
                 };
 
+                // All events received since the last period are passed to the plugin at once
                 midi_input_events.clear();
                 if let Some(input_event_rb_consumer) = &mut input_event_rb_consumer {
-                    if let Ok(event) = input_event_rb_consumer.pop() {
+                    while let Ok(event) = input_event_rb_consumer.pop() {
                         midi_input_events.push(event);
                     }
                 }
@@ -885,22 +1219,45 @@ impl CpalMidir {
                     &midi_input_events,
                     &mut midi_output_events,
                 ) {
-                    // TODO: Some way to immediately terminate the stream here would be nice
-                    unparker.unpark();
-                    return;
+                    should_stop.store(true, Ordering::Relaxed);
+                    data.fill(T::EQUILIBRIUM);
+                    return false;
                 }
             }
 
             // The buffer's samples need to be written to `data` in an interlaced format
             // SAFETY: Dropping `buffers` allows us to borrow `main_io_storage` again
-            for (output_sample, buffer_sample) in data
-                .iter_mut()
-                .zip(main_io_storage.iter().flat_map(|channels| channels.iter()))
-            {
-                *output_sample = T::from_sample(*buffer_sample);
+            if num_device_output_channels > 0 {
+                for (sample_idx, output_frame) in data
+                    .chunks_exact_mut(num_device_output_channels)
+                    .enumerate()
+                {
+                    let channels = main_io_storage.iter().chain(
+                        aux_output_storage[..num_aux_outputs_on_output]
+                            .iter()
+                            .flatten(),
+                    );
+                    for (output_sample, channel) in output_frame.iter_mut().zip(channels) {
+                        *output_sample = T::from_sample(channel[sample_idx]);
+                    }
+                }
             }
 
             if let Some(output_event_rb_producer) = &mut output_event_rb_producer {
+                // midir sends events right away, so the events need to be sent in the order they
+                // occur in the period. Nothing stops plugins from sending events out of order. The
+                // standard library's stable sort allocates, so this uses an in-place insertion sort
+                // instead. The events are usually already sorted, and events at the same timing
+                // stay in the order they were sent in.
+                for event_idx in 1..midi_output_events.len() {
+                    let mut idx = event_idx;
+                    while idx > 0
+                        && midi_output_events[idx - 1].timing() > midi_output_events[idx].timing()
+                    {
+                        midi_output_events.swap(idx - 1, idx);
+                        idx -= 1;
+                    }
+                }
                 for event in midi_output_events.drain(..) {
                     if output_event_rb_producer
                         .try_send(MidiOutputTask::Send(event))
@@ -913,6 +1270,637 @@ impl CpalMidir {
             }
 
             num_processed_samples += buffer_size;
+
+            true
+        }
+    }
+}
+
+/// Pop a sample from an input stream's ring buffer, spinning if the output callback somehow
+/// outpaces the input callback. Returns `None` if `should_stop` gets set while waiting, since no
+/// more samples will arrive if the input stream ran into an error.
+fn pop_input_sample(
+    rb_consumer: &mut rtrb::Consumer<f32>,
+    should_stop: &AtomicBool,
+) -> Option<f32> {
+    loop {
+        if let Ok(sample) = rb_consumer.pop() {
+            return Some(sample);
+        } else if should_stop.load(Ordering::Relaxed) {
+            return None;
+        }
+    }
+}
+
+/// Find the input device called `name`, or the device at that index in the list of input devices.
+/// If no such device exists, then the error message lists all available input devices.
+fn find_input_device(host: &cpal::Host, name: &str) -> Result<Device> {
+    select_device(
+        host.input_devices()
+            .context("No audio input devices available")?
+            .collect(),
+        "input",
+        name,
+    )
+}
+
+/// Select a device from `devices` by its index in that list or by its name. Integers are treated
+/// as indices first, and they're only matched against the device names if the index is out of
+/// range. If no device matches, then the error message lists all devices along with their indices.
+/// `direction` is either `input` or `output`, and it is used in the error message.
+fn select_device(mut devices: Vec<Device>, direction: &str, name: &str) -> Result<Device> {
+    if let Ok(index) = name.parse::<usize>() {
+        if index < devices.len() {
+            return Ok(devices.swap_remove(index));
+        }
+    }
+
+    // `.name()` returns a `Result` with a non-Eq error type so you can't compare this directly
+    if let Some(index) = devices
+        .iter()
+        .position(|d| d.name().as_deref().map(|n| n == name).unwrap_or(false))
+    {
+        return Ok(devices.swap_remove(index));
+    }
+
+    // This is a bit awkward, but instead of adding a dedicated option we'll just list all of the
+    // available devices in the error message when the chosen device does not exist
+    let mut message = format!("Unknown {direction} device '{name}'. Available devices are:");
+    for (index, device) in devices.iter().enumerate() {
+        match device.name() {
+            Ok(device_name) => message.push_str(&format!("\n{index}: {device_name}")),
+            Err(_) => message.push_str(&format!("\n{index}: <unknown name>")),
+        }
+    }
+
+    anyhow::bail!(message)
+}
+
+/// Print the input and output devices for every available CPAL host along with their supported
+/// stream configurations. Unlike [`CpalMidir::new()`], this doesn't require a default device.
+pub fn list_devices() {
+    for host_id in cpal::available_hosts() {
+        println!("{}:", host_id.name());
+        let host = match cpal::host_from_id(host_id) {
+            Ok(host) => host,
+            Err(err) => {
+                println!("  The Audio API is unavailable: {err}");
+                continue;
+            }
+        };
+
+        print_devices(
+            "input",
+            host.input_devices(),
+            host.default_input_device(),
+            |device| Ok(device.supported_input_configs()?.collect()),
+        );
+        print_devices(
+            "output",
+            host.output_devices(),
+            host.default_output_device(),
+            |device| Ok(device.supported_output_configs()?.collect()),
+        );
+    }
+}
+
+/// Print a host's input or output devices for [`list_devices()`]. `direction` is either `input` or
+/// `output`.
+fn print_devices(
+    direction: &str,
+    devices: Result<impl Iterator<Item = Device>, cpal::DevicesError>,
+    default_device: Option<Device>,
+    supported_configs: impl Fn(&Device) -> Result<Vec<SupportedStreamConfigRange>>,
+) {
+    match default_device.map(|d| d.name()) {
+        Some(Ok(name)) => println!("  Default {direction} device: {name}"),
+        Some(Err(err)) => println!("  Could not get the default {direction} device's name: {err}"),
+        None => println!("  No default {direction} device available"),
+    }
+
+    let devices = match devices {
+        Ok(devices) => devices,
+        Err(err) => {
+            println!("  Could not list the {direction} devices: {err}");
+            return;
+        }
+    };
+
+    println!("  Available {direction} devices:");
+    let mut num_devices = 0;
+    for (index, device) in devices.enumerate() {
+        num_devices += 1;
+        match device.name() {
+            Ok(name) => println!("    {index}: {name}"),
+            Err(err) => println!("    {index}: <unknown name: {err}>"),
+        }
+
+        match supported_configs(&device) {
+            Ok(configs) => {
+                for config in configs {
+                    println!("      {}", describe_config_range(&config));
+                }
+            }
+            Err(err) => println!("      Could not get the supported configurations: {err:#}"),
+        }
+    }
+
+    if num_devices == 0 {
+        println!("    None");
+    }
+}
+
+/// Describe the channel count, sample rates, period sizes, and sample format of a supported stream
+/// configuration.
+fn describe_config_range(config: &SupportedStreamConfigRange) -> String {
+    let sample_rates = if config.min_sample_rate() == config.max_sample_rate() {
+        format!("{} Hz", config.min_sample_rate().0)
+    } else {
+        format!(
+            "{}-{} Hz",
+            config.min_sample_rate().0,
+            config.max_sample_rate().0
+        )
+    };
+    let period_sizes = match config.buffer_size() {
+        cpal::SupportedBufferSize::Range { min, max } => format!("{min}-{max} samples"),
+        cpal::SupportedBufferSize::Unknown => String::from("unknown period sizes"),
+    };
+
+    format!(
+        "{} channels, {sample_rates}, {period_sizes}, {:?}",
+        config.channels(),
+        config.sample_format()
+    )
+}
+
+/// Find a stream configuration with `num_channels` channels among a device's supported
+/// configurations that supports the requested sample rate and period size. If no configuration
+/// supports those settings, then the closest supported sample rate and period size are used
+/// instead, unless `strict_sample_rate` or `strict_period_size` is set for the setting that would
+/// need to change. Floating point sample formats are preferred to avoid conversions. `direction` is
+/// either `input` or `output`, and it is used in messages.
+fn negotiate_stream_config(
+    supported_configs: impl Iterator<Item = SupportedStreamConfigRange>,
+    direction: &str,
+    num_channels: usize,
+    sample_rate: u32,
+    period_size: u32,
+    strict_sample_rate: bool,
+    strict_period_size: bool,
+) -> Result<(StreamConfig, SampleFormat)> {
+    let supported_configs: Vec<_> = supported_configs.collect();
+    let describe_supported_configs = || {
+        let mut message = String::from("Supported configurations are:");
+        for config in &supported_configs {
+            message.push_str(&format!("\n{}", describe_config_range(config)));
         }
+
+        message
+    };
+
+    // This picks the configuration that needs the smallest change in sample rate, and then the
+    // smallest change in period size. For an exact match both differences are zero. If the period
+    // size is strict, then configurations supporting the exact period size are preferred over ones
+    // that support the exact sample rate. Devices that don't report their period size range can't
+    // be used since the period size is fixed.
+    let (config, supported_sample_rate, supported_period_size) = supported_configs
+        .iter()
+        .filter(|config| config.channels() as usize == num_channels)
+        .filter_map(|config| match config.buffer_size() {
+            cpal::SupportedBufferSize::Range { min, max } => Some((
+                config,
+                sample_rate.clamp(config.min_sample_rate().0, config.max_sample_rate().0),
+                period_size.clamp(*min, *max),
+            )),
+            cpal::SupportedBufferSize::Unknown => None,
+        })
+        .min_by_key(|(config, supported_sample_rate, supported_period_size)| {
+            (
+                strict_period_size && *supported_period_size != period_size,
+                supported_sample_rate.abs_diff(sample_rate),
+                supported_period_size.abs_diff(period_size),
+                config.sample_format() != SampleFormat::F32,
+            )
+        })
+        .with_context(|| {
+            format!(
+                "The audio {direction} device does not support {num_channels} audio channels. {}",
+                describe_supported_configs()
+            )
+        })?;
+
+    let sample_rate_changed = supported_sample_rate != sample_rate;
+    let period_size_changed = supported_period_size != period_size;
+    if sample_rate_changed || period_size_changed {
+        if (strict_sample_rate && sample_rate_changed)
+            || (strict_period_size && period_size_changed)
+        {
+            anyhow::bail!(
+                "The audio {direction} device does not support {num_channels} audio channels at a \
+                 sample rate of {sample_rate} Hz and a period size of {period_size} samples. {}",
+                describe_supported_configs()
+            );
+        }
+
+        nih_warn!(
+            "The audio {direction} device does not support a sample rate of {sample_rate} Hz and \
+             a period size of {period_size} samples, using {supported_sample_rate} Hz and \
+             {supported_period_size} samples instead. Use '--strict' to disable this fallback, or \
+             '--strict-buffer-size' to only disable it for the period size."
+        );
+    }
+
+    Ok((
+        StreamConfig {
+            channels: config.channels(),
+            sample_rate: cpal::SampleRate(supported_sample_rate),
+            buffer_size: cpal::BufferSize::Fixed(supported_period_size),
+        },
+        config.sample_format(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{new_nonzero_u32, ParamPtr, Params, ProcessContext, ProcessStatus};
+    use clap::Parser;
+    use std::sync::atomic::AtomicUsize;
+
+    const PERIOD_SIZE: usize = 4;
+
+    struct EmptyParams;
+
+    unsafe impl Params for EmptyParams {
+        fn param_map(&self) -> Vec<(String, ParamPtr, String)> {
+            Vec::new()
+        }
+    }
+
+    #[derive(Default)]
+    struct StereoPlugin;
+
+    impl Plugin for StereoPlugin {
+        const NAME: &'static str = "Stereo Plugin";
+        const VENDOR: &'static str = "";
+        const URL: &'static str = "";
+        const EMAIL: &'static str = "";
+        const VERSION: &'static str = "0.0.0";
+
+        const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[
+            AudioIOLayout {
+                main_output_channels: NonZeroU32::new(2),
+                ..AudioIOLayout::const_default()
+            },
+            AudioIOLayout {
+                main_input_channels: NonZeroU32::new(2),
+                main_output_channels: NonZeroU32::new(2),
+                aux_input_ports: &[new_nonzero_u32(1)],
+                ..AudioIOLayout::const_default()
+            },
+            AudioIOLayout {
+                main_output_channels: NonZeroU32::new(2),
+                aux_output_ports: &[new_nonzero_u32(2), new_nonzero_u32(1)],
+                ..AudioIOLayout::const_default()
+            },
+        ];
+
+        type SysExMessage = ();
+        type BackgroundTask = ();
+
+        fn params(&self) -> Arc<dyn Params> {
+            Arc::new(EmptyParams)
+        }
+
+        fn process(
+            &mut self,
+            _buffer: &mut Buffer,
+            _aux: &mut AuxiliaryBuffers,
+            _context: &mut impl ProcessContext<Self>,
+        ) -> ProcessStatus {
+            ProcessStatus::Normal
+        }
+    }
+
+    /// A backend without any devices. This can still be used to build the output data callback.
+    fn backend(audio_io_layout: AudioIOLayout) -> CpalMidir {
+        CpalMidir {
+            config: WrapperConfig::parse_from([
+                "standalone",
+                "--period-size",
+                &PERIOD_SIZE.to_string(),
+            ]),
+            audio_io_layout,
+
+            input: None,
+            aux_inputs_from_input: false,
+            aux_inputs: Vec::new(),
+            num_aux_outputs_on_output: 0,
+            output: None,
+
+            midi_input: Mutex::new(None),
+            midi_output: Mutex::new(None),
+        }
+    }
+
+    /// One configuration supports the requested sample rate, and the other one supports the
+    /// requested period size.
+    fn negotiation_test_configs() -> impl Iterator<Item = SupportedStreamConfigRange> {
+        [
+            SupportedStreamConfigRange::new(
+                2,
+                cpal::SampleRate(48000),
+                cpal::SampleRate(48000),
+                cpal::SupportedBufferSize::Range { min: 64, max: 256 },
+                SampleFormat::F32,
+            ),
+            SupportedStreamConfigRange::new(
+                2,
+                cpal::SampleRate(44100),
+                cpal::SampleRate(44100),
+                cpal::SupportedBufferSize::Range { min: 512, max: 512 },
+                SampleFormat::F32,
+            ),
+        ]
+        .into_iter()
+    }
+
+    #[test]
+    fn negotiate_closest_period_size() {
+        let (config, _) = negotiate_stream_config(
+            negotiation_test_configs(),
+            "output",
+            2,
+            48000,
+            512,
+            false,
+            false,
+        )
+        .unwrap();
+        assert_eq!(config.sample_rate, cpal::SampleRate(48000));
+        assert_eq!(config.buffer_size, cpal::BufferSize::Fixed(256));
+    }
+
+    #[test]
+    fn negotiate_strict_period_size() {
+        let (config, _) = negotiate_stream_config(
+            negotiation_test_configs(),
+            "output",
+            2,
+            48000,
+            512,
+            false,
+            true,
+        )
+        .unwrap();
+        assert_eq!(config.sample_rate, cpal::SampleRate(44100));
+        assert_eq!(config.buffer_size, cpal::BufferSize::Fixed(512));
+
+        assert!(negotiate_stream_config(
+            negotiation_test_configs(),
+            "output",
+            2,
+            48000,
+            1024,
+            false,
+            true
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn negotiate_strict() {
+        assert!(negotiate_stream_config(
+            negotiation_test_configs(),
+            "output",
+            2,
+            48000,
+            512,
+            true,
+            true
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn describe_config_ranges() {
+        let config = SupportedStreamConfigRange::new(
+            2,
+            cpal::SampleRate(44100),
+            cpal::SampleRate(48000),
+            cpal::SupportedBufferSize::Range { min: 64, max: 4096 },
+            SampleFormat::F32,
+        );
+        assert_eq!(
+            describe_config_range(&config),
+            "2 channels, 44100-48000 Hz, 64-4096 samples, F32"
+        );
+
+        let config = SupportedStreamConfigRange::new(
+            1,
+            cpal::SampleRate(48000),
+            cpal::SampleRate(48000),
+            cpal::SupportedBufferSize::Unknown,
+            SampleFormat::I16,
+        );
+        assert_eq!(
+            describe_config_range(&config),
+            "1 channels, 48000 Hz, unknown period sizes, I16"
+        );
+    }
+
+    #[test]
+    fn no_processing_after_stop() {
+        let num_calls = Arc::new(AtomicUsize::new(0));
+        let should_stop = Arc::new(AtomicBool::new(false));
+        let mut process = backend(StereoPlugin::AUDIO_IO_LAYOUTS[0])
+            .build_output_data_callback::<StereoPlugin, f32>(
+                None,
+                Vec::new(),
+                None,
+                None,
+                should_stop.clone(),
+                {
+                    let num_calls = num_calls.clone();
+                    move |buffer, _aux, _transport, _input_events, _output_events| {
+                        for channel in buffer.as_slice() {
+                            channel.fill(1.0);
+                        }
+
+                        // Processing stops during the second period
+                        num_calls.fetch_add(1, Ordering::Relaxed) == 0
+                    }
+                },
+            );
+
+        let mut data = [0.0f32; PERIOD_SIZE * 2];
+        assert!(process(&mut data));
+        assert_eq!(data, [1.0; PERIOD_SIZE * 2]);
+
+        assert!(!process(&mut data));
+        assert!(should_stop.load(Ordering::Relaxed));
+        assert_eq!(data, [0.0; PERIOD_SIZE * 2]);
+
+        // The stream may still fire a couple more callbacks before it is dropped
+        assert!(!process(&mut data));
+        assert!(!process(&mut data));
+        assert_eq!(num_calls.load(Ordering::Relaxed), 2);
+        assert_eq!(data, [0.0; PERIOD_SIZE * 2]);
+    }
+
+    #[test]
+    fn interleaved_output() {
+        let mut process = backend(StereoPlugin::AUDIO_IO_LAYOUTS[0])
+            .build_output_data_callback::<StereoPlugin, f32>(
+                None,
+                Vec::new(),
+                None,
+                None,
+                Arc::new(AtomicBool::new(false)),
+                |buffer, _aux, _transport, _input_events, _output_events| {
+                    for (channel_idx, channel) in buffer.as_slice().iter_mut().enumerate() {
+                        channel.fill(channel_idx as f32 + 1.0);
+                    }
+
+                    true
+                },
+            );
+
+        let mut data = [0.0f32; PERIOD_SIZE * 2];
+        assert!(process(&mut data));
+        assert_eq!(data, [1.0, 2.0].repeat(PERIOD_SIZE)[..]);
+    }
+
+    #[test]
+    fn aux_outputs_on_output_channels() {
+        // Only the first auxiliary output fits on the output device, so each frame contains the
+        // two main output channels followed by the first auxiliary output's two channels
+        let mut backend = backend(StereoPlugin::AUDIO_IO_LAYOUTS[2]);
+        backend.num_aux_outputs_on_output = 1;
+        let mut process = backend.build_output_data_callback::<StereoPlugin, f32>(
+            None,
+            Vec::new(),
+            None,
+            None,
+            Arc::new(AtomicBool::new(false)),
+            |buffer, aux, _transport, _input_events, _output_events| {
+                for (channel_idx, channel) in buffer.as_slice().iter_mut().enumerate() {
+                    channel.fill(channel_idx as f32 + 1.0);
+                }
+                for (port_idx, port) in aux.outputs.iter_mut().enumerate() {
+                    for (channel_idx, channel) in port.as_slice().iter_mut().enumerate() {
+                        channel.fill((port_idx as f32 + 1.0) * 10.0 + channel_idx as f32);
+                    }
+                }
+
+                true
+            },
+        );
+
+        let mut data = [0.0f32; PERIOD_SIZE * 4];
+        assert!(process(&mut data));
+        assert_eq!(data, [1.0, 2.0, 10.0, 11.0].repeat(PERIOD_SIZE)[..]);
+    }
+
+    #[test]
+    fn aux_inputs_from_input_channels() {
+        // Each frame contains the two main input channels followed by the mono sidechain input
+        let (mut input_rb_producer, input_rb_consumer) = RingBuffer::new(PERIOD_SIZE * 3);
+        for sample_idx in 0..PERIOD_SIZE {
+            for channel_offset in [0.0, 10.0, 100.0] {
+                input_rb_producer
+                    .push(sample_idx as f32 + channel_offset)
+                    .unwrap();
+            }
+        }
+
+        let mut backend = backend(StereoPlugin::AUDIO_IO_LAYOUTS[1]);
+        backend.aux_inputs_from_input = true;
+        let num_calls = Arc::new(AtomicUsize::new(0));
+        let mut process = backend.build_output_data_callback::<StereoPlugin, f32>(
+            Some(input_rb_consumer),
+            Vec::new(),
+            None,
+            None,
+            Arc::new(AtomicBool::new(false)),
+            {
+                let num_calls = num_calls.clone();
+                move |buffer, aux, _transport, _input_events, _output_events| {
+                    let expected = |offset: f32| -> Vec<f32> {
+                        (0..PERIOD_SIZE)
+                            .map(|sample_idx| sample_idx as f32 + offset)
+                            .collect()
+                    };
+                    assert_eq!(buffer.as_slice()[0], expected(0.0));
+                    assert_eq!(buffer.as_slice()[1], expected(10.0));
+                    assert_eq!(aux.inputs[0].as_slice()[0], expected(100.0));
+
+                    num_calls.fetch_add(1, Ordering::Relaxed);
+                    true
+                }
+            },
+        );
+
+        // The plugin doesn't touch the main buffer, so the main input is passed through as is
+        let mut data = [0.0f32; PERIOD_SIZE * 2];
+        assert!(process(&mut data));
+        assert_eq!(num_calls.load(Ordering::Relaxed), 1);
+        assert_eq!(data, [0.0, 10.0, 1.0, 11.0, 2.0, 12.0, 3.0, 13.0]);
+    }
+
+    #[test]
+    fn midi_input_events_per_period() {
+        let (mut input_event_rb_producer, input_event_rb_consumer) =
+            RingBuffer::new(MIDI_EVENT_QUEUE_CAPACITY);
+        let num_events = Arc::new(AtomicUsize::new(0));
+        let mut process = backend(StereoPlugin::AUDIO_IO_LAYOUTS[0])
+            .build_output_data_callback::<StereoPlugin, f32>(
+                None,
+                Vec::new(),
+                Some(input_event_rb_consumer),
+                None,
+                Arc::new(AtomicBool::new(false)),
+                {
+                    let num_events = num_events.clone();
+                    move |_buffer, _aux, _transport, input_events, _output_events| {
+                        num_events.store(input_events.len(), Ordering::Relaxed);
+
+                        true
+                    }
+                },
+            );
+
+        for note in [60, 64, 67] {
+            let event = NoteEvent::from_midi(0, &[0x90, note, 100]).unwrap();
+            input_event_rb_producer.push(event).unwrap();
+        }
+
+        let mut data = [0.0f32; PERIOD_SIZE * 2];
+        assert!(process(&mut data));
+        assert_eq!(num_events.load(Ordering::Relaxed), 3);
+        assert!(process(&mut data));
+        assert_eq!(num_events.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn stopping_outputs_equilibrium() {
+        let should_stop = Arc::new(AtomicBool::new(true));
+        let mut process = backend(StereoPlugin::AUDIO_IO_LAYOUTS[0])
+            .build_output_data_callback::<StereoPlugin, u16>(
+                None,
+                Vec::new(),
+                None,
+                None,
+                should_stop,
+                |_buffer, _aux, _transport, _input_events, _output_events| {
+                    panic!("The plugin should not be called after processing has stopped")
+                },
+            );
+
+        // Unsigned samples are silent at the midpoint
+        let mut data = [0u16; PERIOD_SIZE * 2];
+        assert!(!process(&mut data));
+        assert_eq!(data, [u16::EQUILIBRIUM; PERIOD_SIZE * 2]);
     }
 }
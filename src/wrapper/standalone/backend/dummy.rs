@@ -16,6 +16,10 @@ pub struct Dummy {
 }
 
 impl<P: Plugin> Backend<P> for Dummy {
+    fn actual_config(&self) -> WrapperConfig {
+        self.config.clone()
+    }
+
     fn run(
         &mut self,
         mut cb: impl FnMut(
@@ -53,6 +53,10 @@ impl ChannelPointerVec {
 }
 
 impl<P: Plugin> Backend<P> for Jack {
+    fn actual_config(&self) -> WrapperConfig {
+        self.config.clone()
+    }
+
     fn run(
         &mut self,
         mut cb: impl FnMut(
@@ -321,7 +325,7 @@ impl Jack {
     /// Initialize the JACK backend. Returns an error if this failed for whatever reason. The plugin
     /// generic argument is to get the name for the client, and to know whether or not the
     /// standalone should expose JACK MIDI ports.
-    pub fn new<P: Plugin>(config: WrapperConfig) -> Result<Self> {
+    pub fn new<P: Plugin>(mut config: WrapperConfig) -> Result<Self> {
         let audio_io_layout = config.audio_io_layout_or_exit::<P>();
         let plugin_name = P::NAME.to_lowercase().replace(' ', "_");
         let (client, status) = Client::new(&plugin_name, ClientOptions::NO_START_SERVER)
@@ -330,6 +334,11 @@ impl Jack {
             anyhow::bail!("The JACK server returned an error: {status:?}");
         }
 
+        // The sample rate and period size options are ignored for JACK since the server dictates
+        // these
+        config.sample_rate = client.sample_rate() as f32;
+        config.period_size = client.buffer_size();
+
         if config.connect_jack_inputs.is_none() && audio_io_layout.main_input_channels.is_some() {
             nih_log!(
                 "Audio inputs are not connected automatically to prevent feedback. Use the \
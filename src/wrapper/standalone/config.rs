@@ -1,5 +1,6 @@
 use clap::{Parser, ValueEnum};
 use std::num::NonZeroU32;
+use std::path::PathBuf;
 
 use crate::prelude::{AudioIOLayout, Plugin};
 
@@ -31,16 +32,68 @@ pub struct WrapperConfig {
     /// This setting is ignored when using the JACK backend.
     #[clap(value_parser, short = 'p', long, default_value = "512")]
     pub period_size: u32,
+    /// Fail if the audio device does not support the exact sample rate and period size. By
+    /// default the closest supported settings are used instead.
+    ///
+    /// This setting is ignored when using the JACK backend.
+    #[clap(value_parser, long)]
+    pub strict: bool,
+    /// Fail if the audio device does not support the exact period size, but still fall back to the
+    /// closest supported sample rate. Configurations that support the exact period size are
+    /// preferred over ones that support the exact sample rate.
+    ///
+    /// This setting is ignored when using the JACK backend.
+    #[clap(value_parser, long)]
+    pub strict_buffer_size: bool,
+    /// Start playback as soon as half a period of audio input has been captured instead of waiting
+    /// for a full period. This halves the latency added by the input device, but the output will
+    /// underrun when the second half of a period's input does not arrive in time.
+    ///
+    /// This setting is ignored when using the JACK backend, and it only has an effect when an input
+    /// device is used.
+    #[clap(value_parser, long)]
+    pub low_latency: bool,
 
+    /// List the input and output devices for the ALSA, CoreAudio, and WASAPI backends along with
+    /// their supported channel counts, sample rates, and period sizes, and then exit.
+    #[clap(value_parser, long)]
+    pub list_devices: bool,
     /// The input device for the ALSA, CoreAudio, and WASAPI backends. No input will be connected if
     /// this is not specified.
     ///
+    /// If the plugin has sidechain inputs and no '--aux-input-device' options are used, then the
+    /// sidechain inputs are read from the device's channels after the main input's channels when
+    /// the device has enough channels.
+    ///
+    /// The device can be specified either by its name or by its index in the list of input devices.
     /// Specifying an empty string or other invalid value will list all available input devices.
     #[clap(value_parser, long)]
     pub input_device: Option<String>,
+    /// Loop this WAV file as the plugin's main audio input instead of using an input device.
+    ///
+    /// The file is resampled to the audio backend's sample rate if needed. Mono files are played
+    /// back on all input channels. This works with every backend, including the dummy backend.
+    #[clap(value_parser, long)]
+    pub input_file: Option<PathBuf>,
+    /// The input device for one of the plugin's sidechain inputs with the ALSA, CoreAudio, and
+    /// WASAPI backends. This option can be repeated, with the first device being used for the first
+    /// sidechain input, and so on. Sidechain inputs without a device receive silence. When this
+    /// option is not used, the sidechain inputs may be read from the input device instead.
+    ///
+    /// The device needs to have exactly as many channels as the sidechain input, and it needs to
+    /// support the output device's sample rate and period size. Like with '--input-device', the
+    /// device can also be specified by its index. Specifying an empty string or other invalid value
+    /// will list all available input devices.
+    #[clap(value_parser, long)]
+    pub aux_input_device: Vec<String>,
     /// The output device for the ALSA, CoreAudio, and WASAPI backends.
     ///
-    /// Specifying an empty string or other invalid value will list all available output devices.
+    /// If the plugin has auxiliary outputs, then they are written to the device's channels after
+    /// the main output's channels. Auxiliary outputs that don't fit on the device are discarded.
+    ///
+    /// The device can be specified either by its name or by its index in the list of output
+    /// devices. Specifying an empty string or other invalid value will list all available output
+    /// devices.
     #[clap(value_parser, long)]
     pub output_device: Option<String>,
     /// The input MIDI device for the ALSA, CoreAudio, and WASAPI backends.
@@ -93,6 +146,19 @@ pub struct WrapperConfig {
     /// The time signature's denominator.
     #[clap(value_parser, long, default_value = "4")]
     pub timesig_denom: u32,
+
+    /// Record all parameter changes and incoming MIDI events to this JSON file. The file is
+    /// written when the application exits.
+    #[clap(value_parser, long)]
+    pub record_automation: Option<PathBuf>,
+    /// Replay the parameter changes and MIDI events from a file recorded with
+    /// '--record-automation'.
+    ///
+    /// Events are replayed at the same sample positions they were recorded at, so the same sample
+    /// rate and period size should be used to get identical results. This pairs well with the
+    /// dummy backend.
+    #[clap(value_parser, long)]
+    pub replay_automation: Option<PathBuf>,
 }
 
 /// Determines which audio and MIDI backend should be used.
@@ -3,8 +3,8 @@ use std::sync::Arc;
 use super::backend::Backend;
 use super::wrapper::{Task, Wrapper};
 use crate::prelude::{
-    GuiContext, InitContext, ParamPtr, Plugin, PluginApi, PluginNoteEvent, ProcessContext,
-    Transport,
+    BufferConfig, GuiContext, InitContext, Param, ParamPtr, Plugin, PluginApi, PluginNoteEvent,
+    ProcessContext, Transport,
 };
 
 /// An [`InitContext`] implementation for the standalone wrapper.
@@ -52,6 +52,18 @@ impl<P: Plugin, B: Backend<P>> InitContext<P> for WrapperInitContext<'_, P, B> {
     fn set_current_voice_capacity(&self, _capacity: u32) {
         // This is only supported by CLAP
     }
+
+    fn set_active_aux_output_ports(&self, _num_ports: usize) {
+        // The standalone backends always expose all ports
+    }
+
+    fn set_param_active(&self, _param: &impl Param, _active: bool) {
+        // There's no host to report this to
+    }
+
+    fn note_names_changed(&self) {
+        // This is only supported by CLAP
+    }
 }
 
 impl<P: Plugin, B: Backend<P>> ProcessContext<P> for WrapperProcessContext<'_, P, B> {
@@ -97,6 +109,18 @@ impl<P: Plugin, B: Backend<P>> ProcessContext<P> for WrapperProcessContext<'_, P
     fn set_current_voice_capacity(&self, _capacity: u32) {
         // This is only supported by CLAP
     }
+
+    fn set_active_aux_output_ports(&self, _num_ports: usize) {
+        // The standalone backends always expose all ports
+    }
+
+    fn set_param_active(&self, _param: &impl Param, _active: bool) {
+        // There's no host to report this to
+    }
+
+    fn note_names_changed(&self) {
+        // This is only supported by CLAP
+    }
 }
 
 impl<P: Plugin, B: Backend<P>> GuiContext for WrapperGuiContext<P, B> {
@@ -104,11 +128,27 @@ impl<P: Plugin, B: Backend<P>> GuiContext for WrapperGuiContext<P, B> {
         PluginApi::Standalone
     }
 
+    fn plugin_name(&self) -> &'static str {
+        P::NAME
+    }
+
     fn request_resize(&self) -> bool {
         self.wrapper.request_resize();
         true
     }
 
+    fn buffer_config(&self) -> Option<BufferConfig> {
+        Some(self.wrapper.buffer_config())
+    }
+
+    fn cpu_usage(&self) -> Option<f32> {
+        self.wrapper.cpu_usage_meter.load()
+    }
+
+    fn note_names_changed(&self) {
+        // This is only supported by CLAP
+    }
+
     unsafe fn raw_begin_set_parameter(&self, _param: ParamPtr) {
         // Since there's no automation being recorded here, gestures don't mean anything
 
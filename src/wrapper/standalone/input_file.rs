@@ -0,0 +1,174 @@
+//! Looping an audio file as the standalone wrapper's main audio input. This makes it possible to
+//! audition a plugin with real material without having to route audio from another application.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::buffer::Buffer;
+
+/// A decoded audio file that is played back in a loop. The file is resampled to the wrapper's
+/// sample rate when it's loaded, so playing it back is just a matter of copying samples.
+#[derive(Debug)]
+pub struct InputFilePlayer {
+    /// The file's samples at the wrapper's sample rate, one vector per channel. All channels have
+    /// the same non-zero length.
+    channels: Vec<Vec<f32>>,
+    /// The playback position in `channels`. This wraps around to zero at the end of the file.
+    position: usize,
+}
+
+impl InputFilePlayer {
+    /// Decode a WAV file and resample it to `sample_rate`. Both integer and floating point WAV
+    /// files are supported.
+    pub fn read(path: &Path, sample_rate: f32) -> Result<Self> {
+        let mut reader = hound::WavReader::open(path)
+            .with_context(|| format!("Could not open '{}'", path.display()))?;
+        let spec = reader.spec();
+        let interleaved_samples: Vec<f32> = match spec.sample_format {
+            hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>(),
+            hound::SampleFormat::Int => {
+                let scale = ((1u64 << (spec.bits_per_sample - 1)) as f32).recip();
+                reader
+                    .samples::<i32>()
+                    .map(|sample| sample.map(|sample| sample as f32 * scale))
+                    .collect::<Result<_, _>>()
+            }
+        }
+        .with_context(|| format!("Could not decode '{}'", path.display()))?;
+
+        let num_channels = spec.channels as usize;
+        let mut channels = vec![Vec::new(); num_channels];
+        for frame in interleaved_samples.chunks_exact(num_channels) {
+            for (channel, sample) in channels.iter_mut().zip(frame) {
+                channel.push(*sample);
+            }
+        }
+
+        Self::from_channels(channels, spec.sample_rate as f32, sample_rate)
+            .with_context(|| format!("'{}' does not contain any audio", path.display()))
+    }
+
+    /// Create a player for already decoded audio. The channels are resampled from
+    /// `file_sample_rate` to `sample_rate` if those differ. Returns `None` if there are no channels
+    /// or if the channels are empty.
+    pub fn from_channels(
+        channels: Vec<Vec<f32>>,
+        file_sample_rate: f32,
+        sample_rate: f32,
+    ) -> Option<Self> {
+        let num_samples = channels.first()?.len();
+        if num_samples == 0 {
+            return None;
+        }
+        nih_debug_assert!(channels.iter().all(|channel| channel.len() == num_samples));
+
+        let channels = if file_sample_rate == sample_rate {
+            channels
+        } else {
+            channels
+                .iter()
+                .map(|channel| resample_looping(channel, file_sample_rate, sample_rate))
+                .collect()
+        };
+
+        Some(Self {
+            channels,
+            position: 0,
+        })
+    }
+
+    /// Overwrite the first `num_channels` channels in `buffer` with the next samples from the file,
+    /// looping back to the start of the file when the end is reached. Files shorter than the
+    /// buffer are looped multiple times within the same buffer. If the buffer has more channels
+    /// than the file, then the file's channels are repeated, so a mono file is played back on all
+    /// channels.
+    pub fn fill(&mut self, buffer: &mut Buffer, num_channels: usize) {
+        let file_len = self.channels[0].len();
+        let num_samples = buffer.samples();
+        for (channel_idx, channel) in buffer.as_slice().iter_mut().take(num_channels).enumerate() {
+            let source = &self.channels[channel_idx % self.channels.len()];
+
+            let mut position = self.position;
+            let mut sample_idx = 0;
+            while sample_idx < num_samples {
+                let samples_to_copy = (file_len - position).min(num_samples - sample_idx);
+                channel[sample_idx..sample_idx + samples_to_copy]
+                    .copy_from_slice(&source[position..position + samples_to_copy]);
+
+                sample_idx += samples_to_copy;
+                position = (position + samples_to_copy) % file_len;
+            }
+        }
+
+        self.position = (self.position + num_samples) % file_len;
+    }
+}
+
+/// Resample a looping signal from `from_rate` to `to_rate` using linear interpolation. The last
+/// sample is interpolated with the first sample so the loop stays seamless.
+fn resample_looping(samples: &[f32], from_rate: f32, to_rate: f32) -> Vec<f32> {
+    let ratio = from_rate as f64 / to_rate as f64;
+    let num_output_samples = ((samples.len() as f64 / ratio).round() as usize).max(1);
+
+    (0..num_output_samples)
+        .map(|output_idx| {
+            let position = output_idx as f64 * ratio;
+            let idx = position.floor() as usize % samples.len();
+            let next_idx = (idx + 1) % samples.len();
+            let t = position.fract() as f32;
+
+            samples[idx] + (samples[next_idx] - samples[idx]) * t
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fill a stereo buffer with `num_samples` samples from the player.
+    fn fill_stereo(player: &mut InputFilePlayer, num_samples: usize) -> [Vec<f32>; 2] {
+        let mut channels = [vec![0.0; num_samples], vec![0.0; num_samples]];
+        let mut buffer = Buffer::default();
+        unsafe {
+            buffer.set_slices(num_samples, |output_slices| {
+                let [left, right] = &mut channels;
+                *output_slices = vec![left.as_mut_slice(), right.as_mut_slice()];
+            })
+        };
+        player.fill(&mut buffer, 2);
+
+        channels
+    }
+
+    #[test]
+    fn loops_short_files_seamlessly() {
+        let mut player =
+            InputFilePlayer::from_channels(vec![vec![1.0, 2.0, 3.0]], 48000.0, 48000.0).unwrap();
+
+        // The mono file should be played back on both channels, and it's shorter than the buffer
+        let [left, right] = fill_stereo(&mut player, 7);
+        assert_eq!(left, [1.0, 2.0, 3.0, 1.0, 2.0, 3.0, 1.0]);
+        assert_eq!(right, left);
+
+        let [left, _] = fill_stereo(&mut player, 4);
+        assert_eq!(left, [2.0, 3.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn resamples_to_the_output_rate() {
+        let mut player =
+            InputFilePlayer::from_channels(vec![vec![0.0, 1.0], vec![1.0, 0.0]], 24000.0, 48000.0)
+                .unwrap();
+
+        let [left, right] = fill_stereo(&mut player, 6);
+        assert_eq!(left, [0.0, 0.5, 1.0, 0.5, 0.0, 0.5]);
+        assert_eq!(right, [1.0, 0.5, 0.0, 0.5, 1.0, 0.5]);
+    }
+
+    #[test]
+    fn rejects_empty_files() {
+        assert!(InputFilePlayer::from_channels(Vec::new(), 48000.0, 48000.0).is_none());
+        assert!(InputFilePlayer::from_channels(vec![Vec::new()], 48000.0, 48000.0).is_none());
+    }
+}
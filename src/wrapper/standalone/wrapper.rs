@@ -6,22 +6,26 @@ use parking_lot::Mutex;
 use raw_window_handle::HasRawWindowHandle;
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
+use std::num::NonZeroU32;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
 use std::thread;
 
+use super::automation::{AutomationEvent, AutomationPlayer, AutomationRecording};
 use super::backend::Backend;
 use super::config::WrapperConfig;
 use super::context::{WrapperGuiContext, WrapperInitContext, WrapperProcessContext};
+use super::input_file::InputFilePlayer;
 use crate::event_loop::{EventLoop, MainThreadExecutor, OsEventLoop};
 use crate::prelude::{
-    AsyncExecutor, AudioIOLayout, BufferConfig, Editor, ParamFlags, ParamPtr, Params,
+    AsyncExecutor, AudioIOLayout, BufferConfig, Editor, NoteEvent, ParamFlags, ParamPtr, Params,
     ParentWindowHandle, Plugin, PluginNoteEvent, ProcessMode, ProcessStatus, TaskExecutor,
     Transport,
 };
 use crate::util::permit_alloc;
 use crate::wrapper::state::{self, PluginState};
-use crate::wrapper::util::process_wrapper;
+use crate::wrapper::util::cpu_usage::CpuUsageMeter;
+use crate::wrapper::util::{initialize_plugin, process_wrapper};
 
 /// How many parameter changes we can store in our unprocessed parameter change queue. Storing more
 /// than this many parameters at a time will cause changes to get lost.
@@ -88,6 +92,18 @@ pub struct Wrapper<P: Plugin, B: Backend<P>> {
     /// still kept track of to avoid firing debug assertions multiple times for the same latency
     /// value.
     current_latency: AtomicU32,
+    /// Measures the plugin's CPU usage if `P::MEASURE_CPU_USAGE` is enabled.
+    pub cpu_usage_meter: CpuUsageMeter,
+
+    /// The parameter changes and note events received by the plugin, if the
+    /// `--record-automation` option was passed. This is written to a file when the wrapper exits.
+    automation_recording: AtomicRefCell<Option<AutomationRecording>>,
+    /// Parameter changes and note events that should be sent to the plugin, if the
+    /// `--replay-automation` option was passed.
+    automation_player: AtomicRefCell<Option<AutomationPlayer>>,
+    /// The audio file that's looped as the plugin's main input, if the `--input-file` option was
+    /// passed and the plugin has a main input.
+    input_file_player: AtomicRefCell<Option<InputFilePlayer>>,
 }
 
 /// Tasks that can be sent from the plugin to be executed on the main thread in a non-blocking
@@ -107,10 +123,17 @@ pub enum Task<P: Plugin> {
 }
 
 /// Errors that may arise while initializing the wrapped plugins.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum WrapperError {
-    /// The plugin returned `false` during initialization.
-    InitializationFailed,
+    /// The plugin failed to initialize. Contains the reason returned by
+    /// [`Plugin::try_initialize()`].
+    InitializationFailed(String),
+    /// The file passed to `--replay-automation` could not be read.
+    ReadAutomationFailed,
+    /// The recorded automation could not be written to the file passed to `--record-automation`.
+    WriteAutomationFailed,
+    /// The file passed to `--input-file` could not be read.
+    ReadInputFileFailed,
 }
 
 struct WrapperWindowHandler {
@@ -181,6 +204,48 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
         //       the config itself. Right now clap doesn't support this.
         let audio_io_layout = config.audio_io_layout_or_exit::<P>();
 
+        let automation_recording = config
+            .record_automation
+            .as_ref()
+            .map(|_| AutomationRecording::new(config.sample_rate));
+        let automation_player = match &config.replay_automation {
+            Some(path) => match AutomationRecording::read(path) {
+                Ok(recording) => {
+                    if recording.sample_rate != config.sample_rate {
+                        nih_warn!(
+                            "The automation was recorded at {} Hz but the sample rate is {} Hz, \
+                             events will be replayed at different times",
+                            recording.sample_rate,
+                            config.sample_rate
+                        );
+                    }
+
+                    Some(AutomationPlayer::new(recording))
+                }
+                Err(err) => {
+                    nih_error!("{err:#}");
+                    return Err(WrapperError::ReadAutomationFailed);
+                }
+            },
+            None => None,
+        };
+        let input_file_player = match &config.input_file {
+            Some(_) if audio_io_layout.main_input_channels.is_none() => {
+                nih_warn!(
+                    "The audio layout does not have a main input, the input file will be ignored"
+                );
+                None
+            }
+            Some(path) => match InputFilePlayer::read(path, config.sample_rate) {
+                Ok(player) => Some(player),
+                Err(err) => {
+                    nih_error!("{err:#}");
+                    return Err(WrapperError::ReadInputFileFailed);
+                }
+            },
+            None => None,
+        };
+
         let mut plugin = P::default();
         let task_executor = Mutex::new(plugin.task_executor());
         let params = plugin.params();
@@ -253,6 +318,11 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
             updated_state_sender,
             updated_state_receiver,
             current_latency: AtomicU32::new(0),
+            cpu_usage_meter: CpuUsageMeter::default(),
+
+            automation_recording: AtomicRefCell::new(automation_recording),
+            automation_player: AtomicRefCell::new(automation_player),
+            input_file_player: AtomicRefCell::new(input_file_player),
         });
 
         *wrapper.event_loop.borrow_mut() =
@@ -289,12 +359,12 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
 
         {
             let mut plugin = wrapper.plugin.lock();
-            if !plugin.initialize(
+            if let Err(reason) = plugin.try_initialize(
                 &wrapper.audio_io_layout,
                 &wrapper.buffer_config,
                 &mut wrapper.make_init_context(),
             ) {
-                return Err(WrapperError::InitializationFailed);
+                return Err(WrapperError::InitializationFailed(reason));
             }
             process_wrapper(|| plugin.reset());
         }
@@ -389,6 +459,23 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
         // application, but it seems like a good idea to stay consistent.
         self.plugin.lock().deactivate();
 
+        if let (Some(path), Some(recording)) = (
+            &self.config.record_automation,
+            &*self.automation_recording.borrow(),
+        ) {
+            match recording.write(path) {
+                Ok(()) => nih_log!(
+                    "Wrote {} recorded events to '{}'",
+                    recording.events.len(),
+                    path.display()
+                ),
+                Err(err) => {
+                    nih_error!("{err:#}");
+                    return Err(WrapperError::WriteAutomationFailed);
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -399,6 +486,11 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
         self.param_ptr_to_id.get(&param).map(|s| s.as_str())
     }
 
+    /// The buffer configuration is static for the standalone target.
+    pub fn buffer_config(&self) -> BufferConfig {
+        self.buffer_config
+    }
+
     /// Set a parameter based on a `ParamPtr`. The value will be updated at the end of the next
     /// processing cycle, and this won't do anything if the parameter has not been registered by the
     /// plugin.
@@ -489,6 +581,28 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
         }
     }
 
+    /// Immediately set a parameter's normalized value from the audio thread and inform the editor
+    /// about the change. If automation is being recorded, then the change is recorded at the
+    /// sample position `timing`.
+    fn set_parameter_from_audio_thread(&self, timing: u64, param_ptr: ParamPtr, normalized: f32) {
+        if unsafe { param_ptr.set_normalized_value(normalized) } {
+            unsafe { param_ptr.update_smoother(self.buffer_config.sample_rate, false) };
+            let task_posted = self.schedule_gui(Task::ParameterValueChanged(param_ptr, normalized));
+            nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+
+            if let Some(recording) = &mut *self.automation_recording.borrow_mut() {
+                // Recording is an opt-in debugging feature, so allocating here is fine
+                permit_alloc(|| {
+                    recording.events.push(AutomationEvent::ParameterChange {
+                        timing,
+                        param_id: self.param_ptr_to_id[&param_ptr].clone(),
+                        normalized_value: normalized,
+                    })
+                });
+            }
+        }
+    }
+
     pub fn set_latency_samples(&self, samples: u32) {
         // This should only change the value if it's actually needed
         let old_latency = self.current_latency.swap(samples, Ordering::SeqCst);
@@ -505,6 +619,14 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
         should_terminate: Arc<AtomicBool>,
         gui_task_sender: channel::Sender<GuiTask>,
     ) {
+        // The absolute position of the current block in samples, used for recording and replaying
+        // automation
+        let mut block_start = 0u64;
+        // When replaying automation, the backend's input events and the replayed note events are
+        // merged into this vector. This never grows beyond its initial capacity so it doesn't
+        // allocate on the audio thread.
+        let mut replayed_input_events = Vec::with_capacity(EVENT_QUEUE_CAPACITY);
+
         self.clone().backend.borrow_mut().run(
             move |buffer, aux, transport, input_events, output_events| {
                 // TODO: This process wrapper should actually be in the backends (since the backends
@@ -515,14 +637,118 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
                         return false;
                     }
 
-                    let sample_rate = self.buffer_config.sample_rate;
+                    let block_end = block_start + buffer.samples() as u64;
+                    let input_events = match &mut *self.automation_player.borrow_mut() {
+                        Some(automation_player) => {
+                            replayed_input_events.clear();
+                            let num_input_events = input_events.len().min(EVENT_QUEUE_CAPACITY);
+                            nih_debug_assert!(
+                                num_input_events == input_events.len(),
+                                "The replayed input event queue is full, dropping {} input events",
+                                input_events.len() - num_input_events
+                            );
+                            replayed_input_events
+                                .extend_from_slice(&input_events[..num_input_events]);
+
+                            for event in automation_player.next_block(block_end) {
+                                match event {
+                                    // Parameter changes are always recorded at the start of a
+                                    // block, so they can be applied immediately
+                                    AutomationEvent::ParameterChange {
+                                        param_id,
+                                        normalized_value,
+                                        ..
+                                    } => match self.param_id_to_ptr.get(param_id) {
+                                        Some(param_ptr) => self.set_parameter_from_audio_thread(
+                                            block_start,
+                                            *param_ptr,
+                                            *normalized_value,
+                                        ),
+                                        None => nih_debug_assert_failure!(
+                                            "Unknown parameter '{}' in the replayed automation",
+                                            param_id
+                                        ),
+                                    },
+                                    AutomationEvent::Midi { timing, data } => {
+                                        let timing = timing.saturating_sub(block_start) as u32;
+                                        match NoteEvent::from_midi(timing, data) {
+                                            Ok(event)
+                                                if replayed_input_events.len()
+                                                    < EVENT_QUEUE_CAPACITY =>
+                                            {
+                                                replayed_input_events.push(event)
+                                            }
+                                            Ok(_) => nih_debug_assert_failure!(
+                                                "The replayed input event queue is full"
+                                            ),
+                                            Err(event_type) => nih_debug_assert_failure!(
+                                                "Unhandled replayed MIDI event type: {}",
+                                                event_type
+                                            ),
+                                        }
+                                    }
+                                }
+                            }
+
+                            // The events need to remain in order for events at the same timing.
+                            // The standard library's stable sort allocates, so this uses an
+                            // in-place insertion sort instead. Only the replayed events can be out
+                            // of order.
+                            for event_idx in 1..replayed_input_events.len() {
+                                let mut idx = event_idx;
+                                while idx > 0
+                                    && replayed_input_events[idx - 1].timing()
+                                        > replayed_input_events[idx].timing()
+                                {
+                                    replayed_input_events.swap(idx - 1, idx);
+                                    idx -= 1;
+                                }
+                            }
+
+                            &replayed_input_events[..]
+                        }
+                        None => input_events,
+                    };
+
+                    if let Some(recording) = &mut *self.automation_recording.borrow_mut() {
+                        permit_alloc(|| {
+                            recording
+                                .events
+                                .extend(input_events.iter().filter_map(|event| {
+                                    AutomationEvent::from_note_event(block_start, event.clone())
+                                }))
+                        });
+                    }
+
+                    // The input file replaces whatever input the backend provided
+                    if let Some(input_file_player) = &mut *self.input_file_player.borrow_mut() {
+                        let num_input_channels = self
+                            .audio_io_layout
+                            .main_input_channels
+                            .map(NonZeroU32::get)
+                            .unwrap_or_default();
+                        input_file_player.fill(buffer, num_input_channels as usize);
+                    }
+
                     {
                         let mut plugin = self.plugin.lock();
-                        if let ProcessStatus::Error(err) = plugin.process(
-                            buffer,
-                            aux,
-                            &mut self.make_process_context(transport, input_events, output_events),
-                        ) {
+                        let num_samples = buffer.samples();
+                        let result = self.cpu_usage_meter.measure::<P, _>(
+                            num_samples,
+                            self.buffer_config.sample_rate,
+                            || {
+                                plugin.process(
+                                    buffer,
+                                    aux,
+                                    &mut self.make_process_context(
+                                        transport,
+                                        input_events,
+                                        output_events,
+                                    ),
+                                )
+                            },
+                        );
+                        if let ProcessStatus::Error(err) = result {
                             nih_error!("The plugin returned an error while processing:");
                             nih_error!("{}", err);
 
@@ -544,17 +770,12 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
                     while let Some((param_ptr, normalized_value)) =
                         self.unprocessed_param_changes.pop()
                     {
-                        if unsafe { param_ptr.set_normalized_value(normalized_value) } {
-                            unsafe { param_ptr.update_smoother(sample_rate, false) };
-                            let task_posted = self.schedule_gui(Task::ParameterValueChanged(
-                                param_ptr,
-                                normalized_value,
-                            ));
-                            nih_debug_assert!(
-                                task_posted,
-                                "The task queue is full, dropping task..."
-                            );
-                        }
+                        // These changes will take effect from the next block onwards
+                        self.set_parameter_from_audio_thread(
+                            block_end,
+                            param_ptr,
+                            normalized_value,
+                        );
                     }
 
                     // After processing audio, we'll check if the editor has sent us updated plugin
@@ -577,6 +798,8 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
                         };
                     }
 
+                    block_start = block_end;
+
                     true
                 })
             },
@@ -649,7 +872,8 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
 
             // See above
             success = permit_alloc(|| {
-                plugin.initialize(
+                initialize_plugin(
+                    &mut *plugin,
                     &self.audio_io_layout,
                     &self.buffer_config,
                     &mut init_context,
@@ -660,10 +884,7 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
             }
         }
 
-        nih_debug_assert!(
-            success,
-            "Plugin returned false when reinitializing after loading state"
-        );
+        nih_debug_assert!(success, "Plugin failed to reinitialize after loading state");
 
         // Reinitialize the plugin after loading state so it can respond to the new parameter values
         let task_posted = self.schedule_gui(Task::ParameterValuesChanged);
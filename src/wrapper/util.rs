@@ -3,11 +3,14 @@ use std::cmp;
 use std::marker::PhantomData;
 use std::os::raw::c_char;
 
+use crate::prelude::{AudioIOLayout, BufferConfig, InitContext, Plugin, ProcessStatus};
 use crate::util::permit_alloc;
 
 pub(crate) mod buffer_management;
 #[cfg(debug_assertions)]
 pub(crate) mod context_checks;
+pub(crate) mod cpu_usage;
+pub(crate) mod note_ids;
 
 /// The bit that controls flush-to-zero behavior for denormals in 32 and 64-bit floating point
 /// numbers on AArch64.
@@ -89,6 +92,36 @@ pub fn clamp_output_event_timing(timing: u32, total_buffer_len: u32) -> u32 {
     timing.min(last_valid_index)
 }
 
+/// The tail length in samples to report to the host, based on the last status returned by
+/// [`Plugin::process()`] and the plugin's [`tail_length_samples()`][Plugin::tail_length_samples()]
+/// after that call. `u32::MAX` means an infinite tail and zero means no tail, which is what both
+/// CLAP and VST3 expect.
+#[inline]
+pub fn reported_tail_length(status: ProcessStatus, tail_length_samples: Option<u32>) -> u32 {
+    match status {
+        ProcessStatus::Tail(samples) => samples,
+        ProcessStatus::KeepAlive => u32::MAX,
+        _ => tail_length_samples.unwrap_or(0),
+    }
+}
+
+/// Initialize the plugin using [`Plugin::try_initialize()`]. If this fails, then the reason is
+/// logged and `false` is returned.
+pub fn initialize_plugin<P: Plugin>(
+    plugin: &mut P,
+    audio_io_layout: &AudioIOLayout,
+    buffer_config: &BufferConfig,
+    context: &mut impl InitContext<P>,
+) -> bool {
+    match plugin.try_initialize(audio_io_layout, buffer_config, context) {
+        Ok(()) => true,
+        Err(reason) => {
+            nih_error!("The plugin failed to initialize: {reason}");
+            false
+        }
+    }
+}
+
 /// Set up the logger so that the `nih_*!()` logging and assertion macros log output to a
 /// centralized location and panics also get written there. By default this logs to STDERR. If a
 /// Windows debugger is attached, then messages will be sent there instead. This uses
@@ -42,6 +42,11 @@ pub struct BufferManager {
     aux_input_storage: Vec<Vec<Vec<f32>>>,
 
     aux_output_buffers: Vec<Buffer<'static>>,
+    /// Scratch storage for auxiliary output ports the host did not provide any buffers for. This
+    /// happens when the plugin only exposes some of its auxiliary outputs, or when the host
+    /// deactivates an auxiliary output bus. The plugin can still write to these buffers, but their
+    /// contents are discarded.
+    aux_output_storage: Vec<Vec<Vec<f32>>>,
 }
 
 // SAFETY: The raw pointers in the `ChannelPointers` fields/vectors are only used as scratch storage
@@ -119,6 +124,7 @@ impl BufferManager {
         }
 
         let mut aux_output_buffers = Vec::with_capacity(audio_io_layout.aux_output_ports.len());
+        let mut aux_output_storage = Vec::with_capacity(audio_io_layout.aux_output_ports.len());
         for num_channels in audio_io_layout.aux_output_ports {
             let mut buffer = Buffer::default();
             unsafe {
@@ -128,6 +134,10 @@ impl BufferManager {
             };
 
             aux_output_buffers.push(buffer);
+            aux_output_storage.push(vec![
+                vec![0.0; max_buffer_size];
+                num_channels.get() as usize
+            ]);
         }
 
         Self {
@@ -142,6 +152,7 @@ impl BufferManager {
             aux_input_storage,
 
             aux_output_buffers,
+            aux_output_storage,
         }
     }
 
@@ -153,7 +164,9 @@ impl BufferManager {
     ///
     /// `sample_offset` and `num_samples` can be used to slice a set of host channel pointers for
     /// sample accurate automation. If any of the outputs are missing because the host hasn't
-    /// provided enough channels or outputs, then they will be replaced by empty slices.
+    /// provided enough channels or outputs, then they will be replaced by empty slices. The
+    /// exception to this are auxiliary output ports the host did not provide any buffers for at
+    /// all. Those will point to scratch storage instead.
     ///
     /// # Panics
     ///
@@ -314,10 +327,12 @@ impl BufferManager {
 
         // The auxiliary output buffers can point directly to the host's buffers. This logic is the
         // same as the main outputs, minus the copying of input cdata
-        for (output_channel_pointers, output_buffer) in self
-            .aux_output_channel_pointers
-            .iter()
-            .zip(self.aux_output_buffers.iter_mut())
+        for (output_channel_pointers, (output_storage, output_buffer)) in
+            self.aux_output_channel_pointers.iter().zip(
+                self.aux_output_storage
+                    .iter_mut()
+                    .zip(self.aux_output_buffers.iter_mut()),
+            )
         {
             output_buffer.set_slices(num_samples, |output_slices| {
                 match output_channel_pointers {
@@ -349,10 +364,20 @@ impl BufferManager {
                         output_slices[output_channel_pointers.num_channels..].fill_with(|| &mut [])
                     }
                     None => {
-                        nih_debug_assert_eq!(output_slices.len(), 0);
+                        // The host did not provide any buffers for this port, either because it's
+                        // inactive or because the plugin chose to not expose it. The plugin will
+                        // write to our scratch storage instead.
+                        nih_debug_assert_eq!(output_slices.len(), output_storage.len());
+                        for (output_slice, channel_storage) in
+                            output_slices.iter_mut().zip(output_storage.iter_mut())
+                        {
+                            let channel_storage = &mut channel_storage[..num_samples];
+                            channel_storage.fill(0.0);
 
-                        // Same as above
-                        output_slices.fill_with(|| &mut [])
+                            // SAFETY: `channel_storage` is no longer used accessed directly after
+                            //         this
+                            *output_slice = &mut *(channel_storage as *mut [f32]);
+                        }
                     }
                 }
             });
@@ -497,4 +522,62 @@ mod miri {
             }
         }
     }
+
+    #[test]
+    fn missing_aux_outputs() {
+        let mut main_io_storage = vec![vec![0.0f32; BUFFER_SIZE]; NUM_MAIN_OUTPUT_CHANNELS];
+        let mut aux_output_storage = vec![vec![0.0f32; BUFFER_SIZE]; NUM_AUX_CHANNELS];
+
+        let mut main_io_channel_pointers: Vec<*mut f32> = main_io_storage
+            .iter_mut()
+            .map(|channel_slice| channel_slice.as_mut_ptr())
+            .collect();
+        let mut aux_output_channel_pointers: Vec<*mut f32> = aux_output_storage
+            .iter_mut()
+            .map(|channel_slice| channel_slice.as_mut_ptr())
+            .collect();
+
+        // Only the first auxiliary output port is provided by the host here, the second port
+        // should then be backed by the buffer manager's own scratch storage
+        let mut buffer_manager = BufferManager::for_audio_io_layout(
+            BUFFER_SIZE,
+            AudioIOLayout {
+                aux_input_ports: &[],
+                ..AUDIO_IO_LAYOUT
+            },
+        );
+        let buffers = unsafe {
+            buffer_manager.create_buffers(0, BUFFER_SIZE, |buffer_sources| {
+                *buffer_sources.main_output_channel_pointers = Some(ChannelPointers {
+                    ptrs: NonNull::new(main_io_channel_pointers.as_mut_ptr()).unwrap(),
+                    num_channels: main_io_channel_pointers.len(),
+                });
+                *buffer_sources.main_input_channel_pointers = Some(ChannelPointers {
+                    ptrs: NonNull::new(main_io_channel_pointers.as_mut_ptr()).unwrap(),
+                    num_channels: NUM_MAIN_INPUT_CHANNELS.min(main_io_channel_pointers.len()),
+                });
+                buffer_sources.aux_output_channel_pointers[0] = Some(ChannelPointers {
+                    ptrs: NonNull::new(aux_output_channel_pointers.as_mut_ptr()).unwrap(),
+                    num_channels: aux_output_channel_pointers.len(),
+                });
+            })
+        };
+
+        assert_eq!(buffers.aux_outputs.len(), NUM_AUX_PORTS);
+        for buffer in buffers.aux_outputs.iter_mut() {
+            assert_eq!(buffer.samples(), BUFFER_SIZE);
+            for channel_samples in buffer.iter_samples() {
+                for sample in channel_samples {
+                    assert!(*sample == 0.0);
+                    *sample += 1.0;
+                }
+            }
+        }
+
+        for channel in aux_output_storage.iter() {
+            for sample in channel {
+                assert!(*sample == 1.0);
+            }
+        }
+    }
 }
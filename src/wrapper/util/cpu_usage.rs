@@ -0,0 +1,74 @@
+//! Measuring how much of the real-time budget a plugin's `process()` function uses.
+
+use atomic_float::AtomicF32;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use crate::prelude::Plugin;
+
+/// The time constant for smoothing the measured CPU usage, in seconds of processed audio.
+const SMOOTHING_TIME_CONSTANT_SECS: f32 = 0.3;
+
+/// Measures how long the plugin's `process()` calls take relative to the duration of the audio
+/// they process. Nothing is measured unless [`Plugin::MEASURE_CPU_USAGE`] is enabled. The smoothed
+/// result can be read from any thread.
+#[derive(Debug)]
+pub struct CpuUsageMeter {
+    /// The smoothed CPU usage as a fraction of the real-time budget, or a negative value if nothing
+    /// has been measured yet.
+    usage: AtomicF32,
+}
+
+impl Default for CpuUsageMeter {
+    fn default() -> Self {
+        Self {
+            usage: AtomicF32::new(-1.0),
+        }
+    }
+}
+
+impl CpuUsageMeter {
+    /// Call `f`, which processes `num_samples` samples at `sample_rate`, and update the CPU usage
+    /// with the time that took. If [`Plugin::MEASURE_CPU_USAGE`] is disabled then this simply calls
+    /// `f`. Blocks are weighted by their length, so splitting a buffer into smaller blocks does not
+    /// affect the result.
+    #[inline]
+    pub fn measure<P: Plugin, T>(
+        &self,
+        num_samples: usize,
+        sample_rate: f32,
+        f: impl FnOnce() -> T,
+    ) -> T {
+        if !P::MEASURE_CPU_USAGE || num_samples == 0 {
+            return f();
+        }
+
+        let start = Instant::now();
+        let result = f();
+        let elapsed_secs = start.elapsed().as_secs_f32();
+
+        let block_duration_secs = num_samples as f32 / sample_rate;
+        let block_usage = elapsed_secs / block_duration_secs;
+        let previous_usage = self.usage.load(Ordering::Relaxed);
+        let new_usage = if previous_usage < 0.0 {
+            block_usage
+        } else {
+            let t = 1.0 - (-block_duration_secs / SMOOTHING_TIME_CONSTANT_SECS).exp();
+            previous_usage + ((block_usage - previous_usage) * t)
+        };
+        self.usage.store(new_usage, Ordering::Relaxed);
+
+        result
+    }
+
+    /// Get the smoothed CPU usage as a fraction of the real-time budget. Returns `None` if nothing
+    /// has been measured yet.
+    pub fn load(&self) -> Option<f32> {
+        let usage = self.usage.load(Ordering::Relaxed);
+        if usage >= 0.0 {
+            Some(usage)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Keeping track of the host's note IDs between note on and note off events.
+
+/// The maximum number of notes with note IDs that can be tracked at the same time. When more notes
+/// are playing, the oldest notes are forgotten.
+const MAX_TRACKED_NOTES: usize = 512;
+
+/// Matches the note IDs from note on events to the note off and choke events for the same notes.
+/// Some hosts don't include a note ID in their note off events even if the corresponding note on
+/// event had one. A plugin that keys its voices on note IDs would then never release those voices.
+/// This fills in the missing note IDs so the plugin always receives the note ID the note was
+/// started with. Events from MIDI-only sources never have a note ID, so nothing is tracked for
+/// them and their events are passed through as is.
+#[derive(Debug)]
+pub struct NoteIdTracker {
+    /// The currently playing notes that were started with a note ID, as `(note_id, channel, note)`
+    /// tuples in the order they were started. This never grows beyond its initial capacity.
+    active_notes: Vec<(i32, u8, u8)>,
+}
+
+impl Default for NoteIdTracker {
+    fn default() -> Self {
+        Self {
+            active_notes: Vec::with_capacity(MAX_TRACKED_NOTES),
+        }
+    }
+}
+
+impl NoteIdTracker {
+    /// Register a note on event. Returns the event's note ID unchanged.
+    pub fn note_on(&mut self, voice_id: Option<i32>, channel: u8, note: u8) -> Option<i32> {
+        let voice_id = voice_id?;
+
+        if let Some(idx) = self
+            .active_notes
+            .iter()
+            .position(|(note_id, _, _)| *note_id == voice_id)
+        {
+            nih_debug_assert_failure!(
+                "The host sent a note on event for note ID {}, which is still playing",
+                voice_id
+            );
+            self.active_notes.remove(idx);
+        }
+        if self.active_notes.len() == MAX_TRACKED_NOTES {
+            self.active_notes.remove(0);
+        }
+        self.active_notes.push((voice_id, channel, note));
+
+        Some(voice_id)
+    }
+
+    /// Register a note off or choke event and return the note ID that should be sent to the plugin.
+    /// If the event doesn't have a note ID and exactly one note with a note ID is playing on that
+    /// channel and key, then that note's ID is returned. If multiple overlapping notes are playing
+    /// on that key, then the event applies to all of them and it's passed on without a note ID.
+    pub fn note_off(&mut self, voice_id: Option<i32>, channel: u8, note: u8) -> Option<i32> {
+        match voice_id {
+            Some(voice_id) => {
+                self.active_notes
+                    .retain(|(note_id, _, _)| *note_id != voice_id);
+
+                Some(voice_id)
+            }
+            None => {
+                let mut matching_notes =
+                    self.active_notes
+                        .iter()
+                        .filter(|(_, note_channel, note_note)| {
+                            *note_channel == channel && *note_note == note
+                        });
+                let resolved_voice_id = match (matching_notes.next(), matching_notes.next()) {
+                    (Some((note_id, _, _)), None) => Some(*note_id),
+                    _ => None,
+                };
+
+                self.active_notes.retain(|(_, note_channel, note_note)| {
+                    !(*note_channel == channel && *note_note == note)
+                });
+
+                resolved_voice_id
+            }
+        }
+    }
+
+    /// Forget about all playing notes. Called when the plugin is reset.
+    pub fn reset(&mut self) {
+        self.active_notes.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_in_missing_note_ids() {
+        let mut tracker = NoteIdTracker::default();
+        assert_eq!(tracker.note_on(Some(10), 0, 60), Some(10));
+        assert_eq!(tracker.note_on(Some(11), 0, 62), Some(11));
+        assert_eq!(tracker.note_on(None, 0, 64), None);
+
+        assert_eq!(tracker.note_off(None, 0, 62), Some(11));
+        assert_eq!(tracker.note_off(Some(10), 0, 60), Some(10));
+        assert_eq!(tracker.note_off(None, 0, 64), None);
+        assert_eq!(tracker.note_off(None, 0, 60), None);
+    }
+
+    #[test]
+    fn overlapping_notes_stay_ambiguous() {
+        let mut tracker = NoteIdTracker::default();
+        tracker.note_on(Some(1), 0, 60);
+        tracker.note_on(Some(2), 0, 60);
+        tracker.note_on(Some(3), 1, 60);
+
+        assert_eq!(tracker.note_off(None, 0, 60), None);
+        assert_eq!(tracker.note_off(None, 1, 60), Some(3));
+    }
+}
@@ -3,11 +3,11 @@ use std::cell::Cell;
 use std::collections::VecDeque;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use vst3_sys::vst::IComponentHandler;
+use vst3_sys::vst::{IComponentHandler, RestartFlags};
 
 use crate::prelude::{
-    GuiContext, InitContext, ParamPtr, PluginApi, PluginNoteEvent, PluginState, ProcessContext,
-    Transport, Vst3Plugin,
+    BufferConfig, GuiContext, HostFeatures, InitContext, Param, ParamPtr, PluginApi,
+    PluginNoteEvent, PluginState, ProcessContext, Transport, Vst3Plugin,
 };
 
 use super::inner::{Task, WrapperInner};
@@ -32,6 +32,10 @@ pub(crate) struct WrapperInitContext<'a, P: Vst3Plugin> {
 pub(crate) struct PendingInitContextRequests {
     /// The value of the last `.set_latency_samples()` call.
     latency_changed: Cell<Option<u32>>,
+    /// The value of the last `.set_active_aux_output_ports()` call.
+    active_aux_output_ports_changed: Cell<Option<usize>>,
+    /// Whether a `.set_param_active()` call changed a parameter's active state.
+    param_info_changed: Cell<bool>,
 }
 
 /// A [`ProcessContext`] implementation for the wrapper. This is a separate object so it can hold on
@@ -59,6 +63,15 @@ impl<P: Vst3Plugin> Drop for WrapperInitContext<'_, P> {
         if let Some(samples) = self.pending_requests.latency_changed.take() {
             self.inner.set_latency_samples(samples)
         }
+        if let Some(num_ports) = self.pending_requests.active_aux_output_ports_changed.take() {
+            self.inner.set_active_aux_output_ports(num_ports)
+        }
+        if self.pending_requests.param_info_changed.take() {
+            let task_posted = self.inner.schedule_gui(Task::TriggerRestart(
+                RestartFlags::kParamTitlesChanged as i32,
+            ));
+            nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+        }
     }
 }
 
@@ -67,6 +80,10 @@ impl<P: Vst3Plugin> InitContext<P> for WrapperInitContext<'_, P> {
         PluginApi::Vst3
     }
 
+    fn host_features(&self) -> HostFeatures {
+        self.inner.host_features()
+    }
+
     fn execute(&self, task: P::BackgroundTask) {
         (self.inner.task_executor.lock())(task);
     }
@@ -79,6 +96,25 @@ impl<P: Vst3Plugin> InitContext<P> for WrapperInitContext<'_, P> {
     fn set_current_voice_capacity(&self, _capacity: u32) {
         // This is only supported by CLAP
     }
+
+    fn set_active_aux_output_ports(&self, num_ports: usize) {
+        // See this struct's docstring
+        self.pending_requests
+            .active_aux_output_ports_changed
+            .set(Some(num_ports));
+    }
+
+    fn set_param_active(&self, param: &impl Param, active: bool) {
+        // See this struct's docstring. The active state is stored immediately, only the host
+        // notification is deferred.
+        if self.inner.update_param_active(param.as_ptr(), active) {
+            self.pending_requests.param_info_changed.set(true);
+        }
+    }
+
+    fn note_names_changed(&self) {
+        // This is only supported by CLAP
+    }
 }
 
 impl<P: Vst3Plugin> ProcessContext<P> for WrapperProcessContext<'_, P> {
@@ -86,6 +122,10 @@ impl<P: Vst3Plugin> ProcessContext<P> for WrapperProcessContext<'_, P> {
         PluginApi::Vst3
     }
 
+    fn host_features(&self) -> HostFeatures {
+        self.inner.host_features()
+    }
+
     fn execute_background(&self, task: P::BackgroundTask) {
         let task_posted = self.inner.schedule_background(Task::PluginTask(task));
         nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
@@ -116,6 +156,18 @@ impl<P: Vst3Plugin> ProcessContext<P> for WrapperProcessContext<'_, P> {
     fn set_current_voice_capacity(&self, _capacity: u32) {
         // This is only supported by CLAP
     }
+
+    fn set_active_aux_output_ports(&self, num_ports: usize) {
+        self.inner.set_active_aux_output_ports(num_ports)
+    }
+
+    fn set_param_active(&self, param: &impl Param, active: bool) {
+        self.inner.set_param_active(param.as_ptr(), active)
+    }
+
+    fn note_names_changed(&self) {
+        // This is only supported by CLAP
+    }
 }
 
 impl<P: Vst3Plugin> GuiContext for WrapperGuiContext<P> {
@@ -123,6 +175,10 @@ impl<P: Vst3Plugin> GuiContext for WrapperGuiContext<P> {
         PluginApi::Vst3
     }
 
+    fn plugin_name(&self) -> &'static str {
+        P::NAME
+    }
+
     fn request_resize(&self) -> bool {
         let task_posted = self.inner.schedule_gui(Task::RequestResize);
         nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
@@ -132,6 +188,25 @@ impl<P: Vst3Plugin> GuiContext for WrapperGuiContext<P> {
         true
     }
 
+    fn buffer_config(&self) -> Option<BufferConfig> {
+        // The processing mode may have changed since the buffer config was last set
+        self.inner
+            .current_buffer_config
+            .load()
+            .map(|buffer_config| BufferConfig {
+                process_mode: self.inner.current_process_mode.load(),
+                ..buffer_config
+            })
+    }
+
+    fn cpu_usage(&self) -> Option<f32> {
+        self.inner.cpu_usage_meter.load()
+    }
+
+    fn note_names_changed(&self) {
+        // This is only supported by CLAP
+    }
+
     // All of these functions are supposed to be called from the main thread, so we'll put some
     // trust in the caller and assume that this is indeed the case
     unsafe fn raw_begin_set_parameter(&self, param: ParamPtr) {
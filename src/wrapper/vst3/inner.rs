@@ -3,7 +3,7 @@ use crossbeam::atomic::AtomicCell;
 use crossbeam::channel::{self, SendTimeoutError};
 use parking_lot::{Mutex, RwLock};
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use vst3_sys::base::{kInvalidArgument, kResultOk, tresult};
@@ -16,13 +16,16 @@ use super::util::{ObjectPtr, VstPtr, VST3_MIDI_PARAMS_END, VST3_MIDI_PARAMS_STAR
 use super::view::WrapperView;
 use crate::event_loop::{EventLoop, MainThreadExecutor, OsEventLoop};
 use crate::prelude::{
-    AsyncExecutor, AudioIOLayout, BufferConfig, Editor, MidiConfig, ParamFlags, ParamPtr, Params,
-    Plugin, PluginNoteEvent, ProcessMode, ProcessStatus, TaskExecutor, Transport, Vst3Plugin,
+    AsyncExecutor, AudioIOLayout, BufferConfig, Editor, HostFeatures, MidiConfig, ParamFlags,
+    ParamPtr, Params, Plugin, PluginNoteEvent, ProcessMode, ProcessStatus, TaskExecutor, Transport,
+    Vst3Plugin,
 };
 use crate::util::permit_alloc;
 use crate::wrapper::state::{self, PluginState};
 use crate::wrapper::util::buffer_management::BufferManager;
-use crate::wrapper::util::{hash_param_id, process_wrapper};
+use crate::wrapper::util::cpu_usage::CpuUsageMeter;
+use crate::wrapper::util::note_ids::NoteIdTracker;
+use crate::wrapper::util::{hash_param_id, initialize_plugin, process_wrapper};
 
 /// The actual wrapper bits. We need this as an `Arc<T>` so we can safely use our event loop API.
 /// Since we can't combine that with VST3's interior reference counting this just has to be moved to
@@ -44,6 +47,9 @@ pub(crate) struct WrapperInner<P: Vst3Plugin> {
     /// The host's [`IComponentHandler`] instance, if passed through
     /// [`IEditController::set_component_handler`].
     pub component_handler: AtomicRefCell<Option<VstPtr<dyn IComponentHandler>>>,
+    /// The features the host supports, based on `component_handler`. This is updated when the
+    /// plugin gets activated so the audio thread doesn't need to borrow `component_handler`.
+    pub host_features: AtomicCell<HostFeatures>,
 
     /// Our own [`IPlugView`] instance. This is set while the editor is actually visible (which is
     /// different form the lifetime of [`WrapperView`][super::WrapperView] itself).
@@ -73,11 +79,21 @@ pub(crate) struct WrapperInner<P: Vst3Plugin> {
     pub current_buffer_config: AtomicCell<Option<BufferConfig>>,
     /// The current audio processing mode. Set in `IAudioProcessor::setup_processing()`.
     pub current_process_mode: AtomicCell<ProcessMode>,
+    /// Measures the plugin's CPU usage if `P::MEASURE_CPU_USAGE` is enabled.
+    pub cpu_usage_meter: CpuUsageMeter,
     /// The last process status returned by the plugin. This is used for tail handling.
     pub last_process_status: AtomicCell<ProcessStatus>,
+    /// The plugin's [`Plugin::tail_length_samples()`] after the last process call. This is cached
+    /// so `getTailSamples()` doesn't need to lock the plugin.
+    pub last_tail_length: AtomicCell<Option<u32>>,
     /// The current latency in samples, as set by the plugin through the [`InitContext`] and the
     /// [`ProcessContext`].
     pub current_latency: AtomicU32,
+    /// The number of auxiliary output busses that should be reported as active, as set by the
+    /// plugin through the [`InitContext`] and the [`ProcessContext`]. This is clamped to the
+    /// number of auxiliary output ports in the current audio IO layout when it's read, and it
+    /// defaults to `usize::MAX` so all busses are active by default.
+    pub active_aux_output_ports: AtomicUsize,
     /// A data structure that helps manage and create buffers for all of the plugin's inputs and
     /// outputs based on channel pointers provided by the host.
     pub buffer_manager: AtomicRefCell<BufferManager>,
@@ -97,6 +113,8 @@ pub(crate) struct WrapperInner<P: Vst3Plugin> {
     /// the most recent VST3 note IDs we've seen, and then map those back to MIDI note IDs and
     /// channels as needed.
     pub note_expression_controller: AtomicRefCell<NoteExpressionController>,
+    /// Fills in the note IDs for note off events that were sent without one.
+    pub note_id_tracker: AtomicRefCell<NoteIdTracker>,
     /// Unprocessed parameter changes and note events sent by the host during a process call.
     /// Parameter changes are sent as separate queues for each parameter, and note events are in
     /// another queue on top of that. And if `P::MIDI_INPUT >= MidiConfig::MidiCCs`, then we can
@@ -137,6 +155,10 @@ pub(crate) struct WrapperInner<P: Vst3Plugin> {
     /// having to add a setter function to the parameter (or even worse, have it be completely
     /// untyped).
     pub param_ptr_to_hash: HashMap<ParamPtr, u32>,
+    /// Whether a parameter is currently active, indexed by the parameter's hash. Inactive
+    /// parameters are reported as read-only. Set by the plugin through the [`InitContext`] and the
+    /// [`ProcessContext`].
+    pub param_active_by_hash: HashMap<u32, AtomicBool>,
 }
 
 /// Tasks that can be sent from the plugin to be executed on the main thread in a non-blocking
@@ -269,6 +291,10 @@ impl<P: Vst3Plugin> WrapperInner<P> {
             .iter()
             .map(|(id, hash, _, _)| (id.clone(), *hash))
             .collect();
+        let param_active_by_hash = param_id_hashes_ptrs_groups
+            .iter()
+            .map(|(_, hash, _, _)| (*hash, AtomicBool::new(true)))
+            .collect();
         let param_ptr_to_hash = param_id_hashes_ptrs_groups
             .into_iter()
             .map(|(_, hash, ptr, _)| (ptr, hash))
@@ -282,6 +308,7 @@ impl<P: Vst3Plugin> WrapperInner<P> {
             editor: AtomicRefCell::new(None),
 
             component_handler: AtomicRefCell::new(None),
+            host_features: AtomicCell::new(HostFeatures::default()),
 
             plug_view: RwLock::new(None),
 
@@ -297,8 +324,11 @@ impl<P: Vst3Plugin> WrapperInner<P> {
             ),
             current_buffer_config: AtomicCell::new(None),
             current_process_mode: AtomicCell::new(ProcessMode::Realtime),
+            cpu_usage_meter: CpuUsageMeter::default(),
             last_process_status: AtomicCell::new(ProcessStatus::Normal),
+            last_tail_length: AtomicCell::new(None),
             current_latency: AtomicU32::new(0),
+            active_aux_output_ports: AtomicUsize::new(usize::MAX),
             // This is initialized just before calling `Plugin::initialize()` so that during the
             // process call buffers can be initialized without any allocations
             buffer_manager: AtomicRefCell::new(BufferManager::for_audio_io_layout(
@@ -308,6 +338,7 @@ impl<P: Vst3Plugin> WrapperInner<P> {
             input_events: AtomicRefCell::new(VecDeque::with_capacity(1024)),
             output_events: AtomicRefCell::new(VecDeque::with_capacity(1024)),
             note_expression_controller: AtomicRefCell::new(NoteExpressionController::default()),
+            note_id_tracker: AtomicRefCell::new(NoteIdTracker::default()),
             process_events: AtomicRefCell::new(Vec::with_capacity(4096)),
             updated_state_sender,
             updated_state_receiver,
@@ -318,6 +349,7 @@ impl<P: Vst3Plugin> WrapperInner<P> {
             param_units,
             param_id_to_hash,
             param_ptr_to_hash,
+            param_active_by_hash,
         });
 
         // FIXME: Right now this is safe, but if we are going to have a singleton main thread queue
@@ -526,6 +558,25 @@ impl<P: Vst3Plugin> WrapperInner<P> {
         nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
     }
 
+    /// The features the host supports, as of the last time the plugin was activated.
+    pub fn host_features(&self) -> HostFeatures {
+        self.host_features.load()
+    }
+
+    /// Update the cached host features. All of the supported features use the host's component
+    /// handler to restart the component. This is called from the main thread when the plugin gets
+    /// activated.
+    pub fn update_host_features(&self) {
+        let has_component_handler = self.component_handler.borrow().is_some();
+
+        self.host_features.store(HostFeatures {
+            latency_changes: has_component_handler,
+            audio_port_changes: has_component_handler,
+            param_info_changes: has_component_handler,
+            ..Default::default()
+        });
+    }
+
     pub fn set_latency_samples(&self, samples: u32) {
         // Only trigger a restart if it's actually needed
         let old_latency = self.current_latency.swap(samples, Ordering::SeqCst);
@@ -536,6 +587,44 @@ impl<P: Vst3Plugin> WrapperInner<P> {
         }
     }
 
+    pub fn set_active_aux_output_ports(&self, num_ports: usize) {
+        // Same as the above, this only triggers a restart when the value changes
+        let old_num_ports = self
+            .active_aux_output_ports
+            .swap(num_ports, Ordering::SeqCst);
+        let num_aux_output_ports = self.current_audio_io_layout.load().aux_output_ports.len();
+        if old_num_ports.min(num_aux_output_ports) != num_ports.min(num_aux_output_ports) {
+            let task_posted =
+                self.schedule_gui(Task::TriggerRestart(RestartFlags::kIoChanged as i32));
+            nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+        }
+    }
+
+    /// Update a parameter's active state. Returns `true` if the state changed, in which case the
+    /// host should be informed by triggering a restart with the `kParamTitlesChanged` flag.
+    pub fn update_param_active(&self, param: ParamPtr, active: bool) -> bool {
+        match self
+            .param_ptr_to_hash
+            .get(&param)
+            .and_then(|hash| self.param_active_by_hash.get(hash))
+        {
+            Some(param_active) => param_active.swap(active, Ordering::SeqCst) != active,
+            None => {
+                nih_debug_assert_failure!("Unknown parameter: {:?}", param);
+                false
+            }
+        }
+    }
+
+    pub fn set_param_active(&self, param: ParamPtr, active: bool) {
+        if self.update_param_active(param, active) {
+            let task_posted = self.schedule_gui(Task::TriggerRestart(
+                RestartFlags::kParamTitlesChanged as i32,
+            ));
+            nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+        }
+    }
+
     /// Immediately set the plugin state. Returns `false` if the deserialization failed. The plugin
     /// state is set from a couple places, so this function aims to deduplicate that. Includes
     /// `permit_alloc()`s around the deserialization and initialization for the use case where
@@ -577,17 +666,19 @@ impl<P: Vst3Plugin> WrapperInner<P> {
 
             // See above
             success = permit_alloc(|| {
-                plugin.initialize(&audio_io_layout, &buffer_config, &mut init_context)
+                initialize_plugin(
+                    &mut *plugin,
+                    &audio_io_layout,
+                    &buffer_config,
+                    &mut init_context,
+                )
             });
             if success {
                 process_wrapper(|| plugin.reset());
             }
         }
 
-        nih_debug_assert!(
-            success,
-            "Plugin returned false when reinitializing after loading state"
-        );
+        nih_debug_assert!(success, "Plugin failed to reinitialize after loading state");
 
         // Reinitialize the plugin after loading state so it can respond to the new parameter values
         let task_posted = self.schedule_gui(Task::ParameterValuesChanged);
@@ -33,7 +33,10 @@ use crate::prelude::{
 use crate::util::permit_alloc;
 use crate::wrapper::state;
 use crate::wrapper::util::buffer_management::{BufferManager, ChannelPointers};
-use crate::wrapper::util::{clamp_input_event_timing, clamp_output_event_timing, process_wrapper};
+use crate::wrapper::util::{
+    clamp_input_event_timing, clamp_output_event_timing, initialize_plugin, process_wrapper,
+    reported_tail_length,
+};
 
 // Alias needed for the VST3 attribute macro
 use vst3_sys as vst3_com;
@@ -212,6 +215,11 @@ impl<P: Vst3Plugin> IComponent for Wrapper<P> {
                     kResultOk
                 } else if aux_output_idx < current_audio_io_layout.aux_output_ports.len() {
                     info.bus_type = vst3_sys::vst::BusTypes::kAux as i32;
+                    // The plugin can choose to only use some of its auxiliary outputs. The other
+                    // busses still exist, but they are marked as inactive by default.
+                    if aux_output_idx >= self.inner.active_aux_output_ports.load(Ordering::SeqCst) {
+                        info.flags = 0;
+                    }
                     info.channel_count =
                         current_audio_io_layout.aux_output_ports[aux_output_idx].get() as i32;
                     u16strlcpy(
@@ -378,11 +386,20 @@ impl<P: Vst3Plugin> IComponent for Wrapper<P> {
                     param.update_smoother(buffer_config.sample_rate, true);
                 }
 
+                // The host features are cached here so the contexts don't need to check the
+                // component handler from the audio thread
+                self.inner.update_host_features();
+
                 // NOTE: This needs to be dropped after the `plugin` lock to avoid deadlocks
                 let mut init_context = self.inner.make_init_context();
                 let audio_io_layout = self.inner.current_audio_io_layout.load();
                 let mut plugin = self.inner.plugin.lock();
-                if plugin.initialize(&audio_io_layout, &buffer_config, &mut init_context) {
+                if initialize_plugin(
+                    &mut *plugin,
+                    &audio_io_layout,
+                    &buffer_config,
+                    &mut init_context,
+                ) {
                     // NOTE: We don't call `Plugin::reset()` here. The call is done in `set_process()`
                     //       instead. Otherwise we would call the function twice, and `set_process()` needs
                     //       to be called after this function before the plugin may process audio again.
@@ -584,6 +601,9 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
             if hidden {
                 info.flags |= ParameterFlags::kIsReadOnly as i32 | (1 << 4); // kIsHidden
             }
+            if !self.inner.param_active_by_hash[param_hash].load(Ordering::SeqCst) {
+                info.flags |= ParameterFlags::kIsReadOnly as i32;
+            }
             if is_bypass {
                 info.flags |= ParameterFlags::kIsBypass as i32;
             }
@@ -900,6 +920,7 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
 
         // Always reset the processing status when the plugin gets activated or deactivated
         self.inner.last_process_status.store(ProcessStatus::Normal);
+        self.inner.last_tail_length.store(None);
         self.inner.is_processing.store(state, Ordering::SeqCst);
 
         // This function is also used to reset buffers on the plugin, so we should do the same
@@ -921,6 +942,7 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                 }
             };
 
+            self.inner.note_id_tracker.borrow_mut().reset();
             process_wrapper(|| plugin.reset());
         }
 
@@ -1068,6 +1090,7 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
             if P::MIDI_INPUT >= MidiConfig::Basic {
                 let mut note_expression_controller =
                     self.inner.note_expression_controller.borrow_mut();
+                let mut note_id_tracker = self.inner.note_id_tracker.borrow_mut();
                 if let Some(events) = data.input_events.upgrade() {
                     let num_events = events.get_event_count();
 
@@ -1091,11 +1114,15 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
 
                             process_events.push(ProcessEvent::NoteEvent(NoteEvent::NoteOn {
                                 timing,
-                                voice_id: if event.note_id != -1 {
-                                    Some(event.note_id)
-                                } else {
-                                    None
-                                },
+                                voice_id: note_id_tracker.note_on(
+                                    if event.note_id != -1 {
+                                        Some(event.note_id)
+                                    } else {
+                                        None
+                                    },
+                                    event.channel as u8,
+                                    event.pitch as u8,
+                                ),
                                 channel: event.channel as u8,
                                 note: event.pitch as u8,
                                 velocity: event.velocity,
@@ -1104,11 +1131,17 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                             let event = event.event.note_off;
                             process_events.push(ProcessEvent::NoteEvent(NoteEvent::NoteOff {
                                 timing,
-                                voice_id: if event.note_id != -1 {
-                                    Some(event.note_id)
-                                } else {
-                                    None
-                                },
+                                // Some hosts don't send note IDs for note off events, so we'll
+                                // fill them in if we can
+                                voice_id: note_id_tracker.note_off(
+                                    if event.note_id != -1 {
+                                        Some(event.note_id)
+                                    } else {
+                                        None
+                                    },
+                                    event.channel as u8,
+                                    event.pitch as u8,
+                                ),
                                 channel: event.channel as u8,
                                 note: event.pitch as u8,
                                 velocity: event.velocity,
@@ -1290,7 +1323,7 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                                     .enumerate()
                                 {
                                     let aux_output_idx = aux_output_no + aux_output_start_idx;
-                                    if aux_output_idx > data.num_outputs as usize {
+                                    if aux_output_idx >= data.num_outputs as usize {
                                         break;
                                     }
 
@@ -1398,8 +1431,15 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                             outputs: buffers.aux_outputs,
                         };
                         let mut context = self.inner.make_process_context(transport);
-                        let result = plugin.process(buffers.main_buffer, &mut aux, &mut context);
+                        let result = self.inner.cpu_usage_meter.measure::<P, _>(
+                            block_len,
+                            sample_rate,
+                            || plugin.process(buffers.main_buffer, &mut aux, &mut context),
+                        );
                         self.inner.last_process_status.store(result);
+                        self.inner
+                            .last_tail_length
+                            .store(plugin.tail_length_samples());
                         result
                     } else {
                         ProcessStatus::Normal
@@ -1672,11 +1712,11 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
 
     unsafe fn get_tail_samples(&self) -> u32 {
         // https://github.com/steinbergmedia/vst3_pluginterfaces/blob/2ad397ade5b51007860bedb3b01b8afd2c5f6fba/vst/ivstaudioprocessor.h#L145-L159
-        match self.inner.last_process_status.load() {
-            ProcessStatus::Tail(samples) => samples,
-            ProcessStatus::KeepAlive => u32::MAX, // kInfiniteTail
-            _ => 0,                               // kNoTail
-        }
+        // `u32::MAX` is `kInfiniteTail`, and zero is `kNoTail`
+        reported_tail_length(
+            self.inner.last_process_status.load(),
+            self.inner.last_tail_length.load(),
+        )
     }
 }
 